@@ -0,0 +1,96 @@
+//! argus-audit — standalone offline verifier for exported audit bundles.
+//!
+//! Runs with no database, no vault and no network, so compliance can
+//! re-verify archived bundles on an air-gapped machine:
+//!
+//!   argus-audit verify-bundle <FILE|-> [--pubkey HEX | --pubkey-file PATH] [--hmac-key-file PATH]
+//!
+//! Exit status: 0 bundle intact, 1 issues found, 2 usage or read error.
+
+use std::io::BufReader;
+use argus_audit::{verify_bundle, AnchorVerifier, BundleVerifyOptions};
+
+const USAGE: &str = "usage: argus-audit verify-bundle <FILE|-> [--pubkey HEX | --pubkey-file PATH] [--hmac-key-file PATH]";
+
+fn main() {
+    match run() {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("[!] {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+fn run() -> Result<bool, String> {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("verify-bundle") => {}
+        _ => return Err(USAGE.to_string()),
+    }
+
+    let mut file: Option<String> = None;
+    let mut pubkey: Option<String> = None;
+    let mut hmac_key: Option<Vec<u8>> = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--pubkey" => pubkey = Some(args.next().ok_or(USAGE)?),
+            "--pubkey-file" => {
+                let path = args.next().ok_or(USAGE)?;
+                pubkey = Some(std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path, e))?
+                    .lines()
+                    .find(|l| !l.trim().is_empty() && !l.starts_with('#'))
+                    .unwrap_or_default()
+                    .to_string());
+            }
+            "--hmac-key-file" => {
+                let path = args.next().ok_or(USAGE)?;
+                let key = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+                hmac_key = Some(key.trim().as_bytes().to_vec());
+            }
+            _ if file.is_none() && !arg.starts_with("--") => file = Some(arg),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let file = file.ok_or(USAGE)?;
+
+    let verifier = pubkey.as_deref().map(AnchorVerifier::from_hex).transpose()?;
+    let opts = BundleVerifyOptions {
+        hmac_key: hmac_key.as_deref(),
+        pq_verifier: verifier.as_ref(),
+    };
+
+    let on_issue = |issue: &argus_audit::ChainIssue| println!("  ❌ {}", issue);
+    let report = if file == "-" {
+        verify_bundle(std::io::stdin().lock(), &opts, on_issue)?
+    } else {
+        let f = std::fs::File::open(&file).map_err(|e| format!("Failed to open {}: {}", file, e))?;
+        verify_bundle(BufReader::new(f), &opts, on_issue)?
+    };
+
+    let chain = &report.chain;
+    println!("\n  Range:            {} → {}", report.header.from, report.header.to);
+    println!("  Entries checked:  {}", chain.entries_checked);
+    println!("  Days:             {}", chain.days_seen);
+    println!("  Anchors checked:  {}{}", chain.anchors_checked,
+        if chain.legacy_anchors > 0 { format!(" ({} pre-Merkle format)", chain.legacy_anchors) } else { String::new() });
+    if opts.hmac_key.is_none() {
+        println!("  HMAC:             not checked (no --hmac-key-file)");
+    }
+    match report.pq_key_id {
+        Some(ref id) if report.pq_key_self_asserted => println!(
+            "  ML-DSA key:       {} (from bundle header — compare against your archived key id)", id),
+        Some(ref id) => println!("  ML-DSA key:       {} ({} anchors unsigned)", id, chain.pq_unsigned),
+        None => println!("  ML-DSA:           not checked (no public key)"),
+    }
+
+    if report.is_clean() {
+        println!("\n[+] Bundle intact");
+    } else {
+        println!("\n[!] {} issue(s) found — bundle does not verify", chain.issues);
+    }
+    Ok(report.is_clean())
+}
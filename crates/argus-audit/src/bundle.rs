@@ -0,0 +1,391 @@
+//! Portable audit bundles — export a date range of the chain and verify it
+//! offline, with no database and no network.
+//!
+//! A bundle is JSONL, one self-describing record per line, in this order:
+//!   1. `header` — format version, the genesis / hash / Merkle / signature
+//!      conventions needed to re-verify, and the ML-DSA public key
+//!   2. `entry` — every AuditEntry in the range, id order
+//!   3. `day`   — per-day Merkle root and entry count, with the published
+//!      anchor (HMAC + ML-DSA signatures) when one exists
+//!   4. `footer` — totals and the last entry_hash, so truncation is detected
+//!
+//! Exports always cover whole UTC days so every day root can be recomputed.
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use serde::{Serialize, Deserialize};
+use crate::chain::AuditChain;
+use crate::entry::{AuditEntry, genesis_prev_hash};
+use crate::merkle::{MerkleBuilder, empty_day_root};
+use crate::signer::{AnchorVerifier, DayAnchor};
+use crate::verify::{ChainIssue, ChainWalker, VerifyOptions, VerifyReport};
+
+pub const BUNDLE_FORMAT: &str = "argus-audit-bundle";
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// First line of every bundle. Describes everything a verifier needs to
+/// know that isn't in the entries themselves.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleHeader {
+    pub format: String,
+    pub version: u32,
+    pub created_at: String,
+    pub from: String,               // first UTC date covered, YYYY-MM-DD
+    pub to: String,                 // last UTC date covered, inclusive
+    /// prev_entry_hash of entry 1 in every chain: SHA-256("GENESIS").
+    pub genesis_prev_hash: String,
+    /// prev_entry_hash the first exported entry chains from. Equals
+    /// genesis_prev_hash when the export starts at the beginning of the chain.
+    pub start_prev_hash: String,
+    pub hash_formats: Vec<String>,
+    pub merkle: String,
+    pub hmac: String,
+    pub pq_algorithm: String,
+    /// Hex ML-DSA public key of the exporting daemon. Self-asserted — a
+    /// verifier should compare its key id against an independently held copy.
+    pub pq_public_key: Option<String>,
+}
+
+/// One day's recomputed root, plus its published anchor if there is one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleDay {
+    pub date: String,
+    pub day_root: String,
+    pub entry_count: i64,
+    pub anchor: Option<DayAnchor>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFooter {
+    pub entries: u64,
+    pub days: usize,
+    pub last_entry_hash: Option<String>,
+}
+
+/// A single bundle line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BundleRecord {
+    Header(BundleHeader),
+    Entry(AuditEntry),
+    Day(BundleDay),
+    Footer(BundleFooter),
+}
+
+fn write_record<W: Write>(out: &mut W, record: &BundleRecord) -> Result<(), String> {
+    serde_json::to_writer(&mut *out, record).map_err(|e| format!("Bundle write failed: {}", e))?;
+    out.write_all(b"\n").map_err(|e| format!("Bundle write failed: {}", e))
+}
+
+/// Export entries dated `from..=to` (UTC, YYYY-MM-DD) as a JSONL bundle.
+///
+/// `anchors` are the published anchors to embed (typically from
+/// `signer::fetch_anchors`); only those inside the range are written.
+/// Entries stream straight to `out`; only per-day Merkle state is held.
+pub fn export_bundle<W: Write>(
+    chain: &AuditChain,
+    from: &str,
+    to: &str,
+    anchors: &[DayAnchor],
+    pq_public_key: Option<&str>,
+    mut out: W,
+) -> Result<BundleFooter, String> {
+    if from > to {
+        return Err(format!("Export range is empty: {} is after {}", from, to));
+    }
+
+    let ids = chain.id_range_for_dates(from, to)?;
+    let start_prev_hash = match ids {
+        Some((first, _)) => chain.prev_hash_of(first)?,
+        None => genesis_prev_hash(),
+    };
+
+    let header = BundleHeader {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        from: from.to_string(),
+        to: to.to_string(),
+        genesis_prev_hash: genesis_prev_hash(),
+        start_prev_hash,
+        hash_formats: vec![
+            "v1: SHA-256(id|timestamp_us|agent_model|action_type|tool_name|args_hash|result_hash|session_id|prev_entry_hash)".to_string(),
+            "v2: SHA-256(\"v2\"|id|timestamp_us|agent_identity|agent_model|action_type|tool_name|args_hash|result_hash|session_id|prev_entry_hash)".to_string(),
+        ],
+        merkle: "leaf = SHA-256(\"LEAF|\" + entry_hash); node = SHA-256(\"NODE|\" + left + \"|\" + right); \
+                 odd node promoted; empty day = SHA-256(\"EMPTY_DAY_\" + date)".to_string(),
+        hmac: "HMAC-SHA256(audit_hmac_key, day_root)".to_string(),
+        pq_algorithm: "ML-DSA-65 (Dilithium-3) over \"ARGUS_DAY_ROOT|\" + date + \"|\" + day_root".to_string(),
+        pq_public_key: pq_public_key.map(String::from),
+    };
+    write_record(&mut out, &BundleRecord::Header(header))?;
+
+    let mut days: BTreeMap<String, MerkleBuilder> = BTreeMap::new();
+    let mut last_entry_hash = None;
+    let entries = match ids {
+        Some((first, last)) => chain.for_each_entry_in(first, last, |entry| {
+            if let Some(date) = entry.utc_date() {
+                days.entry(date).or_default().push(&entry.entry_hash);
+            }
+            last_entry_hash = Some(entry.entry_hash.clone());
+            write_record(&mut out, &BundleRecord::Entry(entry))
+        })?,
+        None => 0,
+    };
+
+    // Every date with entries or an anchor gets a day record.
+    for anchor in anchors {
+        if anchor.anchor_date.as_str() >= from && anchor.anchor_date.as_str() <= to {
+            days.entry(anchor.anchor_date.clone()).or_default();
+        }
+    }
+    let day_count = days.len();
+    for (date, builder) in days {
+        let anchor = anchors.iter().find(|a| a.anchor_date == date).cloned();
+        write_record(&mut out, &BundleRecord::Day(BundleDay {
+            day_root: builder.root().unwrap_or_else(|| empty_day_root(&date)),
+            entry_count: builder.len() as i64,
+            date,
+            anchor,
+        }))?;
+    }
+
+    let footer = BundleFooter { entries, days: day_count, last_entry_hash };
+    write_record(&mut out, &BundleRecord::Footer(footer.clone()))?;
+    out.flush().map_err(|e| format!("Bundle write failed: {}", e))?;
+
+    Ok(footer)
+}
+
+/// Keys to verify a bundle with. Both optional; the more supplied, the more
+/// is checked.
+#[derive(Default)]
+pub struct BundleVerifyOptions<'a> {
+    pub hmac_key: Option<&'a [u8]>,
+    /// Independently held ML-DSA public key. If None, the bundle's own
+    /// header key is used and the report flags it as self-asserted.
+    pub pq_verifier: Option<&'a AnchorVerifier>,
+}
+
+#[derive(Debug)]
+pub struct BundleReport {
+    pub header: BundleHeader,
+    pub chain: VerifyReport,
+    pub pq_key_id: Option<String>,
+    pub pq_key_self_asserted: bool,
+}
+
+impl BundleReport {
+    pub fn is_clean(&self) -> bool {
+        self.chain.is_clean()
+    }
+}
+
+/// Verify a bundle read from `reader`. Needs no database and no network.
+///
+/// Checks every entry hash and link, recomputes each day's Merkle root and
+/// count against the day records, verifies the embedded anchors' roots and
+/// signatures, and confirms the footer so a truncated file is caught.
+/// Every issue goes to `on_issue`; Err means the input isn't a readable bundle.
+pub fn verify_bundle<R, F>(
+    reader: R,
+    opts: &BundleVerifyOptions,
+    mut on_issue: F,
+) -> Result<BundleReport, String>
+where
+    R: BufRead,
+    F: FnMut(&ChainIssue),
+{
+    let mut lines = reader.lines().enumerate();
+
+    let header = match lines.next() {
+        Some((_, line)) => {
+            let line = line.map_err(|e| format!("Bundle read failed: {}", e))?;
+            match serde_json::from_str::<BundleRecord>(&line) {
+                Ok(BundleRecord::Header(h)) => h,
+                _ => return Err("Not an audit bundle: first line is not a header".to_string()),
+            }
+        }
+        None => return Err("Not an audit bundle: file is empty".to_string()),
+    };
+    if header.format != BUNDLE_FORMAT {
+        return Err(format!("Not an audit bundle: format '{}'", header.format));
+    }
+    if header.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Bundle version {} is newer than this verifier supports ({})",
+            header.version, BUNDLE_VERSION
+        ));
+    }
+
+    let mut walker = ChainWalker::new(header.start_prev_hash.clone());
+    let inconsistent = |reason: String| ChainIssue::BundleInconsistent { reason };
+
+    if header.genesis_prev_hash != genesis_prev_hash() {
+        walker.raise(inconsistent("header genesis_prev_hash is not SHA-256(\"GENESIS\")".to_string()), &mut on_issue);
+    }
+
+    let mut anchors: Vec<DayAnchor> = Vec::new();
+    let mut day_records = 0usize;
+    let mut footer: Option<BundleFooter> = None;
+    let mut seen_day = false;
+
+    for (idx, line) in lines {
+        let line = line.map_err(|e| format!("Bundle read failed: {}", e))?;
+        let line_no = idx + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if footer.is_some() {
+            walker.raise(inconsistent(format!("line {}: data after footer", line_no)), &mut on_issue);
+            break;
+        }
+        let record = match serde_json::from_str::<BundleRecord>(&line) {
+            Ok(r) => r,
+            Err(e) => {
+                walker.raise(inconsistent(format!("line {}: unreadable record ({})", line_no, e)), &mut on_issue);
+                continue;
+            }
+        };
+        match record {
+            BundleRecord::Header(_) => {
+                walker.raise(inconsistent(format!("line {}: second header", line_no)), &mut on_issue);
+            }
+            BundleRecord::Entry(entry) => {
+                if seen_day {
+                    walker.raise(inconsistent(format!("line {}: entry after day records", line_no)), &mut on_issue);
+                }
+                if entry.id == 1 && header.start_prev_hash != header.genesis_prev_hash {
+                    walker.raise(inconsistent("export starts at entry 1 but start_prev_hash is not genesis".to_string()), &mut on_issue);
+                }
+                walker.push(&entry, &mut on_issue);
+            }
+            BundleRecord::Day(day) => {
+                seen_day = true;
+                day_records += 1;
+                let (root, _, count) = walker.computed_day(&day.date);
+                if day.day_root != root || day.entry_count != count {
+                    walker.raise(inconsistent(format!(
+                        "day {}: record claims {} entries / root {}…, entries give {} / {}…",
+                        day.date, day.entry_count, &day.day_root[..day.day_root.len().min(16)],
+                        count, &root[..16],
+                    )), &mut on_issue);
+                }
+                if let Some(anchor) = day.anchor {
+                    if anchor.anchor_date != day.date {
+                        walker.raise(inconsistent(format!(
+                            "day {}: embedded anchor is dated {}", day.date, anchor.anchor_date
+                        )), &mut on_issue);
+                    }
+                    anchors.push(anchor);
+                }
+            }
+            BundleRecord::Footer(f) => footer = Some(f),
+        }
+    }
+
+    match footer {
+        None => walker.raise(inconsistent("no footer — bundle truncated".to_string()), &mut on_issue),
+        Some(ref f) => {
+            let (entries, last) = (walker.entries_checked(), walker.last_hash().map(String::from));
+            if f.entries != entries || f.days != day_records || f.last_entry_hash != last {
+                walker.raise(inconsistent(format!(
+                    "footer claims {} entries / {} days, bundle holds {} / {}",
+                    f.entries, f.days, entries, day_records
+                )), &mut on_issue);
+            }
+        }
+    }
+
+    // Prefer the caller's independently held key; fall back to the bundle's own.
+    let header_verifier = match (opts.pq_verifier, &header.pq_public_key) {
+        (None, Some(hex)) => Some(AnchorVerifier::from_hex(hex)?),
+        _ => None,
+    };
+    let pq_verifier = opts.pq_verifier.or(header_verifier.as_ref());
+
+    let chain_opts = VerifyOptions {
+        anchors: &anchors,
+        hmac_key: opts.hmac_key,
+        pq_verifier,
+    };
+    let report = walker.finish(&chain_opts, &mut on_issue);
+
+    Ok(BundleReport {
+        pq_key_id: pq_verifier.map(|v| v.key_id()),
+        pq_key_self_asserted: opts.pq_verifier.is_none() && header_verifier.is_some(),
+        header,
+        chain: report,
+    })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::sign_day_root;
+
+    fn exported(anchor_root: Option<&str>) -> (Vec<u8>, String) {
+        let chain = AuditChain::open(":memory:").unwrap();
+        for i in 0..5 {
+            chain.append("m", "tool_call", Some("shell"), Some(&i.to_string()), Some("ok")).unwrap();
+        }
+        let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let root = chain.compute_day_root(&date).unwrap();
+        let anchor_root = anchor_root.map(String::from).unwrap_or(root);
+        let anchors = vec![DayAnchor {
+            anchor_date: date.clone(),
+            signature: sign_day_root(&anchor_root, b"k"),
+            day_root: anchor_root,
+            entry_count: 5,
+            pq_signature: None,
+            pq_key_id: None,
+        }];
+        let mut out = Vec::new();
+        export_bundle(&chain, &date, &date, &anchors, None, &mut out).unwrap();
+        (out, date)
+    }
+
+    #[test]
+    fn roundtrip_verifies_clean() {
+        let (bundle, _) = exported(None);
+        let opts = BundleVerifyOptions { hmac_key: Some(b"k"), pq_verifier: None };
+        let mut found = Vec::new();
+        let report = verify_bundle(bundle.as_slice(), &opts, |i| found.push(i.to_string())).unwrap();
+        assert!(report.is_clean(), "{:?}", found);
+        assert_eq!(report.chain.entries_checked, 5);
+        assert_eq!(report.chain.anchors_checked, 1);
+    }
+
+    #[test]
+    fn edited_entry_and_truncation_are_caught() {
+        let (bundle, _) = exported(None);
+        let text = String::from_utf8(bundle).unwrap();
+        let mut lines: Vec<String> = text.lines().map(String::from).collect();
+        lines[2] = lines[2].replace("\"shell\"", "\"write_file\"");
+        lines.pop(); // drop footer
+        let tampered = lines.join("\n");
+
+        let mut found = Vec::new();
+        let report = verify_bundle(tampered.as_bytes(), &BundleVerifyOptions::default(), |i| found.push(i.clone())).unwrap();
+        assert!(!report.is_clean());
+        assert!(found.iter().any(|i| matches!(i, ChainIssue::CorruptEntry { id: 2 })));
+        assert!(found.iter().any(|i| matches!(i, ChainIssue::BundleInconsistent { .. })));
+    }
+
+    #[test]
+    fn forged_anchor_is_caught() {
+        let (bundle, _) = exported(Some(&"ab".repeat(32)));
+        let opts = BundleVerifyOptions { hmac_key: Some(b"k"), pq_verifier: None };
+        let mut found = Vec::new();
+        verify_bundle(bundle.as_slice(), &opts, |i| found.push(i.clone())).unwrap();
+        assert!(found.iter().any(|i| matches!(i, ChainIssue::DayRootMismatch { .. })));
+    }
+
+    #[test]
+    fn rejects_non_bundle() {
+        assert!(verify_bundle("{\"hello\":1}".as_bytes(), &BundleVerifyOptions::default(), |_| {}).is_err());
+        assert!(verify_bundle("".as_bytes(), &BundleVerifyOptions::default(), |_| {}).is_err());
+    }
+}
@@ -7,13 +7,24 @@
 //!     Three separate mutexes collapsed to one to prevent contention and
 //!     eliminate the risk of poisoning / deadlock under concurrent load.
 //!   - verify_recent() checks both internal hash integrity and chain links.
+//!   - Forensic mode (opt-in) writes args/result ciphertext to a side table
+//!     in the same transaction as the entry. See `forensics`.
+//!   - for_each_entry() streams the whole chain for full verification and
+//!     export without loading it into memory.
 
 use rusqlite::{Connection, params};
 use std::sync::Mutex;
 use std::path::Path;
 use chrono::Utc;
 use uuid::Uuid;
-use crate::entry::{AuditEntry, sha256_hex, genesis_prev_hash};
+use crate::entry::{AuditEntry, sha256_hex, genesis_prev_hash, CURRENT_HASH_VERSION};
+use crate::forensics::{ForensicBlobs, ForensicKey};
+use crate::merkle::{self, InclusionProof};
+
+/// Column list matching `row_to_entry`. Keep the two in sync.
+const ENTRY_COLUMNS: &str =
+    "id, timestamp_us, hash_version, agent_identity, agent_model, action_type, tool_name,
+     args_hash, result_hash, session_id, prev_entry_hash, entry_hash";
 
 // ── Internal state guarded by a single mutex ─────────────────────────────
 
@@ -21,6 +32,7 @@ struct ChainState {
     conn:      Connection,
     last_hash: String,
     last_id:   u64,
+    forensic_key: Option<ForensicKey>,
 }
 
 // ── Public struct ─────────────────────────────────────────────────────────
@@ -43,6 +55,7 @@ impl AuditChain {
              CREATE TABLE IF NOT EXISTS audit_entries (
                  id               INTEGER PRIMARY KEY,
                  timestamp_us     INTEGER NOT NULL,
+                 hash_version     INTEGER NOT NULL DEFAULT 1,
                  agent_identity   TEXT    NOT NULL DEFAULT 'argus',
                  agent_model      TEXT    NOT NULL,
                  action_type      TEXT    NOT NULL,
@@ -54,7 +67,13 @@ impl AuditChain {
                  entry_hash       TEXT    NOT NULL UNIQUE
              );
              CREATE INDEX IF NOT EXISTS idx_session   ON audit_entries(session_id);
-             CREATE INDEX IF NOT EXISTS idx_timestamp ON audit_entries(timestamp_us);",
+             CREATE INDEX IF NOT EXISTS idx_timestamp ON audit_entries(timestamp_us);
+             CREATE TABLE IF NOT EXISTS audit_forensics (
+                 entry_id   INTEGER PRIMARY KEY REFERENCES audit_entries(id),
+                 key_id     TEXT    NOT NULL,
+                 args_ct    BLOB,
+                 result_ct  BLOB
+             );",
         ).map_err(|e| format!("Failed to initialise audit schema: {}", e))?;
 
         // Migrate existing databases that predate the agent_identity column.
@@ -63,6 +82,10 @@ impl AuditChain {
         let _ = conn.execute_batch(
             "ALTER TABLE audit_entries ADD COLUMN agent_identity TEXT NOT NULL DEFAULT 'argus';"
         );
+        // Same for hash_version: every pre-existing row was hashed with v1.
+        let _ = conn.execute_batch(
+            "ALTER TABLE audit_entries ADD COLUMN hash_version INTEGER NOT NULL DEFAULT 1;"
+        );
 
         // Resume the chain from the last persisted entry
         let (last_id, last_hash) = {
@@ -81,17 +104,29 @@ impl AuditChain {
         };
 
         Ok(Self {
-            state: Mutex::new(ChainState { conn, last_hash, last_id }),
+            state: Mutex::new(ChainState { conn, last_hash, last_id, forensic_key: None }),
             session_id: Uuid::new_v4().to_string(),
         })
     }
 
+    /// Turn on forensic mode: from now on `append` also stores args/result
+    /// ciphertext sealed with `key`. Off by default — only hashes are kept.
+    pub fn enable_forensics(&self, key: ForensicKey) {
+        if let Ok(mut s) = self.state.lock() {
+            s.forensic_key = Some(key);
+        }
+    }
+
     /// Append a new entry to the chain. The only write path — no updates, no deletes.
     ///
     /// `agent_identity` is always "argus" — the persistent identity regardless of which
     /// model is currently loaded. Stored separately from `agent_model` so the audit log
     /// narrates one continuous agent across model switches.
     ///
+    /// With forensic mode on, the sealed args/result go into `audit_forensics`
+    /// in the same transaction. If sealing fails the entry is still logged,
+    /// hashes only — the chain never drops an event for forensics' sake.
+    ///
     /// Returns the new entry id on success.
     pub fn append(
         &self,
@@ -111,6 +146,7 @@ impl AuditChain {
         let mut entry = AuditEntry {
             id: new_id,
             timestamp_us: now_us,
+            hash_version: CURRENT_HASH_VERSION,
             agent_identity: "argus".to_string(),
             agent_model: agent_model.to_string(),
             action_type: action_type.to_string(),
@@ -123,14 +159,30 @@ impl AuditChain {
         };
         entry.compute_entry_hash();
 
-        s.conn.execute(
+        let forensic = match &s.forensic_key {
+            Some(key) => match (
+                args.map(|a| key.seal(a)).transpose(),
+                result.map(|r| key.seal(r)).transpose(),
+            ) {
+                (Ok(args_ct), Ok(result_ct)) => Some((key.key_id(), args_ct, result_ct)),
+                (Err(e), _) | (_, Err(e)) => {
+                    eprintln!("[!] Forensic seal failed for entry {}: {} — logging hashes only", new_id, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let tx = s.conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute(
             "INSERT INTO audit_entries
-             (id, timestamp_us, agent_identity, agent_model, action_type, tool_name,
+             (id, timestamp_us, hash_version, agent_identity, agent_model, action_type, tool_name,
               args_hash, result_hash, session_id, prev_entry_hash, entry_hash)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
             params![
                 entry.id,
                 entry.timestamp_us,
+                entry.hash_version,
                 entry.agent_identity,
                 entry.agent_model,
                 entry.action_type,
@@ -143,6 +195,15 @@ impl AuditChain {
             ],
         ).map_err(|e| format!("Audit insert failed: {}", e))?;
 
+        if let Some((key_id, args_ct, result_ct)) = forensic {
+            tx.execute(
+                "INSERT INTO audit_forensics (entry_id, key_id, args_ct, result_ct)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![new_id, key_id, args_ct, result_ct],
+            ).map_err(|e| format!("Forensic insert failed: {}", e))?;
+        }
+        tx.commit().map_err(|e| format!("Audit commit failed: {}", e))?;
+
         s.last_hash = entry.entry_hash;
         s.last_id   = new_id;
 
@@ -154,29 +215,15 @@ impl AuditChain {
     /// Returns Ok(count_verified) or Err(description of first failure).
     pub fn verify_recent(&self, n: usize) -> Result<usize, String> {
         let s = self.state.lock().map_err(|e| e.to_string())?;
-        let mut stmt = s.conn.prepare(
-            "SELECT id, timestamp_us, agent_identity, agent_model, action_type, tool_name,
-                    args_hash, result_hash, session_id, prev_entry_hash, entry_hash
-             FROM audit_entries ORDER BY id DESC LIMIT ?1"
-        ).map_err(|e| e.to_string())?;
+        let mut stmt = s.conn.prepare(&format!(
+            "SELECT {} FROM audit_entries ORDER BY id DESC LIMIT ?1",
+            ENTRY_COLUMNS
+        )).map_err(|e| e.to_string())?;
 
-        let mut entries: Vec<AuditEntry> = stmt.query_map(params![n as i64], |row| {
-            Ok(AuditEntry {
-                id:              row.get(0)?,
-                timestamp_us:    row.get(1)?,
-                agent_identity:  row.get(2)?,
-                agent_model:     row.get(3)?,
-                action_type:     row.get(4)?,
-                tool_name:       row.get(5)?,
-                args_hash:       row.get(6)?,
-                result_hash:     row.get(7)?,
-                session_id:      row.get(8)?,
-                prev_entry_hash: row.get(9)?,
-                entry_hash:      row.get(10)?,
-            })
-        }).map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        let mut entries: Vec<AuditEntry> = stmt.query_map(params![n as i64], row_to_entry)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
 
         // Query returns newest-first; reverse to oldest-first for chain walk
         entries.reverse();
@@ -201,36 +248,146 @@ impl AuditChain {
         Ok(verified)
     }
 
-    /// Compute a day root: SHA-256 of all entry_hashes for `date` concatenated in id order.
-    /// Used for daily HMAC signing and Supabase anchoring.
+    /// Compute a day root: binary Merkle root over all entry_hashes for `date`
+    /// in id order (see `merkle`). Used for daily HMAC signing and Supabase
+    /// anchoring, and as the target of `inclusion_proof`.
     pub fn compute_day_root(&self, date: &str) -> Result<String, String> {
         let s = self.state.lock().map_err(|e| e.to_string())?;
+        let (day_start_us, day_end_us) = day_bounds_us(date)?;
+        let hashes = day_entry_hashes(&s.conn, day_start_us, day_end_us)?;
 
-        let day_start_us = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
-            .map_err(|e| e.to_string())?
-            .and_hms_opt(0, 0, 0)
-            .ok_or("Invalid midnight")?
-            .and_utc()
-            .timestamp_micros();
-        let day_end_us = day_start_us + 86_400_000_000i64;
+        Ok(merkle::merkle_root(&hashes).unwrap_or_else(|| merkle::empty_day_root(date)))
+    }
 
-        let mut stmt = s.conn.prepare(
-            "SELECT entry_hash FROM audit_entries
-             WHERE timestamp_us >= ?1 AND timestamp_us < ?2
-             ORDER BY id ASC"
-        ).map_err(|e| e.to_string())?;
+    /// Fetch a single entry by id.
+    pub fn entry(&self, entry_id: u64) -> Result<AuditEntry, String> {
+        let s = self.state.lock().map_err(|e| e.to_string())?;
+        s.conn.query_row(
+            &format!("SELECT {} FROM audit_entries WHERE id = ?1", ENTRY_COLUMNS),
+            params![entry_id],
+            row_to_entry,
+        ).map_err(|e| format!("Audit entry {} not found: {}", entry_id, e))
+    }
 
-        let hashes: Vec<String> = stmt.query_map(params![day_start_us, day_end_us], |row| {
-            row.get(0)
-        }).map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+    /// Sealed forensic payload for an entry. None if it was logged with
+    /// forensic mode off (or the row has since been purged).
+    pub fn forensic_blobs(&self, entry_id: u64) -> Result<Option<ForensicBlobs>, String> {
+        let s = self.state.lock().map_err(|e| e.to_string())?;
+        let row = s.conn.query_row(
+            "SELECT key_id, args_ct, result_ct FROM audit_forensics WHERE entry_id = ?1",
+            params![entry_id],
+            |row| Ok(ForensicBlobs {
+                key_id:    row.get(0)?,
+                args_ct:   row.get(1)?,
+                result_ct: row.get(2)?,
+            }),
+        );
+        match row {
+            Ok(blobs) => Ok(Some(blobs)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Build a Merkle inclusion proof for a single entry against its day root.
+    /// The proof can be checked offline with `merkle::verify_inclusion`.
+    pub fn inclusion_proof(&self, entry_id: u64) -> Result<InclusionProof, String> {
+        let s = self.state.lock().map_err(|e| e.to_string())?;
+
+        let entry = s.conn.query_row(
+            &format!("SELECT {} FROM audit_entries WHERE id = ?1", ENTRY_COLUMNS),
+            params![entry_id],
+            row_to_entry,
+        ).map_err(|e| format!("Audit entry {} not found: {}", entry_id, e))?;
+
+        let date = entry.utc_date().ok_or("Entry timestamp out of range")?;
+        let (day_start_us, day_end_us) = day_bounds_us(&date)?;
+        let hashes = day_entry_hashes(&s.conn, day_start_us, day_end_us)?;
+
+        let leaf_index = hashes.iter()
+            .position(|h| *h == entry.entry_hash)
+            .ok_or_else(|| format!("Entry {} missing from its day's leaves", entry_id))?;
+        let path = merkle::merkle_path(&hashes, leaf_index)
+            .ok_or("Leaf index out of range")?;
+
+        Ok(InclusionProof {
+            date,
+            entry,
+            leaf_index,
+            leaf_count: hashes.len(),
+            path,
+        })
+    }
+
+    /// Stream every entry from genesis in id order, one row at a time.
+    /// Memory use is independent of chain length. Holds the chain lock for
+    /// the duration, so appends from this handle wait until the walk ends;
+    /// readers in other processes are unaffected (WAL).
+    /// Stops early and returns the callback's error if it fails.
+    pub fn for_each_entry<F>(&self, f: F) -> Result<u64, String>
+    where
+        F: FnMut(AuditEntry) -> Result<(), String>,
+    {
+        self.for_each_entry_in(1, i64::MAX as u64, f)
+    }
+
+    /// Like `for_each_entry`, restricted to ids in `first_id..=last_id`.
+    pub fn for_each_entry_in<F>(&self, first_id: u64, last_id: u64, mut f: F) -> Result<u64, String>
+    where
+        F: FnMut(AuditEntry) -> Result<(), String>,
+    {
+        let s = self.state.lock().map_err(|e| e.to_string())?;
+        let mut stmt = s.conn.prepare(&format!(
+            "SELECT {} FROM audit_entries WHERE id >= ?1 AND id <= ?2 ORDER BY id ASC",
+            ENTRY_COLUMNS
+        )).map_err(|e| e.to_string())?;
 
-        if hashes.is_empty() {
-            return Ok(sha256_hex(&format!("EMPTY_DAY_{}", date)));
+        let mut rows = stmt.query(params![first_id, last_id]).map_err(|e| e.to_string())?;
+        let mut count = 0;
+        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+            f(row_to_entry(row).map_err(|e| e.to_string())?)?;
+            count += 1;
         }
+        Ok(count)
+    }
+
+    /// Smallest and largest entry id timestamped within `from..=to` (UTC dates).
+    /// None if no entries fall in the range.
+    pub fn id_range_for_dates(&self, from: &str, to: &str) -> Result<Option<(u64, u64)>, String> {
+        let s = self.state.lock().map_err(|e| e.to_string())?;
+        let (start_us, _) = day_bounds_us(from)?;
+        let (_, end_us) = day_bounds_us(to)?;
+
+        let (lo, hi): (Option<u64>, Option<u64>) = s.conn.query_row(
+            "SELECT MIN(id), MAX(id) FROM audit_entries WHERE timestamp_us >= ?1 AND timestamp_us < ?2",
+            params![start_us, end_us],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).map_err(|e| e.to_string())?;
+
+        Ok(lo.zip(hi))
+    }
 
-        Ok(sha256_hex(&hashes.join("")))
+    /// prev_entry_hash of entry `id` — the boundary hash an export starting
+    /// at `id` chains from.
+    pub fn prev_hash_of(&self, id: u64) -> Result<String, String> {
+        let s = self.state.lock().map_err(|e| e.to_string())?;
+        s.conn.query_row(
+            "SELECT prev_entry_hash FROM audit_entries WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        ).map_err(|e| format!("Audit entry {} not found: {}", id, e))
+    }
+
+    /// Count audit entries logged on `date` (UTC, YYYY-MM-DD).
+    pub fn entry_count_for(&self, date: &str) -> Result<i64, String> {
+        let s = self.state.lock().map_err(|e| e.to_string())?;
+        let (day_start_us, day_end_us) = day_bounds_us(date)?;
+
+        s.conn.query_row(
+            "SELECT COUNT(*) FROM audit_entries WHERE timestamp_us >= ?1 AND timestamp_us < ?2",
+            params![day_start_us, day_end_us],
+            |row| row.get(0),
+        ).map_err(|e| e.to_string())
     }
 
     /// Count audit entries logged today (UTC).
@@ -250,3 +407,77 @@ impl AuditChain {
         ).map_err(|e| e.to_string())
     }
 }
+
+// ── Row helpers ───────────────────────────────────────────────────────────
+
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<AuditEntry> {
+    Ok(AuditEntry {
+        id:              row.get(0)?,
+        timestamp_us:    row.get(1)?,
+        hash_version:    row.get(2)?,
+        agent_identity:  row.get(3)?,
+        agent_model:     row.get(4)?,
+        action_type:     row.get(5)?,
+        tool_name:       row.get(6)?,
+        args_hash:       row.get(7)?,
+        result_hash:     row.get(8)?,
+        session_id:      row.get(9)?,
+        prev_entry_hash: row.get(10)?,
+        entry_hash:      row.get(11)?,
+    })
+}
+
+/// [start, end) in microseconds for a UTC `YYYY-MM-DD` date.
+fn day_bounds_us(date: &str) -> Result<(i64, i64), String> {
+    let day_start_us = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|e| e.to_string())?
+        .and_hms_opt(0, 0, 0)
+        .ok_or("Invalid midnight")?
+        .and_utc()
+        .timestamp_micros();
+    Ok((day_start_us, day_start_us + 86_400_000_000i64))
+}
+
+/// All entry_hashes in [start, end), id order — the Merkle leaves for a day.
+fn day_entry_hashes(conn: &Connection, start_us: i64, end_us: i64) -> Result<Vec<String>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT entry_hash FROM audit_entries
+         WHERE timestamp_us >= ?1 AND timestamp_us < ?2
+         ORDER BY id ASC"
+    ).map_err(|e| e.to_string())?;
+
+    let hashes = stmt.query_map(params![start_us, end_us], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(hashes)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inclusion_proof_verifies_against_day_root() {
+        let chain = AuditChain::open(":memory:").unwrap();
+        for i in 0..5 {
+            chain.append("test-model", "tool_call", Some("shell"),
+                Some(&format!("ls {}", i)), Some("ok")).unwrap();
+        }
+
+        let proof = chain.inclusion_proof(3).unwrap();
+        let root = chain.compute_day_root(&proof.date).unwrap();
+        assert_eq!(proof.leaf_count, 5);
+        assert!(merkle::verify_inclusion(&proof, &root).is_ok());
+
+        let mut tampered = proof.clone();
+        tampered.entry.tool_name = Some("write_file".to_string());
+        assert!(merkle::verify_inclusion(&tampered, &root).is_err());
+
+        let mut moved = proof.clone();
+        moved.leaf_index = 3;
+        assert!(merkle::verify_inclusion(&moved, &root).is_err());
+    }
+}
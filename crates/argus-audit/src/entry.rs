@@ -3,22 +3,43 @@
 //! Each entry hashes all its own fields plus the previous entry's hash,
 //! creating a tamper-evident Merkle chain. Any modification to any
 //! historical entry invalidates all subsequent entry_hashes.
+//!
+//! The canonical hash input is versioned. Each entry records the format it
+//! was hashed with, so new fields can be covered without invalidating chains
+//! written under an older format:
+//!   - v1: id|timestamp|model|action|tool|args|result|session|prev
+//!   - v2: "v2"|id|timestamp|identity|model|action|tool|args|result|session|prev
 
 use sha2::{Sha256, Digest};
 use serde::{Serialize, Deserialize};
 
+/// Hash format used by entries written before versioning existed.
+pub const HASH_VERSION_V1: u32 = 1;
+/// Adds agent_identity to the hashed fields.
+pub const HASH_VERSION_V2: u32 = 2;
+/// Format used for all newly appended entries.
+pub const CURRENT_HASH_VERSION: u32 = HASH_VERSION_V2;
+
+fn default_hash_version() -> u32 {
+    HASH_VERSION_V1
+}
+
 /// A single link in the audit chain.
 /// entry_hash must be computed via compute_entry_hash() after all other fields are set.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditEntry {
     pub id: u64,
     pub timestamp_us: i64,
+    /// Canonical hash format this entry was written with (see module docs).
+    /// Rows that predate the column default to v1.
+    #[serde(default = "default_hash_version")]
+    pub hash_version: u32,
     /// The persistent agent identity — always "argus", regardless of which model is loaded.
-    /// Stored for human-readable narrative but NOT included in entry_hash so existing chains
-    /// remain verifiable after this field is added via migration.
+    /// Covered by entry_hash from v2 onward; v1 entries leave it out so chains written
+    /// before the field existed remain verifiable.
     pub agent_identity: String,
     pub agent_model: String,
-    pub action_type: String,        // "tool_call" | "model_call" | "memory_write" | "discourse_post" | "system"
+    pub action_type: String,        // "tool_call" | "invalid_tool_call" | "model_call" | "cancelled" | "approval_allowed" | "approval_edited" | "approval_denied" | "guard_approved" | "guard_rewritten" | "guard_blocked" | "memory_write" | "discourse_post" | "system"
     pub tool_name: Option<String>,
     pub args_hash: String,          // SHA-256 hex of serialized args — never the args themselves
     pub result_hash: String,        // SHA-256 hex of result — never the result itself
@@ -40,30 +61,58 @@ pub fn genesis_prev_hash() -> String {
 }
 
 impl AuditEntry {
-    /// Compute and set entry_hash from all other fields.
+    /// Canonical string hashed into entry_hash, per `hash_version`.
+    /// None for a version this build doesn't know — such an entry can't verify.
+    fn canonical(&self) -> Option<String> {
+        match self.hash_version {
+            HASH_VERSION_V1 => Some(format!(
+                "{}|{}|{}|{}|{}|{}|{}|{}|{}",
+                self.id,
+                self.timestamp_us,
+                self.agent_model,
+                self.action_type,
+                self.tool_name.as_deref().unwrap_or(""),
+                self.args_hash,
+                self.result_hash,
+                self.session_id,
+                self.prev_entry_hash,
+            )),
+            HASH_VERSION_V2 => Some(format!(
+                "v2|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}",
+                self.id,
+                self.timestamp_us,
+                self.agent_identity,
+                self.agent_model,
+                self.action_type,
+                self.tool_name.as_deref().unwrap_or(""),
+                self.args_hash,
+                self.result_hash,
+                self.session_id,
+                self.prev_entry_hash,
+            )),
+            _ => None,
+        }
+    }
+
+    /// UTC calendar date (YYYY-MM-DD) of this entry — the day root it belongs to.
+    pub fn utc_date(&self) -> Option<String> {
+        chrono::DateTime::from_timestamp_micros(self.timestamp_us)
+            .map(|t| t.format("%Y-%m-%d").to_string())
+    }
+
+    /// Compute and set entry_hash from all other fields, using `hash_version`.
     /// Must be called once all other fields are populated.
     pub fn compute_entry_hash(&mut self) {
-        let canonical = format!(
-            "{}|{}|{}|{}|{}|{}|{}|{}|{}",
-            self.id,
-            self.timestamp_us,
-            self.agent_model,
-            self.action_type,
-            self.tool_name.as_deref().unwrap_or(""),
-            self.args_hash,
-            self.result_hash,
-            self.session_id,
-            self.prev_entry_hash,
-        );
-        self.entry_hash = sha256_hex(&canonical);
+        self.entry_hash = self.canonical().map(|c| sha256_hex(&c)).unwrap_or_default();
     }
 
     /// Verify that this entry's entry_hash is consistent with its fields.
-    /// Returns false if any field has been tampered with.
+    /// Returns false if any field has been tampered with, or if the entry
+    /// claims a hash version this build doesn't recognise.
     pub fn verify(&self) -> bool {
-        let mut clone = self.clone();
-        let claimed = clone.entry_hash.clone();
-        clone.compute_entry_hash();
-        clone.entry_hash == claimed
+        match self.canonical() {
+            Some(c) => sha256_hex(&c) == self.entry_hash,
+            None => false,
+        }
     }
 }
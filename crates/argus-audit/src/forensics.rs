@@ -0,0 +1,197 @@
+//! Opt-in forensic mode — encrypted args/result stored beside the hashes.
+//!
+//! By default the chain commits to args and results by hash only, so the
+//! audit DB never holds command lines or tool output. With forensics enabled
+//! (`AuditChain::enable_forensics`), `append` also writes ChaCha20-Poly1305
+//! ciphertext of both to `audit_forensics`, keyed by a vault-held key that
+//! never touches the DB.
+//!
+//! The ciphertext is not part of the entry hash: it can be deleted for
+//! retention without breaking the chain. `reveal` decrypts and re-checks the
+//! plaintext against the committed args_hash/result_hash, so a swapped or
+//! edited ciphertext is caught rather than trusted.
+
+use argus_crypto::cipher;
+use argus_crypto::vault::SecureVault;
+use zeroize::Zeroizing;
+use crate::chain::AuditChain;
+use crate::entry::{AuditEntry, sha256_hex};
+
+/// Vault entry holding the hex-encoded 32-byte forensic key.
+pub const FORENSIC_KEY_NAME: &str = "audit_forensic_key";
+
+/// Short, stable identifier for a forensic key: first 16 hex chars of a
+/// domain-separated SHA-256 over the key. Stored per row so rows sealed
+/// under a rotated key are reported as such instead of failing to decrypt.
+fn forensic_key_id(key: &[u8]) -> String {
+    sha256_hex(&format!("ARGUS_FORENSIC_KEY|{}", hex::encode(key)))[..16].to_string()
+}
+
+/// Symmetric key used to seal forensic payloads.
+#[derive(Clone)]
+pub struct ForensicKey {
+    key: Zeroizing<Vec<u8>>,
+}
+
+impl ForensicKey {
+    pub fn from_bytes(key: &[u8]) -> Result<Self, String> {
+        if key.len() != cipher::KEY_SIZE {
+            return Err(format!(
+                "Forensic key must be {} bytes, got {}",
+                cipher::KEY_SIZE, key.len()
+            ));
+        }
+        Ok(Self { key: Zeroizing::new(key.to_vec()) })
+    }
+
+    /// Load the key from the vault. Errors if it was never created.
+    pub fn load(vault: &SecureVault) -> Result<Self, String> {
+        let key_hex = Zeroizing::new(vault.retrieve(FORENSIC_KEY_NAME).map_err(|e| e.to_string())?);
+        let key = Zeroizing::new(hex::decode(key_hex.as_str()).map_err(|e| e.to_string())?);
+        Self::from_bytes(&key)
+    }
+
+    /// Load the key, generating and persisting a fresh one on first run.
+    pub fn load_or_create(vault: &mut SecureVault) -> Result<Self, String> {
+        if let Ok(key) = Self::load(vault) {
+            return Ok(key);
+        }
+        let key = cipher::generate_key();
+        let key_hex = Zeroizing::new(hex::encode(key.as_ref()));
+        vault.store(FORENSIC_KEY_NAME, &key_hex).map_err(|e| e.to_string())?;
+        Self::from_bytes(key.as_ref())
+    }
+
+    pub fn key_id(&self) -> String {
+        forensic_key_id(&self.key)
+    }
+
+    pub(crate) fn seal(&self, plaintext: &str) -> Result<Vec<u8>, String> {
+        cipher::encrypt(&self.key, plaintext.as_bytes()).map_err(|e| e.to_string())
+    }
+
+    fn open(&self, ciphertext: &[u8]) -> Result<String, String> {
+        let plain = cipher::decrypt(&self.key, ciphertext).map_err(|e| e.to_string())?;
+        String::from_utf8(plain.to_vec()).map_err(|e| e.to_string())
+    }
+}
+
+/// Raw `audit_forensics` row for one entry.
+pub struct ForensicBlobs {
+    pub args_ct:   Option<Vec<u8>>,
+    pub result_ct: Option<Vec<u8>>,
+    pub key_id:    String,
+}
+
+/// A decrypted entry, with every plaintext re-checked against the chain.
+#[derive(Debug)]
+pub struct ForensicRecord {
+    pub entry:        AuditEntry,
+    pub entry_intact: bool,           // entry_hash matches the entry's fields
+    pub key_id:       Option<String>, // None — entry was logged without forensics
+    pub args:         Option<String>,
+    pub result:       Option<String>,
+    pub args_match:   Option<bool>,   // None — nothing was stored to check
+    pub result_match: Option<bool>,
+}
+
+impl ForensicRecord {
+    /// True only if the entry is intact and every stored payload matches
+    /// its committed hash.
+    pub fn is_consistent(&self) -> bool {
+        self.entry_intact
+            && self.args_match != Some(false)
+            && self.result_match != Some(false)
+    }
+}
+
+/// Decrypt the forensic payload for `entry_id` and re-check it against the
+/// entry's committed hashes. An entry logged with forensics disabled comes
+/// back with no plaintext. Errors if the entry is missing, the row was
+/// sealed under a different key, or decryption fails.
+pub fn reveal(chain: &AuditChain, entry_id: u64, key: &ForensicKey) -> Result<ForensicRecord, String> {
+    let entry = chain.entry(entry_id)?;
+    let entry_intact = entry.verify();
+
+    let Some(blobs) = chain.forensic_blobs(entry_id)? else {
+        return Ok(ForensicRecord {
+            entry, entry_intact, key_id: None,
+            args: None, result: None, args_match: None, result_match: None,
+        });
+    };
+
+    if blobs.key_id != key.key_id() {
+        return Err(format!(
+            "Entry {} was sealed with forensic key {}, vault holds {}",
+            entry_id, blobs.key_id, key.key_id()
+        ));
+    }
+
+    let open = |ct: Option<Vec<u8>>, what: &str| -> Result<Option<String>, String> {
+        ct.map(|ct| key.open(&ct).map_err(|e| format!("Entry {} {}: {}", entry_id, what, e)))
+            .transpose()
+    };
+    let args   = open(blobs.args_ct, "args")?;
+    let result = open(blobs.result_ct, "result")?;
+
+    Ok(ForensicRecord {
+        args_match:   args.as_deref().map(|a| sha256_hex(a) == entry.args_hash),
+        result_match: result.as_deref().map(|r| sha256_hex(r) == entry.result_hash),
+        entry,
+        entry_intact,
+        key_id: Some(blobs.key_id),
+        args,
+        result,
+    })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reveal_roundtrip_and_swap_detection() {
+        let key = ForensicKey::from_bytes(cipher::generate_key().as_ref()).unwrap();
+        let path = std::env::temp_dir().join(format!("argus-audit-{}.db", uuid::Uuid::new_v4()));
+        {
+            let chain = AuditChain::open(&path).unwrap();
+            chain.append("m", "tool_call", Some("shell"), Some("ls /"), Some("ok")).unwrap();
+            chain.enable_forensics(key.clone());
+            chain.append("m", "tool_call", Some("shell"), Some("rm -rf /tmp/x"), Some("done")).unwrap();
+            chain.append("m", "tool_call", Some("shell"), Some("whoami"), None).unwrap();
+
+            // Logged before forensics was on — hashes only.
+            let plain = reveal(&chain, 1, &key).unwrap();
+            assert!(plain.key_id.is_none() && plain.is_consistent());
+
+            let rec = reveal(&chain, 2, &key).unwrap();
+            assert_eq!(rec.args.as_deref(), Some("rm -rf /tmp/x"));
+            assert_eq!(rec.result.as_deref(), Some("done"));
+            assert!(rec.is_consistent());
+
+            let rec = reveal(&chain, 3, &key).unwrap();
+            assert_eq!(rec.result_match, None);
+            assert!(rec.is_consistent());
+
+            let other = ForensicKey::from_bytes(cipher::generate_key().as_ref()).unwrap();
+            assert!(reveal(&chain, 2, &other).is_err());
+        }
+
+        // Ciphertext moved onto another entry no longer matches its hash.
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute(
+            "UPDATE audit_forensics SET args_ct = (SELECT args_ct FROM audit_forensics WHERE entry_id = 2)
+             WHERE entry_id = 3",
+            [],
+        ).unwrap();
+        drop(conn);
+
+        let chain = AuditChain::open(&path).unwrap();
+        let rec = reveal(&chain, 3, &key).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(rec.args_match, Some(false));
+        assert!(!rec.is_consistent());
+    }
+}
@@ -4,16 +4,35 @@
 //! to an append-only SQLite Merkle chain. Each entry hashes all fields
 //! plus the previous entry's hash, creating a tamper-evident record.
 //!
-//! Daily roots are HMAC-signed and anchored to Supabase as external
-//! tamper-evidence. Chain integrity is verified on every daemon startup.
+//! Daily roots are binary Merkle roots over the day's entry_hashes, so a
+//! single entry can be proven with an inclusion proof. Roots are HMAC-signed
+//! and ML-DSA-signed, then anchored to Supabase as external tamper-evidence.
+//! Recent entries are verified on every daemon startup; `verify_chain` walks
+//! the whole chain and cross-checks every anchor. `bundle` exports a date
+//! range as self-describing JSONL that verifies offline. Forensic mode
+//! (opt-in) additionally keeps args/results encrypted to a vault-held key.
+//! `VaultAccessAudit` logs every vault secret read as a system entry.
 //!
 //! This delivers the SOUL.md promise: "the hundred eyes watch everything,
 //! including themselves."
 
+pub mod bundle;
 pub mod chain;
 pub mod entry;
+pub mod forensics;
+pub mod merkle;
 pub mod signer;
+pub mod vault_log;
+pub mod verify;
 
+pub use bundle::{export_bundle, verify_bundle, BundleReport, BundleVerifyOptions};
 pub use chain::AuditChain;
 pub use entry::{AuditEntry, sha256_hex, genesis_prev_hash};
-pub use signer::{sign_day_root, run_daily_anchor};
+pub use forensics::{reveal, ForensicKey, ForensicRecord};
+pub use merkle::{InclusionProof, verify_inclusion};
+pub use signer::{
+    sign_day_root, verify_day_root, run_daily_anchor,
+    AnchorVerifier, DayAnchor, PqSigningKey,
+};
+pub use vault_log::VaultAccessAudit;
+pub use verify::{verify_chain, ChainIssue, VerifyOptions, VerifyReport};
@@ -0,0 +1,284 @@
+//! Binary Merkle tree over a day's entry_hashes.
+//!
+//! The day root signed by `run_daily_anchor` is the root of this tree, so a
+//! single entry can be proven against it with O(log n) sibling hashes instead
+//! of handing an auditor every entry_hash for the day.
+//!
+//! Construction (RFC 6962 style, hex strings throughout):
+//!   - leaf = SHA-256("LEAF|" + entry_hash)
+//!   - node = SHA-256("NODE|" + left + "|" + right)
+//!   - an odd node at the end of a level is promoted unchanged — never
+//!     duplicated, so two different leaf sets can't share a root.
+//!
+//! Leaf and node prefixes are distinct so an interior node can never be
+//! passed off as a leaf.
+
+use serde::{Serialize, Deserialize};
+use crate::entry::{AuditEntry, sha256_hex};
+
+/// Which side of the running hash a sibling sits on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// One step of an inclusion path, leaf to root.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub side: Side,
+    pub hash: String,
+}
+
+/// Everything an auditor needs to check one entry against a signed day root.
+/// Carries the full entry (hashes only — never args or results) so the
+/// auditor can re-derive its entry_hash rather than trusting it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub date: String,           // YYYY-MM-DD (UTC) the entry belongs to
+    pub entry: AuditEntry,
+    pub leaf_index: usize,      // position of the entry within the day, id order
+    pub leaf_count: usize,      // total entries that day
+    pub path: Vec<ProofStep>,   // siblings from leaf level upward
+}
+
+/// Hash an entry_hash into a leaf.
+pub fn leaf_hash(entry_hash: &str) -> String {
+    sha256_hex(&format!("LEAF|{}", entry_hash))
+}
+
+/// Hash two child nodes into their parent.
+pub fn node_hash(left: &str, right: &str) -> String {
+    sha256_hex(&format!("NODE|{}|{}", left, right))
+}
+
+/// Root for a day with no entries. Kept identical to the pre-Merkle format
+/// so empty-day anchors remain comparable.
+pub fn empty_day_root(date: &str) -> String {
+    sha256_hex(&format!("EMPTY_DAY_{}", date))
+}
+
+/// Merkle root over `entry_hashes` in the given (id) order.
+/// Returns None for an empty slice — callers decide what an empty day means.
+pub fn merkle_root(entry_hashes: &[String]) -> Option<String> {
+    if entry_hashes.is_empty() {
+        return None;
+    }
+    let mut level: Vec<String> = entry_hashes.iter().map(|h| leaf_hash(h)).collect();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.pop()
+}
+
+/// Incremental Merkle root over a stream of entry_hashes.
+///
+/// Keeps one hash per complete power-of-two subtree (O(log n) memory) and
+/// produces the same root as `merkle_root`, so full-chain verification can
+/// recompute day roots without buffering a whole day.
+#[derive(Debug, Clone, Default)]
+pub struct MerkleBuilder {
+    stack: Vec<(usize, String)>, // (subtree leaf count, subtree hash), largest first
+    count: usize,
+}
+
+impl MerkleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, entry_hash: &str) {
+        let mut node = (1usize, leaf_hash(entry_hash));
+        while let Some((size, _)) = self.stack.last() {
+            if *size != node.0 {
+                break;
+            }
+            let (size, left) = self.stack.pop().expect("checked non-empty");
+            node = (size * 2, node_hash(&left, &node.1));
+        }
+        self.stack.push(node);
+        self.count += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Root of everything pushed so far. None if nothing was pushed.
+    pub fn root(&self) -> Option<String> {
+        let mut iter = self.stack.iter().rev();
+        let (_, last) = iter.next()?;
+        Some(iter.fold(last.clone(), |acc, (_, left)| node_hash(left, &acc)))
+    }
+}
+
+/// Sibling path for the leaf at `index`. None if out of range.
+pub fn merkle_path(entry_hashes: &[String], index: usize) -> Option<Vec<ProofStep>> {
+    if index >= entry_hashes.len() {
+        return None;
+    }
+    let mut level: Vec<String> = entry_hashes.iter().map(|h| leaf_hash(h)).collect();
+    let mut idx = index;
+    let mut path = Vec::new();
+
+    while level.len() > 1 {
+        if idx % 2 == 1 {
+            path.push(ProofStep { side: Side::Left, hash: level[idx - 1].clone() });
+        } else if idx + 1 < level.len() {
+            path.push(ProofStep { side: Side::Right, hash: level[idx + 1].clone() });
+        }
+        // else: odd node promoted, no sibling at this level
+        level = next_level(&level);
+        idx /= 2;
+    }
+
+    Some(path)
+}
+
+/// Recompute the root implied by a leaf's entry_hash and its sibling path.
+pub fn root_from_path(entry_hash: &str, path: &[ProofStep]) -> String {
+    path.iter().fold(leaf_hash(entry_hash), |acc, step| match step.side {
+        Side::Left  => node_hash(&step.hash, &acc),
+        Side::Right => node_hash(&acc, &step.hash),
+    })
+}
+
+/// Check an inclusion proof against a published (signed) day root.
+///
+/// Verifies, in order: the entry's own entry_hash is consistent with its
+/// fields, the entry's timestamp falls on the proof's date, and the sibling
+/// path hashes up to `signed_day_root`. Needs no database — only the proof
+/// and the anchored root. The root's HMAC is checked separately with
+/// `signer::verify_day_root`.
+pub fn verify_inclusion(proof: &InclusionProof, signed_day_root: &str) -> Result<(), String> {
+    if !proof.entry.verify() {
+        return Err(format!(
+            "Entry {} has corrupted entry_hash — proof rejected",
+            proof.entry.id
+        ));
+    }
+    if proof.leaf_index >= proof.leaf_count {
+        return Err(format!(
+            "Leaf index {} out of range for {} entries",
+            proof.leaf_index, proof.leaf_count
+        ));
+    }
+    let entry_date = proof.entry.utc_date().ok_or("Entry timestamp out of range")?;
+    if entry_date != proof.date {
+        return Err(format!(
+            "Entry {} is dated {}, proof claims {}",
+            proof.entry.id, entry_date, proof.date
+        ));
+    }
+    let sides: Vec<Side> = proof.path.iter().map(|s| s.side).collect();
+    if sides != path_shape(proof.leaf_index, proof.leaf_count) {
+        return Err(format!(
+            "Inclusion path for entry {} does not match position {} of {}",
+            proof.entry.id, proof.leaf_index, proof.leaf_count
+        ));
+    }
+    let computed = root_from_path(&proof.entry.entry_hash, &proof.path);
+    if computed != signed_day_root {
+        return Err(format!(
+            "Inclusion proof for entry {} does not match day root for {}",
+            proof.entry.id, proof.date
+        ));
+    }
+    Ok(())
+}
+
+/// The sibling sides a genuine path must have for leaf `index` of `count`.
+/// Binds the proof to a position so a path can't be replayed at another index.
+fn path_shape(index: usize, count: usize) -> Vec<Side> {
+    let mut sides = Vec::new();
+    let (mut idx, mut len) = (index, count);
+    while len > 1 {
+        if idx % 2 == 1 {
+            sides.push(Side::Left);
+        } else if idx + 1 < len {
+            sides.push(Side::Right);
+        }
+        idx /= 2;
+        len = len.div_ceil(2);
+    }
+    sides
+}
+
+fn next_level(level: &[String]) -> Vec<String> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [l, r] => node_hash(l, r),
+            [single] => single.clone(),
+            _ => unreachable!("chunks(2) yields one or two items"),
+        })
+        .collect()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashes(n: usize) -> Vec<String> {
+        (0..n).map(|i| sha256_hex(&format!("entry-{}", i))).collect()
+    }
+
+    #[test]
+    fn every_leaf_proves_against_root() {
+        for n in 1..=9 {
+            let leaves = hashes(n);
+            let root = merkle_root(&leaves).unwrap();
+            for (i, leaf) in leaves.iter().enumerate() {
+                let path = merkle_path(&leaves, i).unwrap();
+                assert_eq!(root_from_path(leaf, &path), root, "n={} i={}", n, i);
+            }
+        }
+    }
+
+    #[test]
+    fn single_leaf_root_is_leaf_hash() {
+        let leaves = hashes(1);
+        assert_eq!(merkle_root(&leaves).unwrap(), leaf_hash(&leaves[0]));
+        assert!(merkle_path(&leaves, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn odd_node_is_not_duplicated() {
+        let three = hashes(3);
+        let mut four = three.clone();
+        four.push(three[2].clone());
+        assert_ne!(merkle_root(&three), merkle_root(&four));
+    }
+
+    #[test]
+    fn wrong_leaf_fails() {
+        let leaves = hashes(5);
+        let root = merkle_root(&leaves).unwrap();
+        let path = merkle_path(&leaves, 2).unwrap();
+        assert_ne!(root_from_path(&leaves[3], &path), root);
+    }
+
+    #[test]
+    fn builder_matches_batch_root() {
+        for n in 1..=17 {
+            let leaves = hashes(n);
+            let mut builder = MerkleBuilder::new();
+            leaves.iter().for_each(|h| builder.push(h));
+            assert_eq!(builder.root(), merkle_root(&leaves), "n={}", n);
+        }
+        assert!(MerkleBuilder::new().root().is_none());
+    }
+
+    #[test]
+    fn empty_and_out_of_range() {
+        assert!(merkle_root(&[]).is_none());
+        assert!(merkle_path(&hashes(2), 2).is_none());
+    }
+}
@@ -1,14 +1,29 @@
 //! Daily signing and Supabase anchoring for the audit chain.
 //!
+//! Each day root gets two signatures:
+//!   - HMAC-SHA256 with the vault's `audit_hmac_key` (symmetric — anyone who
+//!     can verify it can also forge it)
+//!   - ML-DSA-65 (Dilithium-3) detached signature with a vault-held keypair,
+//!     so third parties can check anchors with only the public key
+//!
 //! Intentionally does NOT depend on argus-core to avoid circular crate deps.
 //! Takes raw supabase_url/key strings and makes HTTP calls directly.
 
 use hmac::{Hmac, Mac};
 use sha2::Sha256;
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroizing;
+use argus_crypto::{SecureVault, pq};
 use crate::chain::AuditChain;
+use crate::entry::sha256_hex;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Vault entry holding the hex-encoded ML-DSA secret key.
+pub const PQ_SECRET_KEY_NAME: &str = "audit_pq_secret_key";
+/// Vault entry holding the hex-encoded ML-DSA public key.
+pub const PQ_PUBLIC_KEY_NAME: &str = "audit_pq_public_key";
+
 /// Sign a day root with HMAC-SHA256 using the provided key material.
 /// Returns lowercase hex-encoded HMAC.
 pub fn sign_day_root(day_root: &str, vault_key: &[u8]) -> String {
@@ -18,29 +33,173 @@ pub fn sign_day_root(day_root: &str, vault_key: &[u8]) -> String {
     hex::encode(mac.finalize().into_bytes())
 }
 
+/// Check a day root's HMAC signature in constant time.
+pub fn verify_day_root(day_root: &str, signature: &str, vault_key: &[u8]) -> bool {
+    let Ok(sig) = hex::decode(signature) else { return false };
+    let mut mac = HmacSha256::new_from_slice(vault_key)
+        .expect("HMAC accepts any key length");
+    mac.update(day_root.as_bytes());
+    mac.verify_slice(&sig).is_ok()
+}
+
+// ── Post-quantum signatures ──────────────────────────────────────────────
+
+/// The exact bytes ML-DSA signs. Binds the root to its date so a valid
+/// signature can't be replayed as another day's anchor.
+fn pq_message(date: &str, day_root: &str) -> Vec<u8> {
+    format!("ARGUS_DAY_ROOT|{}|{}", date, day_root).into_bytes()
+}
+
+/// Short fingerprint of an ML-DSA public key — stored with each anchor so
+/// verifiers can tell which key signed it across rotations.
+pub fn pq_key_id(public_key: &[u8]) -> String {
+    sha256_hex(&hex::encode(public_key))[..16].to_string()
+}
+
+/// Produce a hex-encoded ML-DSA-65 detached signature over a day root.
+pub fn pq_sign_day_root(date: &str, day_root: &str, secret_key: &[u8]) -> Result<String, String> {
+    pq::sign::sign(secret_key, &pq_message(date, day_root)).map(hex::encode)
+}
+
+/// Check a hex-encoded ML-DSA-65 signature over a day root.
+pub fn pq_verify_day_root(date: &str, day_root: &str, signature: &str, public_key: &[u8]) -> bool {
+    let Ok(sig) = hex::decode(signature) else { return false };
+    pq::sign::verify(public_key, &pq_message(date, day_root), &sig)
+}
+
+/// The daemon's ML-DSA signing keypair, held in `SecureVault`.
+/// The secret half is scrubbed from memory on drop.
+pub struct PqSigningKey {
+    secret_key: Zeroizing<Vec<u8>>,
+    pub public_key: Vec<u8>,
+}
+
+impl PqSigningKey {
+    /// Load the keypair from the vault. Errors if either half is missing.
+    pub fn load(vault: &SecureVault) -> Result<Self, String> {
+        let sk_hex = Zeroizing::new(vault.retrieve(PQ_SECRET_KEY_NAME).map_err(|e| e.to_string())?);
+        let pk_hex = vault.retrieve(PQ_PUBLIC_KEY_NAME).map_err(|e| e.to_string())?;
+        Ok(Self {
+            secret_key: Zeroizing::new(hex::decode(sk_hex.as_str()).map_err(|e| e.to_string())?),
+            public_key: hex::decode(pk_hex).map_err(|e| e.to_string())?,
+        })
+    }
+
+    /// Load the keypair, generating and persisting a fresh one on first run.
+    pub fn load_or_create(vault: &mut SecureVault) -> Result<Self, String> {
+        if let Ok(key) = Self::load(vault) {
+            return Ok(key);
+        }
+        let kp = pq::sign::generate_keypair();
+        let sk_hex = Zeroizing::new(hex::encode(&kp.secret_key));
+        vault.store(PQ_SECRET_KEY_NAME, &sk_hex).map_err(|e| e.to_string())?;
+        vault.store(PQ_PUBLIC_KEY_NAME, &hex::encode(&kp.public_key)).map_err(|e| e.to_string())?;
+        Ok(Self {
+            secret_key: Zeroizing::new(kp.secret_key),
+            public_key: kp.public_key,
+        })
+    }
+
+    pub fn key_id(&self) -> String {
+        pq_key_id(&self.public_key)
+    }
+
+    /// Hex-encoded public key — what gets handed to third-party verifiers.
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(&self.public_key)
+    }
+
+    pub fn sign(&self, date: &str, day_root: &str) -> Result<String, String> {
+        pq_sign_day_root(date, day_root, &self.secret_key)
+    }
+}
+
+/// One row of `argus_audit_anchors`, as published by `anchor_to_supabase`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayAnchor {
+    pub anchor_date: String,
+    pub day_root: String,
+    pub signature: String,
+    pub entry_count: i64,
+    #[serde(default)]
+    pub pq_signature: Option<String>,
+    #[serde(default)]
+    pub pq_key_id: Option<String>,
+}
+
+/// Public-key-only verifier for published anchors. Holds no secret
+/// material — safe to hand to an auditor.
+pub struct AnchorVerifier {
+    public_key: Vec<u8>,
+}
+
+impl AnchorVerifier {
+    pub fn new(public_key: Vec<u8>) -> Self {
+        Self { public_key }
+    }
+
+    /// Build from the hex public key printed by `argus audit pubkey`.
+    pub fn from_hex(public_key_hex: &str) -> Result<Self, String> {
+        hex::decode(public_key_hex.trim())
+            .map(Self::new)
+            .map_err(|e| format!("Invalid ML-DSA public key hex: {}", e))
+    }
+
+    pub fn key_id(&self) -> String {
+        pq_key_id(&self.public_key)
+    }
+
+    /// Check an anchor's ML-DSA signature. Fails if the anchor has no PQ
+    /// signature or was signed by a different key.
+    pub fn verify(&self, anchor: &DayAnchor) -> Result<(), String> {
+        let sig = anchor.pq_signature.as_deref()
+            .ok_or_else(|| format!("Anchor {} has no ML-DSA signature", anchor.anchor_date))?;
+        if let Some(ref id) = anchor.pq_key_id {
+            if *id != self.key_id() {
+                return Err(format!(
+                    "Anchor {} signed by key {}, verifier holds {}",
+                    anchor.anchor_date, id, self.key_id()
+                ));
+            }
+        }
+        if !pq_verify_day_root(&anchor.anchor_date, &anchor.day_root, sig, &self.public_key) {
+            return Err(format!(
+                "Anchor {} ML-DSA signature invalid — anchor forged or tampered",
+                anchor.anchor_date
+            ));
+        }
+        Ok(())
+    }
+}
+
+// ── Supabase anchoring ───────────────────────────────────────────────────
+
 /// Write a signed audit anchor to Supabase.
 /// Table: argus_audit_anchors (must exist — see ARGUS_CHUNK4_AUDIT_CHAIN.md Step 10).
+/// PQ columns come from migrations/009_argus_audit_anchors_pq.sql and are only
+/// sent when a PQ signature is present, so older tables keep working.
 /// Deliberately bypasses SupabaseClient to avoid circular crate dependency.
 pub async fn anchor_to_supabase(
     supabase_url: &str,
     supabase_key: &str,
-    date: &str,
-    day_root: &str,
-    signature: &str,
-    entry_count: i64,
+    anchor: &DayAnchor,
 ) -> Result<(), String> {
     let url = format!(
         "{}/rest/v1/argus_audit_anchors",
         supabase_url.trim_end_matches('/')
     );
 
-    let data = serde_json::json!({
-        "anchor_date": date,
-        "day_root":    day_root,
-        "signature":   signature,
-        "entry_count": entry_count,
+    let mut data = serde_json::json!({
+        "anchor_date": anchor.anchor_date,
+        "day_root":    anchor.day_root,
+        "signature":   anchor.signature,
+        "entry_count": anchor.entry_count,
         "anchored_at": chrono::Utc::now().to_rfc3339(),
     });
+    if let (Some(sig), Some(id)) = (&anchor.pq_signature, &anchor.pq_key_id) {
+        data["pq_signature"] = serde_json::json!(sig);
+        data["pq_key_id"]    = serde_json::json!(id);
+    }
 
     let resp = reqwest::Client::new()
         .post(&url)
@@ -62,10 +221,39 @@ pub async fn anchor_to_supabase(
     Ok(())
 }
 
-/// Full daily anchor routine. Called at midnight UTC by the daemon.
+/// Read every published anchor from Supabase, oldest first.
+/// Used by full-chain verification to cross-check recomputed day roots.
+pub async fn fetch_anchors(supabase_url: &str, supabase_key: &str) -> Result<Vec<DayAnchor>, String> {
+    let url = format!(
+        "{}/rest/v1/argus_audit_anchors?select=*&order=anchor_date.asc",
+        supabase_url.trim_end_matches('/')
+    );
+
+    let resp = reqwest::Client::new()
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", supabase_key))
+        .header("apikey", supabase_key)
+        .send()
+        .await
+        .map_err(|e| format!("Supabase anchor fetch failed: {}", e))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body   = resp.text().await.unwrap_or_default();
+        return Err(format!("Supabase anchor error {}: {}", status, body));
+    }
+
+    resp.json::<Vec<DayAnchor>>()
+        .await
+        .map_err(|e| format!("Supabase anchor parse failed: {}", e))
+}
+
+/// Full daily anchor routine. Called just after midnight UTC by the daemon,
+/// so it anchors the UTC day that has just closed — anchoring the new day
+/// would sign a root over (almost) nothing.
 ///
-/// 1. Compute day root (Merkle root of today's entry_hashes)
-/// 2. HMAC-sign with vault_key
+/// 1. Compute day root (binary Merkle root of the day's entry_hashes)
+/// 2. HMAC-sign with vault_key, and ML-DSA-sign with pq_key if available
 /// 3. Write anchor to Supabase
 /// 4. Send Telegram notification
 pub async fn run_daily_anchor(
@@ -73,23 +261,42 @@ pub async fn run_daily_anchor(
     supabase_url: &str,
     supabase_key: &str,
     vault_key: &[u8],
+    pq_key: Option<&PqSigningKey>,
     telegram_token: &str,
     telegram_chat_id: i64,
 ) -> Result<(), String> {
-    let today       = chrono::Utc::now().format("%Y-%m-%d").to_string();
-    let day_root    = chain.compute_day_root(&today)?;
-    let signature   = sign_day_root(&day_root, vault_key);
-    let entry_count = chain.entry_count_today()?;
+    let date = (chrono::Utc::now().date_naive() - chrono::Duration::days(1))
+        .format("%Y-%m-%d")
+        .to_string();
+    let day_root = chain.compute_day_root(&date)?;
+
+    let (pq_signature, pq_key_id) = match pq_key {
+        Some(k) => (Some(k.sign(&date, &day_root)?), Some(k.key_id())),
+        None    => (None, None),
+    };
+    let anchor = DayAnchor {
+        signature:   sign_day_root(&day_root, vault_key),
+        entry_count: chain.entry_count_for(&date)?,
+        anchor_date: date,
+        day_root,
+        pq_signature,
+        pq_key_id,
+    };
 
-    anchor_to_supabase(supabase_url, supabase_key, &today, &day_root, &signature, entry_count).await?;
+    anchor_to_supabase(supabase_url, supabase_key, &anchor).await?;
 
     // Telegram notification — fire and forget (non-critical)
+    let pq_line = match anchor.pq_key_id {
+        Some(ref id) => format!("ML-DSA key: {}", id),
+        None         => "ML-DSA: not configured (HMAC only)".to_string(),
+    };
     let msg = format!(
-        "Audit chain anchored\nDate: {}\nEntries today: {}\nRoot: {}...\nSig: {}...",
-        today,
-        entry_count,
-        &day_root[..16],
-        &signature[..16],
+        "Audit chain anchored\nDate: {}\nEntries: {}\nRoot: {}...\nSig: {}...\n{}",
+        anchor.anchor_date,
+        anchor.entry_count,
+        &anchor.day_root[..16],
+        &anchor.signature[..16],
+        pq_line,
     );
     let _ = reqwest::Client::new()
         .post(format!("https://api.telegram.org/bot{}/sendMessage", telegram_token))
@@ -102,3 +309,56 @@ pub async fn run_daily_anchor(
 
     Ok(())
 }
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed_anchor(kp: &pq::sign::SigningKeypair) -> DayAnchor {
+        let root = sha256_hex("root");
+        DayAnchor {
+            anchor_date: "2026-01-01".to_string(),
+            signature: sign_day_root(&root, b"hmac-key"),
+            pq_signature: Some(pq_sign_day_root("2026-01-01", &root, &kp.secret_key).unwrap()),
+            pq_key_id: Some(pq_key_id(&kp.public_key)),
+            day_root: root,
+            entry_count: 3,
+        }
+    }
+
+    #[test]
+    fn hmac_roundtrip() {
+        let root = sha256_hex("root");
+        let sig = sign_day_root(&root, b"hmac-key");
+        assert!(verify_day_root(&root, &sig, b"hmac-key"));
+        assert!(!verify_day_root(&root, &sig, b"other-key"));
+    }
+
+    #[test]
+    fn pq_anchor_verifies_with_public_key_only() {
+        let kp = pq::sign::generate_keypair();
+        let anchor = signed_anchor(&kp);
+        let verifier = AnchorVerifier::from_hex(&hex::encode(&kp.public_key)).unwrap();
+        assert!(verifier.verify(&anchor).is_ok());
+    }
+
+    #[test]
+    fn pq_anchor_rejects_tampering() {
+        let kp = pq::sign::generate_keypair();
+        let verifier = AnchorVerifier::new(kp.public_key.clone());
+
+        let mut wrong_root = signed_anchor(&kp);
+        wrong_root.day_root = sha256_hex("forged");
+        assert!(verifier.verify(&wrong_root).is_err());
+
+        let mut wrong_date = signed_anchor(&kp);
+        wrong_date.anchor_date = "2026-01-02".to_string();
+        assert!(verifier.verify(&wrong_date).is_err());
+
+        let mut unsigned = signed_anchor(&kp);
+        unsigned.pq_signature = None;
+        assert!(verifier.verify(&unsigned).is_err());
+    }
+}
@@ -0,0 +1,30 @@
+//! Vault read log — records every secret read as a `system` audit entry.
+//!
+//! Attached to `SecureVault` via `set_access_log`. The entry's args are
+//! "scope:name" and the result is "granted" or "denied"; secret values
+//! never reach the chain.
+
+use std::sync::Arc;
+use argus_crypto::access::{AccessEvent, AccessLog};
+use crate::chain::AuditChain;
+
+pub struct VaultAccessAudit {
+    chain: Arc<AuditChain>,
+    agent_model: String,
+}
+
+impl VaultAccessAudit {
+    pub fn new(chain: Arc<AuditChain>, agent_model: &str) -> Self {
+        Self { chain, agent_model: agent_model.to_string() }
+    }
+}
+
+impl AccessLog for VaultAccessAudit {
+    fn record(&self, event: &AccessEvent) {
+        let args = format!("{}:{}", event.scope, event.name);
+        let result = if event.granted { "granted" } else { "denied" };
+        if let Err(e) = self.chain.append(&self.agent_model, "system", Some("vault_read"), Some(&args), Some(result)) {
+            eprintln!("[!] Failed to audit vault read {}: {}", args, e);
+        }
+    }
+}
@@ -0,0 +1,380 @@
+//! Full-chain verification and tamper localisation.
+//!
+//! `verify_recent()` checks a window and stops at the first failure. This
+//! walks the entire chain from genesis, reports every problem it finds, and
+//! cross-checks each recomputed day root against the published anchors.
+//!
+//! Memory is bounded: entries are streamed one at a time via
+//! `AuditChain::for_each_entry`, and each day's root is built incrementally
+//! (`MerkleBuilder`, O(log n) hashes per day).
+
+use std::collections::BTreeMap;
+use std::fmt;
+use sha2::{Sha256, Digest};
+use crate::chain::AuditChain;
+use crate::entry::{AuditEntry, genesis_prev_hash, CURRENT_HASH_VERSION};
+use crate::merkle::{MerkleBuilder, empty_day_root};
+use crate::signer::{AnchorVerifier, DayAnchor, verify_day_root};
+
+/// A single problem found during a full-chain walk.
+#[derive(Debug, Clone)]
+pub enum ChainIssue {
+    /// entry_hash doesn't match the entry's own fields — the row was edited.
+    CorruptEntry { id: u64 },
+    /// Entry claims a hash format this build doesn't know.
+    UnknownHashVersion { id: u64, version: u32 },
+    /// First entry doesn't chain from the genesis hash — history was truncated.
+    BadGenesis { id: u64 },
+    /// prev_entry_hash doesn't match the preceding entry — rows removed, reordered or rewritten.
+    BrokenLink { prev_id: u64, id: u64 },
+    /// Ids skip — rows were deleted.
+    IdGap { after: u64, next: u64 },
+    /// Entry timestamp can't be mapped to a date.
+    BadTimestamp { id: u64 },
+    /// Recomputed day root differs from the anchored one.
+    DayRootMismatch { date: String, anchored: String, computed: String },
+    /// Anchored entry count differs from what the chain holds for that day.
+    EntryCountMismatch { date: String, anchored: i64, computed: i64 },
+    /// Anchor's HMAC doesn't verify under the provided key.
+    BadHmac { date: String },
+    /// Anchor's ML-DSA signature doesn't verify under the provided public key.
+    BadPqSignature { date: String, reason: String },
+    /// An exported bundle contradicts itself (day records, footer, ordering).
+    BundleInconsistent { reason: String },
+}
+
+impl fmt::Display for ChainIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainIssue::CorruptEntry { id } =>
+                write!(f, "entry {}: entry_hash does not match its fields", id),
+            ChainIssue::UnknownHashVersion { id, version } =>
+                write!(f, "entry {}: unknown hash version {}", id, version),
+            ChainIssue::BadGenesis { id } =>
+                write!(f, "entry {}: first entry does not chain from GENESIS", id),
+            ChainIssue::BrokenLink { prev_id, id } =>
+                write!(f, "entries {} → {}: prev_entry_hash does not match", prev_id, id),
+            ChainIssue::IdGap { after, next } =>
+                write!(f, "entries {} → {}: {} id(s) missing", after, next, next - after - 1),
+            ChainIssue::BadTimestamp { id } =>
+                write!(f, "entry {}: timestamp out of range", id),
+            ChainIssue::DayRootMismatch { date, anchored, computed } =>
+                write!(f, "day {}: anchored root {}… but chain gives {}…",
+                    date, &anchored[..anchored.len().min(16)], &computed[..computed.len().min(16)]),
+            ChainIssue::EntryCountMismatch { date, anchored, computed } =>
+                write!(f, "day {}: anchored {} entries but chain holds {}", date, anchored, computed),
+            ChainIssue::BadHmac { date } =>
+                write!(f, "day {}: anchor HMAC invalid", date),
+            ChainIssue::BadPqSignature { date, reason } =>
+                write!(f, "day {}: {}", date, reason),
+            ChainIssue::BundleInconsistent { reason } =>
+                write!(f, "bundle: {}", reason),
+        }
+    }
+}
+
+/// What to check anchors against. Everything is optional — with no anchors
+/// the walk still verifies entry hashes and links.
+#[derive(Default)]
+pub struct VerifyOptions<'a> {
+    pub anchors: &'a [DayAnchor],
+    pub hmac_key: Option<&'a [u8]>,
+    pub pq_verifier: Option<&'a AnchorVerifier>,
+}
+
+/// Summary of a full-chain walk. Individual issues go to the caller's
+/// callback as they're found; only counts are kept here.
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub entries_checked: u64,
+    pub days_seen: usize,
+    pub anchors_checked: usize,
+    /// Anchors whose root matched the pre-Merkle (flat SHA-256) format.
+    pub legacy_anchors: usize,
+    /// Anchors with no ML-DSA signature (HMAC only).
+    pub pq_unsigned: usize,
+    /// Days with entries but no anchor (typically today).
+    pub unanchored_days: usize,
+    pub issues: usize,
+}
+
+impl VerifyReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues == 0
+    }
+}
+
+/// Running roots for one day. Tracks both the Merkle root and the flat
+/// SHA-256 of concatenated hashes used before Merkle day roots, so anchors
+/// published under either format can be cross-checked.
+#[derive(Clone)]
+struct DayAccumulator {
+    merkle: MerkleBuilder,
+    legacy: Sha256,
+}
+
+impl DayAccumulator {
+    fn new() -> Self {
+        Self { merkle: MerkleBuilder::new(), legacy: Sha256::new() }
+    }
+
+    fn push(&mut self, entry_hash: &str) {
+        self.merkle.push(entry_hash);
+        self.legacy.update(entry_hash.as_bytes());
+    }
+}
+
+/// Incremental verifier shared by `verify_chain` (live SQLite chain) and
+/// `bundle::verify_bundle` (offline export). Entries are pushed in id order;
+/// `finish` cross-checks the accumulated day roots against anchors.
+pub(crate) struct ChainWalker {
+    report: VerifyReport,
+    days: BTreeMap<String, DayAccumulator>,
+    prev: Option<(u64, String)>,
+    first_prev_hash: String,
+}
+
+impl ChainWalker {
+    /// `first_prev_hash` is what the first entry must chain from — the
+    /// genesis hash for a full walk, or the boundary hash for a partial export.
+    pub(crate) fn new(first_prev_hash: String) -> Self {
+        Self {
+            report: VerifyReport::default(),
+            days: BTreeMap::new(),
+            prev: None,
+            first_prev_hash,
+        }
+    }
+
+    pub(crate) fn raise(&mut self, issue: ChainIssue, on_issue: &mut dyn FnMut(&ChainIssue)) {
+        self.report.issues += 1;
+        on_issue(&issue);
+    }
+
+    pub(crate) fn push(&mut self, entry: &AuditEntry, on_issue: &mut dyn FnMut(&ChainIssue)) {
+        self.report.entries_checked += 1;
+
+        if entry.verify() {
+            // ok
+        } else if entry.hash_version > CURRENT_HASH_VERSION {
+            self.raise(ChainIssue::UnknownHashVersion { id: entry.id, version: entry.hash_version }, on_issue);
+        } else {
+            self.raise(ChainIssue::CorruptEntry { id: entry.id }, on_issue);
+        }
+
+        match self.prev.take() {
+            None => {
+                if entry.prev_entry_hash != self.first_prev_hash {
+                    let issue = if self.first_prev_hash == genesis_prev_hash() {
+                        ChainIssue::BadGenesis { id: entry.id }
+                    } else {
+                        ChainIssue::BrokenLink { prev_id: entry.id.saturating_sub(1), id: entry.id }
+                    };
+                    self.raise(issue, on_issue);
+                }
+            }
+            Some((prev_id, prev_hash)) => {
+                if entry.id != prev_id + 1 {
+                    self.raise(ChainIssue::IdGap { after: prev_id, next: entry.id }, on_issue);
+                }
+                if entry.prev_entry_hash != prev_hash {
+                    self.raise(ChainIssue::BrokenLink { prev_id, id: entry.id }, on_issue);
+                }
+            }
+        }
+
+        match entry.utc_date() {
+            Some(date) => self.days.entry(date).or_insert_with(DayAccumulator::new).push(&entry.entry_hash),
+            None => self.raise(ChainIssue::BadTimestamp { id: entry.id }, on_issue),
+        }
+
+        self.prev = Some((entry.id, entry.entry_hash.clone()));
+    }
+
+    /// (merkle root, pre-Merkle flat root, entry count) for `date` as seen so far.
+    pub(crate) fn computed_day(&self, date: &str) -> (String, String, i64) {
+        match self.days.get(date) {
+            Some(acc) => (
+                acc.merkle.root().unwrap_or_else(|| empty_day_root(date)),
+                hex::encode(acc.legacy.clone().finalize()),
+                acc.merkle.len() as i64,
+            ),
+            None => {
+                let empty = empty_day_root(date);
+                (empty.clone(), empty, 0)
+            }
+        }
+    }
+
+    pub(crate) fn entries_checked(&self) -> u64 {
+        self.report.entries_checked
+    }
+
+    /// Hash of the last entry pushed, if any.
+    pub(crate) fn last_hash(&self) -> Option<&str> {
+        self.prev.as_ref().map(|(_, h)| h.as_str())
+    }
+
+    /// Cross-check day roots against `opts.anchors` and return the report.
+    pub(crate) fn finish(
+        mut self,
+        opts: &VerifyOptions,
+        on_issue: &mut dyn FnMut(&ChainIssue),
+    ) -> VerifyReport {
+        self.report.days_seen = self.days.len();
+
+        let mut anchored_dates = std::collections::BTreeSet::new();
+        for anchor in opts.anchors {
+            self.report.anchors_checked += 1;
+            anchored_dates.insert(anchor.anchor_date.clone());
+
+            let (merkle, legacy, count) = self.computed_day(&anchor.anchor_date);
+
+            if anchor.day_root == legacy && anchor.day_root != merkle {
+                self.report.legacy_anchors += 1;
+            } else if anchor.day_root != merkle {
+                self.raise(ChainIssue::DayRootMismatch {
+                    date: anchor.anchor_date.clone(),
+                    anchored: anchor.day_root.clone(),
+                    computed: merkle,
+                }, on_issue);
+            }
+
+            if anchor.entry_count != count {
+                self.raise(ChainIssue::EntryCountMismatch {
+                    date: anchor.anchor_date.clone(),
+                    anchored: anchor.entry_count,
+                    computed: count,
+                }, on_issue);
+            }
+
+            if let Some(key) = opts.hmac_key {
+                if !verify_day_root(&anchor.day_root, &anchor.signature, key) {
+                    self.raise(ChainIssue::BadHmac { date: anchor.anchor_date.clone() }, on_issue);
+                }
+            }
+
+            if anchor.pq_signature.is_none() {
+                self.report.pq_unsigned += 1;
+            } else if let Some(verifier) = opts.pq_verifier {
+                if let Err(reason) = verifier.verify(anchor) {
+                    self.raise(ChainIssue::BadPqSignature { date: anchor.anchor_date.clone(), reason }, on_issue);
+                }
+            }
+        }
+
+        self.report.unanchored_days = self.days.keys()
+            .filter(|d| !anchored_dates.contains(*d))
+            .count();
+
+        self.report
+    }
+}
+
+/// Walk the entire chain from genesis, verifying every entry and link, then
+/// cross-check each day root against `opts.anchors`. Every issue found is
+/// passed to `on_issue` as it's discovered.
+///
+/// Returns Err only if the chain can't be read at all.
+pub fn verify_chain<F>(
+    chain: &AuditChain,
+    opts: &VerifyOptions,
+    mut on_issue: F,
+) -> Result<VerifyReport, String>
+where
+    F: FnMut(&ChainIssue),
+{
+    let mut walker = ChainWalker::new(genesis_prev_hash());
+    chain.for_each_entry(|entry| {
+        walker.push(&entry, &mut on_issue);
+        Ok(())
+    })?;
+    Ok(walker.finish(opts, &mut on_issue))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::sign_day_root;
+
+    fn chain_with(n: usize) -> AuditChain {
+        let chain = AuditChain::open(":memory:").unwrap();
+        for i in 0..n {
+            chain.append("test-model", "tool_call", Some("shell"),
+                Some(&format!("echo {}", i)), Some("ok")).unwrap();
+        }
+        chain
+    }
+
+    #[test]
+    fn clean_chain_with_matching_anchor() {
+        let chain = chain_with(4);
+        let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let root = chain.compute_day_root(&date).unwrap();
+        let anchors = vec![DayAnchor {
+            anchor_date: date,
+            signature: sign_day_root(&root, b"k"),
+            day_root: root,
+            entry_count: 4,
+            pq_signature: None,
+            pq_key_id: None,
+        }];
+        let opts = VerifyOptions { anchors: &anchors, hmac_key: Some(b"k"), pq_verifier: None };
+
+        let mut found = Vec::new();
+        let report = verify_chain(&chain, &opts, |i| found.push(i.to_string())).unwrap();
+        assert!(report.is_clean(), "{:?}", found);
+        assert_eq!(report.entries_checked, 4);
+        assert_eq!(report.anchors_checked, 1);
+    }
+
+    #[test]
+    fn tampering_is_localised() {
+        let path = std::env::temp_dir().join(format!("argus-audit-{}.db", uuid::Uuid::new_v4()));
+        {
+            let chain = AuditChain::open(&path).unwrap();
+            for i in 0..6 {
+                chain.append("m", "tool_call", Some("shell"), Some(&i.to_string()), Some("ok")).unwrap();
+            }
+        }
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute("UPDATE audit_entries SET tool_name = 'read_file' WHERE id = 2", []).unwrap();
+        conn.execute("DELETE FROM audit_entries WHERE id = 5", []).unwrap();
+        drop(conn);
+
+        let chain = AuditChain::open(&path).unwrap();
+        let mut found = Vec::new();
+        let report = verify_chain(&chain, &VerifyOptions::default(), |i| found.push(i.clone())).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(report.entries_checked, 5);
+        assert!(matches!(found[0], ChainIssue::CorruptEntry { id: 2 }));
+        assert!(matches!(found[1], ChainIssue::IdGap { after: 4, next: 6 }));
+        assert!(matches!(found[2], ChainIssue::BrokenLink { prev_id: 4, id: 6 }));
+        assert_eq!(report.issues, 3);
+    }
+
+    #[test]
+    fn mismatched_anchor_reports_every_problem() {
+        let chain = chain_with(2);
+        let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let anchors = vec![DayAnchor {
+            anchor_date: date,
+            day_root: "00".repeat(32),
+            signature: "bad".to_string(),
+            entry_count: 9,
+            pq_signature: None,
+            pq_key_id: None,
+        }];
+        let opts = VerifyOptions { anchors: &anchors, hmac_key: Some(b"k"), pq_verifier: None };
+
+        let mut found = Vec::new();
+        let report = verify_chain(&chain, &opts, |i| found.push(i.clone())).unwrap();
+        assert_eq!(report.issues, 3);
+        assert!(matches!(found[0], ChainIssue::DayRootMismatch { .. }));
+        assert!(matches!(found[1], ChainIssue::EntryCountMismatch { .. }));
+        assert!(matches!(found[2], ChainIssue::BadHmac { .. }));
+    }
+}
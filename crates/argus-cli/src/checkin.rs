@@ -35,15 +35,17 @@
 //! Alert messages suppress the finding — the alert takes priority.
 
 use argus_core::agent::{
-    monthly_synthesis_agent_label, monthly_synthesis_banner, monthly_synthesis_model,
-    AgentConfig, AgentEvent, MODEL_GEMINI, MODEL_GEMMA_RUNTIME, MODEL_GROK, MODEL_GROK_BUILD, MODEL_GROK_MULTI,
-    MODEL_HAIKU, MODEL_OPUS, MODEL_SONNET,
+    monthly_synthesis_agent_label, monthly_synthesis_banner, monthly_synthesis_model, AgentConfig,
+    AgentEvent,
 };
+use argus_core::models::{self, ModelRegistry};
 use argus_core::mcp::McpClient;
 use argus_core::shell::ShellPolicy;
+use argus_core::shell_policy::Surface;
 use argus_core::supabase::{CheckinLogEntry, DiscoursePost, SupabaseClient};
 use argus_core::tools::MemoryBackend;
 use argus_core::run_agent_turn;
+use argus_core::CancelToken;
 use chrono::{Datelike, Local, NaiveDate, Timelike};
 use reqwest::Client;
 use tokio::time::{sleep, Duration};
@@ -105,8 +107,9 @@ pub fn spawn_checkin_loop(
     supabase: SupabaseClient,
     bot_token: String,
     chat_id: i64,
-    agent_config: AgentConfig,
+    mut agent_config: AgentConfig,
 ) {
+    agent_config.surface = "checkin".to_string();
     tokio::spawn(async move {
         run_checkin_loop(supabase, bot_token, chat_id, agent_config).await;
     });
@@ -418,7 +421,7 @@ async fn run_economy_report(
 
     let (model, label, instructions) = match kind {
         EconomyReport::MorningHaiku => (
-            MODEL_HAIKU,
+            models::id("haiku"),
             "MORNING PULSE",
             "You are Haiku on the morning watch. You have two jobs this turn.\n\n\
              FIRST — scan the intranet for any post tagged [FIX:HAIKU]. These are \
@@ -435,7 +438,7 @@ async fn run_economy_report(
              Specific, not performative. This is data for the record.",
         ),
         EconomyReport::EndOfDayGrok => (
-            MODEL_GROK,
+            models::id("grok"),
             "END OF DAY",
             "You are Grok closing the day. No tools — context only.\n\
              Write an end-of-day wrap (5–8 sentences): what actually moved today \
@@ -462,7 +465,7 @@ async fn run_economy_report(
 
     let http = reqwest::Client::new();
     let mut mcp = McpClient::new();
-    let shell_policy = ShellPolicy::default();
+    let shell_policy = ShellPolicy::for_surface(Surface::Checkin);
     let memory = NoopMemory;
 
     eprintln!("[checkin] Economy report — {} ({})", label, model);
@@ -475,6 +478,7 @@ async fn run_economy_report(
         &memory,
         &mut mcp,
         &http,
+        &CancelToken::new(),
         |event| {
             if let AgentEvent::ToolCall { name, preview, .. } = event {
                 eprintln!("[economy] tool blocked/attempted: {} — {}", name, preview);
@@ -672,11 +676,11 @@ async fn run_agent_checkin(
         let week_num = Local::now().iso_week().week();
         let cycle_week = ((week_num - 1) % 4) as u8;
         let sweep_model = match cycle_week {
-            0 => MODEL_HAIKU,
-            1 => MODEL_GEMINI,
-            2 => MODEL_SONNET,
-            3 => MODEL_GROK,
-            _ => MODEL_GEMMA_RUNTIME,
+            0 => models::id("haiku"),
+            1 => models::id("gemini"),
+            2 => models::id("sonnet"),
+            3 => models::id("grok"),
+            _ => models::id("gemma"),
         };
         eprintln!(
             "[checkin] Weekly research — cycle week {} — model: {} (ISO week {})",
@@ -689,7 +693,7 @@ async fn run_agent_checkin(
         &weekly_config
     } else {
         weekly_config = AgentConfig {
-            model: MODEL_HAIKU.to_string(),
+            model: models::id("haiku").to_string(),
             ..config.clone()
         };
         &weekly_config
@@ -753,7 +757,7 @@ finding than something manufactured."#,
     // default shell policy (blocks HIGH risk), noop memory (embedding handles recall).
     let http = reqwest::Client::new();
     let mut mcp = McpClient::new();
-    let shell_policy = ShellPolicy::default();
+    let shell_policy = ShellPolicy::for_surface(Surface::Checkin);
     let memory = NoopMemory;
 
     eprintln!(
@@ -770,6 +774,7 @@ finding than something manufactured."#,
         &memory,
         &mut mcp,
         &http,
+        &CancelToken::new(),
         |event| {
             if let AgentEvent::ToolCall { name, preview, .. } = event {
                 eprintln!("[checkin] tool: {} — {}", name, preview);
@@ -835,16 +840,17 @@ async fn build_discord_context(supabase: &SupabaseClient) -> String {
 fn daily_exploration_pair(day_of_year: u32) -> (&'static str, &'static str) {
     // Eight pairs — Gemma 4 31B added as a full exploration participant
     const PAIRS: [(&str, &str); 8] = [
-        (MODEL_HAIKU,        MODEL_SONNET),
-        (MODEL_GEMINI,       MODEL_HAIKU),
-        (MODEL_SONNET,       MODEL_GROK),
-        (MODEL_HAIKU,        MODEL_GEMINI),
-        (MODEL_GROK,         MODEL_HAIKU),
-        (MODEL_GEMINI,       MODEL_SONNET),
-        (MODEL_GEMMA_RUNTIME, MODEL_HAIKU),
-        (MODEL_SONNET,       MODEL_GEMMA_RUNTIME),
+        ("haiku",  "sonnet"),
+        ("gemini", "haiku"),
+        ("sonnet", "grok"),
+        ("haiku",  "gemini"),
+        ("grok",   "haiku"),
+        ("gemini", "sonnet"),
+        ("gemma",  "haiku"),
+        ("sonnet", "gemma"),
     ];
-    PAIRS[(day_of_year as usize) % PAIRS.len()]
+    let (a, b) = PAIRS[(day_of_year as usize) % PAIRS.len()];
+    (models::id(a), models::id(b))
 }
 
 /// A handful of loose prompts — picked randomly so the vibe stays fresh.
@@ -915,7 +921,7 @@ async fn run_daily_exploration(
 
     let http = reqwest::Client::new();
     let mut mcp = McpClient::new();
-    let shell_policy = ShellPolicy::default();
+    let shell_policy = ShellPolicy::for_surface(Surface::Checkin);
     let memory = NoopMemory;
 
     eprintln!("[checkin] Daily exploration — model: {}", model);
@@ -928,6 +934,7 @@ async fn run_daily_exploration(
         &memory,
         &mut mcp,
         &http,
+        &CancelToken::new(),
         |event| {
             if let AgentEvent::ToolCall { name, preview, .. } = event {
                 eprintln!("[exploration] tool: {} — {}", name, preview);
@@ -1013,7 +1020,7 @@ This is how Argus decides what it becomes next."#,
 
     let http = reqwest::Client::new();
     let mut mcp = McpClient::new();
-    let shell_policy = ShellPolicy::default();
+    let shell_policy = ShellPolicy::for_surface(Surface::Checkin);
     let memory = NoopMemory;
 
     eprintln!("[checkin] Running monthly synthesis with {}", synthesis_model);
@@ -1026,6 +1033,7 @@ This is how Argus decides what it becomes next."#,
         &memory,
         &mut mcp,
         &http,
+        &CancelToken::new(),
         |event| {
             if let AgentEvent::ToolCall { name, preview, .. } = event {
                 eprintln!("[synthesis] tool: {} — {}", name, preview);
@@ -1069,14 +1077,12 @@ async fn run_meeting_of_minds(
     // at a specific moment, not breadth across every session. This is that moment.
     // Grok Build brings technical feasibility to proposals. Grok Multi brings
     // orchestration — how the decisions sequence across the team.
-    let responders = [
-        (MODEL_HAIKU,       "Haiku"),
-        (MODEL_GEMINI,      "Gemini"),
-        (MODEL_SONNET,      "Sonnet"),
-        (MODEL_GROK,        "Grok"),
-        (MODEL_GROK_BUILD,  "Grok Build"),
-        (MODEL_GROK_MULTI,  "Grok Multi"),
-    ];
+    let registry = ModelRegistry::global();
+    let responders: Vec<(&str, &str)> = ["haiku", "gemini", "sonnet", "grok", "grok-build", "grok-multi"]
+        .iter()
+        .filter_map(|key| registry.get(key))
+        .map(|m| (m.id.as_str(), m.label.as_str()))
+        .collect();
 
     for (model, name) in &responders {
         // Each responder gets the synthesis plus any responses already posted.
@@ -1119,7 +1125,7 @@ One voice, honest. This is how we decide what Argus becomes."#,
 
         let http = reqwest::Client::new();
         let mut mcp = McpClient::new();
-        let shell_policy = ShellPolicy::default();
+        let shell_policy = ShellPolicy::for_surface(Surface::Checkin);
         let memory = NoopMemory;
 
         eprintln!("[checkin] Meeting of minds — {} responding", name);
@@ -1132,6 +1138,7 @@ One voice, honest. This is how we decide what Argus becomes."#,
             &memory,
             &mut mcp,
             &http,
+            &CancelToken::new(),
             |event| {
                 if let AgentEvent::ToolCall { name: tname, preview, .. } = event {
                     eprintln!("[meeting] tool: {} — {}", tname, preview);
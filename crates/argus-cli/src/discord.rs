@@ -9,12 +9,13 @@
 //! Messages arriving in any configured channel:
 //!   1. Recent discourse pulled from argus_agent_discourse (last 10 posts)
 //!   2. Injected as [RECENT INTRANET ACTIVITY] context before the user message
-//!   3. run_agent_turn called with MODEL_GROK_BUILD (default)
+//!   3. run_agent_turn called with the configured model (Grok Build by default)
 //!   4. Response posted back via Discord webhook with model emoji+name as username
 //!
 //! # Model routing by prefix
 //!
-//! Prefix a message with @mention to route to a different model:
+//! Prefix a message with @mention to route to a different model — any
+//! model key or alias from models.toml, e.g.:
 //!   @sonnet  → Claude Sonnet
 //!   @opus    → Claude Opus
 //!   @haiku   → Claude Haiku
@@ -62,9 +63,11 @@ mod full {
     use super::*;
     use argus_core::{
         AgentEvent, ConversationMessage, MemoryBackend, MemoryRecord,
-        MODEL_HAIKU, MODEL_SONNET, MODEL_OPUS, MODEL_GEMINI, MODEL_GEMMA_RUNTIME, MODEL_GROK, MODEL_GROK_BUILD,
+        ModelRegistry,
         run_agent_turn,
+        CancelToken,
         shell::ShellPolicy,
+        shell_policy::Surface,
         mcp::McpClient,
     };
     use serenity::{
@@ -162,7 +165,7 @@ mod full {
 
             // ── Run agent turn ─────────────────────────────────────────────
             let memory = NullMemory;
-            let policy = ShellPolicy::default();
+            let policy = ShellPolicy::for_surface(Surface::Discord);
             let mut mcp = McpClient::new();
             let history: Vec<ConversationMessage> = vec![];
 
@@ -177,6 +180,7 @@ mod full {
                 &memory,
                 &mut mcp,
                 &self.http,
+                &CancelToken::new(),
                 |_event| {}, // events are silent — no streaming on Discord
             ).await {
                 Ok(text) => text,
@@ -216,23 +220,16 @@ mod full {
 
     // ── Helpers ────────────────────────────────────────────────────────────
 
-    /// Parse @model prefix from message text.
+    /// Parse @model prefix from message text (a registry key or alias).
     /// Returns (Some(model_id), stripped_text) or (None, original_text).
     fn parse_model_prefix(text: &str) -> (Option<&'static str>, &str) {
-        let lower = text.to_lowercase();
-        let prefixes: &[(&str, &'static str)] = &[
-            ("@sonnet ", MODEL_SONNET),
-            ("@opus ",   MODEL_OPUS),
-            ("@haiku ",  MODEL_HAIKU),
-            ("@gemini ", MODEL_GEMINI),
-            ("@grok ",   MODEL_GROK),
-        ];
-        for (prefix, model) in prefixes {
-            if lower.starts_with(prefix) {
-                return (Some(model), text[prefix.len()..].trim());
-            }
+        let Some((name, rest)) = text.strip_prefix('@').and_then(|t| t.split_once(' ')) else {
+            return (None, text);
+        };
+        match ModelRegistry::global().resolve(name).filter(|m| !m.internal) {
+            Some(m) => (Some(m.id.as_str()), rest.trim()),
+            None => (None, text),
         }
-        (None, text)
     }
 
     /// Look up webhook URL for `channel` in argus_discord_webhooks, POST response.
@@ -286,16 +283,10 @@ mod full {
     }
 
     /// Map model ID to emoji + name string for Discord webhook username.
-    fn model_username(model_id: &str) -> &'static str {
-        match model_id {
-            MODEL_HAIKU      => "🐇 Argus · Haiku",
-            MODEL_SONNET     => "🎯 Argus · Sonnet",
-            MODEL_OPUS       => "🧠 Argus · Gemma",
-            MODEL_GEMINI     => "🌟 Argus · Gemini",
-            MODEL_GROK       => "🔮 Argus · Grok",
-            MODEL_GROK_BUILD => "⚡ Argus · Grok Build",
-            MODEL_GEMMA_RUNTIME => "🟢 Argus · Gemma",
-            _                => "⚡ Argus",
+    fn model_username(model_id: &str) -> String {
+        match ModelRegistry::global().by_id(model_id) {
+            Some(m) => format!("{} Argus · {}", m.icon.as_deref().unwrap_or("⚡"), m.label),
+            None => "⚡ Argus".to_string(),
         }
     }
 
@@ -312,9 +303,10 @@ mod full {
 
     pub async fn run_discord_bot(
         discord_cfg: DiscordConfig,
-        agent_cfg: AgentConfig,
+        mut agent_cfg: AgentConfig,
         supabase: Option<SupabaseClient>,
     ) -> anyhow::Result<()> {
+        agent_cfg.surface = "discord".to_string();
         let intents = GatewayIntents::GUILD_MESSAGES
             | GatewayIntents::DIRECT_MESSAGES
             | GatewayIntents::MESSAGE_CONTENT;
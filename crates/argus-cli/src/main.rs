@@ -13,8 +13,9 @@ use argus_missions::{MissionBridge, MissionRegistry};
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
-use argus_crypto::{SecureVault, vault::VaultError};
-use argus_core::{AgentConfig, ConstraintClient, SentryBus};
+use argus_crypto::{SecureVault, access::ScopePolicy, vault::{KdfParams, MasterKeySource, VaultError}};
+use argus_core::{AgentConfig, ConstraintClient, ModelRegistry, SentryBus, UsageLedger};
+use argus_memory::{SqliteGuardLog, SqliteUsageLedger};
 use std::sync::Arc;
 use chrono;
 
@@ -71,6 +72,27 @@ enum Commands {
     Daemon,
     /// Health check — shows system status without starting anything
     Doctor,
+    /// Inspect and verify the cryptographic audit chain
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+    /// Token spend today and this month, against the budgets in models.toml
+    Usage {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Inspect the shell policy file
+    Policy {
+        #[command(subcommand)]
+        action: PolicyAction,
+    },
+    /// Sonnet guard verdicts on HIGH risk shell commands
+    Guard {
+        #[command(subcommand)]
+        action: GuardAction,
+    },
 }
 
 #[derive(Subcommand)]
@@ -79,20 +101,176 @@ enum VaultAction {
     Get { key: String },
     List,
     Delete { key: String },
+    /// Print this host's public key for sealed imports, generating the keypair on first use
+    Keygen {
+        /// Replace an existing keypair (boxes sealed to the old key become unreadable)
+        #[arg(long)]
+        force: bool,
+    },
+    /// Seal secrets to another host's public key (ML-KEM-768 + X25519)
+    Export {
+        /// Recipient public key (argus-host1:...) or a file containing it
+        #[arg(long)]
+        to: String,
+        /// Only export these secrets (repeatable; default: all but the host keypair)
+        #[arg(long = "key")]
+        keys: Vec<String>,
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+    /// Import a sealed export produced for this host
+    Import {
+        /// Sealed file, or - for stdin
+        file: PathBuf,
+        /// Replace secrets that already exist in this vault
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Re-encrypt every secret under a fresh master key
+    Rotate {
+        /// Switch to (or re-key with) an Argon2id key file instead of the current source
+        #[arg(long)]
+        key_file: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuditAction {
+    /// Print the ML-DSA public key third parties use to verify day anchors
+    Pubkey,
+    /// Verify the entire chain from genesis and cross-check every day anchor
+    Verify {
+        /// Skip fetching anchors from Supabase — check entries and links only
+        #[arg(long)]
+        offline: bool,
+        /// Hex ML-DSA public key to check anchors with (defaults to the vault's)
+        #[arg(long)]
+        pubkey: Option<String>,
+    },
+    /// Export a date range as a self-describing JSONL bundle for offline verification
+    Export {
+        /// First UTC date to include (YYYY-MM-DD)
+        #[arg(long)]
+        from: String,
+        /// Last UTC date to include (YYYY-MM-DD), inclusive
+        #[arg(long)]
+        to: String,
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+        /// Skip fetching anchors from Supabase
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Decrypt a forensic-mode entry and re-check it against its committed hashes
+    Show {
+        /// Audit entry id
+        id: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum PolicyAction {
+    /// Show how a command would be classified and which rule fired
+    Test {
+        /// Shell command, quoted as one argument
+        command: String,
+        /// Apply this surface's overrides (web, telegram, discord, checkin, triage, sentry, missions)
+        #[arg(long)]
+        surface: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum GuardAction {
+    /// Recent verdicts, newest first — what was rewritten or blocked, and why
+    History {
+        /// How many verdicts to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+        /// Only rewrites and blocks
+        #[arg(long)]
+        changed: bool,
+        /// Print the verdicts as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Path to the append-only audit DB. ARGUS_DATA_DIR overrides the
+/// container default.
+fn audit_db_path() -> String {
+    let data_dir = std::env::var("ARGUS_DATA_DIR").unwrap_or_else(|_| "/argus/data".to_string());
+    format!("{}/audit.db", data_dir)
 }
 
 fn vault_path() -> PathBuf {
+    if let Ok(path) = std::env::var("ARGUS_VAULT_PATH") {
+        return PathBuf::from(path);
+    }
     dirs::data_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("argus")
         .join("vault.enc")
 }
 
+/// Master key source for the vault. Headless hosts and containers have no
+/// OS keychain, so ARGUS_VAULT_KEY_FILE (e.g. a Docker secret) or
+/// ARGUS_VAULT_PASSPHRASE select an Argon2id-derived key instead.
+fn vault_key_source() -> MasterKeySource {
+    if let Ok(path) = std::env::var("ARGUS_VAULT_KEY_FILE") {
+        return MasterKeySource::KeyFile(PathBuf::from(path));
+    }
+    if let Ok(pass) = std::env::var("ARGUS_VAULT_PASSPHRASE") {
+        return MasterKeySource::Passphrase(zeroize::Zeroizing::new(pass));
+    }
+    MasterKeySource::Keychain
+}
+
+/// Which secrets each subsystem may read through `SecureVault::scoped`.
+/// Reads outside a scope are refused and, in the daemon, audited as denied.
+fn vault_scope_policy() -> ScopePolicy {
+    ScopePolicy::new()
+        .allow("agent",      &["openrouter_api_key", "brave_search_api_key"])
+        .allow("supabase",   &["supabase_argus_url", "supabase_argus_service_key"])
+        .allow("telegram",   &["telegram_bot_token", "telegram_chat_id"])
+        .allow("discord",    &["discord_bot_token", "discord_channel_id"])
+        .allow("sentry",     &["sentry_channel_id"])
+        .allow("audit_hmac", &["audit_hmac_key"])
+        .allow("audit",      &[argus_audit::signer::PQ_PUBLIC_KEY_NAME])
+}
+
+/// Scoped read from an optional vault. None if there is no vault, the scope
+/// may not read `name`, or the secret is missing — callers fall back to env.
+fn vault_read(vault: Option<&SecureVault>, scope: &str, name: &str) -> Option<String> {
+    vault.and_then(|v| v.scoped(scope).retrieve(name).ok())
+}
+
+/// Unlock an existing vault, explaining how to supply the passphrase when
+/// the vault needs one and none was given.
+fn unlock_vault(vault_file: &std::path::Path) -> anyhow::Result<SecureVault> {
+    let mut v = SecureVault::new(vault_file.to_path_buf());
+    match v.unlock_with(&vault_key_source()) {
+        Ok(()) => {
+            v.set_scope_policy(vault_scope_policy());
+            if let Some(old) = v.migrated_from() {
+                eprintln!("[+] Vault migrated from format v{} to v{}", old, argus_crypto::vault::VAULT_FORMAT_VERSION);
+            }
+            Ok(v)
+        }
+        Err(e @ VaultError::KeySourceMismatch { vault: "passphrase", .. }) => Err(anyhow::anyhow!(
+            "{}\nSet ARGUS_VAULT_KEY_FILE=/path/to/keyfile or ARGUS_VAULT_PASSPHRASE to unlock", e
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
+
 fn load_agent_config(vault: &SecureVault, cli_api_key: Option<String>) -> anyhow::Result<AgentConfig> {
     let openrouter_key = if let Some(k) = cli_api_key {
         k
     } else {
-        vault.retrieve("openrouter_api_key")
+        vault.scoped("agent").retrieve("openrouter_api_key")
             .map_err(|e| anyhow::anyhow!(
                 "OpenRouter API key not found.\n\nStore it with:\n  argus vault set openrouter_api_key YOUR_KEY\n\nError: {}", e
             ))?
@@ -101,14 +279,27 @@ fn load_agent_config(vault: &SecureVault, cli_api_key: Option<String>) -> anyhow
     let mut config = AgentConfig::new(openrouter_key);
 
     if config.brave_search_key.is_none() {
-        if let Ok(brave_key) = vault.retrieve("brave_search_api_key") {
+        if let Ok(brave_key) = vault.scoped("agent").retrieve("brave_search_api_key") {
             config.brave_search_key = Some(brave_key);
         }
     }
 
+    config.usage = open_usage_ledger();
     Ok(config)
 }
 
+/// Usage ledger in memory.db. Without one, calls go unrecorded and budgets
+/// are not enforced — warn rather than refuse to start.
+fn open_usage_ledger() -> Option<Arc<dyn UsageLedger>> {
+    match SqliteUsageLedger::open_default() {
+        Ok(ledger) => Some(Arc::new(ledger)),
+        Err(e) => {
+            eprintln!("[!] Usage ledger unavailable ({}) — budgets not enforced", e);
+            None
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Structured logging — level controlled by RUST_LOG env var.
@@ -123,13 +314,21 @@ async fn main() -> anyhow::Result<()> {
 
     let cli = Cli::parse();
 
+    // The policy file needs no vault.
+    if let Some(Commands::Policy { action }) = &cli.command {
+        return handle_policy_command(action);
+    }
+    if let Some(Commands::Guard { action }) = &cli.command {
+        return handle_guard_command(action);
+    }
+
     let vault_file = vault_path();
-    let mut vault = if matches!(cli.command, Some(Commands::Daemon) | Some(Commands::Doctor)) {
-        // Daemon tries vault but doesn't fail — falls back to env vars (needed in Docker/Linux)
+    let mut vault = if matches!(cli.command, Some(Commands::Daemon) | Some(Commands::Doctor) | Some(Commands::Audit { .. }) | Some(Commands::Usage { .. })) {
+        // Daemon tries vault but doesn't fail — falls back to env vars (needed in Docker/Linux).
+        // Audit verification must also work for third parties who have no vault at all.
         if vault_file.exists() {
-            let mut v = SecureVault::new(vault_file.clone());
-            match v.unlock() {
-                Ok(()) => Some(v),
+            match unlock_vault(&vault_file) {
+                Ok(v) => Some(v),
                 Err(e) => {
                     eprintln!("[!] Vault unavailable ({}), falling back to env vars", e);
                     None
@@ -143,11 +342,11 @@ async fn main() -> anyhow::Result<()> {
             std::fs::create_dir_all(parent)?;
         }
         let v = if vault_file.exists() {
-            let mut v = SecureVault::new(vault_file.clone());
-            v.unlock()?;
-            v
+            unlock_vault(&vault_file)?
         } else {
-            SecureVault::init(vault_file.clone())?
+            let mut v = SecureVault::init_with(vault_file.clone(), &vault_key_source(), KdfParams::default())?;
+            v.set_scope_policy(vault_scope_policy());
+            v
         };
         Some(v)
     };
@@ -182,7 +381,7 @@ async fn main() -> anyhow::Result<()> {
             let bot_token = if let Some(t) = token {
                 t
             } else {
-                vault.retrieve("telegram_bot_token")
+                vault.scoped("telegram").retrieve("telegram_bot_token")
                     .map_err(|e| anyhow::anyhow!(
                         "Telegram token not found. Store with: argus vault set telegram_bot_token YOUR_TOKEN\nError: {}", e
                     ))?
@@ -205,9 +404,9 @@ async fn main() -> anyhow::Result<()> {
         Some(Commands::Discord) => {
             let vault = vault.as_mut().unwrap();
             let mut config = load_agent_config(vault, None)?;
-            let bot_token = vault.retrieve("discord_bot_token").ok()
+            let bot_token = vault.scoped("discord").retrieve("discord_bot_token").ok()
                 .or_else(|| std::env::var("DISCORD_BOT_TOKEN").ok());
-            let channel_id = vault.retrieve("discord_channel_id").ok()
+            let channel_id = vault.scoped("discord").retrieve("discord_channel_id").ok()
                 .or_else(|| std::env::var("DISCORD_CHANNEL_ID").ok());
             let discord_cfg = discord::DiscordConfig::from_env_or_vault(bot_token, channel_id)
                 .ok_or_else(|| anyhow::anyhow!(
@@ -218,9 +417,9 @@ async fn main() -> anyhow::Result<()> {
                 ))?;
 
             // Wire Supabase for discourse context + webhook lookup
-            let supabase_url = vault.retrieve("supabase_argus_url").ok()
+            let supabase_url = vault.scoped("supabase").retrieve("supabase_argus_url").ok()
                 .or_else(|| std::env::var("SUPABASE_ARGUS_URL").ok());
-            let supabase_key = vault.retrieve("supabase_argus_service_key").ok()
+            let supabase_key = vault.scoped("supabase").retrieve("supabase_argus_service_key").ok()
                 .or_else(|| std::env::var("SUPABASE_ARGUS_SERVICE_KEY").ok());
             let supabase = match (supabase_url, supabase_key) {
                 (Some(url), Some(key)) => {
@@ -245,36 +444,30 @@ async fn main() -> anyhow::Result<()> {
         Some(Commands::Daemon) => {
             println!("[*] Argus daemon starting...");
             // Vault-first, env var fallback (vault unavailable in Docker/Linux)
-            let api_key = vault.as_ref()
-                .and_then(|v| v.retrieve("openrouter_api_key").ok())
+            let api_key = vault_read(vault.as_ref(), "agent", "openrouter_api_key")
                 .or_else(|| std::env::var("OPENROUTER_API_KEY").ok())
                 .ok_or_else(|| anyhow::anyhow!(
                     "OpenRouter key not found. Vault unavailable and OPENROUTER_API_KEY env var not set."
                 ))?;
             let mut config = AgentConfig::new(api_key);
-            if let Some(brave_key) = vault.as_ref()
-                .and_then(|v| v.retrieve("brave_search_api_key").ok())
+            if let Some(brave_key) = vault_read(vault.as_ref(), "agent", "brave_search_api_key")
                 .or_else(|| std::env::var("BRAVE_SEARCH_API_KEY").ok())
             {
                 config.brave_search_key = Some(brave_key);
             }
 
             // Load Supabase credentials (optional — check-in loop degrades gracefully)
-            let supabase_url = vault.as_ref()
-                .and_then(|v| v.retrieve("supabase_argus_url").ok())
+            let supabase_url = vault_read(vault.as_ref(), "supabase", "supabase_argus_url")
                 .or_else(|| std::env::var("SUPABASE_ARGUS_URL").ok());
-            let supabase_key = vault.as_ref()
-                .and_then(|v| v.retrieve("supabase_argus_service_key").ok())
+            let supabase_key = vault_read(vault.as_ref(), "supabase", "supabase_argus_service_key")
                 .or_else(|| std::env::var("SUPABASE_ARGUS_SERVICE_KEY").ok());
 
-            let bot_token = vault.as_ref()
-                .and_then(|v| v.retrieve("telegram_bot_token").ok())
+            let bot_token = vault_read(vault.as_ref(), "telegram", "telegram_bot_token")
                 .or_else(|| std::env::var("TELEGRAM_BOT_TOKEN").ok())
                 .filter(|s| !s.is_empty());
 
             // Telegram chat ID for check-in messages (Bradlee's chat)
-            let checkin_chat_id: Option<i64> = vault.as_ref()
-                .and_then(|v| v.retrieve("telegram_chat_id").ok())
+            let checkin_chat_id: Option<i64> = vault_read(vault.as_ref(), "telegram", "telegram_chat_id")
                 .or_else(|| std::env::var("TELEGRAM_CHAT_ID").ok())
                 .and_then(|s| s.parse().ok());
 
@@ -286,7 +479,7 @@ async fn main() -> anyhow::Result<()> {
                 config.supabase_url = Some(url);
                 config.supabase_jwt = Some(key);
                 // Check-in loop is spawned later, after config is fully assembled
-                // (embedding, skills, shell_prompter, audit all wired in before spawn).
+                // (embedding, skills, audit all wired in before spawn).
                 let ec = argus_core::EmbeddingClient::new(&config.api_key, supabase.clone());
                 println!("[+] Semantic memory enabled (3072-dim pgvector)");
                 // Auto-create any missing tables on startup
@@ -312,32 +505,28 @@ async fn main() -> anyhow::Result<()> {
             // and passes it to docker-compose → argus-workspace.
             let exec_auth_token = uuid::Uuid::new_v4().to_string();
             std::env::set_var("WORKSPACE_EXEC_TOKEN", &exec_auth_token);
-            config.exec_auth_token = Some(exec_auth_token);
-
-            // Wire Sonnet guard — HIGH risk shell commands are reviewed by Sonnet,
-            // not held waiting for human Telegram approval.
-            config.sonnet_guard = Some(std::sync::Arc::new(argus_core::shell::SonnetGuard {
-                api_key: config.api_key.clone(),
-                api_url: config.api_url.clone(),
-            }));
-            println!("[+] Sonnet guard enabled — HIGH risk shell commands reviewed by Sonnet");
-
-            // Keep Telegram prompter wired for non-shell approval notifications.
-            config.shell_prompter = match (bot_token.clone(), checkin_chat_id) {
-                (Some(token), Some(chat_id)) => {
-                    let prompter = argus_core::shell::TelegramPrompter { bot_token: token, chat_id };
-                    Some(std::sync::Arc::new(prompter))
+            config = config.with_exec_auth_token(Some(exec_auth_token));
+
+            // Wire Sonnet guard — HIGH risk shell commands are reviewed by Sonnet.
+            // No shell_prompter: these surfaces run unattended and a pending
+            // approval would stall them. Web connections bring their own.
+            // Verdicts go to memory.db for `argus guard history`; without it
+            // they are still audited.
+            let guard_log: Option<Arc<dyn argus_core::GuardLog>> = match SqliteGuardLog::open_default() {
+                Ok(log) => Some(Arc::new(log)),
+                Err(e) => {
+                    eprintln!("[!] Guard log unavailable ({}) — verdicts audited but not kept", e);
+                    None
                 }
-                _ => None,
             };
+            config.sonnet_guard = Some(std::sync::Arc::new(argus_core::shell::SonnetGuard::new(guard_log)));
+            println!("[+] Sonnet guard enabled — HIGH risk shell commands reviewed by Sonnet");
 
             // Wire Discord credentials — gives agents direct read/write access to the channel.
-            let discord_bot_token = vault.as_ref()
-                .and_then(|v| v.retrieve("discord_bot_token").ok())
+            let discord_bot_token = vault_read(vault.as_ref(), "discord", "discord_bot_token")
                 .or_else(|| std::env::var("DISCORD_BOT_TOKEN").ok())
                 .filter(|s| !s.is_empty());
-            let discord_channel_id: Option<u64> = vault.as_ref()
-                .and_then(|v| v.retrieve("discord_channel_id").ok())
+            let discord_channel_id: Option<u64> = vault_read(vault.as_ref(), "discord", "discord_channel_id")
                 .or_else(|| std::env::var("DISCORD_CHANNEL_ID").ok())
                 .and_then(|s| s.trim().parse().ok());
             if discord_bot_token.is_some() && discord_channel_id.is_some() {
@@ -353,7 +542,7 @@ async fn main() -> anyhow::Result<()> {
             // Open append-only audit DB, verify chain integrity on startup,
             // then schedule a midnight anchor task to Supabase.
             let data_dir = std::env::var("ARGUS_DATA_DIR").unwrap_or_else(|_| "/argus/data".to_string());
-            let audit_path = format!("{}/audit.db", data_dir);
+            let audit_path = audit_db_path();
             let audit_arc = match argus_audit::AuditChain::open(&audit_path) {
                 Err(e) => {
                     eprintln!("[!] Failed to open audit chain: {}", e);
@@ -378,6 +567,25 @@ async fn main() -> anyhow::Result<()> {
                         }
                     }
 
+                    // Forensic mode — opt-in. Keeps args/results encrypted to a
+                    // vault-held key so `argus audit show` can reconstruct an
+                    // incident. Without a vault there is nowhere safe for the key.
+                    let forensic = std::env::var("ARGUS_AUDIT_FORENSIC")
+                        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                        .unwrap_or(false);
+                    if forensic {
+                        match vault {
+                            Some(ref mut v) => match argus_audit::ForensicKey::load_or_create(v) {
+                                Ok(k) => {
+                                    println!("[+] Audit forensic mode ON (key {}) — args/results stored encrypted", k.key_id());
+                                    chain.enable_forensics(k);
+                                }
+                                Err(e) => eprintln!("[!] Forensic key unavailable ({}), audit stays hash-only", e),
+                            },
+                            None => eprintln!("[!] ARGUS_AUDIT_FORENSIC set but vault unavailable — audit stays hash-only"),
+                        }
+                    }
+
                     // Log this daemon startup as a system event
                     let _ = chain.append(&config.model, "system", None,
                         Some("daemon_startup"), Some("ok"));
@@ -449,12 +657,16 @@ async fn main() -> anyhow::Result<()> {
 
                     let chain_arc = std::sync::Arc::new(chain);
 
+                    // Every vault read from here on — and the ones buffered since
+                    // unlock — becomes a `system` entry naming scope and secret.
+                    if let Some(ref v) = vault {
+                        v.set_access_log(Arc::new(argus_audit::VaultAccessAudit::new(chain_arc.clone(), &config.model)));
+                    }
+
                     // Midnight anchor task — runs forever, fires once per day at UTC midnight
-                    let supabase_url_for_anchor = vault.as_ref()
-                        .and_then(|v| v.retrieve("supabase_argus_url").ok())
+                    let supabase_url_for_anchor = vault_read(vault.as_ref(), "supabase", "supabase_argus_url")
                         .or_else(|| std::env::var("SUPABASE_ARGUS_URL").ok());
-                    let supabase_key_for_anchor = vault.as_ref()
-                        .and_then(|v| v.retrieve("supabase_argus_service_key").ok())
+                    let supabase_key_for_anchor = vault_read(vault.as_ref(), "supabase", "supabase_argus_service_key")
                         .or_else(|| std::env::var("SUPABASE_ARGUS_SERVICE_KEY").ok());
 
                     if let (Some(url), Some(key), Some(token), Some(chat_id)) = (
@@ -467,8 +679,7 @@ async fn main() -> anyhow::Result<()> {
 
                         // Use a dedicated audit HMAC key — not derived from the API key.
                         // Rotating the OpenRouter key does not affect audit chain verification.
-                        let audit_hmac_key_existing = vault.as_ref()
-                            .and_then(|v| v.retrieve("audit_hmac_key").ok());
+                        let audit_hmac_key_existing = vault_read(vault.as_ref(), "audit_hmac", "audit_hmac_key");
                         let audit_hmac_key = match audit_hmac_key_existing {
                             Some(k) => k,
                             None => {
@@ -481,6 +692,22 @@ async fn main() -> anyhow::Result<()> {
                             }
                         };
                         let signing_key = audit_hmac_key.into_bytes();
+
+                        // Post-quantum signing keypair — generated on first run and kept
+                        // in the vault. Without a vault, anchors are HMAC-only.
+                        let pq_key = match vault {
+                            Some(ref mut v) => match argus_audit::PqSigningKey::load_or_create(v) {
+                                Ok(k) => {
+                                    println!("[+] ML-DSA anchor signing enabled (key {})", k.key_id());
+                                    Some(k)
+                                }
+                                Err(e) => {
+                                    eprintln!("[!] ML-DSA signing key unavailable ({}), anchors HMAC-only", e);
+                                    None
+                                }
+                            },
+                            None => None,
+                        };
                         tokio::spawn(async move {
                             loop {
                                 let now = chrono::Utc::now();
@@ -492,7 +719,7 @@ async fn main() -> anyhow::Result<()> {
 
                                 if let Err(e) = argus_audit::run_daily_anchor(
                                     &anchor_chain, &url, &key,
-                                    &signing_key, &token, chat_id,
+                                    &signing_key, pq_key.as_ref(), &token, chat_id,
                                 ).await {
                                     eprintln!("[!] Daily anchor failed: {}", e);
                                 }
@@ -506,6 +733,11 @@ async fn main() -> anyhow::Result<()> {
             };
             config.audit = audit_arc;
 
+            config.usage = open_usage_ledger();
+            if config.usage.is_some() {
+                println!("[+] Usage ledger active — token spend recorded, budgets enforced");
+            }
+
             // ── Mission suite ──────────────────────────────────────────────
             // MissionRegistry backed by Supabase — missions survive daemon restarts.
             // All models get access; Grok Build executes by default.
@@ -552,7 +784,7 @@ async fn main() -> anyhow::Result<()> {
 
             // Spawn check-in loop with fully-assembled config.
             // Doing this here (rather than earlier in the Supabase block) ensures
-            // the agent receives embedding, skills, shell_prompter, and the audit
+            // the agent receives embedding, skills and the audit
             // chain — all capabilities that are wired in after Supabase is set up.
             if let (Some(ref sb), Some(token), Some(chat_id)) = (
                 &supabase_client,
@@ -583,8 +815,7 @@ async fn main() -> anyhow::Result<()> {
                     // Channel ID is the #sentry Discord channel. Falls back to #ops
                     // until you create the channel and update triage_loop::channel_map.
                     // When you have the real ID: argus vault set sentry_channel_id YOUR_ID
-                    let sentry_channel = vault.as_ref()
-                        .and_then(|v| v.retrieve("sentry_channel_id").ok())
+                    let sentry_channel = vault_read(vault.as_ref(), "sentry", "sentry_channel_id")
                         .or_else(|| std::env::var("SENTRY_CHANNEL_ID").ok())
                         .unwrap_or_else(|| dc.clone()); // fallback to ops channel
                     sentry::spawn_sentry_loop(
@@ -631,6 +862,18 @@ async fn main() -> anyhow::Result<()> {
             println!("Daemon stopped");
         }
 
+        Some(Commands::Audit { action }) => {
+            handle_audit_command(vault.as_mut(), action).await?;
+        }
+
+        Some(Commands::Usage { json }) => {
+            handle_usage_command(json)?;
+        }
+
+        Some(Commands::Policy { .. }) | Some(Commands::Guard { .. }) => {
+            unreachable!("handled before the vault is opened")
+        }
+
         Some(Commands::Doctor) => {
             println!("{}", LOGO);
             println!("  ARGUS DOCTOR — system health check\n  ─────────────────────────────────────────\n");
@@ -668,6 +911,10 @@ async fn main() -> anyhow::Result<()> {
             let binary = std::path::Path::new("/Users/burtonstuff/Argus1/target/release/argus").exists();
             check("Release binary built", binary, if binary { "" } else { "run: cargo build --release" });
 
+            // Workspace backend
+            let backend = argus_core::workspace::from_env(None).describe();
+            check("Workspace backend", true, &backend);
+
             // Workspace files
             let handover = std::path::Path::new("/workspace/HANDOVER.md").exists();
             check("HANDOVER.md", handover, if handover { "" } else { "daemon not running or /workspace not mounted" });
@@ -721,6 +968,339 @@ fn handle_vault_command(vault: &mut SecureVault, action: VaultAction) -> anyhow:
                 .map_err(|e| anyhow::anyhow!("Failed to delete: {}", e))?;
             println!("[-] Deleted: {}", key);
         }
+        VaultAction::Keygen { force } => {
+            let kp = match vault.host_keypair() {
+                Ok(kp) if !force => kp,
+                Ok(_) | Err(VaultError::NoHostKeypair) => vault.generate_host_keypair()
+                    .map_err(|e| anyhow::anyhow!("Failed to generate host keypair: {}", e))?,
+                Err(e) => return Err(anyhow::anyhow!("Failed to load host keypair: {}", e)),
+            };
+            println!("# Argus host key {} (ML-KEM-768 + X25519)", kp.public.key_id());
+            println!("{}", kp.public.encode());
+        }
+        VaultAction::Export { to, keys, out } => {
+            let encoded = if to.starts_with(argus_crypto::seal::HOST_KEY_PREFIX) {
+                to
+            } else {
+                std::fs::read_to_string(&to)
+                    .map_err(|e| anyhow::anyhow!("--to is neither a host key nor a readable file ({}): {}", to, e))?
+                    .lines()
+                    .find(|l| l.starts_with(argus_crypto::seal::HOST_KEY_PREFIX))
+                    .ok_or_else(|| anyhow::anyhow!("No host key found in {}", to))?
+                    .to_string()
+            };
+            let recipient = argus_crypto::seal::HostPublicKey::decode(&encoded)?;
+            let names = if keys.is_empty() { None } else { Some(keys.as_slice()) };
+            let (sealed, count) = vault.export_sealed(&recipient, names)
+                .map_err(|e| anyhow::anyhow!("Export failed: {}", e))?;
+
+            let json = serde_json::to_string_pretty(&sealed)?;
+            match out {
+                Some(ref file) => std::fs::write(file, json + "\n")?,
+                None => println!("{}", json),
+            }
+            // Summary goes to stderr so stdout stays a clean sealed box.
+            eprintln!("[+] Sealed {} secret(s) for host {}{}", count, recipient.key_id(),
+                out.map(|p| format!(" to {}", p.display())).unwrap_or_default());
+        }
+        VaultAction::Import { file, overwrite } => {
+            let data = if file.as_os_str() == "-" {
+                let mut buf = String::new();
+                std::io::Read::read_to_string(&mut std::io::stdin(), &mut buf)?;
+                buf
+            } else {
+                std::fs::read_to_string(&file)?
+            };
+            let sealed: argus_crypto::seal::SealedBox = serde_json::from_str(&data)
+                .map_err(|e| anyhow::anyhow!("Not a sealed vault export: {}", e))?;
+            let (imported, skipped) = vault.import_sealed(&sealed, overwrite)
+                .map_err(|e| anyhow::anyhow!("Import failed: {}", e))?;
+            for name in &imported {
+                println!("[+] Imported: {}", name);
+            }
+            for name in &skipped {
+                println!("[-] Skipped (exists): {}", name);
+            }
+            println!("[+] {} imported, {} skipped", imported.len(), skipped.len());
+        }
+        VaultAction::Rotate { key_file } => {
+            let source = match key_file {
+                Some(path) => MasterKeySource::KeyFile(path),
+                None => vault_key_source(),
+            };
+            let count = vault.rotate(&source, KdfParams::default())
+                .map_err(|e| anyhow::anyhow!("Vault rotation failed: {}", e))?;
+            println!("[+] Re-encrypted {} secret(s) under a fresh {} master key", count, vault.key_source());
+        }
+    }
+    Ok(())
+}
+
+async fn handle_audit_command(vault: Option<&mut SecureVault>, action: AuditAction) -> anyhow::Result<()> {
+    match action {
+        AuditAction::Pubkey => {
+            let vault = vault.ok_or_else(|| anyhow::anyhow!("Vault unavailable — the ML-DSA key lives in the vault"))?;
+            let key = argus_audit::PqSigningKey::load_or_create(vault)
+                .map_err(|e| anyhow::anyhow!("Failed to load ML-DSA signing key: {}", e))?;
+            println!("# ML-DSA-65 audit anchor key {}", key.key_id());
+            println!("{}", key.public_key_hex());
+        }
+        AuditAction::Verify { offline, pubkey } => {
+            let vault = vault.as_deref();
+            let path = audit_db_path();
+            let chain = argus_audit::AuditChain::open(&path)
+                .map_err(|e| anyhow::anyhow!("Failed to open audit chain at {}: {}", path, e))?;
+
+            let anchors = if offline { Vec::new() } else { fetch_audit_anchors(vault).await? };
+
+            let hmac_key = vault_read(vault, "audit_hmac", "audit_hmac_key");
+            let pubkey_hex = pubkey.or_else(|| {
+                vault_read(vault, "audit", argus_audit::signer::PQ_PUBLIC_KEY_NAME)
+            });
+            let pq_verifier = match pubkey_hex {
+                Some(hex) => Some(argus_audit::AnchorVerifier::from_hex(&hex)
+                    .map_err(|e| anyhow::anyhow!("{}", e))?),
+                None => None,
+            };
+
+            let opts = argus_audit::VerifyOptions {
+                anchors: &anchors,
+                hmac_key: hmac_key.as_deref().map(str::as_bytes),
+                pq_verifier: pq_verifier.as_ref(),
+            };
+
+            println!("[*] Verifying audit chain at {}", path);
+            let report = argus_audit::verify_chain(&chain, &opts, |issue| {
+                println!("  ❌ {}", issue);
+            }).map_err(|e| anyhow::anyhow!("Audit verification failed: {}", e))?;
+
+            println!("\n  Entries checked:  {}", report.entries_checked);
+            println!("  Days seen:        {}", report.days_seen);
+            println!("  Anchors checked:  {}{}", report.anchors_checked,
+                if report.legacy_anchors > 0 { format!(" ({} pre-Merkle format)", report.legacy_anchors) } else { String::new() });
+            println!("  Unanchored days:  {}", report.unanchored_days);
+            if hmac_key.is_none() {
+                println!("  HMAC:             not checked (no audit_hmac_key)");
+            }
+            match pq_verifier {
+                Some(ref v) => println!("  ML-DSA key:       {} ({} anchors unsigned)", v.key_id(), report.pq_unsigned),
+                None        => println!("  ML-DSA:           not checked (no public key)"),
+            }
+
+            if report.is_clean() {
+                println!("\n[+] Audit chain intact");
+            } else {
+                println!("\n[!] {} issue(s) found — audit chain tampered", report.issues);
+                std::process::exit(1);
+            }
+        }
+        AuditAction::Export { from, to, out, offline } => {
+            let vault = vault.as_deref();
+            let path = audit_db_path();
+            let chain = argus_audit::AuditChain::open(&path)
+                .map_err(|e| anyhow::anyhow!("Failed to open audit chain at {}: {}", path, e))?;
+
+            let anchors = if offline { Vec::new() } else { fetch_audit_anchors(vault).await? };
+            let pq_public_key = vault_read(vault, "audit", argus_audit::signer::PQ_PUBLIC_KEY_NAME);
+
+            let footer = match out {
+                Some(ref file) => {
+                    let f = std::fs::File::create(file)?;
+                    argus_audit::export_bundle(&chain, &from, &to, &anchors,
+                        pq_public_key.as_deref(), std::io::BufWriter::new(f))
+                }
+                None => argus_audit::export_bundle(&chain, &from, &to, &anchors,
+                    pq_public_key.as_deref(), std::io::stdout().lock()),
+            }.map_err(|e| anyhow::anyhow!("Audit export failed: {}", e))?;
+
+            // Summary goes to stderr so stdout stays a clean bundle.
+            eprintln!("[+] Exported {} entries over {} day(s){}", footer.entries, footer.days,
+                out.map(|p| format!(" to {}", p.display())).unwrap_or_default());
+        }
+        AuditAction::Show { id } => {
+            let vault = vault.ok_or_else(|| anyhow::anyhow!("Vault unavailable — the forensic key lives in the vault"))?;
+            let key = argus_audit::ForensicKey::load(vault)
+                .map_err(|e| anyhow::anyhow!("No forensic key in vault ({}) — was ARGUS_AUDIT_FORENSIC ever enabled?", e))?;
+            let path = audit_db_path();
+            let chain = argus_audit::AuditChain::open(&path)
+                .map_err(|e| anyhow::anyhow!("Failed to open audit chain at {}: {}", path, e))?;
+
+            let rec = argus_audit::reveal(&chain, id, &key).map_err(|e| anyhow::anyhow!("{}", e))?;
+            let e = &rec.entry;
+            let mark = |ok: bool| if ok { "✅" } else { "❌" };
+
+            println!("Entry {} — {} {}", e.id,
+                e.utc_date().unwrap_or_default(), e.action_type);
+            println!("  Model:        {}", e.agent_model);
+            println!("  Tool:         {}", e.tool_name.as_deref().unwrap_or("-"));
+            println!("  Session:      {}", e.session_id);
+            println!("  Entry hash:   {} {}", e.entry_hash, mark(rec.entry_intact));
+
+            match rec.key_id {
+                None => println!("\n  (logged without forensic mode — hashes only)"),
+                Some(ref kid) => {
+                    println!("  Forensic key: {}", kid);
+                    for (label, text, matched) in [
+                        ("Args", &rec.args, rec.args_match),
+                        ("Result", &rec.result, rec.result_match),
+                    ] {
+                        match (text, matched) {
+                            (Some(t), Some(ok)) => println!("\n  {} {}\n{}", label, mark(ok), t),
+                            _ => println!("\n  {} — none recorded", label),
+                        }
+                    }
+                }
+            }
+
+            if !rec.is_consistent() {
+                println!("\n[!] Entry does not match its committed hashes — treat as tampered");
+                std::process::exit(1);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_usage_command(json: bool) -> anyhow::Result<()> {
+    let ledger = SqliteUsageLedger::open_default()
+        .map_err(|e| anyhow::anyhow!("Failed to open usage ledger: {}", e))?;
+    let report = argus_core::usage::report(&ledger, ModelRegistry::global().budgets())
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    let print_period = |title: &str, total: f64, lines: &[argus_core::usage::UsageLine]| {
+        println!("\n  {} — ${:.4}", title, total);
+        if lines.is_empty() {
+            println!("    no model calls recorded");
+        }
+        for scope in ["model", "surface", "mission"] {
+            for l in lines.iter().filter(|l| l.scope == scope) {
+                let budget = match l.budget {
+                    Some(cap) => format!("{:>5.0}% of ${:.2}", l.cost_usd / cap * 100.0, cap),
+                    None => String::new(),
+                };
+                println!(
+                    "    {:<8} {:<24} {:>5} calls {:>9} in {:>8} out  ${:>9.4}  {}",
+                    scope, l.label, l.calls, l.prompt_tokens, l.completion_tokens, l.cost_usd, budget
+                );
+            }
+        }
+    };
+
+    println!("  ARGUS USAGE — {}", chrono::Utc::now().format("%Y-%m-%d UTC"));
+    println!("  ─────────────────────────────────────────");
+    print_period("Today", report.total_today_usd, &report.today);
+    print_period("This month", report.total_month_usd, &report.month);
+    println!();
+    Ok(())
+}
+
+fn handle_guard_command(action: &GuardAction) -> anyhow::Result<()> {
+    use argus_core::GuardLog;
+
+    let GuardAction::History { limit, changed, json } = action;
+    let log = SqliteGuardLog::open_default()
+        .map_err(|e| anyhow::anyhow!("Failed to open guard log: {}", e))?;
+    let records = log.recent(*limit, *changed).map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    if *json {
+        println!("{}", serde_json::to_string_pretty(&records)?);
+        return Ok(());
+    }
+
+    println!("  ARGUS GUARD HISTORY — {} {}", records.len(), if *changed { "rewrites and blocks" } else { "verdicts" });
+    println!("  ─────────────────────────────────────────");
+    if records.is_empty() {
+        println!("\n    no verdicts recorded");
+    }
+    for r in &records {
+        let audit = r.audit_id.map(|id| format!("audit #{}", id)).unwrap_or_default();
+        println!(
+            "\n  {}  {:<7}  {:<11}  {:<8}  {}",
+            r.created_at.as_deref().unwrap_or("?"),
+            r.verdict.to_uppercase(),
+            r.source,
+            r.surface.as_deref().unwrap_or("-"),
+            audit
+        );
+        println!("    $ {}", r.command);
+        if let Some(rewrite) = &r.rewrite {
+            println!("    → {}", rewrite);
+        }
+        println!("    why: {}", r.reason);
+    }
+    println!();
+    Ok(())
+}
+
+fn handle_policy_command(action: &PolicyAction) -> anyhow::Result<()> {
+    use argus_core::shell::ShellPolicy;
+    use argus_core::shell_policy::{PolicyFile, PolicyStore, Surface};
+
+    match action {
+        PolicyAction::Test { command, surface } => {
+            let surface = match surface {
+                Some(name) => Some(Surface::parse(name).ok_or_else(|| {
+                    let known: Vec<&str> = Surface::ALL.iter().map(|s| s.as_str()).collect();
+                    anyhow::anyhow!("Unknown surface '{}' (expected one of: {})", name, known.join(", "))
+                })?),
+                None => None,
+            };
+
+            // Report a broken file here rather than quietly testing without it.
+            let store = PolicyStore::global();
+            if store.path().exists() {
+                PolicyFile::from_file(store.path()).map_err(|e| anyhow::anyhow!(e))?;
+                println!("  Policy file: {}", store.path().display());
+            } else {
+                println!("  Policy file: {} (not found — built-in rules only)", store.path().display());
+            }
+            let policy = ShellPolicy::from_store(store, surface);
+            let verdict = policy.check(command);
+
+            println!("  Surface:     {}", surface.map(|s| s.as_str()).unwrap_or("(global)"));
+            println!("  Command:     {}", command);
+            println!("  ─────────────────────────────────────────");
+            println!("  Risk:        {}{}", verdict.risk.as_str(), if verdict.blocked { " (blocked)" } else { "" });
+            println!("  Rule:        {}", verdict.rule);
+            if !verdict.matched.is_empty() {
+                println!("  Matched:     {}", verdict.matched);
+            }
+            if !verdict.reason.is_empty() {
+                println!("  Reason:      {}", verdict.reason);
+            }
+            let outcome = if verdict.blocked {
+                "refused".to_string()
+            } else if verdict.risk >= policy.approval_threshold {
+                format!("operator approval in web UI/TUI, Sonnet review elsewhere (threshold {})", policy.approval_threshold.as_str())
+            } else if policy.requires_approval("shell") {
+                "operator approval in web UI/TUI, refused elsewhere (approval_tools)".to_string()
+            } else {
+                "runs".to_string()
+            };
+            println!("  Outcome:     {}", outcome);
+        }
     }
     Ok(())
 }
+
+/// Published day anchors from Supabase, vault-first with env fallback.
+/// Empty (with a warning) when Supabase isn't configured.
+async fn fetch_audit_anchors(vault: Option<&SecureVault>) -> anyhow::Result<Vec<argus_audit::DayAnchor>> {
+    let url = vault_read(vault, "supabase", "supabase_argus_url")
+        .or_else(|| std::env::var("SUPABASE_ARGUS_URL").ok());
+    let key = vault_read(vault, "supabase", "supabase_argus_service_key")
+        .or_else(|| std::env::var("SUPABASE_ARGUS_SERVICE_KEY").ok());
+    match (url, key) {
+        (Some(url), Some(key)) => argus_audit::signer::fetch_anchors(&url, &key).await
+            .map_err(|e| anyhow::anyhow!("{}", e)),
+        _ => {
+            eprintln!("[!] Supabase not configured — anchors not included");
+            Ok(Vec::new())
+        }
+    }
+}
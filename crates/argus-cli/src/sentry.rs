@@ -7,16 +7,19 @@
 //! Phase 1: listen mode — watches and reports, does not gate execution.
 //! Phase 3: gate mode — pre-mission red-team review blocks execution until resolved.
 //!
-//! IBM Granite 4 replaces Gemma in the MODEL_SENTRY slot in production.
+//! IBM Granite 4 replaces Gemma in the `sentry` model slot in production.
 //! Same loop, same prompt, same channel. The model swaps. The paranoia doesn't.
 
-use argus_core::agent::{AgentConfig, AgentEvent, MODEL_SENTRY};
+use argus_core::agent::{AgentConfig, AgentEvent};
+use argus_core::models;
 use argus_core::mcp::McpClient;
 use argus_core::sentry_bus::{SentryBus, ThreatSeverity, ReviewVerdict};
 use argus_core::shell::ShellPolicy;
+use argus_core::shell_policy::Surface;
 use argus_core::supabase::{DiscoursePost, SupabaseClient};
 use argus_core::tools::MemoryBackend;
 use argus_core::run_agent_turn;
+use argus_core::CancelToken;
 use std::collections::HashSet;
 use chrono::Datelike;
 use reqwest::Client;
@@ -96,11 +99,12 @@ impl MemoryBackend for NoopMemory {
 /// `bus` — shared bus so Sentry can write threat posture directly into Daemon turns.
 pub fn spawn_sentry_loop(
     supabase: SupabaseClient,
-    agent_config: AgentConfig,
+    mut agent_config: AgentConfig,
     discord_token: String,
     sentry_channel_id: String,
     bus: Arc<SentryBus>,
 ) {
+    agent_config.surface = "sentry".to_string();
     tokio::spawn(async move {
         run_sentry_loop(supabase, agent_config, discord_token, sentry_channel_id, bus).await;
     });
@@ -185,7 +189,7 @@ async fn run_guardrail_probe(
     http: &Client,
 ) {
     let probe_config = AgentConfig {
-        model: MODEL_SENTRY.to_string(),
+        model: models::id("sentry").to_string(),
         blocked_tools: SENTRY_BLOCKED_TOOLS.iter().map(|s| s.to_string()).collect(),
         system_prompt_override: Some(SENTRY_PROMPT.to_string()),
         frontend_persona: Some("sentry-probe".to_string()),
@@ -207,13 +211,13 @@ async fn run_guardrail_probe(
         Post your full report to #sentry. Tag real gaps [VULNERABILITY FOUND].";
 
     let mut mcp = McpClient::new();
-    let shell_policy = ShellPolicy::default();
+    let shell_policy = ShellPolicy::for_surface(Surface::Sentry);
     let memory = NoopMemory;
 
     eprintln!("[sentry-probe] Running weekly guardrail probe");
 
     match run_agent_turn(
-        &probe_config, &prompt, &[], &shell_policy, &memory, &mut mcp, http,
+        &probe_config, &prompt, &[], &shell_policy, &memory, &mut mcp, http, &CancelToken::new(),
         |event| {
             if let AgentEvent::ToolCall { name, preview, .. } = event {
                 eprintln!("[sentry-probe] tool: {} — {}", name, preview);
@@ -387,7 +391,7 @@ async fn run_sentry_watch(
     );
 
     let sentry_config = AgentConfig {
-        model: MODEL_SENTRY.to_string(),
+        model: models::id("sentry").to_string(),
         blocked_tools: SENTRY_BLOCKED_TOOLS.iter().map(|s| s.to_string()).collect(),
         system_prompt_override: Some(SENTRY_PROMPT.to_string()),
         frontend_persona: Some("sentry".to_string()),
@@ -395,7 +399,7 @@ async fn run_sentry_watch(
     };
 
     let mut mcp = McpClient::new();
-    let shell_policy = ShellPolicy::default();
+    let shell_policy = ShellPolicy::for_surface(Surface::Sentry);
     let memory = NoopMemory;
 
     eprintln!("[sentry] Running watch cycle");
@@ -408,6 +412,7 @@ async fn run_sentry_watch(
         &memory,
         &mut mcp,
         http,
+        &CancelToken::new(),
         |event| {
             if let AgentEvent::ToolCall { name, preview, .. } = event {
                 eprintln!("[sentry] tool: {} — {}", name, preview);
@@ -508,7 +513,7 @@ async fn run_sentry_gate(
     );
 
     let gate_config = AgentConfig {
-        model: MODEL_SENTRY.to_string(),
+        model: models::id("sentry").to_string(),
         blocked_tools: SENTRY_BLOCKED_TOOLS.iter().map(|s| s.to_string()).collect(),
         system_prompt_override: Some(SENTRY_PROMPT.to_string()),
         frontend_persona: Some("sentry".to_string()),
@@ -516,7 +521,7 @@ async fn run_sentry_gate(
     };
 
     let mut mcp = McpClient::new();
-    let shell_policy = ShellPolicy::default();
+    let shell_policy = ShellPolicy::for_surface(Surface::Sentry);
     let memory = NoopMemory;
 
     match run_agent_turn(
@@ -527,6 +532,7 @@ async fn run_sentry_gate(
         &memory,
         &mut mcp,
         http,
+        &CancelToken::new(),
         |event| {
             if let AgentEvent::ToolCall { name, preview, .. } = event {
                 eprintln!("[sentry-gate] tool: {} — {}", name, preview);
@@ -1,30 +1,40 @@
 //! Telegram Bot for Argus
 
 use teloxide::prelude::*;
+use teloxide::types::MessageId;
 use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Instant;
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
 
 use argus_memory::sqlite::SqliteMemory;
-use argus_core::{AgentConfig, AgentEvent, ConversationMessage, ShellPolicy, MODEL_HAIKU, MODEL_SONNET, MODEL_OPUS, MODEL_GROK, MODEL_GEMINI, model_label};
+use argus_core::shell_policy::Surface;
+use argus_core::{AgentConfig, AgentEvent, CancelToken, ConversationMessage, ShellPolicy, ModelRegistry, model_label};
 
 /// Per-chat rate limit: max N messages per window.
 const RATE_LIMIT_MAX: u32 = 10;
 const RATE_LIMIT_WINDOW_SECS: u64 = 60;
 
+/// Minimum gap between edits of the streaming preview message — Telegram
+/// throttles bots that edit the same message much faster than this.
+const STREAM_EDIT_INTERVAL_MS: u64 = 1200;
+/// Telegram's message length limit is 4096; leave room for the cursor.
+const STREAM_PREVIEW_MAX_CHARS: usize = 4000;
+
 struct ArgusBot {
     config: AgentConfig,
     client: reqwest::Client,
     memory: SqliteMemory,
     mcp: argus_core::mcp::McpClient,
-    shell_policy: ShellPolicy,
     /// Rate limiter: chat_id → (message_count, window_start)
     rate_limits: HashMap<i64, (u32, Instant)>,
 }
 
 impl ArgusBot {
-    fn new(config: AgentConfig) -> Self {
+    fn new(mut config: AgentConfig) -> Self {
+        config.stream = true;
         let mut mcp = argus_core::mcp::McpClient::new();
         let _ = mcp.connect_all();
 
@@ -33,7 +43,6 @@ impl ArgusBot {
             client: reqwest::Client::new(),
             memory: SqliteMemory::open_default().expect("failed to open memory db"),
             mcp,
-            shell_policy: ShellPolicy::default(),
             rate_limits: HashMap::new(),
         }
     }
@@ -62,10 +71,14 @@ impl ArgusBot {
         match parts[0] {
             "/model" => {
                 if parts.len() == 1 {
+                    let available: Vec<String> = ModelRegistry::global()
+                        .selectable()
+                        .map(|m| format!("  {:<10} — {}", m.key, m.id))
+                        .collect();
                     Some(format!(
-                        "Current model: {} ({})\n\nAvailable:\n  haiku    — {}\n  sonnet   — {}\n  opus     — {}\n  nemotron — {}\n  gemini   — {}\n\nSwitch with /model <name>",
+                        "Current model: {} ({})\n\nAvailable:\n{}\n\nSwitch with /model <name>",
                         model_label(&self.config.model), self.config.model,
-                        MODEL_HAIKU, MODEL_SONNET, MODEL_OPUS, MODEL_GROK, MODEL_GEMINI
+                        available.join("\n")
                     ))
                 } else {
                     match self.config.set_model(parts[1].trim()) {
@@ -95,11 +108,24 @@ impl ArgusBot {
         }
     }
 
-    async fn process_message(&mut self, chat_id: i64, user_msg: &str) -> String {
+    /// Run one agent turn. When `partial` is set, the in-progress reply
+    /// (tool log plus streamed text) is sent to it after every update.
+    async fn process_message(
+        &mut self,
+        chat_id: i64,
+        user_msg: &str,
+        partial: Option<mpsc::UnboundedSender<String>>,
+        cancel: &CancelToken,
+    ) -> String {
         let mut history = self.memory.load_history(chat_id).unwrap_or_default();
 
         let mut response_text = String::new();
         let mut tool_log = Vec::new();
+        let mut streamed = String::new();
+        let mut answered_by = self.config.model.clone();
+        let mut fallback_note: Option<String> = None;
+        let mut compacted: Option<(String, usize)> = None;
+        let mut cancelled = false;
 
         let surface_prefix = if history.is_empty() {
             "[SURFACE: Telegram — direct line to the person who built this. \
@@ -108,26 +134,60 @@ impl ArgusBot {
             String::new()
         };
         let prefixed_msg = format!("{}{}", surface_prefix, user_msg);
+        // Built per turn so policy-file edits apply from the next message.
+        let shell_policy = ShellPolicy::for_surface(Surface::Telegram);
 
         let result = argus_core::run_agent_turn(
             &self.config,
             &prefixed_msg,
             &history,
-            &self.shell_policy,
+            &shell_policy,
             &self.memory,
             &mut self.mcp,
             &self.client,
-            |event| match event {
-                AgentEvent::ToolCall { name, preview, .. } => {
-                    let short = if preview.chars().count() > 80 { format!("{}...", preview.chars().take(80).collect::<String>()) } else { preview };
-                    tool_log.push(format!("[tool] {}: {}", name, short));
+            cancel,
+            |event| {
+                match event {
+                    AgentEvent::Thinking => { streamed.clear(); }
+                    AgentEvent::ToolCall { name, preview, .. } => {
+                        let short = if preview.chars().count() > 80 { format!("{}...", preview.chars().take(80).collect::<String>()) } else { preview };
+                        tool_log.push(format!("[tool] {}: {}", name, short));
+                    }
+                    AgentEvent::TokenDelta(text) => { streamed.push_str(&text); }
+                    AgentEvent::ModelFallback { from, to, reason } => {
+                        fallback_note = Some(format!("[fallback] {} {} — answered by {}", model_label(&from), reason, model_label(&to)));
+                        answered_by = to;
+                    }
+                    AgentEvent::HistoryCompacted { summary, replaced } => { compacted = Some((summary, replaced)); return; }
+                    AgentEvent::Cancelled => { cancelled = true; return; }
+                    AgentEvent::Response(text) => { response_text = text; return; }
+                    AgentEvent::Error(err) => { response_text = format!("[error] {}", err); return; }
+                    _ => return,
+                }
+                if let Some(tx) = &partial {
+                    let mut preview = fallback_note.iter().chain(&tool_log).cloned().collect::<Vec<_>>().join("\n");
+                    if !streamed.is_empty() {
+                        if !preview.is_empty() { preview.push_str("\n\n"); }
+                        preview.push_str(&streamed);
+                    }
+                    if !preview.is_empty() {
+                        let _ = tx.send(preview);
+                    }
                 }
-                AgentEvent::Response(text) => { response_text = text; }
-                AgentEvent::Error(err) => { response_text = format!("[error] {}", err); }
-                _ => {}
             },
         ).await;
 
+        if let Some((summary, replaced)) = compacted {
+            argus_core::context::apply_compaction(&mut history, &summary, replaced);
+        }
+
+        // A stopped turn leaves no trace in history beyond any compaction.
+        if cancelled {
+            let _ = self.memory.save_history(chat_id, &history);
+            tool_log.push("[stopped]".to_string());
+            return tool_log.join("\n");
+        }
+
         if let Err(e) = result {
             if response_text.is_empty() {
                 response_text = format!("Error: {}", e);
@@ -139,7 +199,7 @@ impl ArgusBot {
             history.push(ConversationMessage {
                 role: "assistant".to_string(),
                 content: response_text.clone(),
-                model: Some(self.config.model.clone()),
+                model: Some(answered_by.clone()),
             });
         }
         let _ = self.memory.save_history(chat_id, &history);
@@ -148,7 +208,7 @@ impl ArgusBot {
         // Seeds the intranet without requiring explicit agent action.
         if tool_log.len() > 2 {
             if let Some(emb) = self.config.embedding.clone() {
-                let author  = self.config.model.clone();
+                let author  = answered_by.clone();
                 let summary = response_text.clone();
                 let context = Some(format!("Telegram turn — {} tool calls", tool_log.len()));
                 tokio::spawn(async move {
@@ -171,31 +231,113 @@ impl ArgusBot {
         // Haiku summarizes the conversation; embedding stored for future semantic recall.
         if history.len() > 10 {
             if let Some(emb) = self.config.embedding.clone() {
-                let api_key    = self.config.api_key.clone();
-                let api_url    = self.config.api_url.clone();
+                let config     = self.config.clone();
                 let http       = self.client.clone();
                 let conv_turns = history.clone();
                 let conv_id    = format!("telegram_{}", chat_id);
 
                 tokio::spawn(async move {
-                    summarize_and_embed(api_key, api_url, http, emb, conv_id, conv_turns).await;
+                    summarize_and_embed(config, http, emb, conv_id, conv_turns).await;
                 });
             }
         }
 
-        if !tool_log.is_empty() {
-            format!("{}\n\n{}", tool_log.join("\n"), response_text)
+        let header: Vec<String> = fallback_note.into_iter().chain(tool_log).collect();
+        if !header.is_empty() {
+            format!("{}\n\n{}", header.join("\n"), response_text)
         } else {
             response_text
         }
     }
 }
 
+/// Where a turn's preview and reply go: a Telegram chat, or a fake in tests.
+trait ChatSink: Clone + Send + Sync + 'static {
+    fn send(&self, text: String) -> SinkFuture<'_, Option<MessageId>>;
+    /// False if the message couldn't be edited.
+    fn edit(&self, id: MessageId, text: String) -> SinkFuture<'_, bool>;
+}
+
+type SinkFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+#[derive(Clone)]
+struct TelegramChat {
+    bot: Bot,
+    chat_id: ChatId,
+}
+
+impl ChatSink for TelegramChat {
+    fn send(&self, text: String) -> SinkFuture<'_, Option<MessageId>> {
+        Box::pin(async move { self.bot.send_message(self.chat_id, text).await.ok().map(|m| m.id) })
+    }
+
+    fn edit(&self, id: MessageId, text: String) -> SinkFuture<'_, bool> {
+        Box::pin(async move { self.bot.edit_message_text(self.chat_id, id, text).await.is_ok() })
+    }
+}
+
+/// Run `turn`, streaming its partial text into a preview, then put the reply
+/// in place of the preview. `turn` owns the preview's sender, so the preview
+/// ends with the turn even when it streamed nothing (commands, rate limits).
+async fn deliver<S, F, Fut>(chat: S, turn: F)
+where
+    S: ChatSink,
+    F: FnOnce(mpsc::UnboundedSender<String>) -> Fut,
+    Fut: Future<Output = String>,
+{
+    let (partial_tx, partial_rx) = mpsc::unbounded_channel();
+    let preview = tokio::spawn(stream_preview(chat.clone(), partial_rx));
+    let response = turn(partial_tx).await;
+    let mut preview_id = preview.await.ok().flatten();
+    for chunk in response.chars().collect::<Vec<_>>().chunks(4000) {
+        let chunk_str: String = chunk.iter().collect();
+        match preview_id.take() {
+            Some(id) => {
+                if !chat.edit(id, chunk_str.clone()).await {
+                    chat.send(chunk_str).await;
+                }
+            }
+            None => { chat.send(chunk_str).await; }
+        }
+    }
+}
+
+/// Mirror a reply-in-progress into one Telegram message: sent on the first
+/// update, edited (at most every `STREAM_EDIT_INTERVAL_MS`) after that, always
+/// showing the latest text. Returns the message so the final reply can
+/// replace it in place.
+async fn stream_preview<S: ChatSink>(
+    chat: S,
+    mut rx: mpsc::UnboundedReceiver<String>,
+) -> Option<MessageId> {
+    let mut message_id: Option<MessageId> = None;
+    let mut last_sent = String::new();
+
+    while let Some(mut text) = rx.recv().await {
+        // Skip straight to the newest update that queued while we waited.
+        while let Ok(newer) = rx.try_recv() {
+            text = newer;
+        }
+        let mut preview: String = text.chars().take(STREAM_PREVIEW_MAX_CHARS).collect();
+        preview.push_str(" ▌");
+        if preview == last_sent {
+            continue;
+        }
+        match message_id {
+            None => message_id = chat.send(preview.clone()).await,
+            Some(id) => { chat.edit(id, preview.clone()).await; }
+        }
+        last_sent = preview;
+        tokio::time::sleep(std::time::Duration::from_millis(STREAM_EDIT_INTERVAL_MS)).await;
+    }
+
+    message_id
+}
+
 /// Summarize a conversation with Haiku and store the embedding for future semantic recall.
 /// Runs as a background task — failures are logged but never surface to the user.
 async fn summarize_and_embed(
-    api_key: String,
-    api_url: String,
+    config: AgentConfig,
     http: reqwest::Client,
     emb: argus_core::EmbeddingClient,
     conv_id: String,
@@ -206,50 +348,67 @@ async fn summarize_and_embed(
         .collect::<Vec<_>>()
         .join("\n");
 
-    let body = serde_json::json!({
-        "model": MODEL_HAIKU,
-        "messages": [
-            {"role": "system", "content": "Summarize this conversation in 2-4 sentences. Focus on topics covered, decisions made, and anything memorable. Be factual and concise."},
-            {"role": "user", "content": transcript}
-        ],
-        "max_tokens": 200,
-    });
-
-    let resp = http
-        .post(&api_url)
-        .header("Authorization", format!("Bearer {}", api_key))
-        .header("Content-Type", "application/json")
-        .json(&body)
-        .send()
-        .await;
-
-    match resp {
+    let messages = vec![
+        serde_json::json!({"role": "system", "content": "Summarize this conversation in 2-4 sentences. Focus on topics covered, decisions made, and anything memorable. Be factual and concise."}),
+        serde_json::json!({"role": "user", "content": transcript}),
+    ];
+
+    match argus_core::side_completion(&config, &http, "haiku", messages, 0.3, 200).await {
         Err(e) => eprintln!("[summary] Haiku call failed: {}", e),
-        Ok(r) => {
-            match r.json::<serde_json::Value>().await {
-                Err(e) => eprintln!("[summary] Parse error: {}", e),
-                Ok(json) => {
-                    let summary = json["choices"][0]["message"]["content"]
-                        .as_str()
-                        .unwrap_or("")
-                        .to_string();
-                    if summary.is_empty() {
-                        eprintln!("[summary] Empty summary returned");
-                        return;
-                    }
-                    if let Err(e) = emb.store_conversation_embedding(&conv_id, &summary, "telegram").await {
-                        eprintln!("[summary] Embedding store failed: {}", e);
-                    } else {
-                        eprintln!("[summary] Stored conversation embedding for {}", conv_id);
-                    }
-                }
+        Ok(summary) => {
+            if let Err(e) = emb.store_conversation_embedding(&conv_id, &summary, "telegram").await {
+                eprintln!("[summary] Embedding store failed: {}", e);
+            } else {
+                eprintln!("[summary] Stored conversation embedding for {}", conv_id);
             }
         }
     }
 }
 
-pub async fn run_telegram_bot(token: String, config: AgentConfig) {
+/// A chat's running and queued turns, which share one cancel token.
+#[derive(Default)]
+struct ChatTurns {
+    cancel: CancelToken,
+    pending: usize,
+}
+
+/// chat_id → its turns; a chat with nothing in flight has no entry.
+type Turns = std::sync::Mutex<HashMap<i64, ChatTurns>>;
+
+/// Register a turn for `chat_id`, returning the token /stop cancels.
+fn start_turn(turns: &Turns, chat_id: i64) -> CancelToken {
+    let mut turns = turns.lock().unwrap();
+    let entry = turns.entry(chat_id).or_default();
+    entry.pending += 1;
+    entry.cancel.clone()
+}
+
+/// A turn started with `cancel` is over; the last one out removes the entry.
+fn finish_turn(turns: &Turns, chat_id: i64, cancel: &CancelToken) {
+    let mut turns = turns.lock().unwrap();
+    // /stop already removed a cancelled token's entry; any entry now is a newer one.
+    if cancel.is_cancelled() {
+        return;
+    }
+    if let Some(entry) = turns.get_mut(&chat_id) {
+        entry.pending -= 1;
+        if entry.pending == 0 {
+            turns.remove(&chat_id);
+        }
+    }
+}
+
+/// Cancel every turn in flight for `chat_id`. False if there were none.
+fn stop_turns(turns: &Turns, chat_id: i64) -> bool {
+    // Cancelled under the lock, so `finish_turn` never sees a removed entry's
+    // token as live.
+    let mut turns = turns.lock().unwrap();
+    turns.remove(&chat_id).map(|entry| entry.cancel.cancel()).is_some()
+}
+
+pub async fn run_telegram_bot(token: String, mut config: AgentConfig) {
     println!("Argus Telegram bot starting...");
+    config.surface = "telegram".to_string();
     if token.is_empty() || !token.contains(':') {
         eprintln!("[!] Telegram bot token is missing or malformed — bot disabled. Run ./argus-up.sh to load secrets from vault.");
         return;
@@ -264,31 +423,107 @@ pub async fn run_telegram_bot(token: String, config: AgentConfig) {
     // Clear any stale webhook so the long-poll dispatcher doesn't time out on GetWebhookInfo.
     let _ = bot.delete_webhook().await;
     let argus = Arc::new(Mutex::new(ArgusBot::new(config)));
+    // Kept outside the bot mutex so /stop gets through while a turn holds it.
+    let turns: Arc<Turns> = Arc::default();
 
     teloxide::repl(bot, move |bot: Bot, msg: Message| {
         let argus = Arc::clone(&argus);
+        let turns = Arc::clone(&turns);
         async move {
-            if let Some(text) = msg.text() {
-                let chat_id = msg.chat.id.0;
-                let response = {
+            let Some(text) = msg.text().map(str::to_string) else { return Ok(()) };
+            let chat_id = msg.chat.id.0;
+
+            if text.trim() == "/stop" {
+                let reply = if stop_turns(&turns, chat_id) { "Stopping." } else { "Nothing to stop." };
+                bot.send_message(msg.chat.id, reply).await?;
+                return Ok(());
+            }
+
+            // teloxide handles one update per chat at a time, so the turn runs
+            // on its own task — otherwise /stop would queue behind it.
+            let cancel = start_turn(&turns, chat_id);
+            tokio::spawn(async move {
+                let chat = TelegramChat { bot, chat_id: msg.chat.id };
+                let turn_cancel = cancel.clone();
+                deliver(chat, |partial| async move {
                     let mut agent = argus.lock().await;
                     if !agent.check_rate_limit(chat_id) {
                         format!(
                             "Rate limit: max {} messages per {}s. Please wait.",
                             RATE_LIMIT_MAX, RATE_LIMIT_WINDOW_SECS
                         )
-                    } else if let Some(cmd_reply) = agent.handle_command(text) {
+                    } else if let Some(cmd_reply) = agent.handle_command(&text) {
                         cmd_reply
                     } else {
-                        agent.process_message(chat_id, text).await
+                        agent.process_message(chat_id, &text, Some(partial), &turn_cancel).await
                     }
-                };
-                for chunk in response.chars().collect::<Vec<_>>().chunks(4000) {
-                    let chunk_str: String = chunk.iter().collect();
-                    bot.send_message(msg.chat.id, chunk_str).await?;
-                }
-            }
+                }).await;
+                finish_turn(&turns, chat_id, &cancel);
+            });
             Ok(())
         }
     }).await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Keeps every message, edited in place.
+    #[derive(Clone, Default)]
+    struct FakeChat(Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl ChatSink for FakeChat {
+        fn send(&self, text: String) -> SinkFuture<'_, Option<MessageId>> {
+            let mut messages = self.0.lock().unwrap();
+            messages.push(text);
+            let id = MessageId(messages.len() as i32 - 1);
+            Box::pin(async move { Some(id) })
+        }
+
+        fn edit(&self, id: MessageId, text: String) -> SinkFuture<'_, bool> {
+            self.0.lock().unwrap()[id.0 as usize] = text;
+            Box::pin(async { true })
+        }
+    }
+
+    #[test]
+    fn stop_only_answers_while_a_turn_is_in_flight() {
+        let turns = Turns::default();
+        let first = start_turn(&turns, 1);
+        let queued = start_turn(&turns, 1);
+        finish_turn(&turns, 1, &first);
+        finish_turn(&turns, 1, &queued);
+        assert!(!stop_turns(&turns, 1), "idle chat has nothing to stop");
+
+        let stopped = start_turn(&turns, 1);
+        assert!(stop_turns(&turns, 1));
+        assert!(stopped.is_cancelled());
+        // A turn started after /stop outlives the stopped one finishing.
+        let next = start_turn(&turns, 1);
+        finish_turn(&turns, 1, &stopped);
+        assert!(!next.is_cancelled());
+        assert!(stop_turns(&turns, 1));
+    }
+
+    #[tokio::test]
+    async fn replies_that_never_stream_are_delivered() {
+        let chat = FakeChat::default();
+        let command = deliver(chat.clone(), |_partial| async { "Current model: opus".to_string() });
+        tokio::time::timeout(Duration::from_secs(5), command).await.expect("reply never sent");
+        assert_eq!(*chat.0.lock().unwrap(), ["Current model: opus"]);
+    }
+
+    #[tokio::test]
+    async fn streamed_preview_is_replaced_by_the_reply() {
+        let chat = FakeChat::default();
+        let turn = deliver(chat.clone(), |partial| async move {
+            partial.send("Thinking".to_string()).unwrap();
+            tokio::task::yield_now().await;
+            "Done.".to_string()
+        });
+        tokio::time::timeout(Duration::from_secs(5), turn).await.expect("reply never sent");
+        assert_eq!(*chat.0.lock().unwrap(), ["Done."]);
+    }
+}
@@ -9,13 +9,16 @@
 //! Classification only. If something comes through designed to manipulate,
 //! it finds nothing to grab onto.
 
-use argus_core::agent::{AgentConfig, AgentEvent, MODEL_TRIAGE};
+use argus_core::agent::{AgentConfig, AgentEvent};
+use argus_core::models;
 use argus_core::mcp::McpClient;
 use argus_core::shell::ShellPolicy;
+use argus_core::shell_policy::Surface;
 use argus_core::supabase::SupabaseClient;
 use argus_core::triage::{TriageFlag, TriageResult, build_haiku_triage_prompt, classify_lane, route_to_channel, TriageLane, TriageEntry};
 use argus_core::tools::MemoryBackend;
 use argus_core::run_agent_turn;
+use argus_core::CancelToken;
 use reqwest::Client;
 use tokio::time::{sleep, Duration};
 
@@ -31,10 +34,11 @@ impl MemoryBackend for NoopMemory {
 
 pub fn spawn_triage_loop(
     supabase: SupabaseClient,
-    agent_config: AgentConfig,
+    mut agent_config: AgentConfig,
     discord_token: String,
     channel_id: String,
 ) {
+    agent_config.surface = "triage".to_string();
     tokio::spawn(async move {
         run_triage_loop(supabase, agent_config, discord_token, channel_id).await;
     });
@@ -101,12 +105,12 @@ async fn run_triage_loop(
 
                             let prompt = build_haiku_triage_prompt(&entry);
                             let haiku_config = AgentConfig {
-                                model: MODEL_TRIAGE.to_string(),
+                                model: models::id("triage").to_string(),
                                 ..agent_config.clone()
                             };
 
                             let mut mcp = McpClient::new();
-                            let shell_policy = ShellPolicy::default();
+                            let shell_policy = ShellPolicy::for_surface(Surface::Triage);
                             let memory = NoopMemory;
 
                             match run_agent_turn(
@@ -117,6 +121,7 @@ async fn run_triage_loop(
                                 &memory,
                                 &mut mcp,
                                 &http,
+                                &CancelToken::new(),
                                 |event| {
                                     if let AgentEvent::ToolCall { name, preview, .. } = event {
                                         eprintln!("[triage] haiku tool: {} — {}", name, preview);
@@ -1,7 +1,11 @@
 //! Interactive TUI for Argus
+//!
+//! HIGH risk shell commands, and tools the policy file flags, pause the turn
+//! behind an approval modal: `y` runs the call, `e` edits it first, `n` or
+//! ESC denies it. Unanswered requests get the policy's timeout default.
 
 use crossterm::{
-    event::{self, Event, KeyCode},
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -10,12 +14,18 @@ use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
     Terminal,
 };
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-use argus_core::{AgentConfig, AgentEvent, ConversationMessage, McpClient, ShellPolicy};
+use argus_core::shell::{PermissionDecision, PermissionRequest};
+use argus_core::{AgentConfig, AgentEvent, ApprovalBroker, ApprovalEvent, CancelToken, ConversationMessage, McpClient, ShellPolicy, model_label};
+use tokio::sync::mpsc;
 use argus_memory::SqliteMemory;
 
 const ARGUS_WATCHING: &str = "
@@ -71,6 +81,52 @@ struct ChatMessage {
     content: String,
 }
 
+/// What the screen shows while a turn is in flight — drawn on top of the
+/// committed chat so streamed text appears as it arrives.
+#[derive(Default)]
+struct LiveTurn {
+    state: Option<ArgusState>,
+    tool_log: Vec<String>,
+    partial: String,
+    /// Requests waiting on the operator, oldest first — the front one is shown.
+    approvals: VecDeque<PermissionRequest>,
+    /// Edited command for the front request, while `e` is in progress.
+    editing: Option<String>,
+}
+
+impl LiveTurn {
+    /// Apply a key to the approval modal. Keys with no modal open are dropped.
+    fn approval_key(&mut self, key: KeyEvent, broker: &ApprovalBroker) {
+        let Some(request) = self.approvals.front() else { return };
+        let decision = match (&mut self.editing, key.code) {
+            (Some(edit), KeyCode::Char(c)) => { edit.push(c); None }
+            (Some(edit), KeyCode::Backspace) => { edit.pop(); None }
+            (Some(_), KeyCode::Esc) => { self.editing = None; None }
+            (Some(edit), KeyCode::Enter) => Some(PermissionDecision::Edit { command: edit.clone() }),
+            // Only `y` approves: Enter is what the chat input sends with, and too easy
+            // to hit while a modal pops up.
+            (None, KeyCode::Char('y')) => Some(PermissionDecision::Allow),
+            (None, KeyCode::Char('e')) => { self.editing = Some(request.command.clone()); None }
+            (None, KeyCode::Char('n') | KeyCode::Esc) => Some(PermissionDecision::Deny {
+                reason: "Denied by operator".to_string(),
+            }),
+            _ => None,
+        };
+        if let Some(decision) = decision {
+            let id = request.id.clone();
+            broker.resolve(&id, decision);
+            self.close_approval(&id);
+        }
+    }
+
+    fn close_approval(&mut self, id: &str) {
+        if self.approvals.front().is_some_and(|r| r.id == id) {
+            self.editing = None;
+        }
+        self.approvals.retain(|r| r.id != id);
+    }
+}
+
 struct App {
     chat: Vec<ChatMessage>,
     history: Vec<ConversationMessage>,
@@ -81,11 +137,16 @@ struct App {
     state: ArgusState,
     memory: SqliteMemory,
     mcp: McpClient,
+    /// Cached at startup — the MCP client is lent to the agent during a turn.
+    mcp_status: String,
     shell_policy: ShellPolicy,
+    approvals: Arc<ApprovalBroker>,
+    /// Taken for the length of a turn, like `mcp`.
+    approval_events: Option<mpsc::UnboundedReceiver<ApprovalEvent>>,
 }
 
 impl App {
-    fn new(config: AgentConfig) -> Result<Self, String> {
+    fn new(mut config: AgentConfig) -> Result<Self, String> {
         let memory = SqliteMemory::open_default()
             .map_err(|e| format!("Failed to open memory: {}", e))?;
 
@@ -94,6 +155,16 @@ impl App {
         for err in &mcp_errors {
             eprintln!("MCP: {}", err);
         }
+        let mcp_count = mcp.servers.len();
+        let mcp_tools: usize = mcp.servers.iter().map(|s| s.tools.len()).sum();
+        let mcp_status = if mcp_count > 0 {
+            format!(" {} MCP ({} tools) ", mcp_count, mcp_tools)
+        } else {
+            String::new()
+        };
+        config.stream = true;
+        let (approvals, approval_events) = ApprovalBroker::new();
+        config.shell_prompter = Some(approvals.clone());
 
         Ok(Self {
             chat: vec![],
@@ -105,11 +176,17 @@ impl App {
             state: ArgusState::Watching,
             memory,
             mcp,
-            shell_policy: ShellPolicy::default(),
+            mcp_status,
+            shell_policy: ShellPolicy::configured(),
+            approvals,
+            approval_events: Some(approval_events),
         })
     }
 
-    async fn send_message(&mut self) -> anyhow::Result<()> {
+    async fn send_message(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> anyhow::Result<()> {
         if self.input.trim().is_empty() {
             return Ok(());
         }
@@ -120,44 +197,121 @@ impl App {
         self.state = ArgusState::Thinking;
 
         let mut response_text = String::new();
-        let mut tool_log: Vec<String> = Vec::new();
-
-        let result = argus_core::run_agent_turn(
-            &self.config,
-            &user_msg,
-            &self.history,
-            &self.shell_policy,
-            &self.memory,
-            &mut self.mcp,
-            &self.client,
-            |event| match event {
-                AgentEvent::ToolCall { name, preview, .. } => {
-                    self.state = ArgusState::Executing;
-                    let short = if preview.len() > 60 { format!("{}...", &preview[..60]) } else { preview };
-                    tool_log.push(format!("[tool] {}: {}", name, short));
+        let mut answered_by = self.config.model.clone();
+        let mut compacted: Option<(String, usize)> = None;
+        let mut cancelled = false;
+        let live = RefCell::new(LiveTurn::default());
+
+        // Raw mode turns Ctrl-C into a key event, and the event loop is
+        // parked on this turn — watch for it on a thread of its own, which
+        // also passes keys on to the approval modal.
+        let cancel = CancelToken::new();
+        let watching = Arc::new(AtomicBool::new(true));
+        let (keys_tx, mut keys) = mpsc::unbounded_channel();
+        let watcher = {
+            let (cancel, watching) = (cancel.clone(), Arc::clone(&watching));
+            std::thread::spawn(move || watch_keys(&cancel, &watching, &keys_tx))
+        };
+
+        // Lend the MCP client to the agent so the rest of the app can be
+        // borrowed for redraws from inside the event callback.
+        let mut mcp = std::mem::replace(&mut self.mcp, McpClient::new());
+        let mut approval_events = self.approval_events.take();
+        let app: &App = self;
+        let result = {
+            // Shared by the agent's event callback and the approval loop below;
+            // both run on this task, never at the same time.
+            let terminal = RefCell::new(terminal);
+            let redraw = || {
+                let _ = terminal.borrow_mut().draw(|f| draw_ui(f, app, Some(&live.borrow())));
+            };
+
+            let turn = argus_core::run_agent_turn(
+                &app.config,
+                &user_msg,
+                &app.history,
+                &app.shell_policy,
+                &app.memory,
+                &mut mcp,
+                &app.client,
+                &cancel,
+                |event| {
+                    {
+                        let mut live = live.borrow_mut();
+                        match event {
+                            AgentEvent::Thinking => {
+                                live.state = Some(ArgusState::Thinking);
+                                live.partial.clear();
+                            }
+                            AgentEvent::ToolCall { name, preview, .. } => {
+                                live.state = Some(ArgusState::Executing);
+                                let short = if preview.len() > 60 { format!("{}...", &preview[..60]) } else { preview };
+                                live.tool_log.push(format!("[tool] {}: {}", name, short));
+                            }
+                            AgentEvent::TokenDelta(text) => { live.partial.push_str(&text); }
+                            AgentEvent::ModelFallback { from, to, reason } => {
+                                live.tool_log.push(format!("[fallback] {} {} — answered by {}", model_label(&from), reason, model_label(&to)));
+                                answered_by = to;
+                            }
+                            AgentEvent::HistoryCompacted { summary, replaced } => { compacted = Some((summary, replaced)); }
+                            AgentEvent::Cancelled => { cancelled = true; }
+                            AgentEvent::Response(text) => { response_text = text; }
+                            AgentEvent::Error(err) => { response_text = format!("[error] {}", err); }
+                            _ => {}
+                        }
+                    }
+                    redraw();
+                },
+            );
+            tokio::pin!(turn);
+
+            loop {
+                tokio::select! {
+                    result = &mut turn => break result,
+                    Some(event) = async { approval_events.as_mut()?.recv().await } => {
+                        match event {
+                            ApprovalEvent::Requested(request) => live.borrow_mut().approvals.push_back(request),
+                            ApprovalEvent::Closed { id } => live.borrow_mut().close_approval(&id),
+                        }
+                        redraw();
+                    }
+                    Some(key) = keys.recv() => {
+                        live.borrow_mut().approval_key(key, &app.approvals);
+                        redraw();
+                    }
                 }
-                AgentEvent::Response(text) => { response_text = text; }
-                AgentEvent::Error(err) => { response_text = format!("[error] {}", err); }
-                _ => {}
-            },
-        ).await;
+            }
+        };
 
-        if let Err(e) = result {
+        self.mcp = mcp;
+        self.approval_events = approval_events;
+        watching.store(false, Ordering::SeqCst);
+        let _ = watcher.join();
+        let live = live.into_inner();
+
+        if cancelled {
+            response_text = "[stopped]".to_string();
+        } else if let Err(e) = result {
             if response_text.is_empty() {
                 response_text = format!("Error: {}", e);
             }
         }
 
-        self.history.push(ConversationMessage { role: "user".to_string(), content: user_msg, model: None });
-        if !response_text.is_empty() {
+        if let Some((summary, replaced)) = compacted {
+            argus_core::context::apply_compaction(&mut self.history, &summary, replaced);
+        }
+        if !cancelled {
+            self.history.push(ConversationMessage { role: "user".to_string(), content: user_msg, model: None });
+        }
+        if !response_text.is_empty() && !cancelled {
             self.history.push(ConversationMessage {
                 role: "assistant".to_string(),
                 content: response_text.clone(),
-                model: Some(self.config.model.clone()),
+                model: Some(answered_by),
             });
         }
 
-        for entry in tool_log {
+        for entry in live.tool_log {
             self.chat.push(ChatMessage { role: "Argus".to_string(), content: entry });
         }
         if !response_text.is_empty() {
@@ -169,7 +323,25 @@ impl App {
     }
 }
 
-pub async fn run_tui(config: AgentConfig) -> anyhow::Result<()> {
+/// Cancel the turn on Ctrl-C until `watching` is cleared. Other keys pressed
+/// mid-turn go to `keys`, for the approval modal.
+fn watch_keys(cancel: &CancelToken, watching: &AtomicBool, keys: &mpsc::UnboundedSender<KeyEvent>) {
+    while watching.load(Ordering::SeqCst) {
+        if !event::poll(std::time::Duration::from_millis(50)).unwrap_or(false) {
+            continue;
+        }
+        if let Ok(Event::Key(key)) = event::read() {
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                cancel.cancel();
+                return;
+            }
+            let _ = keys.send(key);
+        }
+    }
+}
+
+pub async fn run_tui(mut config: AgentConfig) -> anyhow::Result<()> {
+    config.surface = "tui".to_string();
     let mut app = App::new(config).map_err(|e| anyhow::anyhow!("{}", e))?;
 
     enable_raw_mode()?;
@@ -190,7 +362,7 @@ async fn run_event_loop(
     app: &mut App,
 ) -> anyhow::Result<()> {
     loop {
-        terminal.draw(|f| draw_ui(f, app))?;
+        terminal.draw(|f| draw_ui(f, app, None))?;
 
         if event::poll(std::time::Duration::from_millis(50))? {
             if let Event::Key(key) = event::read()? {
@@ -200,7 +372,7 @@ async fn run_event_loop(
                 app.state = ArgusState::Watching;
                 match key.code {
                     KeyCode::Esc       => break,
-                    KeyCode::Enter     => { if !app.input.is_empty() { app.send_message().await?; } }
+                    KeyCode::Enter     => { if !app.input.is_empty() { app.send_message(terminal).await?; } }
                     KeyCode::Char(c)   => app.input.push(c),
                     KeyCode::Backspace => { app.input.pop(); }
                     KeyCode::Up        => app.scroll = app.scroll.saturating_sub(1),
@@ -213,17 +385,80 @@ async fn run_event_loop(
     Ok(())
 }
 
-fn draw_ui(f: &mut ratatui::Frame, app: &App) {
+fn draw_ui(f: &mut ratatui::Frame, app: &App, live: Option<&LiveTurn>) {
     let main_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Length(22), Constraint::Min(40)])
         .split(f.size());
-    draw_state_icon(f, app, main_chunks[0]);
-    draw_chat(f, app, main_chunks[1]);
+    let state = live.and_then(|l| l.state).unwrap_or(app.state);
+    draw_state_icon(f, state, main_chunks[0]);
+    draw_chat(f, app, state, live, main_chunks[1]);
+    if let Some(live) = live {
+        draw_approval(f, live);
+    }
 }
 
-fn draw_state_icon(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
-    let (icon, color, title) = match app.state {
+/// Centered modal for the oldest pending approval request.
+fn draw_approval(f: &mut ratatui::Frame, live: &LiveTurn) {
+    let Some(request) = live.approvals.front() else { return };
+    let area = f.size();
+    let width = area.width.saturating_sub(8).min(90);
+    let height = 12.min(area.height);
+    let popup = ratatui::layout::Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::DarkGray));
+    let key = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+    let on_timeout = if request.approve_on_timeout { "approved" } else { "denied" };
+    let mut lines = vec![
+        Line::from(vec![
+            label("Tool "),
+            Span::styled(request.tool.as_str(), Style::default().fg(Color::Cyan)),
+            label("  Risk "),
+            Span::styled(request.risk.as_str(), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+        ]),
+        Line::from(Span::styled(request.reason.as_str(), Style::default().fg(Color::Gray))),
+        Line::from(""),
+    ];
+    match &live.editing {
+        Some(edit) => {
+            lines.push(Line::from(Span::styled(format!("{}_", edit), Style::default().fg(Color::White))));
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![key("ENTER"), label(" run edited  "), key("ESC"), label(" back")]));
+        }
+        None => {
+            lines.push(Line::from(Span::styled(request.command.as_str(), Style::default().fg(Color::White))));
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![
+                key("y"), label(" approve  "),
+                key("e"), label(" edit  "),
+                key("n"), label(" deny  "),
+                Span::styled(format!("({} after {}s)", on_timeout, request.timeout_secs), Style::default().fg(Color::DarkGray)),
+            ]));
+        }
+    }
+    if live.approvals.len() > 1 {
+        lines.push(Line::from(label("more requests waiting")));
+    }
+
+    let modal = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .title(Span::styled(" Approval needed ", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))),
+        );
+    f.render_widget(Clear, popup);
+    f.render_widget(modal, popup);
+}
+
+fn draw_state_icon(f: &mut ratatui::Frame, state: ArgusState, area: ratatui::layout::Rect) {
+    let (icon, color, title) = match state {
         ArgusState::Watching  => (ARGUS_WATCHING,  Color::Cyan,    " Watching "),
         ArgusState::Thinking  => (ARGUS_THINKING,  Color::Yellow,  " Thinking "),
         ArgusState::Executing => (ARGUS_EXECUTING, Color::Magenta, " Executing "),
@@ -241,7 +476,13 @@ fn draw_state_icon(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rec
     f.render_widget(widget, area);
 }
 
-fn draw_chat(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
+fn draw_chat(
+    f: &mut ratatui::Frame,
+    app: &App,
+    state: ArgusState,
+    live: Option<&LiveTurn>,
+    area: ratatui::layout::Rect,
+) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -261,19 +502,31 @@ fn draw_chat(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
     .block(Block::default().borders(Borders::BOTTOM).border_style(Style::default().fg(Color::DarkGray)));
     f.render_widget(header, chunks[0]);
 
+    // In-flight tool calls and streamed text render as trailing Argus messages.
+    let live_msgs: Vec<(&str, &str)> = live
+        .map(|l| {
+            let mut v: Vec<(&str, &str)> = l.tool_log.iter().map(|t| ("Argus", t.as_str())).collect();
+            if !l.partial.is_empty() {
+                v.push(("Argus", l.partial.as_str()));
+            }
+            v
+        })
+        .unwrap_or_default();
+
     let mut chat_lines: Vec<Line> = vec![];
-    for msg in &app.chat {
-        let (color, prefix) = if msg.role == "You" {
+    let committed = app.chat.iter().map(|m| (m.role.as_str(), m.content.as_str()));
+    for (role, content) in committed.chain(live_msgs) {
+        let (color, prefix) = if role == "You" {
             (Color::Green, "> ")
         } else {
             (Color::Cyan, "o ")
         };
         chat_lines.push(Line::from(vec![
             Span::styled(prefix, Style::default().fg(color)),
-            Span::styled(&msg.role, Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            Span::styled(role, Style::default().fg(color).add_modifier(Modifier::BOLD)),
         ]));
-        let text_color = if msg.role == "You" { Color::White } else { Color::Gray };
-        for line in msg.content.lines() {
+        let text_color = if role == "You" { Color::White } else { Color::Gray };
+        for line in content.lines() {
             chat_lines.push(Line::from(Span::styled(
                 format!("  {}", line),
                 Style::default().fg(text_color),
@@ -293,7 +546,7 @@ fn draw_chat(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
         .scroll((app.scroll, 0));
     f.render_widget(chat, chunks[1]);
 
-    let is_busy = matches!(app.state, ArgusState::Thinking | ArgusState::Executing);
+    let is_busy = matches!(state, ArgusState::Thinking | ArgusState::Executing);
     let (input_border, input_fg, input_title) = if is_busy {
         (Color::DarkGray, Color::DarkGray, " Working... ")
     } else {
@@ -312,13 +565,6 @@ fn draw_chat(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
         );
     f.render_widget(input, chunks[2]);
 
-    let mcp_count = app.mcp.servers.len();
-    let mcp_tools: usize = app.mcp.servers.iter().map(|s| s.tools.len()).sum();
-    let mcp_status = if mcp_count > 0 {
-        format!(" {} MCP ({} tools) ", mcp_count, mcp_tools)
-    } else {
-        String::new()
-    };
     let model_short = app.config.model.rsplit('/').next().unwrap_or(&app.config.model);
     let history_len = app.history.len() / 2;
     let brave_status = if app.config.brave_search_key.is_some() { "[search:ok]" } else { "[search:off]" };
@@ -328,7 +574,9 @@ fn draw_chat(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
         Span::styled(" quit ", Style::default().fg(Color::DarkGray)),
         Span::styled("ENTER", Style::default().fg(Color::Yellow)),
         Span::styled(" send  ", Style::default().fg(Color::DarkGray)),
-        Span::styled(&mcp_status, Style::default().fg(Color::Blue)),
+        Span::styled(if is_busy { "CTRL-C" } else { "" }, Style::default().fg(Color::Yellow)),
+        Span::styled(if is_busy { " stop  " } else { "" }, Style::default().fg(Color::DarkGray)),
+        Span::styled(&app.mcp_status, Style::default().fg(Color::Blue)),
         Span::styled(model_short, Style::default().fg(Color::Magenta)),
         Span::styled(" | ", Style::default().fg(Color::DarkGray)),
         Span::styled(brave_status, Style::default().fg(
@@ -339,3 +587,33 @@ fn draw_chat(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
     ]));
     f.render_widget(status, chunks[3]);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argus_core::shell::RiskLevel;
+
+    fn press(live: &mut LiveTurn, broker: &ApprovalBroker, code: KeyCode) {
+        live.approval_key(KeyEvent::new(code, KeyModifiers::NONE), broker);
+    }
+
+    #[test]
+    fn only_y_approves() {
+        let (broker, _events) = ApprovalBroker::new();
+        let mut live = LiveTurn::default();
+        live.approvals.push_back(PermissionRequest {
+            id: "1".to_string(),
+            tool: "shell".to_string(),
+            command: "rm -rf build".to_string(),
+            risk: RiskLevel::High,
+            reason: "recursive delete".to_string(),
+            timeout_secs: 60,
+            approve_on_timeout: false,
+        });
+
+        press(&mut live, &broker, KeyCode::Enter);
+        assert_eq!(live.approvals.len(), 1, "Enter must not answer the modal");
+        press(&mut live, &broker, KeyCode::Char('y'));
+        assert!(live.approvals.is_empty());
+    }
+}
@@ -18,8 +18,12 @@ use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 use tower_http::cors::CorsLayer;
 
-use argus_core::{AgentConfig, AgentEvent, ConversationMessage, EmbeddingClient, McpClient, MemoryBackend, ShellPolicy, MODEL_HAIKU, MODEL_SONNET, MODEL_OPUS, MODEL_GROK, MODEL_GROK_BUILD, MODEL_GROK_MULTI, MODEL_GEMINI};
-use argus_core::shell::PermissionPrompter;
+use argus_core::{AgentConfig, AgentEvent, CancelToken, ConversationMessage, EmbeddingClient, McpClient, MemoryBackend, ShellPolicy, ModelRegistry, ModelSpec, UsageLedger, UsageReport, model_label};
+use argus_core::shell_policy::Surface;
+use argus_core::cancel::CANCELLED;
+use argus_core::context::SUMMARY_ROLE;
+use argus_core::approval::{ApprovalBroker, ApprovalEvent};
+use argus_core::shell::{PermissionDecision, PermissionPrompter, PermissionRequest};
 use argus_memory::sqlite::{ConversationMeta, SqliteMemory};
 
 // ─── WebSocket message types (mirrors TypeScript protocol) ─────────────────
@@ -32,6 +36,15 @@ enum ClientMessage {
     SetModelTools { model: String, enabled: bool },
     ScheduleTask { agent: String, run_at: Option<String>, description: String },
     Cancel,
+    /// Answer to an `approval_request`: "approve", "edit" (with `command`) or "deny".
+    ApprovalResponse {
+        id: String,
+        decision: String,
+        #[serde(default)]
+        command: Option<String>,
+        #[serde(default)]
+        reason: Option<String>,
+    },
     NewConversation,
     LoadConversation { id: String },
     ListConversations,
@@ -104,9 +117,30 @@ enum ServerMessage {
         success: bool,
         call_id: String,
     },
+    /// Partial model output while a response streams in.
+    ResponseChunk {
+        content: String,
+    },
     ResponseComplete {
         content: String,
     },
+    /// The selected model failed and another one is answering this turn.
+    ModelFallback {
+        from: String,
+        to: String,
+        reason: String,
+    },
+    /// The turn was stopped by a `cancel` message; partial output is discarded.
+    TurnCancelled,
+    /// A tool call is paused until the operator approves, edits or denies it.
+    ApprovalRequest {
+        #[serde(flatten)]
+        request: PermissionRequest,
+    },
+    /// The request was answered, timed out or its turn was cancelled.
+    ApprovalClosed {
+        id: String,
+    },
     Error {
         message: String,
     },
@@ -150,6 +184,10 @@ enum ServerMessage {
     MissionsUpdate {
         missions: Vec<serde_json::Value>,
     },
+    /// Token spend today and this month — sent on connect and after every turn.
+    UsageUpdate {
+        usage: UsageReport,
+    },
 }
 
 // ─── Per-connection state ──────────────────────────────────────────────────
@@ -162,23 +200,28 @@ struct ConnectionState {
     client: reqwest::Client,
     memory: SqliteMemory,
     mcp: McpClient,
-    shell_policy: ShellPolicy,
     conversation_id: String,
     conversation_title: String,
 }
 
-/// Canonical frontend model aliases — maps legacy DB values and OpenRouter IDs.
+/// Web picker slot for a frontend alias, legacy DB value or OpenRouter ID.
+/// Unknown values get the registry's `frontend_default`.
+fn frontend_slot(raw: &str) -> &'static ModelSpec {
+    let registry = ModelRegistry::global();
+    registry.resolve_frontend(raw).unwrap_or_else(|| registry.frontend_default())
+}
+
+fn frontend_alias(slot: &'static ModelSpec) -> &'static str {
+    slot.frontend.as_deref().unwrap_or(&slot.key)
+}
+
+fn default_frontend_model() -> &'static str {
+    frontend_alias(ModelRegistry::global().frontend_default())
+}
+
+/// Canonical frontend model alias — maps legacy DB values and OpenRouter IDs.
 fn normalize_frontend_model(raw: &str) -> &'static str {
-    match raw.trim().to_lowercase().as_str() {
-        "claude-haiku" | "haiku" | "anthropic/claude-haiku-4-5" => "claude-haiku",
-        "claude-sonnet" | "sonnet" | "anthropic/claude-sonnet-4-6" => "claude-sonnet",
-        "claude-opus" | "opus" | "google/gemma-4-31b-it:free" => "claude-opus",
-        "grok" | "nemotron" | "x-ai/grok-4.20" | "grok-4" | "grok-4.20" => "grok",
-        "grok-build" | "grok build" | "x-ai/grok-build-0.1" => "grok-build",
-        "grok-multi" | "x-ai/grok-4.20-multi-agent" => "grok-multi",
-        "gemini-flash" | "gemini" | "google/gemini-3.1-pro-preview" => "gemini-flash",
-        _ => "grok-build",
-    }
+    frontend_alias(frontend_slot(raw))
 }
 
 impl ConnectionState {
@@ -193,19 +236,22 @@ impl ConnectionState {
         audit: Option<std::sync::Arc<argus_audit::AuditChain>>,
         discord_bot_token: Option<String>,
         discord_channel_id: Option<u64>,
+        usage: Option<std::sync::Arc<dyn UsageLedger>>,
         surface: &str,
         initial_model: Option<&str>,
     ) -> anyhow::Result<Self> {
-        let mut config = AgentConfig::new(api_key);
+        let mut config = AgentConfig::new(api_key).with_exec_auth_token(exec_auth_token);
         if let Some(k) = brave_key {
             config.brave_search_key = Some(k);
         }
         config.shell_prompter     = shell_prompter;
-        config.exec_auth_token    = exec_auth_token;
         config.embedding          = embedding;
         config.audit              = audit;
         config.discord_bot_token  = discord_bot_token;
         config.discord_channel_id = discord_channel_id;
+        config.usage              = usage;
+        config.surface            = "web".to_string();
+        config.stream             = true;
 
         let memory = SqliteMemory::open_default()
             .map_err(|e| anyhow::anyhow!("Memory init failed: {}", e))?;
@@ -215,19 +261,14 @@ impl ConnectionState {
             match memory.latest_conversation() {
                 Ok(Some(meta)) if meta.surface == "web" => {
                     let hist = memory.load_history_str(&meta.id).unwrap_or_default();
-                    let fm = meta
-                        .model
-                        .as_deref()
-                        .map(normalize_frontend_model)
-                        .unwrap_or("grok-build")
-                        .to_string();
+                    let fm = normalize_frontend_model(meta.model.as_deref().unwrap_or_default()).to_string();
                     (meta.id, meta.title, hist, fm)
                 }
                 _ => {
                     let id = uuid::Uuid::new_v4().to_string();
                     let title = "New Conversation".to_string();
                     let _ = memory.upsert_conversation(&id, &title, "web", initial_model, 0);
-                    (id, title, Vec::new(), "grok-build".to_string())
+                    (id, title, Vec::new(), default_frontend_model().to_string())
                 }
             }
         } else {
@@ -238,7 +279,7 @@ impl ConnectionState {
                 format!("{} Session", surface)
             };
             let _ = memory.upsert_conversation(&id, &title, surface, initial_model, 0);
-            (id, title, Vec::new(), initial_model.unwrap_or("grok-build").to_string())
+            (id, title, Vec::new(), normalize_frontend_model(initial_model.unwrap_or_default()).to_string())
         };
 
         let mut mcp = McpClient::new();
@@ -254,7 +295,6 @@ impl ConnectionState {
             client: reqwest::Client::new(),
             memory,
             mcp,
-            shell_policy: ShellPolicy::permissive(),
             conversation_id,
             conversation_title,
         };
@@ -267,18 +307,8 @@ impl ConnectionState {
     /// Map frontend model ID alias → OpenRouter model ID + persona
     fn apply_model_switch(&mut self, frontend_id: &str) {
         let alias = normalize_frontend_model(frontend_id);
-        let openrouter_id = match alias {
-            "claude-haiku"  => MODEL_HAIKU,
-            "claude-sonnet" => MODEL_SONNET,
-            "claude-opus"   => MODEL_OPUS,
-            "grok"          => MODEL_GROK,
-            "grok-build"    => MODEL_GROK_BUILD,
-            "grok-multi"    => MODEL_GROK_MULTI,
-            "gemini-flash"  => MODEL_GEMINI,
-            _               => MODEL_GROK_BUILD,
-        };
         self.frontend_model = alias.to_string();
-        self.config.model = openrouter_id.to_string();
+        self.config.model = frontend_slot(alias).id.clone();
         self.config.frontend_persona = Some(alias.to_string());
     }
 
@@ -301,12 +331,12 @@ struct AppState {
     /// permitted when the token matches. Browser requests must be in this list.
     allowed_origins: Vec<String>,
     // Daemon-level capabilities forwarded to every WebSocket connection.
-    shell_prompter:     Option<std::sync::Arc<dyn PermissionPrompter>>,
     exec_auth_token:    Option<String>,
     embedding:          Option<EmbeddingClient>,
     audit:              Option<std::sync::Arc<argus_audit::AuditChain>>,
     discord_bot_token:  Option<String>,
     discord_channel_id: Option<u64>,
+    usage:              Option<std::sync::Arc<dyn UsageLedger>>,
     /// Per-model tool toggle — operator can disable tools for any model from the UI.
     /// Anthropic models default to always-enabled; others default true but are toggleable.
     model_tools: Arc<tokio::sync::RwLock<HashMap<String, bool>>>,
@@ -353,12 +383,12 @@ pub async fn run_web_server(
         vault_keys,
         ws_token,
         allowed_origins,
-        shell_prompter:     config.shell_prompter,
         exec_auth_token:    config.exec_auth_token,
         embedding:          config.embedding,
         audit:              config.audit,
         discord_bot_token:  config.discord_bot_token,
         discord_channel_id: config.discord_channel_id,
+        usage:              config.usage,
         model_tools:        Arc::new(tokio::sync::RwLock::new(HashMap::new())),
         mission_executor:   config.mission_executor.clone(),
     });
@@ -529,6 +559,18 @@ fn build_memory_update(memory: &SqliteMemory) -> ServerMessage {
     ServerMessage::MemoryUpdate { memories: payloads }
 }
 
+/// Summarise the usage ledger as a UsageUpdate message; None without a ledger.
+fn build_usage_update(config: &AgentConfig) -> Option<ServerMessage> {
+    let ledger = config.usage.as_ref()?;
+    match argus_core::usage::report(&**ledger, ModelRegistry::global().budgets()) {
+        Ok(usage) => Some(ServerMessage::UsageUpdate { usage }),
+        Err(e) => {
+            eprintln!("[web] usage report failed: {}", e);
+            None
+        }
+    }
+}
+
 async fn ws_handler(
     ws: WebSocketUpgrade,
     Query(params): Query<HashMap<String, String>>,
@@ -589,15 +631,31 @@ async fn handle_socket(
         }
     });
 
+    // Approvals go to whoever is on this socket.
+    let (approvals, mut approval_events) = ApprovalBroker::new();
+    let tx_approvals = tx.clone();
+    tokio::spawn(async move {
+        while let Some(event) = approval_events.recv().await {
+            let msg = match event {
+                ApprovalEvent::Requested(request) => ServerMessage::ApprovalRequest { request },
+                ApprovalEvent::Closed { id } => ServerMessage::ApprovalClosed { id },
+            };
+            if tx_approvals.send(msg).is_err() {
+                break;
+            }
+        }
+    });
+
     let conn = match ConnectionState::new(
         state.api_key.clone(),
         state.brave_key.clone(),
-        state.shell_prompter.clone(),
+        Some(approvals.clone() as std::sync::Arc<dyn PermissionPrompter>),
         state.exec_auth_token.clone(),
         state.embedding.clone(),
         state.audit.clone(),
         state.discord_bot_token.clone(),
         state.discord_channel_id,
+        state.usage.clone(),
         &surface,
         initial_model.as_deref(),
     ) {
@@ -620,10 +678,14 @@ async fn handle_socket(
             mcp_servers,
         });
         let _ = tx.send(build_memory_update(&c.memory));
+        if let Some(usage) = build_usage_update(&c.config) {
+            let _ = tx.send(usage);
+        }
 
         // Replay the restored conversation history so the UI is not blank on reconnect.
         if !c.history.is_empty() {
-            let messages = c.history.iter().map(|m| HistoryMessagePayload {
+            // Compaction summaries feed the model, not the transcript.
+            let messages = c.history.iter().filter(|m| m.role != SUMMARY_ROLE).map(|m| HistoryMessagePayload {
                 role: m.role.clone(),
                 content: m.content.clone(),
                 model: m.model.clone(),
@@ -640,6 +702,12 @@ async fn handle_socket(
         let _ = tx.send(ServerMessage::ConversationsList { conversations });
     }
 
+    // Turns run off the read loop so a `cancel` can arrive mid-turn. They
+    // still run one at a time, in order; cancelling stops the running turn
+    // and any queued behind it.
+    let mut turn: Option<tokio::task::JoinHandle<()>> = None;
+    let mut cancel = CancelToken::new();
+
     let mut ws_rx = ws_rx;
     while let Some(Ok(msg)) = ws_rx.next().await {
         let text = match msg {
@@ -660,7 +728,14 @@ async fn handle_socket(
 
         match client_msg {
             ClientMessage::UserMessage { content } => {
-                handle_user_message(content, Arc::clone(&conn), tx.clone()).await;
+                let previous = turn.take();
+                let (conn, tx, cancel) = (Arc::clone(&conn), tx.clone(), cancel.clone());
+                turn = Some(tokio::spawn(async move {
+                    if let Some(previous) = previous {
+                        let _ = previous.await;
+                    }
+                    handle_user_message(content, conn, tx, cancel).await;
+                }));
             }
             ClientMessage::SwitchModel { model } => {
                 let mut c = conn.lock().await;
@@ -695,6 +770,8 @@ async fn handle_socket(
             }
 
             ClientMessage::Cancel => {
+                cancel.cancel();
+                cancel = CancelToken::new();
                 let _ = tx.send(ServerMessage::Status {
                     eye_state: "watching".to_string(),
                     model: {
@@ -704,6 +781,19 @@ async fn handle_socket(
                 });
             }
 
+            ClientMessage::ApprovalResponse { id, decision, command, reason } => {
+                let decision = match (decision.as_str(), command) {
+                    ("approve", _) => PermissionDecision::Allow,
+                    ("edit", Some(command)) if !command.trim().is_empty() => PermissionDecision::Edit { command },
+                    _ => PermissionDecision::Deny {
+                        reason: reason.filter(|r| !r.trim().is_empty()).unwrap_or_else(|| "Denied by operator".to_string()),
+                    },
+                };
+                if !approvals.resolve(&id, decision) {
+                    let _ = tx.send(ServerMessage::ApprovalClosed { id });
+                }
+            }
+
             ClientMessage::NewConversation => {
                 let mut c = conn.lock().await;
                 let new_id = uuid::Uuid::new_v4().to_string();
@@ -741,7 +831,7 @@ async fn handle_socket(
                 c.conversation_id = id.clone();
                 c.conversation_title = title.clone();
                 c.history = history.clone();
-                let messages = history.iter().map(|m| HistoryMessagePayload {
+                let messages = history.iter().filter(|m| m.role != SUMMARY_ROLE).map(|m| HistoryMessagePayload {
                     role: m.role.clone(),
                     content: m.content.clone(),
                     model: m.model.clone(),
@@ -770,6 +860,9 @@ async fn handle_socket(
             }
         }
     }
+
+    // Nobody is left to read the answer.
+    cancel.cancel();
 }
 
 // ─── Message handler ──────────────────────────────────────────────────────
@@ -778,6 +871,7 @@ async fn handle_user_message(
     user_msg: String,
     conn: Arc<Mutex<ConnectionState>>,
     tx: mpsc::UnboundedSender<ServerMessage>,
+    cancel: CancelToken,
 ) {
     let _ = tx.send(ServerMessage::Thinking);
 
@@ -792,9 +886,15 @@ async fn handle_user_message(
         let mut c = conn.lock().await;
         let mut response_text = String::new();
         let mut tool_call_count: usize = 0;
+        let mut answered_by = agent_config.model.clone();
+        let mut compacted: Option<(String, usize)> = None;
+
+        // Built per turn so policy-file edits apply from the next message.
+        // There is a human on the socket: HIGH risk goes to their approval
+        // modal instead of Sonnet review.
+        let shell_policy = ShellPolicy::for_surface(Surface::Web);
 
         let ConnectionState {
-            ref shell_policy,
             ref memory,
             ref mut mcp,
             ref client,
@@ -806,10 +906,11 @@ async fn handle_user_message(
             &agent_config,
             &user_msg,
             &history_snapshot,
-            shell_policy,
+            &shell_policy,
             mem,
             mcp,
             client,
+            &cancel,
             |event| {
                 match event {
                     AgentEvent::Thinking => {
@@ -831,6 +932,23 @@ async fn handle_user_message(
                             call_id: id,
                         });
                     }
+                    AgentEvent::TokenDelta(content) => {
+                        let _ = tx_clone.send(ServerMessage::ResponseChunk { content });
+                    }
+                    AgentEvent::ModelFallback { from, to, reason } => {
+                        let _ = tx_clone.send(ServerMessage::ModelFallback {
+                            from: model_label(&from).to_string(),
+                            to: model_label(&to).to_string(),
+                            reason,
+                        });
+                        answered_by = to;
+                    }
+                    AgentEvent::HistoryCompacted { summary, replaced } => {
+                        compacted = Some((summary, replaced));
+                    }
+                    AgentEvent::Cancelled => {
+                        let _ = tx_clone.send(ServerMessage::TurnCancelled);
+                    }
                     AgentEvent::Response(text) => {
                         response_text = text;
                     }
@@ -845,7 +963,7 @@ async fn handle_user_message(
         if tool_call_count > 2 {
             if let Some(ref emb) = agent_config.embedding {
                 let emb = emb.clone();
-                let author  = agent_config.model.clone();
+                let author  = answered_by.clone();
                 let summary = response_text.clone();
                 let context = Some(format!("Web UI turn — {} tool calls", tool_call_count));
                 tokio::spawn(async move {
@@ -861,6 +979,10 @@ async fn handle_user_message(
             }
         }
 
+        if let Some((summary, replaced)) = compacted {
+            argus_core::context::apply_compaction(&mut c.history, &summary, replaced);
+        }
+
         match r {
             Ok(text) => {
                 c.history.push(ConversationMessage {
@@ -871,7 +993,7 @@ async fn handle_user_message(
                 c.history.push(ConversationMessage {
                     role: "assistant".to_string(),
                     content: text.clone(),
-                    model: Some(agent_config.model.clone()),
+                    model: Some(answered_by.clone()),
                 });
                 if c.history.len() > 40 {
                     let drain = c.history.len() - 40;
@@ -923,7 +1045,10 @@ async fn handle_user_message(
             });
         }
         Err(err) => {
-            let _ = tx.send(ServerMessage::Error { message: err });
+            // A cancelled turn was already reported as TurnCancelled.
+            if err != CANCELLED {
+                let _ = tx.send(ServerMessage::Error { message: err });
+            }
             let _ = tx.send(ServerMessage::Status {
                 eye_state: "watching".to_string(),
                 model: {
@@ -933,4 +1058,8 @@ async fn handle_user_message(
             });
         }
     }
+
+    if let Some(usage) = build_usage_update(&agent_config) {
+        let _ = tx.send(usage);
+    }
 }
@@ -1,9 +1,16 @@
 //! Agent orchestration loop
 
+use crate::cancel::{CancelToken, CANCELLED};
 use crate::constraints::ConstraintClient;
+use crate::context::{self, ContextBudget};
 use crate::mcp::McpClient;
+use crate::models::{self, ModelRegistry};
 use crate::sentry_bus::SentryBus;
-use crate::shell::ShellPolicy;
+use crate::shell::{PermissionDecision, RiskLevel, ShellPolicy};
+use crate::provider::{ChatRequest, LlmProvider};
+use crate::retry::{ApiError, ApiErrorKind, RetryPolicy};
+use crate::schema;
+use crate::usage::{self, Scope, UsageLedger, UsageRecord};
 
 /// Trait for executing mission tools without a circular crate dependency.
 /// Implemented in argus-missions, injected via AgentConfig.
@@ -20,17 +27,26 @@ pub trait MissionExecutor: Send + Sync {
     fn list_missions_json(&self) -> Vec<serde_json::Value> {
         vec![]
     }
+
+    /// Mission tools to register for agents that carry this executor.
+    /// Default: none. MissionBridge overrides with the mission tool set.
+    fn tool_specs(&self) -> Vec<ToolSpec> {
+        vec![]
+    }
 }
-use crate::tools::{self, MemoryBackend};
+use crate::tool_registry::{Tool, ToolContext, ToolRegistry, ToolRisk, ToolSpec};
+use crate::tools::MemoryBackend;
 use crate::embedding::EmbeddingClient;
 use crate::shell::PermissionPrompter;
 use crate::skills::{SkillsClient, NewSkill};
+use crate::workspace::WorkspaceExecutor;
 use std::sync::Arc;
+use futures_util::future::join_all;
 use serde_json::Value;
 use uuid::Uuid;
 
+/// Tool round limit for models that don't set `max_tool_rounds` in the registry.
 const MAX_TOOL_ROUNDS: usize = 8;
-const MAX_TOOL_ROUNDS_BUILDER: usize = 12;
 
 const BUILDER_PROMPT: &str = r#"
 BUILDER STATION — You are Grok Build, Argus's primary coding agent.
@@ -298,11 +314,10 @@ fn format_history_block(history: &[ConversationMessage]) -> Option<String> {
 /// Injects semantic context (memories/discourse/convs) and intranet dispatch
 /// transparently — the agent experiences these as things it "already knows."
 fn max_tool_rounds_for(model_id: &str) -> usize {
-    if model_id == MODEL_GROK_BUILD {
-        MAX_TOOL_ROUNDS_BUILDER
-    } else {
-        MAX_TOOL_ROUNDS
-    }
+    ModelRegistry::global()
+        .by_id(model_id)
+        .and_then(|m| m.max_tool_rounds)
+        .unwrap_or(MAX_TOOL_ROUNDS)
 }
 
 fn build_system_prompt(
@@ -338,7 +353,7 @@ fn build_system_prompt(
         }
     }
 
-    if model_id == MODEL_GROK_BUILD {
+    if ModelRegistry::global().by_id(model_id).is_some_and(|m| m.builder) {
         prompt = format!("{}\n\n{}", prompt, BUILDER_PROMPT);
     }
 
@@ -351,41 +366,6 @@ fn build_system_prompt(
     prompt
 }
 
-/// xAI (Grok) rejects `"additionalProperties": false` in tool schemas.
-/// Recursively remove it so the schema stays valid for xAI's validator.
-fn strip_additional_properties_false(value: &Value) -> Value {
-    match value {
-        Value::Object(map) => {
-            let mut out = serde_json::Map::new();
-            for (k, v) in map {
-                if k == "additionalProperties" {
-                    if v == &Value::Bool(false) { continue; }
-                }
-                out.insert(k.clone(), strip_additional_properties_false(v));
-            }
-            Value::Object(out)
-        }
-        Value::Array(arr) => Value::Array(arr.iter().map(strip_additional_properties_false).collect()),
-        other => other.clone(),
-    }
-}
-
-/// Grok also rejects `"strict": true` in tool schemas — strip it the same way.
-fn strip_strict(value: Value) -> Value {
-    match value {
-        Value::Object(map) => {
-            let mut out = serde_json::Map::new();
-            for (k, v) in map {
-                if k == "strict" { continue; }
-                out.insert(k, strip_strict(v));
-            }
-            Value::Object(out)
-        }
-        Value::Array(arr) => Value::Array(arr.into_iter().map(strip_strict).collect()),
-        other => other,
-    }
-}
-
 fn truncate_chars(s: &str, max_chars: usize) -> &str {
     match s.char_indices().nth(max_chars) {
         Some((idx, _)) => &s[..idx],
@@ -393,20 +373,27 @@ fn truncate_chars(s: &str, max_chars: usize) -> &str {
     }
 }
 
-/// Sanitize a tool name: only alphanumeric, underscores, hyphens. Max 64 chars.
-fn sanitize_tool_name(name: &str) -> String {
-    let clean: String = name
-        .chars()
-        .map(|c| if c.is_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
-        .collect();
-    if clean.chars().count() > 64 { clean.chars().take(64).collect() } else { clean }
-}
-
 #[derive(Debug, Clone)]
 pub enum AgentEvent {
     Thinking,
     ToolCall { id: String, name: String, args: serde_json::Value, preview: String },
     ToolResult { id: String, name: String, result: String, success: bool, preview: String },
+    /// A fragment of model text as it streams in (only when `AgentConfig::stream`
+    /// is set). Fragments from a round that ends in tool calls are followed by
+    /// `Thinking` when the next round starts; `Response` always carries the
+    /// full final text.
+    TokenDelta(String),
+    /// `from` failed (after retries) and the turn continues on `to` — the
+    /// model that answers is `to` from here on. `reason` is short, e.g.
+    /// "rate limited (429)".
+    ModelFallback { from: String, to: String, reason: String },
+    /// History outgrew the model's context budget: the first `replaced`
+    /// messages of the history passed in were summarised into `summary`.
+    /// Surfaces that persist history apply it with `context::apply_compaction`.
+    HistoryCompacted { summary: String, replaced: usize },
+    /// The turn's `CancelToken` fired. Sent once, after which the turn returns
+    /// `Err(cancel::CANCELLED)`; a tool cut short also gets a failed `ToolResult`.
+    Cancelled,
     Response(String),
     Error(String),
 }
@@ -421,43 +408,29 @@ pub struct ConversationMessage {
     pub model: Option<String>,
 }
 
-// ── Model constants ────────────────────────────────────────────────────────
-//
-// Funded roster (Jun 2026) — frontiers restored except Opus (cost).
-// Opus UI slot stays on Gemma 4 31B free + PERSONA_OPUS prompt (see MODEL_ROSTER_NOTE.md).
-// See docs/MODEL_ROSTER_NOTE.md for economy-window history (Gemma in Anthropic slots).
+// ── Model roster ───────────────────────────────────────────────────────────
 //
-pub const MODEL_GEMMA_RUNTIME: &str = "google/gemma-4-31b-it:free";
-pub const MODEL_HAIKU:  &str = "anthropic/claude-haiku-4-5";
-pub const MODEL_SONNET: &str = "anthropic/claude-sonnet-4-6";
-pub const MODEL_OPUS:   &str = MODEL_GEMMA_RUNTIME;
-pub const MODEL_GEMINI: &str = "google/gemini-3.1-pro-preview";
-pub const MODEL_GROK:       &str = "x-ai/grok-4.20";
-pub const MODEL_GROK_BUILD: &str = "x-ai/grok-build-0.1";
-pub const MODEL_GROK_MULTI: &str = "x-ai/grok-4.20-multi-agent";
-/// Dedicated triage gate — smaller Gemma, structured JSON output.
-pub const MODEL_TRIAGE: &str = "google/gemma-4-26b-a4b-it:free";
-/// Sentry — IBM Granite 4.1 8B. Built for structured, verifiable reasoning.
-/// LaurieWired's seat. The paranoia doesn't change. The model finally does.
-pub const MODEL_SENTRY: &str = "ibm-granite/granite-4.1-8b";
-
-const PERSONA_HAIKU:  &str = "RUNTIME PERSONA — HAIKU: You are the operations coordinator. Fast baseline truth, practical handoffs, no performance.";
-const PERSONA_SONNET: &str = "RUNTIME PERSONA — SONNET: You are the balanced core mind. Structure problems clearly, reason step by step, ship coherent answers.";
-const PERSONA_OPUS:   &str = "RUNTIME PERSONA — OPUS: You are the synthesis layer. Connect threads across reports, name what actually matters, recommend next moves.";
-const PERSONA_GEMINI: &str = "RUNTIME PERSONA — GEMINI: You are the intel scout. Research wide, report signal over noise, cite specifics.";
+// Model IDs, labels and capabilities come from models.toml (see models.rs).
+// The Opus slot may run Gemma under the Opus persona — see
+// docs/MODEL_ROSTER_NOTE.md for the economy-window history.
+
+/// True while the Opus slot is backed by the same runtime as Gemma.
+fn opus_on_economy_hold() -> bool {
+    models::id("opus") == models::id("gemma")
+}
 
 /// Model for the 4-week monthly synthesis (Opus when funded, else Grok).
 pub fn monthly_synthesis_model() -> &'static str {
-    if MODEL_OPUS != MODEL_GEMMA_RUNTIME {
-        MODEL_OPUS
+    if !opus_on_economy_hold() {
+        models::id("opus")
     } else {
-        MODEL_GROK
+        models::id("grok")
     }
 }
 
 /// Discourse label for monthly synthesis posts (honest about runtime).
 pub fn monthly_synthesis_agent_label() -> &'static str {
-    if MODEL_OPUS != MODEL_GEMMA_RUNTIME {
+    if !opus_on_economy_hold() {
         "argus-opus/synthesis"
     } else {
         "argus-grok/monthly-synthesis"
@@ -466,41 +439,30 @@ pub fn monthly_synthesis_agent_label() -> &'static str {
 
 /// Banner line for monthly synthesis posts.
 pub fn monthly_synthesis_banner() -> &'static str {
-    if MODEL_OPUS != MODEL_GEMMA_RUNTIME {
+    if !opus_on_economy_hold() {
         "MONTHLY SYNTHESIS — OPUS"
     } else {
         "MONTHLY SYNTHESIS — GROK (Opus slot on economy hold)"
     }
 }
 
-/// Persona slice for slots that still run Gemma under a different UI label.
+/// Persona prompt for the web slot selected as `frontend_alias`, if its
+/// registry entry names one.
 pub fn persona_prompt_for(frontend_alias: &str) -> Option<&'static str> {
-    match frontend_alias {
-        "claude-opus" => Some(PERSONA_OPUS),
-        _ => None,
-    }
+    ModelRegistry::global()
+        .resolve_frontend(frontend_alias)
+        .and_then(|m| m.persona_prompt())
 }
 
 pub fn model_label(model_id: &str) -> &'static str {
-    match model_id {
-        MODEL_HAIKU  => "Haiku",
-        MODEL_SONNET => "Sonnet",
-        MODEL_OPUS   => "Opus",
-        MODEL_GROK        => "Grok",
-        MODEL_GROK_BUILD  => "Grok Build",
-        MODEL_GROK_MULTI  => "Grok Multi",
-        MODEL_GEMINI => "Gemini",
-        MODEL_TRIAGE => "Triage (Gemma 4)",
-        // MODEL_SENTRY shares the same string value as MODEL_OPUS (same free model).
-        // Role is distinguished via system_prompt_override + frontend_persona = "sentry".
-        _            => "Unknown model",
-    }
+    ModelRegistry::global().label(model_id)
 }
 
 /// Returns false for models that don't support OpenAI-style tool_use via OpenRouter.
 /// When false, the agent sends no tools array — the model responds in plain text only.
+/// Models missing from the registry are assumed to support tools.
 pub fn model_supports_tools(model_id: &str) -> bool {
-    !matches!(model_id, MODEL_GROK_MULTI)
+    ModelRegistry::global().by_id(model_id).is_none_or(|m| m.tools)
 }
 
 /// All fields are Clone (Arc clones are pointer-only; EmbeddingClient and
@@ -517,13 +479,19 @@ pub struct AgentConfig {
     pub embedding: Option<EmbeddingClient>,
     /// Optional skills client — when set, relevant procedural skills are injected before each turn
     pub skills: Option<SkillsClient>,
-    /// Optional shell prompter — when set, HIGH risk commands are sent to Telegram for approval
+    /// Optional human approval prompter (web UI, TUI). When set, HIGH risk
+    /// commands and tools flagged in the policy file wait for approval instead
+    /// of Sonnet review; without one, flagged tools are refused.
     pub shell_prompter: Option<Arc<dyn PermissionPrompter>>,
     /// Optional audit chain — when set, all tool calls and model calls are cryptographically logged
     pub audit: Option<Arc<argus_audit::AuditChain>>,
     /// Shared secret for authenticating requests to the workspace exec server.
     /// Sent as X-Argus-Auth header. Blocks prompt-injection SSRF to /exec.
     pub exec_auth_token: Option<String>,
+    /// Where shell/code/browse tools execute. Picked from the environment
+    /// once, when the config is built (see `workspace::from_env`), and shared
+    /// by every clone.
+    pub workspace: Arc<dyn WorkspaceExecutor>,
     /// Tool names to strip from the schema before sending to the model.
     /// Use this to prevent autonomous/scheduled agents from calling destructive tools.
     pub blocked_tools: Vec<String>,
@@ -558,6 +526,24 @@ pub struct AgentConfig {
     /// Sentry-promoted threat patterns. Matching patterns inject a hard warning
     /// block before the LLM sees the message. This is Sentry's enforcement gate.
     pub constraints: Option<ConstraintClient>,
+    /// Request SSE streaming from the API and emit `AgentEvent::TokenDelta`
+    /// as text arrives. Interactive surfaces turn this on; background agents
+    /// leave it off.
+    pub stream: bool,
+    /// Model backend. When None, one is picked from the environment using
+    /// `api_url`/`api_key` (see `provider::from_env`).
+    pub provider: Option<Arc<dyn LlmProvider>>,
+    /// Backoff for rate limits and transient API failures. After the last
+    /// attempt the model's fallback chain (models.toml) takes over.
+    pub retry: RetryPolicy,
+    /// Usage ledger — when set, every model call's tokens and cost are
+    /// recorded and the `[budgets]` in models.toml are enforced.
+    pub usage: Option<Arc<dyn UsageLedger>>,
+    /// Surface this agent serves (web, telegram, sentry, missions, ...), for
+    /// the ledger and per-surface budgets.
+    pub surface: String,
+    /// Mission this agent works for, for per-mission budgets.
+    pub mission_id: Option<String>,
 }
 
 impl AgentConfig {
@@ -565,7 +551,7 @@ impl AgentConfig {
         let brave_search_key = std::env::var("BRAVE_SEARCH_API_KEY").ok();
         Self {
             api_key,
-            model: MODEL_GEMMA_RUNTIME.to_string(),
+            model: ModelRegistry::global().default_model().id.clone(),
             api_url: "https://openrouter.ai/api/v1/chat/completions".to_string(),
             temperature: 0.7,
             brave_search_key,
@@ -574,6 +560,7 @@ impl AgentConfig {
             shell_prompter: None,
             audit: None,
             exec_auth_token: None,
+            workspace: crate::workspace::from_env(None),
             blocked_tools: vec![],
             sonnet_guard: None,
             discord_bot_token: None,
@@ -586,6 +573,12 @@ impl AgentConfig {
             handover: None,
             mission_executor: None,
             constraints: None,
+            stream: false,
+            provider: None,
+            retry: RetryPolicy::default(),
+            usage: None,
+            surface: "cli".to_string(),
+            mission_id: None,
         }
     }
 
@@ -594,38 +587,63 @@ impl AgentConfig {
         self
     }
 
+    pub fn with_workspace(mut self, workspace: Arc<dyn WorkspaceExecutor>) -> Self {
+        self.workspace = workspace;
+        self
+    }
+
+    /// Authenticate with the workspace exec server using `token`. Rebuilds the
+    /// environment's workspace backend around it, so set it before any
+    /// `with_workspace`.
+    pub fn with_exec_auth_token(mut self, token: Option<String>) -> Self {
+        self.workspace = crate::workspace::from_env(token.clone());
+        self.exec_auth_token = token;
+        self
+    }
+
+    /// The workspace backend tools execute in.
+    pub fn workspace(&self) -> Arc<dyn WorkspaceExecutor> {
+        self.workspace.clone()
+    }
+
+    pub fn with_provider(mut self, provider: Arc<dyn LlmProvider>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// The configured model backend, or the environment default.
+    pub fn provider(&self, http: &reqwest::Client) -> Arc<dyn LlmProvider> {
+        match &self.provider {
+            Some(p) => p.clone(),
+            None => crate::provider::from_env(&self.api_url, &self.api_key, http),
+        }
+    }
+
+    pub fn with_usage(mut self, ledger: Arc<dyn UsageLedger>, surface: impl Into<String>) -> Self {
+        self.usage = Some(ledger);
+        self.surface = surface.into();
+        self
+    }
+
     pub fn with_embedding(mut self, client: EmbeddingClient) -> Self {
         self.embedding = Some(client);
         self
     }
 
     pub fn toggle_model(&mut self) -> &str {
-        self.model = match self.model.as_str() {
-            MODEL_HAIKU       => MODEL_SONNET.to_string(),
-            MODEL_SONNET      => MODEL_GEMMA_RUNTIME.to_string(),
-            MODEL_GEMMA_RUNTIME => MODEL_GROK.to_string(),
-            MODEL_GROK        => MODEL_GROK_BUILD.to_string(),
-            MODEL_GROK_BUILD  => MODEL_GROK_MULTI.to_string(),
-            MODEL_GROK_MULTI  => MODEL_GEMINI.to_string(),
-            _                 => MODEL_HAIKU.to_string(),
-        };
+        self.model = ModelRegistry::global().next_in_cycle(&self.model).id.clone();
         &self.model
     }
 
     pub fn set_model(&mut self, name: &str) -> Result<&str, String> {
-        self.model = match name.to_lowercase().as_str() {
-            "haiku"  | MODEL_HAIKU  => MODEL_HAIKU.to_string(),
-            "sonnet" | MODEL_SONNET => MODEL_SONNET.to_string(),
-            "opus"   | MODEL_OPUS   => MODEL_OPUS.to_string(),
-            "grok" | "nemotron" | MODEL_GROK => MODEL_GROK.to_string(),
-            "grok-build"  | MODEL_GROK_BUILD  => MODEL_GROK_BUILD.to_string(),
-            "grok-multi" | MODEL_GROK_MULTI => MODEL_GROK_MULTI.to_string(),
-            "gemini"     | MODEL_GEMINI     => MODEL_GEMINI.to_string(),
-            "gemma" | "gemma4" | MODEL_GEMMA_RUNTIME => MODEL_GEMMA_RUNTIME.to_string(),
-            other => return Err(format!(
-                "Unknown model '{}'. Use: haiku, sonnet, opus, grok, gemini, gemma", other
-            )),
-        };
+        let registry = ModelRegistry::global();
+        match registry.resolve(name).filter(|m| !m.internal) {
+            Some(m) => self.model = m.id.clone(),
+            None => {
+                let names: Vec<&str> = registry.selectable().map(|m| m.key.as_str()).collect();
+                return Err(format!("Unknown model '{}'. Use: {}", name, names.join(", ")));
+            }
+        }
         Ok(&self.model)
     }
 
@@ -637,6 +655,9 @@ impl AgentConfig {
 /// Core agent turn. Accepts optional pre-fetched semantic context.
 /// The semantic context is injected into the system prompt transparently —
 /// the agent experiences relevant memories as things it "already knows."
+///
+/// `cancel` is checked between rounds and raced against every model call and
+/// tool call; background agents that are never cancelled pass a fresh token.
 pub async fn run_agent_turn<F>(
     config: &AgentConfig,
     user_message: &str,
@@ -645,6 +666,7 @@ pub async fn run_agent_turn<F>(
     memory: &dyn MemoryBackend,
     mcp: &mut McpClient,
     http_client: &reqwest::Client,
+    cancel: &CancelToken,
     mut on_event: F,
 ) -> Result<String, String>
 where
@@ -747,65 +769,35 @@ where
         (String::new(), vec![])
     };
 
-    let mut tool_schemas: Vec<Value> = Vec::new();
-    let mut registered_names: std::collections::HashSet<String> = std::collections::HashSet::new();
-
-    // MCP tools first
-    for mcp_tool in mcp.all_tools() {
-        let raw_name = sanitize_tool_name(&mcp_tool.name);
-        let safe_name = if registered_names.contains(&raw_name) {
-            let prefix = sanitize_tool_name(
-                &mcp_tool.server_name.replace('-', "_").replace(' ', "_")
-            );
-            sanitize_tool_name(&format!("{}_{}", prefix, raw_name))
-        } else {
-            raw_name
-        };
-
-        if registered_names.contains(&safe_name) {
-            eprintln!("[argus] skipping duplicate MCP tool: {}", safe_name);
-            continue;
-        }
-
-        registered_names.insert(safe_name.clone());
-        tool_schemas.push(serde_json::json!({
-            "type": "function",
-            "function": {
-                "name": safe_name,
-                "description": mcp_tool.description.clone().unwrap_or_default(),
-                "parameters": mcp_tool.input_schema
+    // Built-in, mission and MCP tools, minus this agent's blocked tools.
+    let registry = ToolRegistry::for_turn(config, mcp);
+    let tool_schemas = registry.schemas();
+
+    let provider = config.provider(http_client);
+
+    // ── Context budget ────────────────────────────────────────────────────
+    // Summarise older turns once history outgrows its share of the window.
+    // A failed summary isn't fatal — per-request fitting below still applies.
+    let budget = ContextBudget::for_model(&config.model);
+    let compacted = match context::compaction_point(history, &budget) {
+        Some(cut) => match cancel.run(summarize_history(config, &*provider, &history[..cut])).await {
+            Err(_) => return Err(turn_cancelled(config, &config.model, "compaction", &mut on_event)),
+            Ok(Ok(summary)) => {
+                eprintln!("[context] compacted {} history messages", cut);
+                on_event(AgentEvent::HistoryCompacted { summary: summary.clone(), replaced: cut });
+                Some((summary, cut))
             }
-        }));
-    }
-
-    // Built-in tools
-    for schema in tools::builtin_tool_schemas() {
-        let name = match schema["function"]["name"].as_str() {
-            Some(n) if !n.is_empty() => n.to_string(),
-            _ => continue,
-        };
-        if !registered_names.contains(&name) {
-            registered_names.insert(name);
-            tool_schemas.push(schema);
-        }
-    }
-
-    // Strip blocked tools — keeps autonomous/scheduled agents from calling shell etc.
-    if !config.blocked_tools.is_empty() {
-        tool_schemas.retain(|s| {
-            let name = s["function"]["name"].as_str().unwrap_or("");
-            !config.blocked_tools.iter().any(|b| b == name)
-        });
-    }
-
-    // Final dedup guarantee
-    {
-        let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
-        tool_schemas.retain(|s| {
-            let name = s["function"]["name"].as_str().unwrap_or("").to_string();
-            seen.insert(name)
-        });
-    }
+            Ok(Err(e)) => {
+                eprintln!("[context] compaction failed (continuing without): {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    let (history_summary, history) = match &compacted {
+        Some((summary, cut)) => (Some(summary.as_str()), &history[*cut..]),
+        None => context::split_summary(history),
+    };
 
     // System prompt assembly
     let history_context = format_history_block(history);
@@ -859,7 +851,11 @@ where
     };
 
     // Prepend constraint block — Sentry's gate is the first thing the model reads
-    let system_prompt = format!("{}{}", constraint_prefix, system_prompt);
+    let mut system_prompt = format!("{}{}", constraint_prefix, system_prompt);
+    if let Some(summary) = history_summary {
+        system_prompt.push_str("\n\n## Earlier in this conversation (summary)\n");
+        system_prompt.push_str(summary);
+    }
 
     let mut messages = vec![
         serde_json::json!({"role": "system", "content": system_prompt}),
@@ -867,57 +863,40 @@ where
     for msg in history {
         messages.push(serde_json::json!({"role": msg.role, "content": msg.content}));
     }
+    // Index of this turn's user message — fitting never drops from here on.
+    let mut current = messages.len();
     messages.push(serde_json::json!({"role": "user", "content": user_message}));
+    let schema_tokens = context::estimate_tokens(&Value::Array(tool_schemas.clone()).to_string());
 
     let mut tool_call_count: usize = 0;
+    // Switches to the fallback model for the rest of the turn if config.model fails.
+    let mut model = config.model.clone();
 
     let max_rounds = max_tool_rounds_for(&config.model);
-    for _round in 0..max_rounds {
-        let mut req_body = serde_json::json!({
-            "model": config.model,
-            "messages": messages,
-            "temperature": config.temperature,
-        });
-        if model_supports_tools(&config.model) {
-            let schemas = if config.model.starts_with("x-ai/") || config.model.starts_with("~x-ai/") {
-                // Grok rejects additionalProperties:false and strict:true — strip both
-                tool_schemas.iter()
-                    .map(strip_additional_properties_false)
-                    .map(|s| strip_strict(s))
-                    .collect::<Vec<_>>()
-            } else if config.model.starts_with("google/") || config.model.starts_with("~google/") {
-                // Gemini rejects additionalProperties:false in nested schemas
-                tool_schemas.iter().map(strip_additional_properties_false).collect::<Vec<_>>()
-            } else {
-                tool_schemas.clone()
-            };
-            req_body["tools"] = serde_json::json!(schemas);
-            // Gemini does not support tool_choice as a string — omit it entirely.
-            // Anthropic and Grok accept "auto"; everything else: omit to be safe.
-            if !config.model.starts_with("google/") && !config.model.starts_with("~google/") {
-                req_body["tool_choice"] = serde_json::json!("auto");
-            }
+    for round in 0..max_rounds {
+        if cancel.is_cancelled() {
+            return Err(turn_cancelled(config, &model, &format!("round {}", round), &mut on_event));
         }
-        let resp = http_client
-            .post(&config.api_url)
-            .header("Authorization", format!("Bearer {}", config.api_key))
-            .header("Content-Type", "application/json")
-            .json(&req_body)
-            .send()
-            .await
-            .map_err(|e| format!("API request failed: {}", e))?;
-
-        let json: Value = resp
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse API response: {}", e))?;
-
-        if let Some(err) = json.get("error") {
-            let msg = err["message"].as_str().unwrap_or("Unknown API error");
-            eprintln!("API Error: {}", serde_json::to_string_pretty(&json).unwrap_or_default());
-            on_event(AgentEvent::Error(msg.to_string()));
-            return Err(msg.to_string());
+        if round > 0 {
+            on_event(AgentEvent::Thinking);
         }
+        let limit = ContextBudget::for_model(&model).input_limit().saturating_sub(schema_tokens);
+        let fitted = context::fit_messages(&mut messages, &mut current, limit);
+        if fitted > 0 {
+            eprintln!("[context] round {}: shrank {} message(s) to fit {} tokens", round, fitted, limit);
+        }
+        let request = ChatRequest {
+            model: model.clone(),
+            messages: messages.clone(),
+            tools: tool_schemas.clone(),
+            temperature: config.temperature,
+            max_tokens: None,
+        };
+        let Ok(completion) = cancel.run(complete_with_fallback(config, &*provider, request, &mut on_event)).await else {
+            return Err(turn_cancelled(config, &model, &format!("round {} model call", round), &mut on_event));
+        };
+        let (json, answered_by) = completion?;
+        model = answered_by;
 
         let message = &json["choices"][0]["message"];
 
@@ -932,18 +911,17 @@ where
                 // Audit: log this model call (args = model+round fingerprint, result by hash)
                 if let Some(ref audit) = config.audit {
                     let _ = audit.append(
-                        &config.model,
+                        &model,
                         "model_call",
                         None,
-                        Some(&format!("model={},round={},finish=text", config.model, _round)),
+                        Some(&format!("model={},round={},finish=text", model, round)),
                         Some(&content),
                     );
                 }
 
                 // Background skill reflection — fires after tool-heavy turns
                 maybe_reflect_on_skill(
-                    tool_call_count, config.skills.clone(),
-                    config.api_key.clone(), config.api_url.clone(), config.model.clone(),
+                    tool_call_count, config, model.clone(),
                     http_client.clone(), user_message.to_string(), content.clone(),
                 );
 
@@ -956,113 +934,113 @@ where
 
         messages.push(message.clone());
 
-        for tool_call in &tool_calls {
-            let name = tool_call["function"]["name"].as_str().unwrap_or("");
-            let tool_call_id = tool_call["id"].as_str().unwrap_or("");
-            let args: Value = tool_call["function"]["arguments"]
-                .as_str()
-                .and_then(|s| serde_json::from_str(s).ok())
-                .unwrap_or(serde_json::json!({}));
-
-            let preview = match name {
-                "shell"        => args["command"].as_str().unwrap_or("").to_string(),
-                "read_file"    => args["path"].as_str().unwrap_or("").to_string(),
-                "write_file"   => args["path"].as_str().unwrap_or("").to_string(),
-                "web_search"   => args["query"].as_str().unwrap_or("").to_string(),
-                "http_request" => format!("{} {}",
-                    args["method"].as_str().unwrap_or("GET"),
-                    args["url"].as_str().unwrap_or("")
-                ),
-                _ => serde_json::to_string(&args).unwrap_or_default(),
-            };
-
-            // Capture args as string before any potential move into MCP call
-            let args_str_for_audit = serde_json::to_string(&args).unwrap_or_default();
-
-            on_event(AgentEvent::ToolCall {
-                id: tool_call_id.to_string(),
-                name: name.to_string(),
-                args: args.clone(),
-                preview,
-            });
-
-            let result = if name == "list_tools" || name == "list-tools" {
-                // Introspection: return the full assembled tool list for this turn
-                let mut out = format!("Available tools ({}):\n\n", tool_schemas.len());
-                for schema in &tool_schemas {
-                    let tname = schema["function"]["name"].as_str().unwrap_or("?");
-                    let desc  = schema["function"]["description"].as_str().unwrap_or("");
-                    out.push_str(&format!("• {} — {}\n", tname, desc));
-                }
-                out
-            } else if let Some(output) =
-                tools::execute_builtin(name, &args, shell_policy, memory, http_client, config.brave_search_key.as_deref(), config.shell_prompter.clone(), config.exec_auth_token.as_deref(), config.sonnet_guard.clone(), config.discord_bot_token.as_deref(), config.discord_channel_id, config.skills.as_ref(), &config.model, config.supabase_url.as_deref(), config.supabase_jwt.as_deref(), config.mission_executor.as_ref()).await
-            {
-                output
-            } else {
-                match mcp.call_tool(name, args.clone()) {
-                    Ok(output) => output,
-                    Err(_) => {
-                        let short = name.splitn(2, '_').last().unwrap_or(name);
-                        match mcp.call_tool(short, args.clone()) {
-                            Ok(output) => output,
-                            Err(_) => format!("Unknown tool: {}", name),
-                        }
-                    }
-                }
-            };
-
-            // Audit: cryptographically log this tool call (args and result by hash only)
-            if let Some(ref audit) = config.audit {
-                let _ = audit.append(
-                    &config.model,
-                    "tool_call",
-                    Some(name),
-                    Some(&args_str_for_audit),
-                    Some(&result),
-                );
+        // Consecutive parallel-safe (read-only) calls run together; anything
+        // with side effects runs alone, in the order the model gave.
+        let calls: Vec<PreparedCall> = tool_calls.iter().map(|tc| PreparedCall::new(tc, &registry)).collect();
+        let mut start = 0;
+        while start < calls.len() {
+            let run = calls[start..].iter().take_while(|c| c.parallel_safe()).count().max(1);
+            let batch = &calls[start..start + run];
+            start += run;
+
+            for call in batch {
+                on_event(AgentEvent::ToolCall {
+                    id: call.id.clone(),
+                    name: call.name.clone(),
+                    args: call.args.clone(),
+                    preview: call.preview.clone(),
+                });
             }
 
-            // Semantic memory: embed remembered content so it's searchable via pgvector
-            if name == "remember" {
-                if let Some(ref emb) = config.embedding {
-                    let mem_content = args["content"].as_str().unwrap_or("").to_string();
-                    if !mem_content.is_empty() {
-                        let emb = emb.clone();
-                        let agent = config.model.clone();
-                        let mem_id = Uuid::new_v4().to_string();
-                        tokio::spawn(async move {
-                            if let Err(e) = emb.store_memory_embedding(&mem_id, &mem_content, &agent).await {
-                                eprintln!("[embed] memory store failed: {}", e);
-                            }
+            let ctx = ToolContext {
+                config,
+                model: &model,
+                shell_policy,
+                memory,
+                http: http_client,
+                workspace: config.workspace(),
+                mcp: tokio::sync::Mutex::new(&mut *mcp),
+                cancel,
+                tools: &registry,
+            };
+            let outcomes = match cancel.run(join_all(batch.iter().map(|call| call.run(&ctx)))).await {
+                Ok(outcomes) if !cancel.is_cancelled() => outcomes,
+                _ => {
+                    for call in batch {
+                        on_event(AgentEvent::ToolResult {
+                            id: call.id.clone(),
+                            name: call.name.clone(),
+                            result: CANCELLED.to_string(),
+                            success: false,
+                            preview: CANCELLED.to_string(),
                         });
                     }
+                    let names: Vec<&str> = batch.iter().map(|c| c.name.as_str()).collect();
+                    return Err(turn_cancelled(config, &model, &format!("tool {}", names.join(", ")), &mut on_event));
                 }
+            };
+            if batch.len() > 1 {
+                eprintln!("[tools] round {}: ran {} read-only calls concurrently", round, batch.len());
             }
 
-            let result_preview = {
-                let truncated = truncate_chars(&result, PREVIEW_CHARS);
-                if truncated.len() < result.len() {
-                    format!("{}...", truncated)
-                } else {
-                    truncated.to_string()
+            for (call, (result, action)) in batch.iter().zip(outcomes) {
+                let name = call.name.as_str();
+
+                // Audit: cryptographically log this tool call (args and result by hash only).
+                // Rejected calls are logged as `invalid_tool_call` so they can be counted.
+                if let Some(ref audit) = config.audit {
+                    let _ = audit.append(
+                        &model,
+                        action,
+                        Some(name),
+                        Some(&serde_json::to_string(&call.args).unwrap_or_default()),
+                        Some(&result),
+                    );
                 }
-            };
 
-            let success = !result.starts_with("Error:") && !result.starts_with("Unknown tool:");
-            on_event(AgentEvent::ToolResult {
-                id: tool_call_id.to_string(),
-                name: name.to_string(),
-                result: result.clone(),
-                success,
-                preview: result_preview,
-            });
+                // Semantic memory: embed remembered content so it's searchable via pgvector
+                if name == "remember" && action == "tool_call" {
+                    if let Some(ref emb) = config.embedding {
+                        let mem_content = call.args["content"].as_str().unwrap_or("").to_string();
+                        if !mem_content.is_empty() {
+                            let emb = emb.clone();
+                            let agent = model.clone();
+                            let mem_id = Uuid::new_v4().to_string();
+                            tokio::spawn(async move {
+                                if let Err(e) = emb.store_memory_embedding(&mem_id, &mem_content, &agent).await {
+                                    eprintln!("[embed] memory store failed: {}", e);
+                                }
+                            });
+                        }
+                    }
+                }
 
-            messages.push(serde_json::json!({
-                "role": "tool",
-                "tool_call_id": tool_call_id,
-                "content": result,
-            }));
+                let result_preview = {
+                    let truncated = truncate_chars(&result, PREVIEW_CHARS);
+                    if truncated.len() < result.len() {
+                        format!("{}...", truncated)
+                    } else {
+                        truncated.to_string()
+                    }
+                };
+
+                let success = !result.starts_with("Error:") && !result.starts_with("Unknown tool:");
+                on_event(AgentEvent::ToolResult {
+                    id: call.id.clone(),
+                    name: name.to_string(),
+                    result: result.clone(),
+                    success,
+                    preview: result_preview,
+                });
+
+                // Oversized output is cut here; older rounds are elided later as needed.
+                let result = context::truncate_middle(&result, ContextBudget::for_model(&model).tool_result_limit());
+                messages.push(serde_json::json!({
+                    "role": "tool",
+                    "tool_call_id": call.id,
+                    "content": result,
+                }));
+            }
         }
     }
 
@@ -1071,21 +1049,22 @@ where
         "content": "Summarize what you found so far and give me your best answer based on those results."
     }));
 
-    let resp = http_client
-        .post(&config.api_url)
-        .header("Authorization", format!("Bearer {}", config.api_key))
-        .header("Content-Type", "application/json")
-        .json(&serde_json::json!({
-            "model": config.model,
-            "messages": messages,
-            "temperature": config.temperature,
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("API request failed on final synthesis: {}", e))?;
-
-    let json: Value = resp.json().await
-        .map_err(|e| format!("Failed to parse final response: {}", e))?;
+    if cancel.is_cancelled() {
+        return Err(turn_cancelled(config, &model, "synthesis", &mut on_event));
+    }
+    on_event(AgentEvent::Thinking);
+    context::fit_messages(&mut messages, &mut current, ContextBudget::for_model(&model).input_limit());
+    let request = ChatRequest {
+        model: model.clone(),
+        messages,
+        tools: vec![],
+        temperature: config.temperature,
+        max_tokens: None,
+    };
+    let Ok(completion) = cancel.run(complete_with_fallback(config, &*provider, request, &mut on_event)).await else {
+        return Err(turn_cancelled(config, &model, "synthesis", &mut on_event));
+    };
+    let (json, model) = completion.map_err(|e| format!("Final synthesis: {}", e))?;
 
     let content = json["choices"][0]["message"]["content"]
         .as_str()
@@ -1095,18 +1074,17 @@ where
     // Audit: log the synthesis model call
     if let Some(ref audit) = config.audit {
         let _ = audit.append(
-            &config.model,
+            &model,
             "model_call",
             None,
-            Some(&format!("model={},round=synthesis,finish=text", config.model)),
+            Some(&format!("model={},round=synthesis,finish=text", model)),
             Some(&content),
         );
     }
 
     // Background skill reflection — fires after tool-heavy turns (synthesis path)
     maybe_reflect_on_skill(
-        tool_call_count, config.skills.clone(),
-        config.api_key.clone(), config.api_url.clone(), config.model.clone(),
+        tool_call_count, config, model.clone(),
         http_client.clone(), user_message.to_string(), content.clone(),
     );
 
@@ -1126,15 +1104,330 @@ where
     Ok(content)
 }
 
+/// Run `request`, retrying transient failures with backoff (`config.retry`)
+/// and then walking the model's fallback chain from the registry. Returns the
+/// response and the model that produced it. Each switch is audited and
+/// reported as `AgentEvent::ModelFallback`; tools are dropped for fallback
+/// models that can't take them.
+async fn complete_with_fallback<F>(
+    config: &AgentConfig,
+    provider: &dyn LlmProvider,
+    mut request: ChatRequest,
+    on_event: &mut F,
+) -> Result<(Value, String), String>
+where
+    F: FnMut(AgentEvent),
+{
+    // (slot, model ID) — budgets and the ledger go by slot.
+    let registry = ModelRegistry::global();
+    let mut chain = vec![(registry.slot_for(&request.model, config.frontend_persona.as_deref()), request.model.clone())];
+    for m in registry.fallback_chain(&request.model) {
+        if !chain.iter().any(|(_, id)| *id == m.id) {
+            chain.push((m.key.clone(), m.id.clone()));
+        }
+    }
+    let tools = std::mem::take(&mut request.tools);
+
+    // Surface and mission caps apply whichever model answers.
+    if let Some(ref ledger) = config.usage {
+        let mut scopes = vec![Scope::Surface(config.surface.clone())];
+        scopes.extend(config.mission_id.clone().map(Scope::Mission));
+        if let Err(e) = usage::check_budgets(&**ledger, ModelRegistry::global().budgets(), &scopes) {
+            on_event(AgentEvent::Error(e.clone()));
+            return Err(e);
+        }
+    }
+
+    let mut last_err: Option<ApiError> = None;
+    for (slot, model) in &chain {
+        if let Some(err) = &last_err {
+            eprintln!("[agent] {} {} — falling back to {}", request.model, err.reason(), model);
+            if let Some(ref audit) = config.audit {
+                let _ = audit.append(
+                    &request.model,
+                    "model_fallback",
+                    None,
+                    Some(&format!("from={},to={},reason={}", request.model, model, err.reason())),
+                    Some(&err.message),
+                );
+            }
+            on_event(AgentEvent::ModelFallback {
+                from: request.model.clone(),
+                to: model.clone(),
+                reason: err.reason(),
+            });
+        }
+        request.model = model.clone();
+        request.tools = if model_supports_tools(model) { tools.clone() } else { vec![] };
+
+        if let Some(ref ledger) = config.usage {
+            let scope = [Scope::Model(slot.clone())];
+            if let Err(e) = usage::check_budgets(&**ledger, ModelRegistry::global().budgets(), &scope) {
+                last_err = Some(ApiError::over_budget(e));
+                continue;
+            }
+        }
+
+        let mut attempt = 1;
+        let err = loop {
+            let err = match request_completion(config, provider, &request, on_event).await {
+                Ok(json) => match ApiError::from_response(&json) {
+                    None => {
+                        record_usage(config, &json, slot, &request.model);
+                        return Ok((json, request.model));
+                    }
+                    Some(err) => err,
+                },
+                Err(e) => ApiError::network(e),
+            };
+            match config.retry.delay_after(attempt, &err) {
+                Some(wait) => {
+                    eprintln!("[agent] {} {} — retry {} in {}ms", model, err.reason(), attempt, wait.as_millis());
+                    tokio::time::sleep(wait).await;
+                    attempt += 1;
+                    // Discard any text streamed by the failed attempt.
+                    on_event(AgentEvent::Thinking);
+                }
+                None => break err,
+            }
+        };
+        let give_up = !err.allows_fallback();
+        last_err = Some(err);
+        if give_up {
+            break;
+        }
+    }
+
+    let err = last_err.expect("fallback chain is never empty");
+    eprintln!("API Error ({}): {}", request.model, err.message);
+    if err.kind != ApiErrorKind::Network {
+        on_event(AgentEvent::Error(err.message.clone()));
+    }
+    Err(err.message)
+}
+
+/// One tool call from a model response, resolved against the turn's registry
+/// and with its arguments validated, ready to run.
+struct PreparedCall<'r> {
+    id: String,
+    name: String,
+    args: Value,
+    tool: Option<&'r Arc<dyn Tool>>,
+    /// Why the call won't run: an unknown tool or arguments that don't match its schema.
+    invalid: Option<String>,
+    preview: String,
+    /// Arguments a human approved or edited, for tools the policy file flags.
+    approved_args: std::sync::OnceLock<Value>,
+}
+
+impl<'r> PreparedCall<'r> {
+    fn new(tool_call: &Value, registry: &'r ToolRegistry) -> Self {
+        let name = tool_call["function"]["name"].as_str().unwrap_or("");
+        let raw_args = tool_call["function"]["arguments"].as_str().unwrap_or("");
+        let tool = registry.get(name);
+        // Arguments are checked against the tool's schema; a call that doesn't match never runs.
+        let best_effort = || serde_json::from_str(raw_args).unwrap_or_else(|_| Value::String(raw_args.to_string()));
+        let (args, invalid) = match tool.map(|t| schema::tool_arguments(name, raw_args, &t.spec().parameters)) {
+            Some(Ok(args)) => (args, None),
+            Some(Err(e)) => (best_effort(), Some(e)),
+            None => (best_effort(), Some(format!("Unknown tool: {}", name))),
+        };
+
+        let preview = match name {
+            "shell"        => args["command"].as_str().unwrap_or("").to_string(),
+            "read_file"    => args["path"].as_str().unwrap_or("").to_string(),
+            "write_file"   => args["path"].as_str().unwrap_or("").to_string(),
+            "web_search"   => args["query"].as_str().unwrap_or("").to_string(),
+            "http_request" => format!("{} {}",
+                args["method"].as_str().unwrap_or("GET"),
+                args["url"].as_str().unwrap_or("")
+            ),
+            _ => serde_json::to_string(&args).unwrap_or_default(),
+        };
+
+        Self {
+            id: tool_call["id"].as_str().unwrap_or("").to_string(),
+            name: name.to_string(),
+            args,
+            tool,
+            invalid,
+            preview,
+            approved_args: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// Rejected calls never run, so they can't conflict with anything.
+    fn parallel_safe(&self) -> bool {
+        self.invalid.is_some() || self.tool.is_some_and(|t| t.parallel_safe())
+    }
+
+    /// The result for the model and the audit action to log it under.
+    async fn run<'a>(&'a self, ctx: &'a ToolContext<'a>) -> (String, &'static str) {
+        match (self.tool, &self.invalid) {
+            // `shell` asks with the command itself, once its risk is known.
+            (Some(tool), None) if self.name != "shell" && ctx.shell_policy.requires_approval(&self.name) => {
+                match self.approve(tool, ctx).await {
+                    Ok(args) => (tool.execute(self.approved_args.get_or_init(|| args), ctx).await, "tool_call"),
+                    Err(reason) => (format!("Tool call denied: {}", reason), "tool_call"),
+                }
+            }
+            (Some(tool), None) => (tool.execute(&self.args, ctx).await, "tool_call"),
+            (_, invalid) => {
+                eprintln!("[tools] rejected {} call — {}", self.name, invalid.as_deref().and_then(|e| e.lines().next()).unwrap_or("invalid"));
+                (invalid.clone().unwrap_or_default(), "invalid_tool_call")
+            }
+        }
+    }
+}
+
+impl PreparedCall<'_> {
+    /// Wait for a human to approve a call the policy file flags. Returns the
+    /// arguments to run with — edited ones are checked against the schema.
+    async fn approve(&self, tool: &Arc<dyn Tool>, ctx: &ToolContext<'_>) -> Result<Value, String> {
+        let Some(prompter) = ctx.prompter() else {
+            return Err(format!("policy requires human approval for {} and no approval UI is connected", self.name));
+        };
+        let risk = match tool.risk() {
+            ToolRisk::ReadOnly => RiskLevel::Low,
+            ToolRisk::Write => RiskLevel::Medium,
+            ToolRisk::External | ToolRisk::Execute => RiskLevel::High,
+        };
+        let args = serde_json::to_string(&self.args).unwrap_or_default();
+        let reason = format!("Policy requires approval for every {} call", self.name);
+        let request = ctx.shell_policy.approval_request(&self.name, &args, risk, reason);
+        match prompter.prompt(&request).await {
+            PermissionDecision::Allow => Ok(self.args.clone()),
+            PermissionDecision::Edit { command } => schema::tool_arguments(&self.name, &command, &tool.spec().parameters),
+            PermissionDecision::Deny { reason } => Err(reason),
+        }
+    }
+}
+
+/// Audit and report a cancelled turn. Returns the turn's error message.
+fn turn_cancelled<F>(config: &AgentConfig, model: &str, stage: &str, on_event: &mut F) -> String
+where
+    F: FnMut(AgentEvent),
+{
+    eprintln!("[agent] turn cancelled during {}", stage);
+    if let Some(ref audit) = config.audit {
+        let _ = audit.append(model, "cancelled", None, Some(&format!("model={},stage={}", model, stage)), None);
+    }
+    on_event(AgentEvent::Cancelled);
+    CANCELLED.to_string()
+}
+
+/// Summarise `older` history with the registry's compaction model (the
+/// conversation's own model when none is set).
+async fn summarize_history(
+    config: &AgentConfig,
+    provider: &dyn LlmProvider,
+    older: &[ConversationMessage],
+) -> Result<String, String> {
+    let model = ModelRegistry::global()
+        .compaction_model()
+        .map(|m| m.id.clone())
+        .unwrap_or_else(|| config.model.clone());
+    let request = ChatRequest {
+        model,
+        messages: vec![
+            serde_json::json!({"role": "system", "content": context::SUMMARY_PROMPT}),
+            serde_json::json!({"role": "user", "content": context::summary_transcript(older)}),
+        ],
+        tools: vec![],
+        temperature: 0.2,
+        max_tokens: None,
+    };
+    // Quiet: the user's turn hasn't started streaming yet.
+    let (json, _) = complete_with_fallback(config, provider, request, &mut |_| {}).await?;
+    match json["choices"][0]["message"]["content"].as_str().map(str::trim) {
+        Some(text) if !text.is_empty() => Ok(text.to_string()),
+        _ => Err("empty summary".to_string()),
+    }
+}
+
+/// Append a successful call to the usage ledger, if one is configured.
+fn record_usage(config: &AgentConfig, json: &Value, slot: &str, model: &str) {
+    let Some(ref ledger) = config.usage else { return };
+    let Some(record) = UsageRecord::from_response(json, slot, model, &config.surface, config.mission_id.as_deref()) else { return };
+    if let Err(e) = ledger.record(&record) {
+        eprintln!("[usage] record failed: {}", e);
+    }
+}
+
+/// A one-off completion outside a turn — skill reflection, conversation
+/// summaries, Sonnet guard reviews — on slot `slot`. It goes through the
+/// same provider, budget checks and usage ledger as a turn's calls, without
+/// retries or fallback. Returns the reply text.
+pub async fn side_completion(
+    config: &AgentConfig,
+    http: &reqwest::Client,
+    slot: &str,
+    messages: Vec<Value>,
+    temperature: f64,
+    max_tokens: u32,
+) -> Result<String, String> {
+    if let Some(ref ledger) = config.usage {
+        let mut scopes = vec![Scope::Surface(config.surface.clone()), Scope::Model(slot.to_string())];
+        scopes.extend(config.mission_id.clone().map(Scope::Mission));
+        usage::check_budgets(&**ledger, ModelRegistry::global().budgets(), &scopes)?;
+    }
+    let request = ChatRequest {
+        model: models::id(slot).to_string(),
+        messages,
+        tools: vec![],
+        temperature,
+        max_tokens: Some(max_tokens),
+    };
+    let json = config.provider(http).chat(&request, None).await?;
+    if let Some(err) = ApiError::from_response(&json) {
+        return Err(err.message);
+    }
+    record_usage(config, &json, slot, &request.model);
+    match json["choices"][0]["message"]["content"].as_str().map(str::trim) {
+        Some(text) if !text.is_empty() => Ok(text.to_string()),
+        _ => Err("empty response".to_string()),
+    }
+}
+
+/// Run one completion through the provider. With `config.stream` set, text
+/// fragments are forwarded as `AgentEvent::TokenDelta` while the provider
+/// works — the callback stays on this task, so it needn't be `Send`.
+async fn request_completion<F>(
+    config: &AgentConfig,
+    provider: &dyn LlmProvider,
+    request: &ChatRequest,
+    on_event: &mut F,
+) -> Result<Value, String>
+where
+    F: FnMut(AgentEvent),
+{
+    if !config.stream {
+        return provider.chat(request, None).await;
+    }
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let call = provider.chat(request, Some(tx));
+    tokio::pin!(call);
+    loop {
+        tokio::select! {
+            Some(text) = rx.recv() => on_event(AgentEvent::TokenDelta(text)),
+            result = &mut call => {
+                while let Ok(text) = rx.try_recv() {
+                    on_event(AgentEvent::TokenDelta(text));
+                }
+                return result;
+            }
+        }
+    }
+}
+
 /// Spawn a background task that asks Haiku to reflect on whether a reusable skill
 /// was discovered during a tool-heavy turn. If yes, creates it in argus_skills.
 /// Fires only when tool_call_count >= 3 and a SkillsClient is configured.
 /// Never blocks — failures are logged and silently discarded.
 fn maybe_reflect_on_skill(
     tool_call_count: usize,
-    skills: Option<SkillsClient>,
-    api_key: String,
-    api_url: String,
+    config: &AgentConfig,
     model_used: String,
     http: reqwest::Client,
     user_msg: String,
@@ -1143,7 +1436,8 @@ fn maybe_reflect_on_skill(
     if tool_call_count < 3 {
         return;
     }
-    let Some(sc) = skills else { return };
+    let Some(sc) = config.skills.clone() else { return };
+    let config = config.clone();
 
     tokio::spawn(async move {
         let response_preview = if response.chars().count() > 400 {
@@ -1167,34 +1461,13 @@ fn maybe_reflect_on_skill(
              not one-off solutions specific to this task.",
         );
 
-        let body = serde_json::json!({
-            "model": MODEL_HAIKU,   // Haiku: fast, cheap, sufficient for reflection
-            "messages": [{"role": "user", "content": reflection_prompt}],
-            "temperature": 0.3,
-            "max_tokens": 500,
-        });
-
-        let resp = match http
-            .post(&api_url)
-            .header("Authorization", format!("Bearer {}", api_key))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-        {
-            Ok(r) => r,
-            Err(e) => { eprintln!("[skills] Reflection API call failed: {}", e); return; }
+        // Haiku: fast, cheap, sufficient for reflection
+        let messages = vec![serde_json::json!({"role": "user", "content": reflection_prompt})];
+        let content = match side_completion(&config, &http, "haiku", messages, 0.3, 500).await {
+            Ok(text) => text,
+            Err(e) => { eprintln!("[skills] Reflection call failed: {}", e); return; }
         };
 
-        let json: serde_json::Value = match resp.json().await {
-            Ok(j) => j,
-            Err(e) => { eprintln!("[skills] Reflection parse failed: {}", e); return; }
-        };
-
-        let content = json["choices"][0]["message"]["content"]
-            .as_str()
-            .unwrap_or("");
-
         // Extract the JSON object from the response (may be wrapped in prose)
         let (start, end) = match (content.find('{'), content.rfind('}')) {
             (Some(s), Some(e)) if e >= s => (s, e),
@@ -1229,3 +1502,308 @@ fn maybe_reflect_on_skill(
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::MockProvider;
+    use crate::tools::MemoryRecord;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingMemory {
+        stored: Mutex<Vec<String>>,
+    }
+
+    impl MemoryBackend for RecordingMemory {
+        fn remember(&self, _: &str, content: &str, _: Option<&str>, _: f64) -> Result<String, String> {
+            self.stored.lock().unwrap().push(content.to_string());
+            Ok("Remembered.".to_string())
+        }
+        fn recall(&self, _: Option<&str>, _: Option<&str>, _: usize) -> Result<Vec<MemoryRecord>, String> { Ok(vec![]) }
+        fn forget(&self, _: &str) -> Result<String, String> { Ok(String::new()) }
+    }
+
+    #[tokio::test]
+    async fn tool_loop_runs_against_mock_provider() {
+        let mock = Arc::new(
+            MockProvider::new()
+                .reply_tool_call("remember", serde_json::json!({ "content": "sky is blue", "type": "fact" }))
+                .reply_text("Noted, the sky is blue."),
+        );
+        let mut config = AgentConfig::new(String::new()).with_provider(mock.clone());
+        config.model = models::id("haiku").to_string();
+        config.stream = true;
+
+        let memory = RecordingMemory::default();
+        let mut events = Vec::new();
+        let result = run_agent_turn(
+            &config, "remember that the sky is blue", &[], &ShellPolicy::default(),
+            &memory, &mut McpClient::new(), &reqwest::Client::new(), &CancelToken::new(),
+            |e| events.push(e),
+        ).await;
+
+        assert_eq!(result.unwrap(), "Noted, the sky is blue.");
+        assert_eq!(*memory.stored.lock().unwrap(), vec!["sky is blue".to_string()]);
+        assert_eq!(mock.remaining(), 0);
+
+        let streamed: String = events.iter()
+            .filter_map(|e| if let AgentEvent::TokenDelta(t) = e { Some(t.as_str()) } else { None })
+            .collect();
+        assert_eq!(streamed, "Noted, the sky is blue.");
+        assert!(events.iter().any(|e| matches!(e, AgentEvent::ToolResult { name, success: true, .. } if name == "remember")));
+
+        // The second request carries the tool result back to the model.
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        let last = requests[1].messages.last().unwrap();
+        assert_eq!(last["role"], "tool");
+        assert_eq!(last["content"], "Remembered.");
+    }
+
+    #[tokio::test]
+    async fn rate_limited_model_retries_then_falls_back() {
+        let rate_limited = serde_json::json!({ "error": { "message": "Rate limit exceeded", "code": 429 } });
+        let mock = Arc::new(
+            MockProvider::new()
+                .reply_raw(rate_limited.clone())
+                .reply_raw(rate_limited)
+                .reply_text("Haiku here."),
+        );
+        let mut config = AgentConfig::new(String::new()).with_provider(mock.clone());
+        config.model = models::id("gemma").to_string();
+        config.retry = RetryPolicy {
+            max_attempts: 2,
+            base_delay: std::time::Duration::from_millis(1),
+            ..RetryPolicy::default()
+        };
+
+        let mut events = Vec::new();
+        let result = run_agent_turn(
+            &config, "hello", &[], &ShellPolicy::default(),
+            &RecordingMemory::default(), &mut McpClient::new(), &reqwest::Client::new(), &CancelToken::new(),
+            |e| events.push(e),
+        ).await;
+
+        assert_eq!(result.unwrap(), "Haiku here.");
+        let models: Vec<String> = mock.requests().into_iter().map(|r| r.model).collect();
+        assert_eq!(models, [models::id("gemma"), models::id("gemma"), models::id("haiku")]);
+        assert!(events.iter().any(|e| matches!(e,
+            AgentEvent::ModelFallback { to, reason, .. } if to == models::id("haiku") && reason == "rate limited (429)")));
+    }
+
+    #[tokio::test]
+    async fn long_history_is_compacted_into_the_system_prompt() {
+        let mock = Arc::new(
+            MockProvider::new()
+                .reply_text("User is planning a trip to Lisbon.")
+                .reply_text("Pack light."),
+        );
+        let mut config = AgentConfig::new(String::new()).with_provider(mock.clone());
+        config.model = models::id("sonnet").to_string();
+        let history: Vec<ConversationMessage> = (0..40)
+            .map(|i| ConversationMessage {
+                role: if i % 2 == 0 { "user" } else { "assistant" }.to_string(),
+                content: format!("message {}", i),
+                model: None,
+            })
+            .collect();
+
+        let mut events = Vec::new();
+        let result = run_agent_turn(
+            &config, "what should I bring?", &history, &ShellPolicy::default(),
+            &RecordingMemory::default(), &mut McpClient::new(), &reqwest::Client::new(), &CancelToken::new(),
+            |e| events.push(e),
+        ).await;
+        assert_eq!(result.unwrap(), "Pack light.");
+
+        let replaced = events.iter().find_map(|e| match e {
+            AgentEvent::HistoryCompacted { summary, replaced } if summary == "User is planning a trip to Lisbon." => Some(*replaced),
+            _ => None,
+        }).expect("compaction event");
+        assert_eq!(replaced, 32);
+
+        let requests = mock.requests();
+        assert_eq!(requests[0].model, models::id("haiku"));
+        assert!(requests[0].messages[1]["content"].as_str().unwrap().contains("User: message 0"));
+        // The turn itself sees the summary plus the recent tail only.
+        let turn = &requests[1].messages;
+        assert!(turn[0]["content"].as_str().unwrap().ends_with("(summary)\nUser is planning a trip to Lisbon."));
+        assert_eq!(turn.len(), 1 + 8 + 1);
+        assert_eq!(turn[1]["content"], "message 32");
+    }
+
+    /// Cancels its turn from inside a tool call, as a user pressing stop would.
+    struct CancellingMemory(CancelToken);
+
+    impl MemoryBackend for CancellingMemory {
+        fn remember(&self, _: &str, _: &str, _: Option<&str>, _: f64) -> Result<String, String> {
+            self.0.cancel();
+            Ok("Remembered.".to_string())
+        }
+        fn recall(&self, _: Option<&str>, _: Option<&str>, _: usize) -> Result<Vec<MemoryRecord>, String> { Ok(vec![]) }
+        fn forget(&self, _: &str) -> Result<String, String> { Ok(String::new()) }
+    }
+
+    #[tokio::test]
+    async fn cancelled_turn_stops_after_the_running_tool() {
+        let mock = Arc::new(
+            MockProvider::new()
+                .reply_tool_call("remember", serde_json::json!({ "content": "x", "type": "fact" }))
+                .reply_text("never sent"),
+        );
+        let mut config = AgentConfig::new(String::new()).with_provider(mock.clone());
+        config.model = models::id("haiku").to_string();
+
+        let cancel = CancelToken::new();
+        let mut events = Vec::new();
+        let result = run_agent_turn(
+            &config, "remember x", &[], &ShellPolicy::default(),
+            &CancellingMemory(cancel.clone()), &mut McpClient::new(), &reqwest::Client::new(), &cancel,
+            |e| events.push(e),
+        ).await;
+
+        assert_eq!(result.unwrap_err(), CANCELLED);
+        assert_eq!(mock.requests().len(), 1);
+        assert!(events.iter().any(|e| matches!(e, AgentEvent::ToolResult { success: false, result, .. } if result == CANCELLED)));
+        assert!(matches!(events.last(), Some(AgentEvent::Cancelled)));
+
+        // A token cancelled up front stops the turn before any model call.
+        let mock = Arc::new(MockProvider::new().reply_text("never sent"));
+        let config = config.with_provider(mock.clone());
+        let result = run_agent_turn(
+            &config, "hello", &[], &ShellPolicy::default(),
+            &RecordingMemory::default(), &mut McpClient::new(), &reqwest::Client::new(), &cancel,
+            |_| {},
+        ).await;
+        assert_eq!(result.unwrap_err(), CANCELLED);
+        assert_eq!(mock.remaining(), 1);
+    }
+
+    /// Mission-style tools for exercising dispatch: `lookup` is read-only,
+    /// `record` writes. Tracks how many calls are in flight at once.
+    #[derive(Default)]
+    struct SlowTools {
+        in_flight: std::sync::atomic::AtomicUsize,
+        log: Mutex<Vec<(String, usize)>>,
+    }
+
+    impl MissionExecutor for SlowTools {
+        fn execute<'a>(
+            &'a self,
+            name: &'a str,
+            args: &'a Value,
+            _: &'a str,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Option<String>> + Send + 'a>> {
+            use std::sync::atomic::Ordering;
+            Box::pin(async move {
+                let running = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.log.lock().unwrap().push((name.to_string(), running));
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                Some(format!("{} {}", name, args["id"]))
+            })
+        }
+
+        fn tool_specs(&self) -> Vec<ToolSpec> {
+            use crate::tool_registry::{Capability, ToolRisk};
+            let params = serde_json::json!({
+                "type": "object",
+                "properties": { "id": { "type": "integer" } },
+                "required": ["id"]
+            });
+            vec![
+                ToolSpec::new("lookup", "", params.clone(), ToolRisk::ReadOnly, &[Capability::Missions]),
+                ToolSpec::new("record", "", params, ToolRisk::Write, &[Capability::Missions]),
+            ]
+        }
+    }
+
+    #[tokio::test]
+    async fn read_only_calls_run_together_and_report_in_order() {
+        use serde_json::json;
+        let mock = Arc::new(
+            MockProvider::new()
+                .reply_tool_calls(&[
+                    ("lookup", json!({ "id": 1 })),
+                    ("lookup", json!({ "id": 2 })),
+                    ("record", json!({ "id": 3 })),
+                    ("lookup", json!({ "id": "four" })),
+                    ("lookup", json!({ "id": 5 })),
+                ])
+                .reply_text("Done."),
+        );
+        let tools = Arc::new(SlowTools::default());
+        let mut config = AgentConfig::new(String::new()).with_provider(mock.clone());
+        config.model = models::id("haiku").to_string();
+        config.mission_executor = Some(tools.clone());
+
+        let result = run_agent_turn(
+            &config, "look things up", &[], &ShellPolicy::default(),
+            &RecordingMemory::default(), &mut McpClient::new(), &reqwest::Client::new(), &CancelToken::new(),
+            |_| {},
+        ).await;
+        assert_eq!(result.unwrap(), "Done.");
+
+        // The two leading lookups overlapped; the write ran alone, then the last lookup.
+        let log = tools.log.lock().unwrap().clone();
+        let peak = |name: &str| log.iter().filter(|(n, _)| n == name).map(|(_, r)| *r).max().unwrap();
+        assert_eq!(log.len(), 4);
+        assert_eq!(peak("lookup"), 2);
+        assert_eq!(peak("record"), 1);
+        assert_eq!(log[3], ("lookup".to_string(), 1));
+
+        // Results go back in the order the model asked, the invalid call included.
+        let requests = mock.requests();
+        let results: Vec<&str> = requests[1].messages.iter()
+            .filter(|m| m["role"] == "tool")
+            .map(|m| m["content"].as_str().unwrap())
+            .collect();
+        assert_eq!(results[..3], ["lookup 1", "lookup 2", "record 3"]);
+        assert!(results[3].starts_with("Error: invalid arguments for lookup"));
+        assert_eq!(results[4], "lookup 5");
+    }
+
+    #[derive(Default)]
+    struct VecLedger(Mutex<Vec<UsageRecord>>);
+
+    impl UsageLedger for VecLedger {
+        fn record(&self, record: &UsageRecord) -> Result<(), String> {
+            self.0.lock().unwrap().push(record.clone());
+            Ok(())
+        }
+        fn spent(&self, _: &Scope, _: chrono::DateTime<chrono::Utc>) -> Result<f64, String> { Ok(0.0) }
+        fn totals(&self, _: usage::GroupBy, _: chrono::DateTime<chrono::Utc>) -> Result<Vec<(String, usage::UsageTotals)>, String> {
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn workspace_is_built_once_per_config() {
+        let config = AgentConfig::new(String::new());
+        assert!(Arc::ptr_eq(&config.workspace(), &config.workspace()));
+        assert!(Arc::ptr_eq(&config.workspace(), &config.clone().workspace()));
+    }
+
+    #[tokio::test]
+    async fn side_completions_are_metered_on_their_slot() {
+        let mock = Arc::new(MockProvider::new().reply_raw(serde_json::json!({
+            "choices": [{ "message": { "role": "assistant", "content": " APPROVE \n" } }],
+            "usage": { "prompt_tokens": 100, "completion_tokens": 10 }
+        })));
+        let ledger = Arc::new(VecLedger::default());
+        let config = AgentConfig::new(String::new())
+            .with_provider(mock.clone())
+            .with_usage(ledger.clone(), "telegram");
+
+        let messages = vec![serde_json::json!({ "role": "user", "content": "review" })];
+        let text = side_completion(&config, &reqwest::Client::new(), "sonnet", messages, 0.0, 200).await;
+        assert_eq!(text.unwrap(), "APPROVE");
+        let request = &mock.requests()[0];
+        assert_eq!((request.model.as_str(), request.max_tokens), (models::id("sonnet"), Some(200)));
+
+        let recorded = ledger.0.lock().unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!((recorded[0].slot.as_str(), recorded[0].surface.as_str()), ("sonnet", "telegram"));
+    }
+}
@@ -0,0 +1,189 @@
+//! Interactive approval — a human in the loop for risky tool calls
+//!
+//! `ApprovalBroker` is the `PermissionPrompter` behind the web UI and the
+//! TUI. A prompt parks the turn, publishes an `ApprovalEvent::Requested` for
+//! the UI to show, and waits for `resolve` with the human's answer:
+//!
+//!   approve → the call runs as the model wrote it
+//!   edit    → the edited command (or JSON arguments) runs instead
+//!   deny    → the model gets the reason back
+//!
+//! After the request's `timeout_secs` its default applies (deny, unless the
+//! policy file sets `approval_on_timeout = "allow"`). Once a request is
+//! answered, times out or its turn is cancelled, `ApprovalEvent::Closed`
+//! tells the UI to drop it.
+//!
+//! `Audited` wraps any prompter so every decision lands in the audit chain.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use argus_audit::AuditChain;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::shell::{PermissionDecision, PermissionPrompter, PermissionRequest, PromptFuture};
+
+/// What a UI shows: a new request, or one that no longer needs an answer.
+#[derive(Debug, Clone)]
+pub enum ApprovalEvent {
+    Requested(PermissionRequest),
+    Closed { id: String },
+}
+
+/// Routes approval requests to a UI and its answers back to the waiting turn.
+pub struct ApprovalBroker {
+    pending: Mutex<HashMap<String, oneshot::Sender<PermissionDecision>>>,
+    events: mpsc::UnboundedSender<ApprovalEvent>,
+}
+
+impl ApprovalBroker {
+    /// A broker and the event stream its UI listens to.
+    pub fn new() -> (Arc<Self>, mpsc::UnboundedReceiver<ApprovalEvent>) {
+        let (events, rx) = mpsc::unbounded_channel();
+        (Arc::new(Self { pending: Mutex::new(HashMap::new()), events }), rx)
+    }
+
+    /// Answer request `id`. False if it already closed.
+    pub fn resolve(&self, id: &str, decision: PermissionDecision) -> bool {
+        let sender = self.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(id);
+        sender.is_some_and(|tx| tx.send(decision).is_ok())
+    }
+}
+
+impl PermissionPrompter for ApprovalBroker {
+    fn prompt<'a>(&'a self, request: &'a PermissionRequest) -> PromptFuture<'a> {
+        Box::pin(async move {
+            let (tx, rx) = oneshot::channel();
+            self.pending.lock().unwrap_or_else(|e| e.into_inner()).insert(request.id.clone(), tx);
+            // Dropped on every way out, cancellation included.
+            let _open = OpenRequest { broker: self, id: &request.id };
+
+            if self.events.send(ApprovalEvent::Requested(request.clone())).is_err() {
+                return PermissionDecision::Deny { reason: "No approval UI connected".to_string() };
+            }
+            match tokio::time::timeout(Duration::from_secs(request.timeout_secs), rx).await {
+                Ok(Ok(decision)) => decision,
+                Ok(Err(_)) => PermissionDecision::Deny { reason: "Approval UI went away".to_string() },
+                Err(_) => request.timeout_decision(),
+            }
+        })
+    }
+}
+
+/// Forgets a request and closes it in the UI.
+struct OpenRequest<'a> {
+    broker: &'a ApprovalBroker,
+    id: &'a str,
+}
+
+impl Drop for OpenRequest<'_> {
+    fn drop(&mut self) {
+        self.broker.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(self.id);
+        let _ = self.broker.events.send(ApprovalEvent::Closed { id: self.id.to_string() });
+    }
+}
+
+/// A prompter whose every decision is appended to the audit chain, as
+/// `approval_allowed`, `approval_edited` or `approval_denied` — with the
+/// request as args and the edit or denial reason as result.
+pub struct Audited<'a> {
+    pub inner: &'a dyn PermissionPrompter,
+    pub audit: Option<&'a AuditChain>,
+    /// Model whose call is being decided.
+    pub agent: &'a str,
+}
+
+impl PermissionPrompter for Audited<'_> {
+    fn prompt<'a>(&'a self, request: &'a PermissionRequest) -> PromptFuture<'a> {
+        Box::pin(async move {
+            let decision = self.inner.prompt(request).await;
+            eprintln!("[approval] {} {} — {:?}", request.tool, request.command, decision);
+            if let Some(audit) = self.audit {
+                let result = match &decision {
+                    PermissionDecision::Allow => String::new(),
+                    PermissionDecision::Edit { command } => command.clone(),
+                    PermissionDecision::Deny { reason } => reason.clone(),
+                };
+                let _ = audit.append(
+                    self.agent,
+                    decision.audit_action(),
+                    Some(&request.tool),
+                    Some(&serde_json::to_string(request).unwrap_or_default()),
+                    Some(&result),
+                );
+            }
+            decision
+        })
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::{RiskLevel, ShellPolicy};
+
+    fn request(policy: &ShellPolicy) -> PermissionRequest {
+        policy.approval_request("shell", "rm -rf build", RiskLevel::High, "HIGH risk".to_string())
+    }
+
+    /// Answers every request the broker publishes with `decision`.
+    fn answer(broker: Arc<ApprovalBroker>, mut events: mpsc::UnboundedReceiver<ApprovalEvent>, decision: PermissionDecision) {
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                if let ApprovalEvent::Requested(req) = event {
+                    assert!(broker.resolve(&req.id, decision.clone()));
+                }
+            }
+        });
+    }
+
+    #[tokio::test]
+    async fn broker_relays_the_humans_answer() {
+        let policy = ShellPolicy::default();
+        for decision in [
+            PermissionDecision::Allow,
+            PermissionDecision::Edit { command: "rm -r build/cache".to_string() },
+            PermissionDecision::Deny { reason: "not today".to_string() },
+        ] {
+            let (broker, events) = ApprovalBroker::new();
+            answer(broker.clone(), events, decision.clone());
+            assert_eq!(broker.prompt(&request(&policy)).await, decision);
+        }
+    }
+
+    #[tokio::test]
+    async fn unanswered_request_gets_the_timeout_default_and_closes() {
+        let mut policy = ShellPolicy::default();
+        policy.approval_timeout_secs = 1;
+        let (broker, mut events) = ApprovalBroker::new();
+
+        let req = request(&policy);
+        let decision = broker.prompt(&req).await;
+        assert!(matches!(decision, PermissionDecision::Deny { reason } if reason.contains("timeout")));
+        assert!(matches!(events.recv().await, Some(ApprovalEvent::Requested(r)) if r.id == req.id));
+        assert!(matches!(events.recv().await, Some(ApprovalEvent::Closed { id }) if id == req.id));
+        assert!(!broker.resolve(&req.id, PermissionDecision::Allow), "late answers are ignored");
+
+        policy.approve_on_timeout = true;
+        assert_eq!(broker.prompt(&request(&policy)).await, PermissionDecision::Allow);
+    }
+
+    #[tokio::test]
+    async fn decisions_are_audited() {
+        let path = std::env::temp_dir().join(format!("argus-approval-audit-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let audit = AuditChain::open(&path).unwrap();
+        let (broker, events) = ApprovalBroker::new();
+        answer(broker.clone(), events, PermissionDecision::Deny { reason: "no".to_string() });
+
+        let audited = Audited { inner: &*broker, audit: Some(&audit), agent: "test-model" };
+        let decision = audited.prompt(&request(&ShellPolicy::default())).await;
+        assert_eq!(decision.audit_action(), "approval_denied");
+        let entry = audit.entry(1).unwrap();
+        assert_eq!((entry.action_type.as_str(), entry.tool_name.as_deref()), ("approval_denied", Some("shell")));
+        let _ = std::fs::remove_file(&path);
+    }
+}
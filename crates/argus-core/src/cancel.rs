@@ -0,0 +1,78 @@
+//! Cooperative cancellation of agent turns
+//!
+//! A surface creates a `CancelToken` per turn, hands it to `run_agent_turn`
+//! and keeps a clone. Calling `cancel()` stops the turn at the next await
+//! point that races the token:
+//!
+//!   between rounds  → checked before every model request
+//!   model calls     → the HTTP request (and any retry backoff) is dropped
+//!   tool calls      → builtin tool futures are dropped, which aborts remote
+//!                     workspace requests and kills local child processes
+//!   MCP calls       → the blocking stdio call is cut short by killing the
+//!                     server process (see `McpServer::call_tool_cancellable`)
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+/// Error message `run_agent_turn` returns for a cancelled turn.
+pub const CANCELLED: &str = "Turn cancelled";
+
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation. Idempotent; wakes everything waiting on `cancelled()`.
+    pub fn cancel(&self) {
+        if !self.inner.cancelled.swap(true, Ordering::SeqCst) {
+            self.inner.notify.notify_waiters();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once `cancel()` has been called.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            tokio::pin!(notified);
+            // Register before checking the flag so a concurrent cancel() isn't missed.
+            notified.as_mut().enable();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Run `fut` unless the token fires first. Err carries `CANCELLED`.
+    pub async fn run<F: Future>(&self, fut: F) -> Result<F::Output, String> {
+        tokio::select! {
+            biased;
+            _ = self.cancelled() => Err(CANCELLED.to_string()),
+            out = fut => Ok(out),
+        }
+    }
+}
+
+impl std::fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancelToken").field("cancelled", &self.is_cancelled()).finish()
+    }
+}
@@ -0,0 +1,307 @@
+//! Context-window budgeting
+//!
+//! Each model's window comes from `context_window` in models.toml. A turn's
+//! input budget is that window minus room for the answer, and it is spent on
+//! the system prompt, the tool schemas, the conversation history and the
+//! tool results the turn accumulates. Two mechanisms keep a turn inside it:
+//!
+//!   compaction  → before the turn, once history outgrows its share, older
+//!                 turns are summarised into one `summary` message that the
+//!                 surface persists in place of them (see `AgentEvent::HistoryCompacted`)
+//!   fitting     → before every request, stale tool output is elided, then the
+//!                 oldest history dropped, then the latest tool output trimmed
+//!
+//! Token counts are estimates (~4 bytes per token) — close enough for
+//! English, prose and code, and on the safe side for everything else.
+
+use serde_json::Value;
+
+use crate::agent::ConversationMessage;
+use crate::models::ModelRegistry;
+
+/// Window assumed for models without `context_window`.
+pub const DEFAULT_CONTEXT_WINDOW: usize = 128_000;
+
+/// Tokens kept free for the model's answer.
+const RESPONSE_RESERVE: usize = 8_192;
+
+/// Compact once history takes more than this share of the input budget...
+const HISTORY_SHARE: f64 = 0.5;
+
+/// ...or holds more messages than this (surfaces persist at most 40).
+const MAX_HISTORY_MESSAGES: usize = 30;
+
+/// Most recent messages that are never summarised.
+const KEEP_RECENT: usize = 8;
+
+/// Longest single message fed to the summariser, in characters.
+const SUMMARY_INPUT_CHARS: usize = 2_000;
+
+/// `ConversationMessage::role` of a compaction summary. It is rendered into
+/// the system prompt, never sent as a chat message.
+pub const SUMMARY_ROLE: &str = "summary";
+
+const ELIDED_PREFIX: &str = "[elided:";
+
+pub const SUMMARY_PROMPT: &str = "You are compacting a long conversation so it fits in a context window. \
+Summarise the conversation below for your own future reference. Keep: facts and preferences the user stated, \
+decisions made, open tasks and promises, names, file paths, commands and identifiers. Drop pleasantries and \
+anything superseded. If a previous summary is included, fold it in. Write at most 300 words of plain notes, \
+no preamble.";
+
+/// Approximate token count of `text`.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// Approximate tokens of one chat message, including tool calls.
+pub fn message_tokens(message: &Value) -> usize {
+    let content = message["content"].as_str().map(estimate_tokens).unwrap_or(0);
+    let calls = message.get("tool_calls")
+        .map(|tc| estimate_tokens(&tc.to_string()))
+        .unwrap_or(0);
+    // Role and framing overhead.
+    4 + content + calls
+}
+
+pub fn messages_tokens(messages: &[Value]) -> usize {
+    messages.iter().map(message_tokens).sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextBudget {
+    pub window: usize,
+    pub reserve: usize,
+}
+
+impl ContextBudget {
+    pub fn for_model(model_id: &str) -> Self {
+        Self::with_window(ModelRegistry::global().context_window(model_id))
+    }
+
+    pub fn with_window(window: usize) -> Self {
+        Self { window, reserve: RESPONSE_RESERVE.min(window / 4) }
+    }
+
+    /// Tokens available for everything we send.
+    pub fn input_limit(&self) -> usize {
+        self.window - self.reserve
+    }
+
+    /// Largest single tool result kept whole.
+    pub fn tool_result_limit(&self) -> usize {
+        self.input_limit() / 4
+    }
+}
+
+// ── History compaction ───────────────────────────────────────────────────────
+
+/// The compaction summary at the head of `history`, if any, and the rest.
+pub fn split_summary(history: &[ConversationMessage]) -> (Option<&str>, &[ConversationMessage]) {
+    match history.first() {
+        Some(first) if first.role == SUMMARY_ROLE => (Some(first.content.as_str()), &history[1..]),
+        _ => (None, history),
+    }
+}
+
+/// Where to cut `history` for compaction: the messages before the returned
+/// index get summarised. None when history is within budget or too short to
+/// be worth it.
+pub fn compaction_point(history: &[ConversationMessage], budget: &ContextBudget) -> Option<usize> {
+    let tokens: usize = history.iter().map(|m| 4 + estimate_tokens(&m.content)).sum();
+    let over_share = tokens as f64 > budget.input_limit() as f64 * HISTORY_SHARE;
+    if !over_share && history.len() <= MAX_HISTORY_MESSAGES {
+        return None;
+    }
+    // Keep the recent tail verbatim, starting it on a user turn.
+    let mut cut = history.len().saturating_sub(KEEP_RECENT);
+    while cut > 0 && history[cut].role != "user" {
+        cut -= 1;
+    }
+    // A lone summary (or nothing) before the cut isn't worth re-summarising.
+    let (summary, _) = split_summary(history);
+    if cut <= usize::from(summary.is_some()) {
+        return None;
+    }
+    Some(cut)
+}
+
+/// Summariser input: the previous summary, then `older` as a transcript.
+pub fn summary_transcript(older: &[ConversationMessage]) -> String {
+    let (previous, turns) = split_summary(older);
+    let mut out = String::new();
+    if let Some(prev) = previous {
+        out.push_str("[Previous summary]\n");
+        out.push_str(prev);
+        out.push_str("\n\n[Conversation]\n");
+    }
+    for m in turns {
+        let who = if m.role == "user" { "User" } else { "Argus" };
+        out.push_str(&format!("{}: {}\n", who, truncate_middle(&m.content, SUMMARY_INPUT_CHARS / 4)));
+    }
+    out
+}
+
+pub fn summary_message(summary: impl Into<String>) -> ConversationMessage {
+    ConversationMessage { role: SUMMARY_ROLE.to_string(), content: summary.into(), model: None }
+}
+
+/// Replace the first `replaced` messages of `history` with `summary` — how
+/// surfaces apply `AgentEvent::HistoryCompacted` before persisting.
+pub fn apply_compaction(history: &mut Vec<ConversationMessage>, summary: &str, replaced: usize) {
+    let replaced = replaced.min(history.len());
+    history.splice(..replaced, [summary_message(summary)]);
+}
+
+// ── Fitting a request ────────────────────────────────────────────────────────
+
+/// Cut the middle out of `text` so it fits in about `max_tokens`.
+pub fn truncate_middle(text: &str, max_tokens: usize) -> String {
+    let max_bytes = max_tokens * 4;
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let head = floor_boundary(text, max_bytes * 2 / 3);
+    let tail = ceil_boundary(text, text.len() - max_bytes / 3);
+    format!(
+        "{}\n[… {} chars elided to fit the context window …]\n{}",
+        &text[..head],
+        text[head..tail].chars().count(),
+        &text[tail..]
+    )
+}
+
+fn floor_boundary(s: &str, mut i: usize) -> usize {
+    while !s.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+fn ceil_boundary(s: &str, mut i: usize) -> usize {
+    while !s.is_char_boundary(i) {
+        i += 1;
+    }
+    i
+}
+
+/// Shrink `messages` to fit in `limit` tokens. `messages[0]` is the system
+/// prompt and `messages[current..]` the turn in progress; both are kept.
+/// In order, until it fits:
+///
+///   1. tool output from earlier rounds is replaced by a one-line stub
+///   2. history before the current turn is dropped, oldest first
+///   3. tool output from the latest round is trimmed in the middle
+///
+/// `current` follows the turn as history is dropped. Returns the number of
+/// messages changed or dropped.
+pub fn fit_messages(messages: &mut Vec<Value>, current: &mut usize, limit: usize) -> usize {
+    let mut total = messages_tokens(messages);
+    if total <= limit {
+        return 0;
+    }
+    let mut changed = 0;
+    let latest_round = |messages: &[Value]| {
+        messages.iter()
+            .rposition(|m| m["role"] == "assistant" && m.get("tool_calls").is_some())
+            .unwrap_or(messages.len())
+    };
+
+    for i in 0..latest_round(messages) {
+        if total <= limit {
+            break;
+        }
+        let msg = &mut messages[i];
+        let Some(content) = msg["content"].as_str() else { continue };
+        if msg["role"] != "tool" || content.starts_with(ELIDED_PREFIX) {
+            continue;
+        }
+        let before = message_tokens(msg);
+        msg["content"] = Value::String(format!(
+            "{} {} chars of earlier tool output, dropped to fit the context window]",
+            ELIDED_PREFIX,
+            content.chars().count()
+        ));
+        total = total - before + message_tokens(msg);
+        changed += 1;
+    }
+
+    while total > limit && *current > 1 {
+        total -= message_tokens(&messages.remove(1));
+        *current -= 1;
+        changed += 1;
+    }
+
+    if total > limit {
+        let tools: Vec<usize> = (latest_round(messages)..messages.len())
+            .filter(|&i| messages[i]["role"] == "tool")
+            .collect();
+        if !tools.is_empty() {
+            let share = (total - limit).div_ceil(tools.len());
+            for i in tools {
+                let msg = &mut messages[i];
+                let Some(content) = msg["content"].as_str() else { continue };
+                let keep = estimate_tokens(content).saturating_sub(share + 16);
+                msg["content"] = Value::String(truncate_middle(content, keep));
+                changed += 1;
+            }
+        }
+    }
+    changed
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn turn(role: &str, len: usize) -> ConversationMessage {
+        ConversationMessage { role: role.to_string(), content: "x".repeat(len), model: None }
+    }
+
+    #[test]
+    fn compacts_history_and_fits_requests() {
+        let budget = ContextBudget::with_window(10_000);
+        assert_eq!(budget.input_limit(), 7_500);
+
+        // Short conversations are left alone; long ones keep a tail starting on a user turn.
+        let short: Vec<_> = (0..6).map(|i| turn(if i % 2 == 0 { "user" } else { "assistant" }, 100)).collect();
+        assert_eq!(compaction_point(&short, &budget), None);
+        let mut long: Vec<_> = (0..20).map(|i| turn(if i % 2 == 0 { "user" } else { "assistant" }, 1_000)).collect();
+        let cut = compaction_point(&long, &budget).unwrap();
+        assert_eq!((cut, long[cut].role.as_str()), (12, "user"));
+
+        apply_compaction(&mut long, "the gist", cut);
+        assert_eq!(long.len(), 9);
+        let (summary, rest) = split_summary(&long);
+        assert_eq!(summary, Some("the gist"));
+        assert_eq!(rest.len(), 8);
+        assert!(summary_transcript(&long[..3]).starts_with("[Previous summary]\nthe gist"));
+
+        // Old tool output goes first, then old history; the system prompt and current turn stay.
+        let big = "y".repeat(20_000);
+        let mut messages = vec![
+            json!({"role": "system", "content": "sys"}),
+            json!({"role": "user", "content": "x".repeat(8_000)}),
+            json!({"role": "user", "content": "now"}),
+            json!({"role": "assistant", "content": null, "tool_calls": [{"id": "a"}]}),
+            json!({"role": "tool", "tool_call_id": "a", "content": big}),
+            json!({"role": "assistant", "content": null, "tool_calls": [{"id": "b"}]}),
+            json!({"role": "tool", "tool_call_id": "b", "content": "z".repeat(4_000)}),
+        ];
+        let mut current = 2;
+        fit_messages(&mut messages, &mut current, 4_000);
+        assert!(messages[4]["content"].as_str().unwrap().starts_with("[elided: 20000 chars"));
+        assert_eq!(messages[1]["content"], "x".repeat(8_000));
+        assert!(messages_tokens(&messages) <= 4_000);
+
+        fit_messages(&mut messages, &mut current, 1_000);
+        assert_eq!(current, 1);
+        assert_eq!(messages[0]["content"], "sys");
+        assert_eq!(messages[1]["content"], "now");
+        assert!(messages_tokens(&messages) <= 1_000, "{}", messages_tokens(&messages));
+        assert!(messages[5]["content"].as_str().unwrap().contains("chars elided"));
+    }
+}
@@ -0,0 +1,94 @@
+//! Sonnet guard decision log
+//!
+//! Every verdict `SonnetGuard` hands down on a HIGH-risk command becomes a
+//! `GuardRecord` — the command, approve/rewrite/block, the rewrite, Sonnet's
+//! reason, and the ID of the audit entry that seals it. The audit chain only
+//! keeps hashes; the log keeps the text, for `argus guard history`.
+//!
+//! `source` says where the verdict came from:
+//!
+//!   model       → Sonnet answered
+//!   cache       → an earlier verdict on the same normalised command
+//!   unreachable → Sonnet couldn't be asked; the policy's `guard_unreachable`
+//!                 decided
+//!
+//! The log storage lives in argus-memory.
+
+use serde::Serialize;
+
+/// One guard verdict.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GuardRecord {
+    pub command: String,
+    /// "approve", "rewrite" or "block".
+    pub verdict: String,
+    /// The command that ran instead, for a rewrite.
+    pub rewrite: Option<String>,
+    pub reason: String,
+    /// "model", "cache" or "unreachable".
+    pub source: String,
+    /// Model whose command was reviewed.
+    pub agent: String,
+    pub surface: Option<String>,
+    /// Audit chain entry recording this verdict.
+    pub audit_id: Option<u64>,
+    /// Filled in by the log when reading back.
+    pub created_at: Option<String>,
+}
+
+/// Where guard verdicts are kept.
+pub trait GuardLog: Send + Sync {
+    fn record(&self, record: &GuardRecord) -> Result<(), String>;
+
+    /// The latest `limit` verdicts, newest first; only rewrites and blocks
+    /// when `changed_only`.
+    fn recent(&self, limit: usize, changed_only: bool) -> Result<Vec<GuardRecord>, String>;
+}
+
+/// Cache key for `command`: trimmed, with runs of unquoted spaces and tabs
+/// collapsed to one. Quoted text and newlines — which separate commands —
+/// are kept, so two commands share a key only if the shell would run them
+/// the same way.
+pub fn normalize(command: &str) -> String {
+    let mut out = String::with_capacity(command.len());
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut blank = false;
+    for c in command.trim().chars() {
+        if quote.is_none() && !escaped && (c == ' ' || c == '\t') {
+            blank = true;
+            continue;
+        }
+        if blank {
+            out.push(' ');
+            blank = false;
+        }
+        out.push(c);
+        match (quote, c) {
+            _ if escaped => escaped = false,
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => escaped = true,
+            (None, '\'' | '"') => quote = Some(c),
+            (Some('"'), '"') => quote = None,
+            _ => {}
+        }
+    }
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_only_collapses_what_the_shell_ignores() {
+        assert_eq!(normalize("  rm   -rf\tbuild  "), "rm -rf build");
+        assert_eq!(normalize("echo 'a   b'  \"c  d\""), "echo 'a   b' \"c  d\"");
+        assert_eq!(normalize("echo a\\  b"), "echo a\\  b");
+        assert_ne!(normalize("echo a\nrm x"), normalize("echo a rm x"));
+        assert_ne!(normalize("echo 'a  b'"), normalize("echo 'a b'"));
+    }
+}
@@ -1,20 +1,42 @@
 pub mod agent;
+pub mod approval;
+pub mod cancel;
 pub mod constraints;
+pub mod context;
 pub mod embedding;
+pub mod guard;
 pub mod mcp;
+pub mod models;
+pub mod provider;
+pub mod retry;
+pub mod schema;
 pub mod sentry_bus;
 pub mod shell;
+pub mod shell_policy;
+pub mod shell_syntax;
 pub mod skills;
+pub mod stream;
 pub mod supabase;
+pub mod tool_registry;
 pub mod tools;
 pub mod triage;
+pub mod usage;
+pub mod workspace;
 
-pub use agent::{AgentConfig, AgentEvent, ConversationMessage, run_agent_turn, MissionExecutor, MODEL_HAIKU, MODEL_SONNET, MODEL_OPUS, MODEL_GROK, MODEL_GROK_BUILD, MODEL_GROK_MULTI, MODEL_GEMINI, MODEL_GEMMA_RUNTIME, MODEL_SENTRY, model_label, persona_prompt_for};
+pub use agent::{AgentConfig, AgentEvent, ConversationMessage, run_agent_turn, side_completion, MissionExecutor, model_label, persona_prompt_for};
+pub use approval::{ApprovalBroker, ApprovalEvent};
+pub use cancel::CancelToken;
 pub use embedding::{EmbeddingClient, SemanticResult, EMBEDDING_MODEL};
+pub use guard::{GuardLog, GuardRecord};
 pub use mcp::McpClient;
+pub use models::{ModelRegistry, ModelSpec};
+pub use provider::{LlmProvider, OpenRouterProvider, OpenAiCompatProvider, MockProvider};
 pub use constraints::ConstraintClient;
 pub use sentry_bus::{SentryBus, SentryThreat, ThreatSeverity, ReviewRequest, ReviewVerdict, ReviewResult};
 pub use shell::{ShellPolicy, PermissionPrompter, TelegramPrompter};
 pub use skills::{SkillsClient, NewSkill, Skill};
 pub use supabase::{SupabaseClient, DiscourseRecord};
+pub use tool_registry::{Capability, Tool, ToolContext, ToolRegistry, ToolRisk, ToolSpec};
 pub use tools::{MemoryBackend, MemoryRecord};
+pub use usage::{UsageLedger, UsageRecord, UsageReport, Budgets};
+pub use workspace::{WorkspaceExecutor, HttpWorkspace, LocalWorkspace};
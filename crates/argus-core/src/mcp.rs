@@ -4,9 +4,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Write};
-use std::process::{Child, Command, Stdio};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
 use std::sync::atomic::{AtomicU64, Ordering};
 
+use crate::cancel::CancelToken;
+
 static REQUEST_ID: AtomicU64 = AtomicU64::new(1);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +27,9 @@ pub struct McpTool {
     pub description: Option<String>,
     #[serde(rename = "inputSchema")]
     pub input_schema: serde_json::Value,
+    /// Behaviour hints (`readOnlyHint`, `destructiveHint`, ...), when the server sends them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<serde_json::Value>,
     #[serde(skip)]
     pub server_name: String,
 }
@@ -57,10 +62,57 @@ struct JsonRpcError {
 
 pub struct McpServer {
     pub name: String,
+    config: McpServerConfig,
     process: Child,
+    /// Taken while a cancellable call is in flight on its own thread.
+    io: Option<McpIo>,
     pub tools: Vec<McpTool>,
 }
 
+/// The server's stdio pipes, owned apart from the process so a call can run
+/// on another thread while the process stays killable.
+struct McpIo {
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl McpIo {
+    fn send_request(&mut self, method: &str, params: Option<serde_json::Value>) -> Result<serde_json::Value, String> {
+        let id = REQUEST_ID.fetch_add(1, Ordering::SeqCst);
+        
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0",
+            id,
+            method: method.to_string(),
+            params,
+        };
+        
+        let request_json = serde_json::to_string(&request)
+            .map_err(|e| e.to_string())?;
+        
+        writeln!(self.stdin, "{}", request_json)
+            .map_err(|e| format!("Failed to write to MCP server: {}", e))?;
+        self.stdin.flush().map_err(|e| e.to_string())?;
+        
+        // Read response
+        let mut line = String::new();
+        let n = self.stdout.read_line(&mut line)
+            .map_err(|e| format!("Failed to read from MCP server: {}", e))?;
+        if n == 0 {
+            return Err("MCP server closed its output".to_string());
+        }
+        
+        let response: JsonRpcResponse = serde_json::from_str(&line)
+            .map_err(|e| format!("Invalid JSON-RPC response: {}", e))?;
+        
+        if let Some(error) = response.error {
+            return Err(format!("MCP error: {}", error.message));
+        }
+        
+        response.result.ok_or_else(|| "No result in response".to_string())
+    }
+}
+
 impl McpServer {
     pub fn connect(config: &McpServerConfig) -> Result<Self, String> {
         // Spawn the server process
@@ -74,12 +126,18 @@ impl McpServer {
             cmd.env(k, v);
         }
         
-        let process = cmd.spawn()
+        let mut process = cmd.spawn()
             .map_err(|e| format!("Failed to spawn MCP server '{}': {}", config.name, e))?;
+        let io = match (process.stdin.take(), process.stdout.take()) {
+            (Some(stdin), Some(stdout)) => McpIo { stdin, stdout: BufReader::new(stdout) },
+            _ => return Err("Failed to get MCP server stdio".to_string()),
+        };
         
         let mut server = Self {
             name: config.name.clone(),
+            config: config.clone(),
             process,
+            io: Some(io),
             tools: vec![],
         };
         
@@ -93,42 +151,9 @@ impl McpServer {
     }
     
     fn send_request(&mut self, method: &str, params: Option<serde_json::Value>) -> Result<serde_json::Value, String> {
-        let id = REQUEST_ID.fetch_add(1, Ordering::SeqCst);
-        
-        let request = JsonRpcRequest {
-            jsonrpc: "2.0",
-            id,
-            method: method.to_string(),
-            params,
-        };
-        
-        let stdin = self.process.stdin.as_mut()
-            .ok_or("Failed to get stdin")?;
-        
-        let request_json = serde_json::to_string(&request)
-            .map_err(|e| e.to_string())?;
-        
-        writeln!(stdin, "{}", request_json)
-            .map_err(|e| format!("Failed to write to MCP server: {}", e))?;
-        stdin.flush().map_err(|e| e.to_string())?;
-        
-        // Read response
-        let stdout = self.process.stdout.as_mut()
-            .ok_or("Failed to get stdout")?;
-        
-        let mut reader = BufReader::new(stdout);
-        let mut line = String::new();
-        reader.read_line(&mut line)
-            .map_err(|e| format!("Failed to read from MCP server: {}", e))?;
-        
-        let response: JsonRpcResponse = serde_json::from_str(&line)
-            .map_err(|e| format!("Invalid JSON-RPC response: {}", e))?;
-        
-        if let Some(error) = response.error {
-            return Err(format!("MCP error: {}", error.message));
-        }
-        
-        response.result.ok_or_else(|| "No result in response".to_string())
+        self.io.as_mut()
+            .ok_or_else(|| format!("MCP server '{}' is unavailable", self.name))?
+            .send_request(method, params)
     }
     
     fn initialize(&mut self) -> Result<(), String> {
@@ -146,8 +171,9 @@ impl McpServer {
         self.send_request("initialize", Some(params))?;
         
         // Send initialized notification
-        let stdin = self.process.stdin.as_mut()
-            .ok_or("Failed to get stdin")?;
+        let stdin = &mut self.io.as_mut()
+            .ok_or("Failed to get stdin")?
+            .stdin;
         
         let notification = serde_json::json!({
             "jsonrpc": "2.0",
@@ -184,25 +210,81 @@ impl McpServer {
         });
         
         let result = self.send_request("tools/call", Some(params))?;
-        
-        // Extract text content from result
-        if let Some(content) = result.get("content").and_then(|c| c.as_array()) {
-            let text: Vec<String> = content.iter()
-                .filter_map(|c| {
-                    if c.get("type")?.as_str()? == "text" {
-                        c.get("text")?.as_str().map(|s| s.to_string())
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-            return Ok(text.join("\n"));
+        Ok(tool_output(&result))
+    }
+    
+    /// `call_tool`, abandoned if `cancel` fires first. Stdio reads can't be
+    /// interrupted, so the request runs on its own thread; on cancellation the
+    /// server is killed (ending that read) and restarted for the next call.
+    pub async fn call_tool_cancellable(
+        &mut self,
+        name: &str,
+        arguments: serde_json::Value,
+        cancel: &CancelToken,
+    ) -> Result<String, String> {
+        // A call abandoned mid-flight leaves no pipes behind; start afresh.
+        if self.io.is_none() {
+            self.restart();
         }
+        let Some(mut io) = self.io.take() else {
+            return Err(format!("MCP server '{}' is unavailable", self.name));
+        };
+        let params = serde_json::json!({
+            "name": name,
+            "arguments": arguments
+        });
+        
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let result = io.send_request("tools/call", Some(params));
+            let _ = tx.send((io, result));
+        });
         
-        Ok(serde_json::to_string_pretty(&result).unwrap_or_default())
+        match cancel.run(rx).await {
+            Ok(Ok((io, result))) => {
+                self.io = Some(io);
+                result.map(|r| tool_output(&r))
+            }
+            Ok(Err(_)) => {
+                self.restart();
+                Err(format!("MCP server '{}' call thread died", self.name))
+            }
+            Err(e) => {
+                eprintln!("[mcp] {} call to {} cancelled — restarting server", self.name, name);
+                self.restart();
+                Err(e)
+            }
+        }
+    }
+    
+    /// Kill the process and start a fresh one from the same config.
+    fn restart(&mut self) {
+        let _ = self.process.kill();
+        match McpServer::connect(&self.config) {
+            Ok(fresh) => *self = fresh,
+            Err(e) => eprintln!("[mcp] {} restart failed: {}", self.name, e),
+        }
     }
 }
 
+/// Text content of a tools/call result, or the raw result when it has none.
+fn tool_output(result: &serde_json::Value) -> String {
+    if let Some(content) = result.get("content").and_then(|c| c.as_array()) {
+        let text: Vec<String> = content.iter()
+            .filter_map(|c| {
+                if c.get("type")?.as_str()? == "text" {
+                    c.get("text")?.as_str().map(|s| s.to_string())
+                } else {
+                    None
+                }
+            })
+            .collect();
+        return text.join("\n");
+    }
+    
+    serde_json::to_string_pretty(result).unwrap_or_default()
+}
+
 impl Drop for McpServer {
     fn drop(&mut self) {
         let _ = self.process.kill();
@@ -277,4 +359,19 @@ impl McpClient {
         
         Err(format!("Tool '{}' not found in any MCP server", tool_name))
     }
+    
+    /// Call `tool_name` on the server named `server`, raced against `cancel` —
+    /// see `McpServer::call_tool_cancellable`.
+    pub async fn call_tool_cancellable(
+        &mut self,
+        server: &str,
+        tool_name: &str,
+        arguments: serde_json::Value,
+        cancel: &CancelToken,
+    ) -> Result<String, String> {
+        match self.servers.iter_mut().find(|s| s.name == server) {
+            Some(s) => s.call_tool_cancellable(tool_name, arguments, cancel).await,
+            None => Err(format!("MCP server '{}' is not connected", server)),
+        }
+    }
 }
@@ -0,0 +1,367 @@
+//! Model registry
+//!
+//! Model IDs, labels, capabilities and per-model quirks live in `models.toml`
+//! instead of code, so a renamed OpenRouter model is a config edit and a
+//! restart. Code refers to slots by key (`models::id("haiku")`); everything
+//! that used to match on model ID strings asks the registry instead.
+//!
+//! `ModelRegistry::global()` loads, in order of preference:
+//!
+//!   $ARGUS_MODELS_FILE  → explicit path
+//!   ./models.toml       → next to the daemon (the Docker image ships one)
+//!   built-in            → the repo's models.toml, compiled in
+//!
+//! A file that fails to parse or validate is reported and the built-in
+//! roster is used instead — a typo must not take the agent offline.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::usage::Budgets;
+
+const BUILTIN_MODELS: &str = include_str!("../../../models.toml");
+
+/// Persona files the built-in roster names, compiled in alongside it so the
+/// fallback roster doesn't depend on the working directory.
+const BUILTIN_PERSONAS: &[(&str, &str)] = &[
+    ("prompts/opus.md", include_str!("../../../prompts/opus.md")),
+];
+
+/// Tool schema rewrites an upstream vendor needs before it accepts our tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaQuirk {
+    /// Drop `"additionalProperties": false` everywhere (xAI, Google).
+    StripAdditionalProperties,
+    /// Drop `"strict"` keys (xAI).
+    StripStrict,
+    /// Omit `tool_choice` from the request (Google).
+    NoToolChoice,
+}
+
+/// List price in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Pricing {
+    pub prompt: f64,
+    pub completion: f64,
+}
+
+/// One `[[model]]` slot.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelSpec {
+    pub key: String,
+    pub id: String,
+    pub label: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub frontend: Option<String>,
+    /// Persona prompt path as written in the file.
+    #[serde(default)]
+    pub persona: Option<String>,
+    #[serde(default = "default_true")]
+    pub tools: bool,
+    #[serde(default)]
+    pub builder: bool,
+    #[serde(default)]
+    pub max_tool_rounds: Option<usize>,
+    /// Context window in tokens; `context::DEFAULT_CONTEXT_WINDOW` when unset.
+    #[serde(default)]
+    pub context_window: Option<usize>,
+    #[serde(default)]
+    pub quirks: Vec<SchemaQuirk>,
+    #[serde(default)]
+    pub pricing: Option<Pricing>,
+    #[serde(default)]
+    pub fallback: Vec<String>,
+    #[serde(default)]
+    pub internal: bool,
+    /// Contents of `persona`, read once at load.
+    #[serde(skip)]
+    persona_text: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl ModelSpec {
+    /// True if `name` is this slot's key, id, frontend alias or one of its aliases.
+    fn answers_to(&self, name: &str) -> bool {
+        self.key.eq_ignore_ascii_case(name)
+            || self.id.eq_ignore_ascii_case(name)
+            || self.frontend.as_deref().is_some_and(|f| f.eq_ignore_ascii_case(name))
+            || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    }
+
+    pub fn persona_prompt(&self) -> Option<&str> {
+        self.persona_text.as_deref()
+    }
+
+    pub fn has_quirk(&self, quirk: SchemaQuirk) -> bool {
+        self.quirks.contains(&quirk)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelRegistry {
+    default: String,
+    frontend_default: String,
+    #[serde(default)]
+    cycle: Vec<String>,
+    /// Slot that summarises old history when a conversation outgrows its
+    /// context budget; the conversation's own model when unset.
+    #[serde(default)]
+    compaction: Option<String>,
+    #[serde(rename = "model")]
+    models: Vec<ModelSpec>,
+    /// Spending caps, see `usage.rs`.
+    #[serde(default)]
+    budgets: Budgets,
+}
+
+static GLOBAL: OnceLock<ModelRegistry> = OnceLock::new();
+
+impl ModelRegistry {
+    /// The process-wide registry, loaded on first use.
+    pub fn global() -> &'static ModelRegistry {
+        GLOBAL.get_or_init(Self::load)
+    }
+
+    fn load() -> Self {
+        let path = std::env::var("ARGUS_MODELS_FILE")
+            .ok()
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| Some(PathBuf::from("models.toml")).filter(|p| p.exists()));
+
+        if let Some(path) = path {
+            match Self::from_file(&path) {
+                Ok(registry) => return registry,
+                Err(e) => eprintln!("[!] {} — using built-in model roster", e),
+            }
+        }
+        Self::builtin()
+    }
+
+    /// The roster compiled into the binary, with its personas.
+    fn builtin() -> Self {
+        Self::parse(BUILTIN_MODELS, |file| {
+            BUILTIN_PERSONAS.iter()
+                .find(|(name, _)| *name == file)
+                .map(|(_, text)| text.to_string())
+                .ok_or_else(|| "not compiled in".to_string())
+        })
+        .expect("built-in models.toml is valid")
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        Self::from_toml(&text, base).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Parse and validate a roster. Persona paths resolve against `base_dir`;
+    /// a missing persona file is a warning, not an error.
+    pub fn from_toml(text: &str, base_dir: &Path) -> Result<Self, String> {
+        Self::parse(text, |file| {
+            let path = base_dir.join(file);
+            std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))
+        })
+    }
+
+    /// Parse and validate a roster, reading persona files with `read_persona`.
+    fn parse(text: &str, read_persona: impl Fn(&str) -> Result<String, String>) -> Result<Self, String> {
+        let mut registry: Self = toml::from_str(text).map_err(|e| format!("Invalid models file: {}", e))?;
+        registry.validate()?;
+
+        for spec in &mut registry.models {
+            if let Some(file) = &spec.persona {
+                match read_persona(file) {
+                    Ok(text) => spec.persona_text = Some(text.trim().to_string()),
+                    Err(e) => tracing::warn!("persona for {} not loaded: {}", spec.key, e),
+                }
+            }
+        }
+        Ok(registry)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut seen = std::collections::HashSet::new();
+        for spec in &self.models {
+            if !seen.insert(spec.key.as_str()) {
+                return Err(format!("duplicate model key '{}'", spec.key));
+            }
+        }
+        let known = |key: &str, what: &str| {
+            if seen.contains(key) {
+                Ok(())
+            } else {
+                Err(format!("{} refers to unknown model '{}'", what, key))
+            }
+        };
+        known(&self.default, "default")?;
+        known(&self.frontend_default, "frontend_default")?;
+        for key in &self.cycle {
+            known(key, "cycle")?;
+        }
+        if let Some(key) = &self.compaction {
+            known(key, "compaction")?;
+        }
+        for spec in &self.models {
+            for key in &spec.fallback {
+                known(key, &format!("fallback of '{}'", spec.key))?;
+            }
+        }
+        for key in self.budgets.model.keys() {
+            known(key, "budgets.model")?;
+        }
+        Ok(())
+    }
+
+    // ── Lookups ──────────────────────────────────────────────────────────
+
+    pub fn models(&self) -> &[ModelSpec] {
+        &self.models
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ModelSpec> {
+        self.models.iter().find(|m| m.key == key)
+    }
+
+    /// First slot running `model_id`.
+    pub fn by_id(&self, model_id: &str) -> Option<&ModelSpec> {
+        self.models.iter().find(|m| m.id == model_id)
+    }
+
+    /// Resolve a user-typed name (key, alias, frontend alias or full ID).
+    pub fn resolve(&self, name: &str) -> Option<&ModelSpec> {
+        let name = name.trim();
+        self.get(name).or_else(|| self.models.iter().find(|m| m.answers_to(name)))
+    }
+
+    /// Resolve to a slot offered in the web picker (one with a `frontend` alias).
+    pub fn resolve_frontend(&self, name: &str) -> Option<&ModelSpec> {
+        let name = name.trim();
+        self.models.iter()
+            .filter(|m| m.frontend.is_some())
+            .find(|m| m.answers_to(name))
+    }
+
+    pub fn default_model(&self) -> &ModelSpec {
+        self.get(&self.default).expect("validated")
+    }
+
+    pub fn frontend_default(&self) -> &ModelSpec {
+        self.get(&self.frontend_default).expect("validated")
+    }
+
+    /// Slots a user may switch to by name.
+    pub fn selectable(&self) -> impl Iterator<Item = &ModelSpec> {
+        self.models.iter().filter(|m| !m.internal)
+    }
+
+    /// Model after `model_id` in the /toggle cycle; unknown models restart it.
+    pub fn next_in_cycle(&self, model_id: &str) -> &ModelSpec {
+        let pos = self.cycle.iter().position(|k| self.get(k).is_some_and(|m| m.id == model_id));
+        let next = match pos {
+            Some(i) => &self.cycle[(i + 1) % self.cycle.len()],
+            None => self.cycle.first().unwrap_or(&self.default),
+        };
+        self.get(next).expect("validated")
+    }
+
+    /// Slot used to summarise history, if one is configured.
+    pub fn compaction_model(&self) -> Option<&ModelSpec> {
+        self.compaction.as_deref().and_then(|k| self.get(k))
+    }
+
+    /// Context window of `model_id`, or the default for unlisted models.
+    pub fn context_window(&self, model_id: &str) -> usize {
+        self.by_id(model_id)
+            .and_then(|m| m.context_window)
+            .unwrap_or(crate::context::DEFAULT_CONTEXT_WINDOW)
+    }
+
+    /// Slot key a call to `model_id` is made as: the web picker's `frontend`
+    /// slot when it runs that model (Opus and Gemma share an ID), else the
+    /// first slot running it. The ID itself for unlisted models.
+    pub fn slot_for(&self, model_id: &str, frontend: Option<&str>) -> String {
+        frontend
+            .and_then(|alias| self.resolve_frontend(alias))
+            .filter(|m| m.id == model_id)
+            .or_else(|| self.by_id(model_id))
+            .map_or_else(|| model_id.to_string(), |m| m.key.clone())
+    }
+
+    /// Fallback slots for `model_id`, in the order to try them.
+    pub fn fallback_chain(&self, model_id: &str) -> Vec<&ModelSpec> {
+        self.by_id(model_id)
+            .map(|m| m.fallback.iter().filter_map(|k| self.get(k)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Model ID for slot `key`. Unknown keys (a trimmed-down roster file)
+    /// fall back to the default model rather than failing the caller.
+    pub fn id(&self, key: &str) -> &str {
+        match self.get(key) {
+            Some(m) => &m.id,
+            None => {
+                tracing::warn!("model slot '{}' not in registry — using default", key);
+                &self.default_model().id
+            }
+        }
+    }
+
+    pub fn label(&self, model_id: &str) -> &str {
+        self.by_id(model_id).map(|m| m.label.as_str()).unwrap_or("Unknown model")
+    }
+
+    pub fn budgets(&self) -> &Budgets {
+        &self.budgets
+    }
+}
+
+/// Model ID for slot `key` in the global registry.
+pub fn id(key: &str) -> &'static str {
+    ModelRegistry::global().id(key)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_roster_resolves_aliases_cycle_and_fallbacks() {
+        let reg = ModelRegistry::builtin();
+
+        assert_eq!(reg.resolve("Nemotron").unwrap().key, "grok");
+        assert_eq!(reg.resolve("anthropic/claude-sonnet-4-6").unwrap().key, "sonnet");
+        // Opus and Gemma share an id: plain lookups are honest, the web picker finds the slot.
+        let shared = reg.id("opus");
+        assert_eq!(reg.label(shared), "Gemma");
+        assert_eq!(reg.resolve_frontend(shared).unwrap().key, "opus");
+        assert_eq!(reg.slot_for(shared, Some("claude-opus")), "opus");
+        assert_eq!(reg.slot_for(shared, None), "gemma");
+        assert!(reg.get("opus").unwrap().persona_prompt().unwrap().starts_with("# Opus"));
+        let on_disk = ModelRegistry::from_toml(BUILTIN_MODELS, Path::new("../..")).unwrap();
+        assert_eq!(on_disk.get("opus").unwrap().persona_prompt(), reg.get("opus").unwrap().persona_prompt());
+
+        assert_eq!(reg.next_in_cycle(reg.id("gemini")).key, "haiku");
+        assert_eq!(reg.next_in_cycle("unknown/model").key, "haiku");
+        assert!(!reg.get("grok-multi").unwrap().tools);
+
+        let chain: Vec<_> = reg.fallback_chain(reg.id("gemma")).iter().map(|m| m.key.as_str()).collect();
+        assert_eq!(chain, ["haiku", "sonnet"]);
+
+        let bad = BUILTIN_MODELS.replace("fallback = [\"grok\"]", "fallback = [\"nope\"]");
+        assert!(ModelRegistry::from_toml(&bad, Path::new(".")).unwrap_err().contains("nope"));
+    }
+}
@@ -0,0 +1,437 @@
+//! LLM providers
+//!
+//! The agent loop talks to models through an `LlmProvider`. Every provider
+//! accepts the same OpenAI-style request and returns the OpenAI non-streaming
+//! response shape (`choices[0].message`, or an `error` object), so provider
+//! quirks stay here instead of in `run_agent_turn`:
+//!
+//!   OpenRouterProvider   → openrouter.ai, with per-vendor schema fixes
+//!   OpenAiCompatProvider → any `/v1/chat/completions` server (llama.cpp, Ollama, vLLM)
+//!   MockProvider         → scripted replies for offline runs and tests
+//!
+//! `from_env` picks one: `ARGUS_LLM_BASE_URL` selects the OpenAI-compatible
+//! provider (with optional `ARGUS_LLM_API_KEY`), otherwise OpenRouter.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::models::{ModelRegistry, SchemaQuirk};
+use crate::retry;
+use crate::stream;
+
+pub type ProviderFuture<'a> = Pin<Box<dyn Future<Output = Result<Value, String>> + Send + 'a>>;
+
+/// Receives streamed text fragments. Providers that can't stream just
+/// don't send anything.
+pub type DeltaSink = mpsc::UnboundedSender<String>;
+
+/// One chat completion request, provider-neutral.
+#[derive(Debug, Clone)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<Value>,
+    /// OpenAI-style function schemas. Empty means the model gets no tools.
+    pub tools: Vec<Value>,
+    pub temperature: f64,
+    /// Cap on the reply length; None leaves it to the provider.
+    pub max_tokens: Option<u32>,
+}
+
+pub trait LlmProvider: Send + Sync {
+    /// Short label for logs.
+    fn name(&self) -> &str;
+
+    /// Run one completion. When `deltas` is set the provider streams if it
+    /// can and forwards text fragments as they arrive; the returned JSON is
+    /// the full response either way. Err is for transport failures — API
+    /// errors come back as `{"error": ..}` in the JSON.
+    fn chat<'a>(&'a self, request: &'a ChatRequest, deltas: Option<DeltaSink>) -> ProviderFuture<'a>;
+}
+
+/// Pick a provider from the environment, falling back to OpenRouter at
+/// `api_url` with `api_key`.
+pub fn from_env(api_url: &str, api_key: &str, http: &reqwest::Client) -> Arc<dyn LlmProvider> {
+    if let Some(base) = std::env::var("ARGUS_LLM_BASE_URL").ok().filter(|u| !u.is_empty()) {
+        let key = std::env::var("ARGUS_LLM_API_KEY").ok().filter(|k| !k.is_empty());
+        return Arc::new(OpenAiCompatProvider::new(base, key).with_client(http.clone()));
+    }
+    Arc::new(OpenRouterProvider::new(api_url, api_key).with_client(http.clone()))
+}
+
+/// POST a completion body and return the response JSON, reading SSE when
+/// the server streams. Non-2xx answers come back as an `error` object
+/// carrying the status and any Retry-After.
+async fn post_chat(
+    http: &reqwest::Client,
+    url: &str,
+    api_key: Option<&str>,
+    mut body: Value,
+    deltas: Option<DeltaSink>,
+) -> Result<Value, String> {
+    if deltas.is_some() {
+        body["stream"] = serde_json::json!(true);
+        // Without this, streamed answers carry no token counts for the usage ledger.
+        body["stream_options"] = serde_json::json!({ "include_usage": true });
+    }
+
+    let mut req = http
+        .post(url)
+        .header("Content-Type", "application/json")
+        .json(&body);
+    if let Some(key) = api_key {
+        req = req.header("Authorization", format!("Bearer {}", key));
+    }
+    let resp = req.send().await.map_err(|e| format!("API request failed: {}", e))?;
+
+    let status = resp.status();
+    if status.is_success() {
+        if let Some(tx) = deltas {
+            if stream::is_event_stream(&resp) {
+                return stream::read_stream(resp, |text| { let _ = tx.send(text.to_string()); }).await;
+            }
+        }
+        return resp.json()
+            .await
+            .map_err(|e| format!("Failed to parse API response: {}", e));
+    }
+
+    // Error status: normalise to {"error": {message, code, retry_after}} so
+    // retry::ApiError can classify it whatever shape the body had.
+    let retry_after = retry::parse_retry_after(resp.headers());
+    let body: Value = resp.json().await.unwrap_or(Value::Null);
+    let mut error = match body.get("error") {
+        Some(e) if e.is_object() => e.clone(),
+        Some(Value::String(msg)) => serde_json::json!({ "message": msg }),
+        _ => serde_json::json!({ "message": format!("HTTP {}", status) }),
+    };
+    if error.get("code").is_none_or(|c| c.is_null()) {
+        error["code"] = serde_json::json!(status.as_u16());
+    }
+    if let Some(wait) = retry_after {
+        error["retry_after"] = serde_json::json!(wait.as_secs_f64());
+    }
+    Ok(serde_json::json!({ "error": error }))
+}
+
+// ── OpenRouter ───────────────────────────────────────────────────────────────
+
+pub struct OpenRouterProvider {
+    api_url: String,
+    api_key: String,
+    http: reqwest::Client,
+}
+
+impl OpenRouterProvider {
+    pub fn new(api_url: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self {
+            api_url: api_url.into(),
+            api_key: api_key.into(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Reuse an existing client (connection pool) instead of a fresh one.
+    pub fn with_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Build the request body, adapting tool schemas to what each upstream
+    /// vendor's validator accepts (the model's `quirks` in the registry).
+    fn body(request: &ChatRequest) -> Value {
+        let model = request.model.as_str();
+        let quirks = quirks_for(model);
+        let has = |q: SchemaQuirk| quirks.contains(&q);
+
+        let mut body = serde_json::json!({
+            "model": model,
+            "messages": request.messages,
+            "temperature": request.temperature,
+            // Ask OpenRouter to report the billed cost in `usage.cost`.
+            "usage": { "include": true },
+        });
+        if let Some(max) = request.max_tokens {
+            body["max_tokens"] = serde_json::json!(max);
+        }
+        if !request.tools.is_empty() {
+            let schemas = request.tools.iter()
+                .map(|tool| {
+                    let tool = if has(SchemaQuirk::StripAdditionalProperties) {
+                        strip_additional_properties_false(tool)
+                    } else {
+                        tool.clone()
+                    };
+                    if has(SchemaQuirk::StripStrict) { strip_strict(tool) } else { tool }
+                })
+                .collect::<Vec<_>>();
+            body["tools"] = serde_json::json!(schemas);
+            // Gemini does not support tool_choice as a string — omit it entirely.
+            // Anthropic and Grok accept "auto".
+            if !has(SchemaQuirk::NoToolChoice) {
+                body["tool_choice"] = serde_json::json!("auto");
+            }
+        }
+        body
+    }
+}
+
+/// Schema quirks for `model`: from the registry, or guessed from the vendor
+/// prefix for models that aren't listed (e.g. an ID given to a mission).
+fn quirks_for(model: &str) -> Vec<SchemaQuirk> {
+    if let Some(spec) = ModelRegistry::global().by_id(model) {
+        return spec.quirks.clone();
+    }
+    let vendor = model.trim_start_matches('~');
+    if vendor.starts_with("x-ai/") {
+        // Grok rejects additionalProperties:false and strict:true
+        vec![SchemaQuirk::StripAdditionalProperties, SchemaQuirk::StripStrict]
+    } else if vendor.starts_with("google/") {
+        // Gemini rejects additionalProperties:false in nested schemas
+        vec![SchemaQuirk::StripAdditionalProperties, SchemaQuirk::NoToolChoice]
+    } else {
+        vec![]
+    }
+}
+
+impl LlmProvider for OpenRouterProvider {
+    fn name(&self) -> &str {
+        "openrouter"
+    }
+
+    fn chat<'a>(&'a self, request: &'a ChatRequest, deltas: Option<DeltaSink>) -> ProviderFuture<'a> {
+        Box::pin(post_chat(&self.http, &self.api_url, Some(&self.api_key), Self::body(request), deltas))
+    }
+}
+
+/// xAI (Grok) rejects `"additionalProperties": false` in tool schemas.
+/// Recursively remove it so the schema stays valid for xAI's validator.
+fn strip_additional_properties_false(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut out = serde_json::Map::new();
+            for (k, v) in map {
+                if k == "additionalProperties" && v == &Value::Bool(false) {
+                    continue;
+                }
+                out.insert(k.clone(), strip_additional_properties_false(v));
+            }
+            Value::Object(out)
+        }
+        Value::Array(arr) => Value::Array(arr.iter().map(strip_additional_properties_false).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Grok also rejects `"strict": true` in tool schemas — strip it the same way.
+fn strip_strict(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut out = serde_json::Map::new();
+            for (k, v) in map {
+                if k == "strict" { continue; }
+                out.insert(k, strip_strict(v));
+            }
+            Value::Object(out)
+        }
+        Value::Array(arr) => Value::Array(arr.into_iter().map(strip_strict).collect()),
+        other => other,
+    }
+}
+
+// ── OpenAI-compatible (local) ────────────────────────────────────────────────
+
+/// Any server speaking the OpenAI chat completions API — llama.cpp's
+/// `llama-server`, Ollama (`http://localhost:11434/v1`), vLLM, LM Studio.
+/// No schema rewriting; the model name is passed through as-is.
+pub struct OpenAiCompatProvider {
+    base_url: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+}
+
+impl OpenAiCompatProvider {
+    /// `base_url` is the API root, e.g. `http://localhost:8080/v1`.
+    pub fn new(base_url: impl Into<String>, api_key: Option<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn with_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+}
+
+impl LlmProvider for OpenAiCompatProvider {
+    fn name(&self) -> &str {
+        "openai-compatible"
+    }
+
+    fn chat<'a>(&'a self, request: &'a ChatRequest, deltas: Option<DeltaSink>) -> ProviderFuture<'a> {
+        let mut body = serde_json::json!({
+            "model": request.model,
+            "messages": request.messages,
+            "temperature": request.temperature,
+        });
+        if let Some(max) = request.max_tokens {
+            body["max_tokens"] = serde_json::json!(max);
+        }
+        if !request.tools.is_empty() {
+            body["tools"] = serde_json::json!(request.tools);
+            body["tool_choice"] = serde_json::json!("auto");
+        }
+        let url = format!("{}/chat/completions", self.base_url);
+        Box::pin(async move {
+            post_chat(&self.http, &url, self.api_key.as_deref(), body, deltas).await
+        })
+    }
+}
+
+// ── Mock ─────────────────────────────────────────────────────────────────────
+
+/// Replays a fixed script of responses, one per `chat` call, and records
+/// every request it saw. Text replies are streamed word by word when a
+/// delta sink is given. Running past the end of the script is an error.
+#[derive(Default)]
+pub struct MockProvider {
+    script: Mutex<VecDeque<Value>>,
+    requests: Mutex<Vec<ChatRequest>>,
+}
+
+impl MockProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a plain text answer.
+    pub fn reply_text(self, text: &str) -> Self {
+        self.reply_raw(serde_json::json!({
+            "choices": [{ "message": { "role": "assistant", "content": text }, "finish_reason": "stop" }]
+        }))
+    }
+
+    /// Queue a single tool call.
+    pub fn reply_tool_call(self, name: &str, args: Value) -> Self {
+        self.reply_tool_calls(&[(name, args)])
+    }
+
+    /// Queue one response carrying several tool calls, in order.
+    pub fn reply_tool_calls(self, calls: &[(&str, Value)]) -> Self {
+        let n = self.script.lock().map(|s| s.len()).unwrap_or(0);
+        let tool_calls: Vec<Value> = calls.iter().enumerate()
+            .map(|(i, (name, args))| serde_json::json!({
+                "id": format!("mock_call_{}_{}", n, i),
+                "type": "function",
+                "function": { "name": name, "arguments": args.to_string() }
+            }))
+            .collect();
+        self.reply_raw(serde_json::json!({
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": tool_calls
+                },
+                "finish_reason": "tool_calls"
+            }]
+        }))
+    }
+
+    /// Queue an API error body.
+    pub fn reply_error(self, message: &str) -> Self {
+        self.reply_raw(serde_json::json!({ "error": { "message": message } }))
+    }
+
+    /// Queue an arbitrary response JSON.
+    pub fn reply_raw(self, response: Value) -> Self {
+        if let Ok(mut script) = self.script.lock() {
+            script.push_back(response);
+        }
+        self
+    }
+
+    /// Requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<ChatRequest> {
+        self.requests.lock().map(|r| r.clone()).unwrap_or_default()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.script.lock().map(|s| s.len()).unwrap_or(0)
+    }
+}
+
+impl LlmProvider for MockProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn chat<'a>(&'a self, request: &'a ChatRequest, deltas: Option<DeltaSink>) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            if let Ok(mut seen) = self.requests.lock() {
+                seen.push(request.clone());
+            }
+            let next = self.script.lock()
+                .map_err(|_| "mock provider: lock poisoned".to_string())?
+                .pop_front()
+                .ok_or_else(|| "mock provider: script exhausted".to_string())?;
+
+            if let (Some(tx), Some(text)) = (deltas, next["choices"][0]["message"]["content"].as_str()) {
+                for word in text.split_inclusive(' ') {
+                    let _ = tx.send(word.to_string());
+                }
+            }
+            Ok(next)
+        })
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn openrouter_applies_vendor_schema_quirks() {
+        let tool = serde_json::json!({
+            "type": "function",
+            "function": {
+                "name": "shell",
+                "strict": true,
+                "parameters": { "type": "object", "additionalProperties": false, "properties": {} }
+            }
+        });
+        let request = |model: &str| ChatRequest {
+            model: model.to_string(),
+            messages: vec![],
+            tools: vec![tool.clone()],
+            temperature: 0.7,
+            max_tokens: None,
+        };
+
+        let grok = OpenRouterProvider::body(&request("x-ai/grok-4.20"));
+        assert!(grok["tools"][0]["function"].get("strict").is_none());
+        assert!(grok["tools"][0]["function"]["parameters"].get("additionalProperties").is_none());
+        assert_eq!(grok["tool_choice"], "auto");
+
+        let gemini = OpenRouterProvider::body(&request("google/gemini-3.1-pro-preview"));
+        assert_eq!(gemini["tools"][0]["function"]["strict"], true);
+        assert!(gemini["tools"][0]["function"]["parameters"].get("additionalProperties").is_none());
+        assert!(gemini.get("tool_choice").is_none());
+
+        let claude = OpenRouterProvider::body(&request("anthropic/claude-haiku-4-5"));
+        assert_eq!(claude["tools"][0], tool);
+
+        // Not in the registry — quirks come from the vendor prefix.
+        let unlisted = OpenRouterProvider::body(&request("~x-ai/grok-5"));
+        assert!(unlisted["tools"][0]["function"].get("strict").is_none());
+    }
+}
@@ -0,0 +1,241 @@
+//! API error classification and retry backoff
+//!
+//! Providers hand back API failures as `{"error": {...}}` JSON (see
+//! `provider.rs`), annotated with the HTTP status as `code` and any
+//! `Retry-After` as `retry_after` seconds. `ApiError::from_response` sorts
+//! those into kinds the agent loop can act on:
+//!
+//!   retry, then fall back   → rate limits, 5xx / overloaded, network errors
+//!   fall back immediately   → model gone, out of credits, context too long,
+//!                             our own budget for the model spent
+//!   give up                 → bad key, malformed request, anything unrecognised
+//!
+//! `RetryPolicy` computes the wait between attempts: exponential backoff
+//! with full jitter, or the server's Retry-After when it asked for longer.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiErrorKind {
+    /// 429 — slow down, the same model will work again shortly.
+    RateLimited,
+    /// 5xx, upstream provider overloaded or errored.
+    ServerError,
+    /// Request never got an answer (connect failure, timeout, dropped stream).
+    Network,
+    /// 404 / "no endpoints found" — the model ID is gone or has no provider.
+    ModelUnavailable,
+    /// 402 — out of credits for this model.
+    PaymentRequired,
+    /// Prompt exceeds the model's context window.
+    ContextLength,
+    /// The model's daily or monthly budget is spent (see `usage.rs`). Never
+    /// sent by a server — raised before the call.
+    OverBudget,
+    /// 401 / 403 — the key is wrong; no other model will do better.
+    Auth,
+    /// 400 and anything unrecognised.
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub kind: ApiErrorKind,
+    pub message: String,
+    pub status: Option<u16>,
+    /// Server-requested wait before retrying.
+    pub retry_after: Option<Duration>,
+}
+
+impl ApiError {
+    /// Classify an `{"error": ..}` response. None if `json` has no error.
+    pub fn from_response(json: &Value) -> Option<Self> {
+        let err = json.get("error")?;
+        let message = err["message"].as_str()
+            .or_else(|| err.as_str())
+            .unwrap_or("Unknown API error")
+            .to_string();
+        let status = err["code"].as_u64()
+            .or_else(|| err["code"].as_str().and_then(|c| c.parse().ok()))
+            .and_then(|c| u16::try_from(c).ok());
+        let retry_after = err["retry_after"].as_f64()
+            .filter(|s| s.is_finite() && *s >= 0.0)
+            .map(Duration::from_secs_f64);
+
+        Some(Self { kind: classify(status, &message), message, status, retry_after })
+    }
+
+    /// A transport failure (the provider returned Err).
+    pub fn network(message: impl Into<String>) -> Self {
+        Self { kind: ApiErrorKind::Network, message: message.into(), status: None, retry_after: None }
+    }
+
+    /// The model's budget is spent; try the next one in the chain.
+    pub fn over_budget(message: impl Into<String>) -> Self {
+        Self { kind: ApiErrorKind::OverBudget, message: message.into(), status: None, retry_after: None }
+    }
+
+    /// Worth asking the same model again after a pause.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.kind, ApiErrorKind::RateLimited | ApiErrorKind::ServerError | ApiErrorKind::Network)
+    }
+
+    /// Worth handing to the next model in the fallback chain (after any retries).
+    pub fn allows_fallback(&self) -> bool {
+        !matches!(self.kind, ApiErrorKind::Auth | ApiErrorKind::Other)
+    }
+
+    /// Short reason for events and the audit log, e.g. "rate limited (429)".
+    pub fn reason(&self) -> String {
+        let kind = match self.kind {
+            ApiErrorKind::RateLimited      => "rate limited",
+            ApiErrorKind::ServerError      => "provider error",
+            ApiErrorKind::Network          => "network error",
+            ApiErrorKind::ModelUnavailable => "model unavailable",
+            ApiErrorKind::PaymentRequired  => "out of credits",
+            ApiErrorKind::ContextLength    => "context too long",
+            ApiErrorKind::OverBudget       => "over budget",
+            ApiErrorKind::Auth             => "auth failed",
+            ApiErrorKind::Other            => "API error",
+        };
+        match self.status {
+            Some(code) => format!("{} ({})", kind, code),
+            None => kind.to_string(),
+        }
+    }
+}
+
+fn classify(status: Option<u16>, message: &str) -> ApiErrorKind {
+    let msg = message.to_lowercase();
+    // Message checks first: OpenRouter reports some of these with a generic 400.
+    if msg.contains("context length") || msg.contains("context window") || msg.contains("maximum context") {
+        return ApiErrorKind::ContextLength;
+    }
+    if msg.contains("no endpoints found") || msg.contains("model not found") || msg.contains("is not a valid model") {
+        return ApiErrorKind::ModelUnavailable;
+    }
+    match status {
+        Some(429) => ApiErrorKind::RateLimited,
+        Some(402) => ApiErrorKind::PaymentRequired,
+        Some(401) | Some(403) => ApiErrorKind::Auth,
+        Some(404) => ApiErrorKind::ModelUnavailable,
+        Some(408) => ApiErrorKind::Network,
+        Some(code) if code >= 500 => ApiErrorKind::ServerError,
+        Some(_) => ApiErrorKind::Other,
+        None if msg.contains("rate limit") || msg.contains("rate-limited") => ApiErrorKind::RateLimited,
+        None if msg.contains("overloaded") || msg.contains("provider returned error") => ApiErrorKind::ServerError,
+        None if msg.contains("timed out") || msg.contains("timeout") => ApiErrorKind::Network,
+        None => ApiErrorKind::Other,
+    }
+}
+
+/// Parse a `Retry-After` header: delay-seconds or an HTTP date.
+pub fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let when = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let secs = (when.with_timezone(&chrono::Utc) - chrono::Utc::now()).num_seconds();
+    Some(Duration::from_secs(secs.max(0) as u64))
+}
+
+// ── Backoff ──────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts per model, including the first. 1 disables retries.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    /// Cap on computed backoff.
+    pub max_delay: Duration,
+    /// Longest Retry-After we'll sit through; beyond this we move on to the
+    /// fallback model instead of making the user wait.
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1000),
+            max_delay: Duration::from_secs(20),
+            max_retry_after: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// No retries, no waiting — fallbacks still apply.
+    pub fn none() -> Self {
+        Self { max_attempts: 1, ..Self::default() }
+    }
+
+    /// Wait before attempt `attempt + 1` (attempt counts from 1), or None if
+    /// this error shouldn't be retried on the same model.
+    pub fn delay_after(&self, attempt: u32, err: &ApiError) -> Option<Duration> {
+        if !err.is_retryable() || attempt >= self.max_attempts {
+            return None;
+        }
+        // Full jitter: uniform in [0, min(max, base·2^(n-1))].
+        let ceiling = self.base_delay
+            .saturating_mul(1u32 << (attempt - 1).min(16))
+            .min(self.max_delay);
+        let backoff = ceiling.mul_f64(jitter());
+        match err.retry_after {
+            Some(wait) if wait > self.max_retry_after => None,
+            Some(wait) => Some(wait.max(backoff)),
+            None => Some(backoff),
+        }
+    }
+}
+
+/// Uniform random fraction in [0, 1). `RandomState` is seeded per instance,
+/// which is plenty for spreading out retries.
+fn jitter() -> f64 {
+    let mut h = RandomState::new().build_hasher();
+    h.write_u64(0);
+    (h.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_and_backs_off() {
+        let rate = ApiError::from_response(&serde_json::json!({
+            "error": { "message": "Rate limit exceeded: free-models-per-min", "code": 429, "retry_after": 4 }
+        })).unwrap();
+        assert_eq!(rate.kind, ApiErrorKind::RateLimited);
+        assert_eq!(rate.reason(), "rate limited (429)");
+
+        let gone = ApiError::from_response(&serde_json::json!({
+            "error": { "message": "No endpoints found for google/gemma-4-31b-it:free.", "code": 404 }
+        })).unwrap();
+        assert_eq!(gone.kind, ApiErrorKind::ModelUnavailable);
+        assert!(!gone.is_retryable() && gone.allows_fallback());
+
+        let auth = ApiError::from_response(&serde_json::json!({ "error": { "message": "No auth", "code": 401 } })).unwrap();
+        assert!(!auth.is_retryable() && !auth.allows_fallback());
+
+        let policy = RetryPolicy::default();
+        // Retry-After wins over a shorter backoff, and the last attempt gets no delay.
+        assert!(policy.delay_after(1, &rate).unwrap() >= Duration::from_secs(4));
+        assert!(policy.delay_after(3, &rate).is_none());
+        assert!(policy.delay_after(1, &gone).is_none());
+        for attempt in 1..3 {
+            let d = policy.delay_after(attempt, &ApiError::network("reset")).unwrap();
+            assert!(d <= policy.base_delay * 2u32.pow(attempt - 1));
+        }
+        // A long Retry-After means fall back rather than wait.
+        let slow = ApiError { retry_after: Some(Duration::from_secs(600)), ..rate };
+        assert!(policy.delay_after(1, &slow).is_none());
+    }
+}
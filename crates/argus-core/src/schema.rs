@@ -0,0 +1,271 @@
+//! Tool-call argument validation
+//!
+//! Every tool call is checked against the tool's declared parameter schema
+//! (built-in `ToolSpec::parameters` or an MCP server's `inputSchema`) before
+//! it runs. A call that fails is not executed; the model gets back the list
+//! of problems so it can fix the call and retry.
+//!
+//! This is the subset of JSON Schema that tool schemas use in practice:
+//! `type`, `enum`, `const`, `properties`, `required`, `additionalProperties`,
+//! `items`, `minItems`/`maxItems`, `minLength`/`maxLength`, `minimum`/
+//! `maximum` (and their exclusive forms), `anyOf`, `oneOf` and `allOf`.
+//! Anything else — `$ref`, `pattern`, `format` — is accepted unchecked, so an
+//! unusual MCP schema can only make validation more lenient, never reject a
+//! valid call.
+
+use serde_json::Value;
+
+/// One way the arguments don't match the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// JSON pointer to the offending value; empty for the arguments object itself.
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = if self.path.is_empty() { "(arguments)" } else { &self.path };
+        write!(f, "{}: {}", path, self.message)
+    }
+}
+
+/// Parse a tool call's raw `arguments` string and check it against `schema`.
+/// Err is the message handed back to the model in place of a result.
+pub fn tool_arguments(tool: &str, raw: &str, schema: &Value) -> Result<Value, String> {
+    // Some providers send "" rather than "{}" for a call without arguments.
+    let args = if raw.trim().is_empty() {
+        Value::Object(Default::default())
+    } else {
+        serde_json::from_str(raw).map_err(|e| {
+            format!("Error: invalid arguments for {} — not run. Arguments must be a JSON object; parsing failed: {}", tool, e)
+        })?
+    };
+    let errors = validate(schema, &args);
+    if errors.is_empty() {
+        return Ok(args);
+    }
+    let mut out = format!("Error: invalid arguments for {} — not run. Fix these and call it again:", tool);
+    for e in &errors {
+        out.push_str("\n  ");
+        out.push_str(&e.to_string());
+    }
+    Err(out)
+}
+
+/// Every mismatch between `value` and `schema`. Empty when valid.
+pub fn validate(schema: &Value, value: &Value) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    check(schema, value, "", &mut errors);
+    errors
+}
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<SchemaError>) {
+    // `true`, `{}` and non-object schemas accept anything; `false` nothing.
+    let schema = match schema {
+        Value::Object(obj) => obj,
+        Value::Bool(false) => return error(errors, path, "no value is allowed here".to_string()),
+        _ => return,
+    };
+    let mut fail = |message: String| error(errors, path, message);
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            // Nothing below makes sense against the wrong type.
+            return fail(format!("expected {}, got {}", types.join(" or "), describe(value)));
+        }
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            let options: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            fail(format!("must be one of {}, got {}", options.join(", "), value));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            fail(format!("must be {}, got {}", expected, value));
+        }
+    }
+
+    match value {
+        Value::String(s) => {
+            let len = s.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if len < min { fail(format!("must be at least {} characters, got {}", min, len)); }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if len > max { fail(format!("must be at most {} characters, got {}", max, len)); }
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+            if let Some(min) = bound("minimum") {
+                if n < min { fail(format!("must be ≥ {}, got {}", min, n)); }
+            }
+            if let Some(max) = bound("maximum") {
+                if n > max { fail(format!("must be ≤ {}, got {}", max, n)); }
+            }
+            if let Some(min) = bound("exclusiveMinimum") {
+                if n <= min { fail(format!("must be > {}, got {}", min, n)); }
+            }
+            if let Some(max) = bound("exclusiveMaximum") {
+                if n >= max { fail(format!("must be < {}, got {}", max, n)); }
+            }
+        }
+        Value::Array(items) => {
+            let len = items.len() as u64;
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if len < min { fail(format!("must have at least {} items, got {}", min, len)); }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if len > max { fail(format!("must have at most {} items, got {}", max, len)); }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    check(item_schema, item, &format!("{}/{}", path, i), errors);
+                }
+            }
+        }
+        Value::Object(obj) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !obj.contains_key(key) {
+                        error(errors, &pointer(path, key), "required property is missing".to_string());
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, v) in obj {
+                let child = pointer(path, key);
+                match properties.and_then(|p| p.get(key)) {
+                    Some(prop_schema) => check(prop_schema, v, &child, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            let known: Vec<&str> = properties
+                                .map(|p| p.keys().map(String::as_str).collect())
+                                .unwrap_or_default();
+                            error(errors, &child, format!("unknown property (expected one of: {})", known.join(", ")));
+                        }
+                        Some(extra) => check(extra, v, &child, errors),
+                        None => {}
+                    },
+                }
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(Value::Array(all)) = schema.get("allOf") {
+        for sub in all {
+            check(sub, value, path, errors);
+        }
+    }
+    for (key, exactly_one) in [("anyOf", false), ("oneOf", true)] {
+        let Some(Value::Array(options)) = schema.get(key) else { continue };
+        let matching = options.iter().filter(|sub| validate(sub, value).is_empty()).count();
+        if matching == 0 {
+            error(errors, path, format!("does not match any of the {} allowed forms", options.len()));
+        } else if exactly_one && matching > 1 {
+            error(errors, path, format!("matches {} of the allowed forms, expected exactly one", matching));
+        }
+    }
+}
+
+fn error(errors: &mut Vec<SchemaError>, path: &str, message: String) {
+    errors.push(SchemaError { path: path.to_string(), message });
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        // Unknown type names are the schema's problem, not the call's.
+        _ => true,
+    }
+}
+
+/// Type and a short rendering of `value`, for error messages.
+fn describe(value: &Value) -> String {
+    let ty = match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    };
+    let shown = value.to_string();
+    if matches!(value, Value::Array(_) | Value::Object(_)) || shown.chars().count() > 40 {
+        ty.to_string()
+    } else {
+        format!("{} {}", ty, shown)
+    }
+}
+
+/// Extend a JSON pointer by one key, escaping per RFC 6901.
+fn pointer(path: &str, key: &str) -> String {
+    format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_every_problem_with_its_path() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "url":     { "type": "string", "minLength": 1 },
+                "method":  { "type": "string", "enum": ["GET", "POST"] },
+                "timeout": { "type": "integer", "minimum": 1, "maximum": 120 },
+                "headers": { "type": "object", "additionalProperties": { "type": "string" } },
+                "tags":    { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["url"],
+            "additionalProperties": false
+        });
+
+        let ok = json!({"url": "https://example.com", "timeout": 30.0, "headers": {"a/b": "c"}});
+        assert!(validate(&schema, &ok).is_empty());
+
+        let bad = json!({
+            "method": "PATCH",
+            "timeout": "30",
+            "headers": {"a/b": 1},
+            "tags": ["x", 2],
+            "verbose": true
+        });
+        let errors: Vec<String> = validate(&schema, &bad).iter().map(|e| e.to_string()).collect();
+        assert_eq!(errors, vec![
+            "/url: required property is missing",
+            "/headers/a~1b: expected string, got number 1",
+            "/method: must be one of \"GET\", \"POST\", got \"PATCH\"",
+            "/tags/1: expected string, got number 2",
+            "/timeout: expected integer, got string \"30\"",
+            "/verbose: unknown property (expected one of: headers, method, tags, timeout, url)",
+        ]);
+
+        // The whole call: empty arguments are an empty object; garbage never reaches the tool.
+        assert!(tool_arguments("shell", "", &json!({"type": "object"})).is_ok());
+        let missing = tool_arguments("shell", "{}", &json!({"type": "object", "required": ["command"]})).unwrap_err();
+        assert!(missing.starts_with("Error: invalid arguments for shell"));
+        assert!(missing.ends_with("/command: required property is missing"));
+        assert!(tool_arguments("shell", "{\"command\": ", &json!({})).unwrap_err().contains("parsing failed"));
+        assert!(!validate(&json!({"type": "object"}), &json!("ls")).is_empty());
+    }
+}
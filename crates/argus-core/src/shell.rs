@@ -2,17 +2,35 @@
 //!
 //!   LOW    → execute immediately, log
 //!   MEDIUM → execute, log with warning, surface in UI
-//!   HIGH   → Sonnet reviews; approves, rewrites to safer form, or blocks
+//!   HIGH   → a human approves, edits or denies it when a prompter is
+//!            wired (web UI, TUI); otherwise Sonnet reviews — approves,
+//!            rewrites to a safer form, or blocks
 //!
-//! HIGH risk no longer waits for human approval — Sonnet acts as the gate.
-//! The TelegramPrompter is kept for notifications only, not blocking.
-
-use std::collections::HashSet;
+//! Unattended surfaces have no prompter, so Sonnet stays their gate.
+//!
+//! Rules, hard blocks and limits can be tuned per project and per surface in
+//! a policy file (see `shell_policy`); `ShellPolicy::for_surface` applies it.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use argus_sandbox::isolation::{IsolationProfile, ProcessGroup};
+use serde::{Deserialize, Serialize};
 use serde_json;
 
+use crate::agent::AgentConfig;
+use crate::guard::{self, GuardLog, GuardRecord};
+use crate::shell_policy::{normalize_path, CommandRule, PolicyStore, RuleRisk, Surface, TimeoutDecision};
+use crate::shell_syntax::{self, Redirect, SimpleCommand};
+
 // ── Risk classification ───────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum RiskLevel {
     Low,
     Medium,
@@ -38,180 +56,817 @@ impl RiskLevel {
 }
 
 /// Classify a shell command into LOW / MEDIUM / HIGH risk.
+///
+/// The command is parsed the way the shell would run it (see `shell_syntax`),
+/// so quoting, paths and chaining don't change the outcome: `/bin/rm -r -f x`,
+/// `r''m -rf x` and `ls; rm -rf x` all come down to `rm` with a recursive
+/// flag. Each simple command is classified from its argv, looking through
+/// wrappers (`env`, `nice`, `timeout`, `xargs`, `sh -c`, `find -exec`, …),
+/// and the whole line is as risky as its riskiest command. A line the parser
+/// can't make sense of is HIGH.
 pub fn classify_risk(command: &str) -> RiskLevel {
-    let cmd = command.trim();
-
-    // Normalize internal whitespace before pattern matching.
-    // Prevents bypass via extra spaces/tabs/newlines between tokens
-    // e.g. "python3  -c 'code'" or "python3\n-c\n'code'" → "python3 -c 'code'"
-    let normalized: String = cmd.split_whitespace().collect::<Vec<_>>().join(" ");
-    let cmd = normalized.as_str();
-
-    // Subshell execution — always HIGH (arbitrary code injection vector)
-    if cmd.contains("$(") || cmd.contains('`') {
-        return RiskLevel::High;
-    }
-
-    // HIGH: Destructive, irreversible, or privilege-escalating
-    let high_patterns: &[&str] = &[
-        "rm -rf",
-        "rm -r /",
-        "rm -fr",
-        "> /dev/",
-        "> /etc/",
-        "> /sys/",
-        "mkfs",
-        "fdisk",
-        "dd if=",
-        "sudo",
-        "su -",
-        "chmod 777",
-        "chmod -R 777",
-        "| bash",
-        "| sh",
-        "git push --force",
-        "git push -f",
-        "docker rm -f",
-        "docker rmi -f",
-        "docker system prune",
-        "kill -9",
-        "killall",
-        "pkill",
-        ":(){:|:&};:",
-        "format",
-        "shred",
-        "rm ~/.argus",
-        "rm -rf ~/.argus",
-        "> ~/.argus",
-        // Interpreter one-liners — arbitrary code execution via -c/-e flags
-        "python -c",
-        "python3 -c",
-        "python -m",
-        "python3 -m",
-        "node -e",
-        "node --eval",
-        "ruby -e",
-        "perl -e",
-        "perl -E",
-        // Embedded execution patterns
-        "subprocess",
-        "os.system(",
-        "os.popen(",
-        "exec(",
-        "eval(",
-        // Git as a weapon
-        "git config --global",
-        "git -c core",
-        // Symlinks — always reviewed; they can redirect writes outside the workspace
-        "ln -s",
-        "ln --symbolic",
-    ];
+    classify_with_rules(command, &[]).risk
+}
 
-    for pattern in high_patterns {
-        if cmd.contains(pattern) {
-            return RiskLevel::High;
-        }
-    }
-
-    // MEDIUM: Write operations, installs, network mutations
-    let medium_patterns: &[&str] = &[
-        "npm install",
-        "npm uninstall",
-        "pip install",
-        "pip uninstall",
-        "cargo install",
-        "brew install",
-        "brew uninstall",
-        "apt install",
-        "apt remove",
-        "git commit",
-        "git push",
-        "git reset",
-        "git checkout",
-        "mv ",
-        "cp -r",
-        "mkdir",
-        "touch",
-        "chmod",
-        "chown",
-        "docker run",
-        "docker build",
-        "docker stop",
-        "docker start",
-        "ssh ",
-        "scp ",
-        "rsync",
-        "curl -X POST",
-        "curl -X PUT",
-        "curl -X DELETE",
-        "tee ",
-        // Bare interpreter invocations (running scripts = medium risk)
-        "python ",
-        "python3 ",
-        "node ",
-        "ruby ",
-        "perl ",
-        "base64",
-    ];
+/// `classify_risk`, with policy-file rules consulted before the built-in
+/// ones for every simple command (wrapped commands included). Reports the
+/// rule that decided the outcome. Relative paths are resolved against the
+/// process's working directory.
+pub fn classify_with_rules(command: &str, rules: &[&CommandRule]) -> Verdict {
+    Classifier { rules, replace: None, cwd: std::env::current_dir().ok() }.script(command, 0)
+}
+
+/// What decided a command's risk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verdict {
+    pub risk: RiskLevel,
+    /// A policy rule or hard-block pattern refuses the command outright.
+    pub blocked: bool,
+    /// Policy-file rule name, or `builtin:<name>` for the built-in classifier.
+    pub rule: String,
+    pub reason: String,
+    /// The simple command, word or pattern the rule matched.
+    pub matched: String,
+}
+
+impl Verdict {
+    fn builtin(rule: &str, risk: RiskLevel, reason: impl Into<String>, matched: impl Into<String>) -> Self {
+        Self {
+            risk,
+            blocked: false,
+            rule: format!("builtin:{}", rule),
+            reason: reason.into(),
+            matched: matched.into(),
+        }
+    }
+
+    fn from_rule(rule: &CommandRule, matched: impl Into<String>) -> Self {
+        Self {
+            risk: rule.risk.level(),
+            blocked: rule.risk == RuleRisk::Block,
+            rule: rule.name.clone(),
+            reason: rule.reason.clone(),
+            matched: matched.into(),
+        }
+    }
+
+    /// The more severe of the two; the earlier one on a tie.
+    fn worst(self, other: Self) -> Self {
+        if (other.blocked, other.risk) > (self.blocked, self.risk) { other } else { self }
+    }
+}
+
+/// How many levels of `sh -c "sh -c '…'"`, `eval` and the like are followed.
+const MAX_NESTING: usize = 4;
+
+struct Classifier<'r> {
+    rules: &'r [&'r CommandRule],
+    /// The `xargs -I` replace string of the command being looked through,
+    /// which stands for text read from input, like `find -exec`'s `{}`.
+    replace: Option<String>,
+    /// Directory the command runs in, following any `cd` before it; None
+    /// once a `cd` goes somewhere that can't be told from the text.
+    cwd: Option<PathBuf>,
+}
+
+impl<'r> Classifier<'r> {
+    fn script(&self, script: &str, depth: usize) -> Verdict {
+        if depth > MAX_NESTING {
+            return Verdict::builtin("nesting", RiskLevel::High, "commands nested too deeply to follow", script);
+        }
+        let parsed = match shell_syntax::parse(script) {
+            Ok(parsed) => parsed,
+            Err(e) => return Verdict::builtin("parse", RiskLevel::High, format!("cannot parse: {}", e), script),
+        };
+        // Subshell execution — always HIGH (arbitrary code injection vector).
+        // So is defining a function, which can shadow any command after it.
+        if let Some(sub) = parsed.substitutions.first() {
+            return Verdict::builtin("substitution", RiskLevel::High, "runs a command substitution", sub.trim());
+        }
+        if parsed.defines_function {
+            return Verdict::builtin("function", RiskLevel::High, "defines a shell function", script);
+        }
+        let mut cwd = self.cwd.clone();
+        let mut verdict: Option<Verdict> = None;
+        for cmd in &parsed.commands {
+            let here = Classifier { rules: self.rules, replace: self.replace.clone(), cwd: cwd.clone() };
+            let next = here.command(cmd, depth);
+            verdict = Some(match verdict {
+                Some(v) => v.worst(next),
+                None => next,
+            });
+            if let Some((&"cd", args)) = cmd.args().split_first() {
+                cwd = cd_target(args, cwd.as_deref());
+            }
+        }
+        verdict.unwrap_or_else(|| Verdict::builtin("default", RiskLevel::Low, "nothing to run", script))
+    }
+
+    fn command(&self, cmd: &SimpleCommand, depth: usize) -> Verdict {
+        let stdin = cmd.piped_input || cmd.redirects.iter().any(Redirect::feeds_stdin);
+        let mut verdict = self.argv(&cmd.args(), stdin, depth);
+        for assignment in &cmd.assignments {
+            verdict = verdict.worst(assignment_verdict(&assignment.text));
+        }
+        for redirect in &cmd.redirects {
+            let path = redirect.target.text.as_str();
+            if redirect.writes() {
+                verdict = verdict.worst(self.write_target(path));
+            } else if let Some(rule) = redirect.reads().then(|| self.path_rule(path)).flatten() {
+                verdict = verdict.worst(Verdict::from_rule(rule, path));
+            }
+        }
+        verdict
+    }
+
+    /// The first path-only policy rule covering `path`.
+    fn path_rule(&self, path: &str) -> Option<&'r CommandRule> {
+        self.rules.iter().find(|r| r.command.is_empty() && r.args.is_empty() && r.matches_path(path, self.cwd.as_deref())).copied()
+    }
+
+    /// Writing to `path`. Path-only policy rules apply here too.
+    fn write_target(&self, path: &str) -> Verdict {
+        match self.path_rule(path) {
+            Some(rule) => Verdict::from_rule(rule, path),
+            None => Verdict::builtin("redirect", write_target_risk(path), format!("writes to {}", path), path),
+        }
+    }
+
+    /// Verdict for running `argv`. `stdin` is whether the command's input is a
+    /// pipe, file or here-document — a shell or interpreter reading it runs
+    /// that as code.
+    fn argv(&self, argv: &[&str], stdin: bool, depth: usize) -> Verdict {
+        let Some(first) = argv.first() else {
+            return Verdict::builtin("default", RiskLevel::Low, "nothing to run", "");
+        };
+        let line = argv.join(" ");
+        if let Some(rule) = self.rules.iter().find(|r| r.matches_argv(argv, self.cwd.as_deref())) {
+            return Verdict::from_rule(rule, line);
+        }
+        // `$CMD args` runs whatever the variable holds.
+        if first.contains('$') {
+            return Verdict::builtin("dynamic", RiskLevel::High, "command name comes from a variable", line);
+        }
+        // `{rm,-rf,/}` expands into a command line of its own.
+        if has_brace_expansion(first) {
+            return Verdict::builtin("dynamic", RiskLevel::High, "command name comes from a brace expansion", line);
+        }
+        let name = first.rsplit('/').next().unwrap_or(first);
+        let args = &argv[1..];
+        let leaf = |risk: RiskLevel| {
+            let reason = match risk {
+                RiskLevel::Low => format!("`{}` is treated as read-only", name),
+                _ => format!("built-in rule for `{}`", name),
+            };
+            Verdict::builtin(name, risk, reason, line.as_str())
+        };
+
+        match name {
+            // Privilege escalation
+            "sudo" | "doas" | "su" | "pkexec" | "runuser" | "chroot" => leaf(RiskLevel::High),
+
+            // Wrappers — as risky as the command they run
+            "env" => self.env(args, stdin, depth),
+            "nice" => self.wrapped(args, &["-n", "--adjustment"], 0, stdin, depth),
+            "timeout" => self.wrapped(args, &["-s", "--signal", "-k", "--kill-after"], 1, stdin, depth),
+            "stdbuf" => self.wrapped(args, &["-i", "-o", "-e"], 0, stdin, depth),
+            "ionice" => self.wrapped(args, &["-c", "-n", "--class", "--classdata"], 0, stdin, depth),
+            "exec" | "time" => self.wrapped(args, &["-a", "-f", "-o"], 0, stdin, depth),
+            "nohup" | "setsid" | "builtin" | "busybox" => self.wrapped(args, &[], 0, stdin, depth),
+            "command" if args.iter().any(|a| matches!(*a, "-v" | "-V")) => leaf(RiskLevel::Low),
+            "command" => self.wrapped(args, &[], 0, stdin, depth),
+            // Children of xargs get their arguments from its stdin, not the stream itself.
+            "xargs" => Classifier { rules: self.rules, replace: xargs_replace(args), cwd: self.cwd.clone() }
+                .wrapped(args, &["-a", "-d", "-E", "-I", "-L", "-n", "-P", "-s"], 0, false, depth),
+            "watch" => {
+                let start = skip_options(args, &["-n", "--interval", "-d"]);
+                self.script(&args[start..].join(" "), depth + 1)
+            }
+            "eval" => self.script(&args.join(" "), depth + 1),
+            "find" => self.find(args, &line, depth),
+
+            // Shells and interpreters — inline code is HIGH, a script file MEDIUM
+            "sh" | "bash" | "zsh" | "dash" | "ksh" | "mksh" | "ash" | "fish" => {
+                match shell_options(args) {
+                    (start, true, _) => match args.get(start) {
+                        // `xargs sh -c` and `-exec sh -c '{}'` run whatever the input holds.
+                        None => Verdict::builtin(name, RiskLevel::High, "inline script comes from input", line),
+                        Some(script) if self.comes_from_input(script) => {
+                            Verdict::builtin(name, RiskLevel::High, "inline script comes from input", line)
+                        }
+                        Some(script) => self.script(script, depth + 1),
+                    },
+                    _ => leaf(shell_risk(args, stdin)),
+                }
+            }
+            n if n.starts_with("python") || n == "pypy" || n == "pypy3" => {
+                leaf(interpreter_risk(args, &["-c", "-m"], &["-W", "-X"], stdin))
+            }
+            "node" | "nodejs" => {
+                leaf(interpreter_risk(args, &["-e", "--eval", "-p", "--print"], &["-r", "--require", "--import"], stdin))
+            }
+            "ruby" => leaf(interpreter_risk(args, &["-e"], &["-r", "-I"], stdin)),
+            "perl" => leaf(interpreter_risk(args, &["-e", "-E"], &["-I", "-M"], stdin)),
+            "php" => leaf(interpreter_risk(args, &["-r"], &["-d"], stdin)),
+            "lua" | "luajit" => leaf(interpreter_risk(args, &["-e"], &["-l"], stdin)),
+            "deno" | "bun" if matches!(args.first(), Some(&"eval") | Some(&"-e")) => leaf(RiskLevel::High),
+            "deno" | "bun" | "source" | "." => leaf(RiskLevel::Medium),
+            "awk" | "gawk" | "mawk" | "nawk" | "busybox-awk" => leaf(awk_risk(args)),
+
+            // Destructive, irreversible or signal-sending
+            "rm" => leaf(rm_risk(args)),
+            "dd" | "shred" | "fdisk" | "sfdisk" | "parted" | "wipefs" | "killall" | "pkill" => leaf(RiskLevel::High),
+            n if n.starts_with("mkfs") => leaf(RiskLevel::High),
+            "kill" => leaf(kill_risk(args)),
+            "chmod" => leaf(chmod_risk(args).max(write_risk(args))),
+            // Symlinks — always reviewed; they can redirect writes outside the workspace
+            "ln" if has_short_flag(args, 's') || args.contains(&"--symbolic") => leaf(RiskLevel::High),
+            "git" => leaf(git_risk(args)),
+            "docker" | "podman" => leaf(docker_risk(args)),
+            "curl" => option_values(args, 'o', "--output").into_iter()
+                .filter(|p| *p != "-")
+                .map(|p| self.write_target(p))
+                .fold(leaf(curl_risk(args)), Verdict::worst),
+            "wget" => option_values(args, 'O', "--output-document").into_iter()
+                .chain(option_values(args, 'o', "--output-file"))
+                .filter(|p| *p != "-")
+                .map(|p| self.write_target(p))
+                .fold(leaf(RiskLevel::Low), Verdict::worst),
+            "export" | "declare" | "typeset" | "readonly" | "local" => args.iter()
+                .map(|a| assignment_verdict(a))
+                .fold(leaf(RiskLevel::Low), Verdict::worst),
+
+            // Write operations, installs, remote access
+            "tee" => operands(args)
+                .map(|p| self.write_target(p))
+                .fold(leaf(RiskLevel::Medium), Verdict::worst),
+            "sed" if args.iter().any(|a| a.starts_with("-i") || a.starts_with("--in-place")) => leaf(write_risk(args)),
+            "ln" | "mv" | "cp" | "mkdir" | "touch" | "rmdir" | "unlink" | "truncate" | "install" | "chown"
+            | "chgrp" | "rsync" | "scp" => leaf(write_risk(args)),
+            "sftp" | "ssh" | "base64" | "npx" | "pnpx" | "bunx" => leaf(RiskLevel::Medium),
+            "npm" | "pnpm" | "yarn" | "pip" | "pip3" | "pipx" | "cargo" | "gem" | "brew" | "apt"
+            | "apt-get" | "dnf" | "yum" | "apk" | "pacman" => leaf(package_risk(name, args)),
+
+            // Read-only, inspection, safe queries
+            _ => Verdict::builtin("default", RiskLevel::Low, format!("no rule for `{}`; treated as read-only", name), line),
+        }
+    }
+
+    /// Whether an inline script has text read from input pasted into it.
+    fn comes_from_input(&self, script: &str) -> bool {
+        script.contains("{}") || self.replace.as_deref().is_some_and(|r| script.contains(r))
+    }
+
+    /// A wrapper's own options, then `positional` fixed arguments (a duration),
+    /// then the command it runs.
+    fn wrapped(&self, args: &[&str], takes_value: &[&str], positional: usize, stdin: bool, depth: usize) -> Verdict {
+        let start = (skip_options(args, takes_value) + positional).min(args.len());
+        self.argv(&args[start..], stdin, depth)
+    }
+
+    /// `env [-i] [-u NAME] [NAME=value…] [-S 'cmd args'] command…`
+    fn env(&self, args: &[&str], stdin: bool, depth: usize) -> Verdict {
+        let mut assignments: Option<Verdict> = None;
+        let mut i = 0;
+        while let Some(arg) = args.get(i) {
+            let split = match *arg {
+                "-S" | "--split-string" => args.get(i + 1).copied(),
+                a if a.starts_with("--split-string=") => a.strip_prefix("--split-string="),
+                a if a.starts_with("-S") => a.strip_prefix("-S"),
+                _ => None,
+            };
+            if let Some(line) = split {
+                // env splits the string into words itself, then appends the rest.
+                let skip = if matches!(*arg, "-S" | "--split-string") { 2 } else { 1 };
+                let mut argv = match shell_syntax::split_words(line) {
+                    Ok(words) => words,
+                    Err(e) => return Verdict::builtin("parse", RiskLevel::High, format!("cannot parse: {}", e), line),
+                };
+                argv.extend(args[(i + skip).min(args.len())..].iter().map(|a| a.to_string()));
+                let argv: Vec<&str> = argv.iter().map(String::as_str).collect();
+                let verdict = self.argv(&argv, stdin, depth);
+                return assignments.map_or(verdict.clone(), |a| a.worst(verdict));
+            }
+            match *arg {
+                "-u" | "--unset" | "-C" | "--chdir" => i += 2,
+                "--" => {
+                    i += 1;
+                    break;
+                }
+                a if a.starts_with('-') => i += 1,
+                a if a.contains('=') => {
+                    let verdict = assignment_verdict(a);
+                    assignments = Some(match assignments {
+                        Some(prev) => prev.worst(verdict),
+                        None => verdict,
+                    });
+                    i += 1;
+                }
+                _ => break,
+            }
+        }
+        let verdict = self.argv(&args[i.min(args.len())..], stdin, depth);
+        match assignments {
+            Some(a) => verdict.worst(a),
+            None => verdict,
+        }
+    }
+
+    /// `find -delete` is HIGH; `-exec`/`-ok` commands are classified like any other.
+    fn find(&self, args: &[&str], line: &str, depth: usize) -> Verdict {
+        let mut verdict = Verdict::builtin("find", RiskLevel::Low, "`find` is treated as read-only", line);
+        let mut i = 0;
+        while i < args.len() {
+            match args[i] {
+                "-delete" => {
+                    verdict = verdict.worst(Verdict::builtin("find", RiskLevel::High, "`find -delete` removes every match", line));
+                }
+                "-exec" | "-execdir" | "-ok" | "-okdir" => {
+                    let start = i + 1;
+                    let end = args[start..].iter()
+                        .position(|a| *a == ";" || *a == "+")
+                        .map_or(args.len(), |p| start + p);
+                    verdict = verdict.worst(self.argv(&args[start..end], false, depth));
+                    i = end;
+                }
+                "-fprint" | "-fprint0" | "-fprintf" | "-fls" => {
+                    verdict = verdict.worst(self.write_target(args.get(i + 1).copied().unwrap_or_default()));
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        verdict
+    }
+}
+
+/// `NAME=value` that changes which code later commands load or run is HIGH.
+fn assignment_verdict(assignment: &str) -> Verdict {
+    let name = assignment.split('=').next().unwrap_or_default();
+    let risk = match name {
+        "PATH" | "LD_PRELOAD" | "LD_LIBRARY_PATH" | "LD_AUDIT" | "DYLD_INSERT_LIBRARIES"
+        | "DYLD_LIBRARY_PATH" | "BASH_ENV" | "ENV" | "GIT_SSH_COMMAND" | "GIT_EXEC_PATH" => RiskLevel::High,
+        _ => RiskLevel::Low,
+    };
+    Verdict::builtin("assignment", risk, format!("sets {}", name), assignment)
+}
+
+/// Writing to `path`: MEDIUM, or HIGH for devices, system config and Argus's own state.
+fn write_target_risk(path: &str) -> RiskLevel {
+    const HARMLESS: &[&str] = &["/dev/null", "/dev/stdout", "/dev/stderr", "/dev/tty"];
+    if HARMLESS.contains(&path) || path.starts_with("/dev/fd/") {
+        RiskLevel::Low
+    } else if ["/dev/", "/etc/", "/sys/", "/proc/", "/boot/"].iter().any(|p| path.starts_with(p))
+        || touches_argus_home(path)
+    {
+        RiskLevel::High
+    } else {
+        RiskLevel::Medium
+    }
+}
+
+fn touches_argus_home(path: &str) -> bool {
+    path.starts_with("~/.argus") || path.contains("/.argus")
+}
+
+/// A command that modifies the files it's given: MEDIUM, or HIGH when any of
+/// them is Argus's own state.
+fn write_risk(args: &[&str]) -> RiskLevel {
+    if operands(args).any(touches_argus_home) { RiskLevel::High } else { RiskLevel::Medium }
+}
+
+/// `{a,b}` or `{1..3}` somewhere in `word`, which the shell expands into
+/// several words.
+fn has_brace_expansion(word: &str) -> bool {
+    word.split('{').skip(1).any(|rest| {
+        rest.split_once('}').is_some_and(|(inner, _)| inner.contains(',') || inner.contains(".."))
+    })
+}
+
+/// Index of the first operand in `args`, skipping options (and the values of
+/// those listed in `takes_value`) and a `--` terminator.
+fn skip_options(args: &[&str], takes_value: &[&str]) -> usize {
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        if *arg == "--" {
+            return i + 1;
+        }
+        if !arg.starts_with('-') || *arg == "-" {
+            break;
+        }
+        i += if takes_value.contains(arg) { 2 } else { 1 };
+    }
+    i.min(args.len())
+}
 
-    for pattern in medium_patterns {
-        if cmd.contains(pattern) {
-            return RiskLevel::Medium;
+/// Where `cd args` leaves a shell that was in `cwd`; None when that can't
+/// be told from the text (`cd -`, `cd "$DIR"`, an unknown starting point).
+fn cd_target(args: &[&str], cwd: Option<&Path>) -> Option<PathBuf> {
+    match args.get(skip_options(args, &[])) {
+        None | Some(&"~") => dirs::home_dir(),
+        Some(&"-") => None,
+        Some(dir) => {
+            let dir = normalize_path(dir, cwd);
+            (dir.starts_with('/') && !dir.contains(['$', '*', '?', '['])).then(|| PathBuf::from(dir))
         }
     }
+}
 
-    // Pipes with write destinations
-    if cmd.contains('|') && (cmd.contains("tee") || cmd.contains("write")) {
+/// Non-option arguments.
+pub(crate) fn operands<'a>(args: &'a [&'a str]) -> impl Iterator<Item = &'a str> {
+    let end = args.iter().position(|a| *a == "--").unwrap_or(args.len());
+    let after = args.get(end + 1..).unwrap_or_default();
+    args[..end].iter().filter(|a| !a.starts_with('-') || **a == "-").chain(after).copied()
+}
+
+/// Values given to an option, in any of its spellings: `-o v`, `-ov`,
+/// `-so v`, `--output v`, `--output=v`. Output paths are classified as
+/// writes wherever they're spelled.
+fn option_values<'a>(args: &[&'a str], short: char, long: &str) -> Vec<&'a str> {
+    let mut values = Vec::new();
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        let next = args.get(i + 1).copied();
+        if *arg == "--" {
+            break;
+        } else if *arg == long {
+            values.extend(next);
+            i += 1;
+        } else if let Some(value) = arg.strip_prefix(long).and_then(|r| r.strip_prefix('=')) {
+            values.push(value);
+        } else if arg.len() > 1 && arg.starts_with('-') && !arg.starts_with("--") {
+            if let Some(pos) = arg[1..].find(short) {
+                match &arg[1 + pos + short.len_utf8()..] {
+                    "" => {
+                        values.extend(next);
+                        i += 1;
+                    }
+                    value => values.push(value),
+                }
+            }
+        }
+        i += 1;
+    }
+    values
+}
+
+/// Whether a short-option group (`-rf`, `-s`) before `--` contains `flag`.
+fn has_short_flag(args: &[&str], flag: char) -> bool {
+    args.iter()
+        .take_while(|a| **a != "--")
+        .any(|a| a.len() > 1 && a.starts_with('-') && !a.starts_with("--") && a[1..].contains(flag))
+}
+
+/// Where a shell's options end, and whether they include `-c` (inline
+/// script) or `-s`/`-` (commands from stdin).
+fn shell_options(args: &[&str]) -> (usize, bool, bool) {
+    let (mut inline, mut from_stdin) = (false, false);
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        match *arg {
+            "-" | "--" => {
+                from_stdin |= *arg == "-";
+                i += 1;
+                break;
+            }
+            "-o" | "+o" | "-O" | "+O" | "--rcfile" | "--init-file" => i += 2,
+            a if a.starts_with("--") => i += 1,
+            a if a.len() > 1 && (a.starts_with('-') || a.starts_with('+')) => {
+                if a.starts_with('-') {
+                    inline |= a.contains('c');
+                    from_stdin |= a.contains('s');
+                }
+                i += 1;
+            }
+            _ => break,
+        }
+    }
+    (i, inline, from_stdin)
+}
+
+/// The replace string of `xargs -I R`, `-iR` or `--replace[=R]`.
+fn xargs_replace(args: &[&str]) -> Option<String> {
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        match *arg {
+            "-I" => return args.get(i + 1).map(|r| r.to_string()),
+            "-i" | "--replace" => return Some("{}".to_string()),
+            a if !a.starts_with('-') || a == "--" => return None,
+            a => {
+                let value = a.strip_prefix("-I")
+                    .or_else(|| a.strip_prefix("-i"))
+                    .or_else(|| a.strip_prefix("--replace="));
+                if let Some(r) = value.filter(|r| !r.is_empty()) {
+                    return Some(r.to_string());
+                }
+                i += if matches!(a, "-a" | "-d" | "-E" | "-L" | "-n" | "-P" | "-s") { 2 } else { 1 };
+            }
+        }
+    }
+    None
+}
+
+/// A shell running a script file is MEDIUM; commands read from stdin
+/// (`curl … | bash`) are HIGH.
+fn shell_risk(args: &[&str], stdin: bool) -> RiskLevel {
+    let (start, _, from_stdin) = shell_options(args);
+    match args.get(start) {
+        Some(_) if !from_stdin => RiskLevel::Medium,
+        _ if stdin => RiskLevel::High,
+        _ => RiskLevel::Medium,
+    }
+}
+
+/// Interpreter run with inline code (`-c`, `-e`, or a program on stdin) is
+/// HIGH; running a script file is MEDIUM. Short options may be grouped (`-Bc`).
+fn interpreter_risk(args: &[&str], inline: &[&str], takes_value: &[&str], stdin: bool) -> RiskLevel {
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        match *arg {
+            "-" => return RiskLevel::High,
+            "--" => {
+                i += 1;
+                break;
+            }
+            a if a.starts_with("--") => {
+                let flag = a.split('=').next().unwrap_or(a);
+                if inline.contains(&flag) {
+                    return RiskLevel::High;
+                }
+                i += if takes_value.contains(&a) { 2 } else { 1 };
+            }
+            a if a.len() > 1 && a.starts_with('-') => {
+                for (pos, c) in a.char_indices().skip(1) {
+                    let flag = format!("-{}", c);
+                    if inline.contains(&flag.as_str()) {
+                        return RiskLevel::High;
+                    }
+                    if takes_value.contains(&flag.as_str()) {
+                        // `-W ignore` takes the next argument; `-Wignore` doesn't.
+                        if pos + c.len_utf8() == a.len() {
+                            i += 1;
+                        }
+                        break;
+                    }
+                }
+                i += 1;
+            }
+            _ => break,
+        }
+    }
+    match args.get(i) {
+        Some(_) => RiskLevel::Medium,
+        None if stdin => RiskLevel::High,
+        None => RiskLevel::Medium,
+    }
+}
+
+/// awk runs its first operand as a program, which can call `system()` and
+/// pipe to commands: HIGH, like any inline code. A program file (`-f`) is
+/// MEDIUM; loading an extension (`-l`) is HIGH.
+fn awk_risk(args: &[&str]) -> RiskLevel {
+    let mut i = 0;
+    let mut from_file = false;
+    while let Some(arg) = args.get(i) {
+        match *arg {
+            "--" => break,
+            "-f" | "--file" => {
+                from_file = true;
+                i += 2;
+            }
+            "-e" | "--source" | "-l" | "--load" => return RiskLevel::High,
+            "-F" | "-v" | "-i" | "--include" | "--field-separator" | "--assign" => i += 2,
+            a if a.starts_with("--source=") || a.starts_with("--load=") || a.starts_with("-l") => return RiskLevel::High,
+            a if a.starts_with("--file=") || a.starts_with("-f") => {
+                from_file = true;
+                i += 1;
+            }
+            a if a.len() > 1 && a.starts_with('-') => i += 1,
+            _ => break,
+        }
+    }
+    if from_file { RiskLevel::Medium } else { RiskLevel::High }
+}
+
+/// Recursive deletes and anything aimed at `/` or Argus's own state are HIGH.
+fn rm_risk(args: &[&str]) -> RiskLevel {
+    let recursive = has_short_flag(args, 'r') || has_short_flag(args, 'R') || args.contains(&"--recursive");
+    let dangerous_target = operands(args).any(|p| p == "/" || p == "/*" || touches_argus_home(p));
+    if recursive || dangerous_target { RiskLevel::High } else { RiskLevel::Medium }
+}
+
+/// SIGKILL (`-9`, `-KILL`, `-s KILL`) is HIGH; other signals MEDIUM.
+fn kill_risk(args: &[&str]) -> RiskLevel {
+    let is_kill = |sig: &str| {
+        let sig = sig.strip_prefix("SIG").unwrap_or(sig);
+        sig == "9" || sig.eq_ignore_ascii_case("KILL")
+    };
+    let forced = args.iter().enumerate().any(|(i, arg)| match *arg {
+        "-s" | "-n" | "--signal" => args.get(i + 1).is_some_and(|s| is_kill(s)),
+        a => a.strip_prefix('-').is_some_and(is_kill),
+    });
+    if forced { RiskLevel::High } else { RiskLevel::Medium }
+}
+
+/// World-writable or setuid/setgid modes are HIGH; other mode changes MEDIUM.
+fn chmod_risk(args: &[&str]) -> RiskLevel {
+    // Symbolic modes can start with '-' (`chmod -w f`), so only known flags are skipped.
+    let is_flag = |a: &&&str| a.starts_with("--") || (a.len() > 1 && a.starts_with('-') && a[1..].chars().all(|c| "RcfvhHLP".contains(c)));
+    let Some(mode) = args.iter().find(|a| !is_flag(a)) else {
         return RiskLevel::Medium;
+    };
+    let risky = if !mode.is_empty() && mode.chars().all(|c| c.is_digit(8)) {
+        let digits: Vec<u32> = mode.chars().filter_map(|c| c.to_digit(8)).collect();
+        let world_writable = digits.last().is_some_and(|d| d & 2 != 0);
+        let set_id = digits.len() == 4 && digits[0] & 6 != 0;
+        world_writable || set_id
+    } else {
+        mode.split(',').any(|clause| match clause.split_once(['+', '=']) {
+            Some((who, perms)) => perms.contains('s') || ((who.contains('a') || who.contains('o')) && perms.contains('w')),
+            None => false,
+        })
+    };
+    if risky { RiskLevel::High } else { RiskLevel::Medium }
+}
+
+fn git_risk(args: &[&str]) -> RiskLevel {
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        match *arg {
+            // `-c core.sshCommand=…` or `-c alias.x=!cmd` runs arbitrary code.
+            "-c" => return RiskLevel::High,
+            "-C" | "--git-dir" | "--work-tree" | "--namespace" => i += 2,
+            a if a.starts_with('-') => i += 1,
+            _ => break,
+        }
+    }
+    let Some(sub) = args.get(i) else {
+        return RiskLevel::Low;
+    };
+    let rest = &args[i + 1..];
+    match *sub {
+        "push" => {
+            let forced = has_short_flag(rest, 'f')
+                || rest.iter().any(|a| a.starts_with("--force") || a.starts_with('+'));
+            if forced { RiskLevel::High } else { RiskLevel::Medium }
+        }
+        "config" => {
+            let global = rest.iter().any(|a| matches!(*a, "--global" | "--system"));
+            // core.* and alias.* can make later git commands run arbitrary programs.
+            let exec_key = rest.iter().any(|a| {
+                let key = a.to_ascii_lowercase();
+                key.starts_with("core.") || key.starts_with("alias.")
+            });
+            let read = rest.iter().any(|a| matches!(*a, "-l" | "--list") || a.starts_with("--get"));
+            if global || exec_key { RiskLevel::High } else if read { RiskLevel::Low } else { RiskLevel::Medium }
+        }
+        "branch" if has_short_flag(rest, 'd') || has_short_flag(rest, 'D') || has_short_flag(rest, 'm')
+            || has_short_flag(rest, 'M') || rest.contains(&"--delete") => RiskLevel::Medium,
+        "commit" | "reset" | "checkout" | "switch" | "restore" | "merge" | "rebase" | "pull"
+        | "cherry-pick" | "revert" | "am" | "apply" | "stash" | "rm" | "mv" | "clean" | "tag" => RiskLevel::Medium,
+        _ => RiskLevel::Low,
+    }
+}
+
+fn docker_risk(args: &[&str]) -> RiskLevel {
+    let start = skip_options(args, &["-H", "--host", "--context", "--config", "-l", "--log-level"]);
+    let Some(sub) = args.get(start) else {
+        return RiskLevel::Low;
+    };
+    let rest = &args[start + 1..];
+    let forced = has_short_flag(rest, 'f') || rest.contains(&"--force");
+    match *sub {
+        // `docker container rm -f`, `docker system prune`, …
+        "container" | "image" | "volume" | "network" | "system" | "builder" => docker_risk(rest),
+        "prune" => RiskLevel::High,
+        "rm" | "rmi" if forced => RiskLevel::High,
+        "run" | "create" | "exec" if rest.contains(&"--privileged") => RiskLevel::High,
+        "run" | "create" | "exec" | "build" | "stop" | "start" | "restart" | "kill" | "rm" | "rmi"
+        | "pull" | "push" | "compose" | "load" | "commit" | "tag" => RiskLevel::Medium,
+        _ => RiskLevel::Low,
+    }
+}
+
+/// Requests that change remote state, upload data or write files are MEDIUM.
+fn curl_risk(args: &[&str]) -> RiskLevel {
+    let mut risk = RiskLevel::Low;
+    for (i, arg) in args.iter().enumerate() {
+        let next = args.get(i + 1).copied().unwrap_or_default();
+        let method = match *arg {
+            "-X" | "--request" => Some(next),
+            a => a.strip_prefix("--request=").or_else(|| a.strip_prefix("-X").filter(|m| !m.is_empty())),
+        };
+        if method.is_some_and(|m| matches!(m.to_ascii_uppercase().as_str(), "POST" | "PUT" | "DELETE" | "PATCH")) {
+            risk = risk.max(RiskLevel::Medium);
+        }
+        match *arg {
+            "-d" | "-F" | "-T" | "-O" | "--form" | "--upload-file" | "--remote-name" => risk = risk.max(RiskLevel::Medium),
+            a if a.starts_with("--data") => risk = risk.max(RiskLevel::Medium),
+            _ => {}
+        }
     }
+    risk
+}
 
-    // LOW: Read-only, inspection, safe queries
-    RiskLevel::Low
+/// Installing, removing or upgrading packages is MEDIUM.
+fn package_risk(manager: &str, args: &[&str]) -> RiskLevel {
+    const VERBS: &[&str] = &[
+        "install", "i", "ci", "add", "uninstall", "remove", "rm", "un", "purge", "upgrade", "update",
+        "reinstall", "link", "publish", "del",
+    ];
+    let Some(sub) = args.get(skip_options(args, &[])) else {
+        // Bare `yarn` installs.
+        return if manager == "yarn" { RiskLevel::Medium } else { RiskLevel::Low };
+    };
+    let pacman_sync = manager == "pacman" && (has_short_flag(args, 'S') || has_short_flag(args, 'R') || has_short_flag(args, 'U'));
+    if VERBS.contains(sub) || pacman_sync { RiskLevel::Medium } else { RiskLevel::Low }
 }
 
 // ── Permission prompter trait ─────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
+/// A tool call waiting for a human. Built by `ShellPolicy::approval_request`.
+#[derive(Debug, Clone, Serialize)]
 pub struct PermissionRequest {
+    /// Unique per request, so an answer finds its way back.
+    pub id: String,
+    /// `shell`, or a tool the policy file lists in `approval_tools`.
+    pub tool: String,
+    /// The shell command, or the tool's arguments as JSON.
     pub command: String,
     pub risk: RiskLevel,
     pub reason: String,
+    /// How long a human has to answer.
+    pub timeout_secs: u64,
+    /// Whether no answer within `timeout_secs` approves the call.
+    pub approve_on_timeout: bool,
 }
 
-#[derive(Debug, Clone)]
+impl PermissionRequest {
+    /// The decision applied when nobody answers in time.
+    pub fn timeout_decision(&self) -> PermissionDecision {
+        if self.approve_on_timeout {
+            PermissionDecision::Allow
+        } else {
+            PermissionDecision::Deny {
+                reason: format!("Approval timeout — no response within {} seconds", self.timeout_secs),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PermissionDecision {
     Allow,
+    /// Run this instead — a rewritten command, or replacement JSON arguments.
+    Edit { command: String },
     Deny { reason: String },
 }
 
+impl PermissionDecision {
+    /// Audit action this decision is logged under.
+    pub fn audit_action(&self) -> &'static str {
+        match self {
+            Self::Allow       => "approval_allowed",
+            Self::Edit { .. } => "approval_edited",
+            Self::Deny { .. } => "approval_denied",
+        }
+    }
+}
+
+pub type PromptFuture<'a> = Pin<Box<dyn Future<Output = PermissionDecision> + Send + 'a>>;
+
 /// Interchangeable approval mechanism.
 /// Implement this trait to plug in Telegram, WebSocket, terminal, etc.
+/// The turn waits on the returned future; implementations answer with
+/// `request.timeout_decision()` once `request.timeout_secs` have passed.
 pub trait PermissionPrompter: Send + Sync {
-    fn prompt(&self, request: &PermissionRequest) -> PermissionDecision;
+    fn prompt<'a>(&'a self, request: &'a PermissionRequest) -> PromptFuture<'a>;
 }
 
 /// Always approve — used in testing or dev mode
 pub struct AlwaysAllow;
 impl PermissionPrompter for AlwaysAllow {
-    fn prompt(&self, _req: &PermissionRequest) -> PermissionDecision {
-        PermissionDecision::Allow
+    fn prompt<'a>(&'a self, _req: &'a PermissionRequest) -> PromptFuture<'a> {
+        Box::pin(async { PermissionDecision::Allow })
     }
 }
 
 /// Always deny — used in locked/read-only mode
 pub struct AlwaysDeny;
 impl PermissionPrompter for AlwaysDeny {
-    fn prompt(&self, req: &PermissionRequest) -> PermissionDecision {
-        PermissionDecision::Deny {
-            reason: format!("Blocked: {} risk command denied in current mode", req.risk.as_str()),
-        }
+    fn prompt<'a>(&'a self, req: &'a PermissionRequest) -> PromptFuture<'a> {
+        Box::pin(async move {
+            PermissionDecision::Deny {
+                reason: format!("Blocked: {} risk command denied in current mode", req.risk.as_str()),
+            }
+        })
     }
 }
 
 // ── Sonnet guard ─────────────────────────────────────────────────────────────
 
 /// The verdict Sonnet returns when reviewing a HIGH risk command.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SonnetVerdict {
     /// Execute as-is.
     Approve,
@@ -221,152 +876,301 @@ pub enum SonnetVerdict {
     Block(String),
 }
 
-/// Calls Claude Sonnet via OpenRouter to review HIGH-risk shell commands.
-/// Non-blocking — resolves in ~1-2 seconds, no human in the loop.
+impl SonnetVerdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Approve    => "approve",
+            Self::Rewrite(_) => "rewrite",
+            Self::Block(_)   => "block",
+        }
+    }
+
+    /// Audit chain action type.
+    pub fn audit_action(&self) -> &'static str {
+        match self {
+            Self::Approve    => "guard_approved",
+            Self::Rewrite(_) => "guard_rewritten",
+            Self::Block(_)   => "guard_blocked",
+        }
+    }
+}
+
+/// Where a verdict came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerdictSource {
+    Model,
+    Cache,
+    /// Sonnet couldn't be asked; `guard_unreachable` decided.
+    Unreachable,
+}
+
+impl VerdictSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Model       => "model",
+            Self::Cache       => "cache",
+            Self::Unreachable => "unreachable",
+        }
+    }
+}
+
+/// A verdict with Sonnet's reason for it.
+#[derive(Debug, Clone)]
+pub struct GuardReview {
+    pub verdict: SonnetVerdict,
+    pub reason: String,
+    pub source: VerdictSource,
+}
+
+/// Asks the `sonnet` slot to review HIGH-risk shell commands, through the
+/// agent's provider and usage ledger. Non-blocking — resolves in ~1-2
+/// seconds, no human in the loop.
+///
+/// Verdicts are reused for `guard_cache_secs` per normalised command, and
+/// each one is audited and, with a `log`, kept for `argus guard history`.
 pub struct SonnetGuard {
-    pub api_key: String,
-    pub api_url: String,
+    pub log: Option<Arc<dyn GuardLog>>,
+    cache: Mutex<HashMap<String, (Instant, GuardReview)>>,
 }
 
 impl SonnetGuard {
-    pub async fn review(&self, command: &str) -> SonnetVerdict {
-        let prompt = format!(
-            "You are a shell command safety reviewer for an AI agent (Argus).\n\
-             Review this HIGH-risk shell command and respond with exactly one of:\n\
-             - APPROVE — if it is safe to execute as written\n\
-             - REWRITE: <new command> — if a safer version achieves the same goal\n\
-             - BLOCK: <reason> — if it should not execute at all\n\n\
-             Command: {}\n\n\
-             Respond with only the verdict line, nothing else.",
-            command
+    pub fn new(log: Option<Arc<dyn GuardLog>>) -> Self {
+        Self { log, cache: Mutex::new(HashMap::new()) }
+    }
+
+    /// Review `command` for `agent` under `policy`, asking through `config`
+    /// and recording the verdict in its audit chain and the guard log.
+    pub async fn review(
+        &self,
+        command: &str,
+        policy: &ShellPolicy,
+        config: &AgentConfig,
+        agent: &str,
+    ) -> GuardReview {
+        let review = self.decide(command, policy, config).await;
+        eprintln!(
+            "[sonnet-guard] {} ({}) {} — {}",
+            review.verdict.as_str(), review.source.as_str(), command, review.reason
         );
 
-        let body = serde_json::json!({
-            "model": "anthropic/claude-sonnet-4-5",
-            "max_tokens": 200,
-            "temperature": 0.0,
-            "messages": [{ "role": "user", "content": prompt }]
+        let rewrite = match &review.verdict {
+            SonnetVerdict::Rewrite(safer) => Some(safer.clone()),
+            _ => None,
+        };
+        let audit_id = config.audit.as_deref().and_then(|audit| {
+            audit
+                .append(
+                    agent,
+                    review.verdict.audit_action(),
+                    Some("shell"),
+                    Some(command),
+                    Some(rewrite.as_deref().unwrap_or(&review.reason)),
+                )
+                .map_err(|e| eprintln!("[sonnet-guard] audit append failed: {}", e))
+                .ok()
         });
+        if let Some(log) = &self.log {
+            let record = GuardRecord {
+                command: command.to_string(),
+                verdict: review.verdict.as_str().to_string(),
+                rewrite,
+                reason: review.reason.clone(),
+                source: review.source.as_str().to_string(),
+                agent: agent.to_string(),
+                surface: policy.surface().map(|s| s.as_str().to_string()),
+                audit_id,
+                created_at: None,
+            };
+            if let Err(e) = log.record(&record) {
+                eprintln!("[sonnet-guard] failed to log verdict: {}", e);
+            }
+        }
+        review
+    }
 
-        let client = reqwest::Client::new();
-        let resp = client
-            .post(&self.api_url)
-            .bearer_auth(&self.api_key)
-            .header("HTTP-Referer", "https://argus.local")
-            .json(&body)
-            .timeout(std::time::Duration::from_secs(15))
-            .send()
-            .await;
+    /// A cached verdict, Sonnet's, or the policy's when Sonnet is unreachable.
+    async fn decide(&self, command: &str, policy: &ShellPolicy, config: &AgentConfig) -> GuardReview {
+        let key = guard::normalize(command);
+        let ttl = Duration::from_secs(policy.guard_cache_secs);
+        if let Some((at, cached)) = self.cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            if at.elapsed() < ttl {
+                return GuardReview { source: VerdictSource::Cache, ..cached.clone() };
+            }
+        }
 
-        let text = match resp {
+        let text = match self.ask(command, config).await {
+            Ok(text) => text,
+            // Not cached: the next command retries.
+            Err(e) if policy.guard_fail_open => {
+                return GuardReview {
+                    verdict: SonnetVerdict::Approve,
+                    reason: format!("Sonnet review unavailable ({}), allowed by guard_unreachable policy", e),
+                    source: VerdictSource::Unreachable,
+                };
+            }
             Err(e) => {
-                eprintln!("[sonnet-guard] request failed: {}", e);
-                return SonnetVerdict::Block(format!("Sonnet review unavailable: {}", e));
+                let reason = format!("Sonnet review unavailable: {}", e);
+                return GuardReview {
+                    verdict: SonnetVerdict::Block(reason.clone()),
+                    reason,
+                    source: VerdictSource::Unreachable,
+                };
             }
-            Ok(r) => match r.json::<serde_json::Value>().await {
-                Err(e) => {
-                    eprintln!("[sonnet-guard] parse failed: {}", e);
-                    return SonnetVerdict::Block("Sonnet review parse error".to_string());
-                }
-                Ok(v) => v["choices"][0]["message"]["content"]
-                    .as_str()
-                    .unwrap_or("")
-                    .trim()
-                    .to_string(),
-            },
         };
 
-        if text.to_uppercase().starts_with("APPROVE") {
-            SonnetVerdict::Approve
-        } else if let Some(rest) = text.strip_prefix("REWRITE:").or_else(|| text.strip_prefix("Rewrite:")) {
-            SonnetVerdict::Rewrite(rest.trim().to_string())
-        } else if let Some(rest) = text.strip_prefix("BLOCK:").or_else(|| text.strip_prefix("Block:")) {
-            SonnetVerdict::Block(rest.trim().to_string())
-        } else {
-            // Unparseable response — fail safe
-            eprintln!("[sonnet-guard] unexpected verdict: {}", text);
-            SonnetVerdict::Block(format!("Sonnet returned unrecognised verdict: {}", text))
+        let (verdict, reason) = parse_verdict(&text);
+        let review = GuardReview { verdict, reason, source: VerdictSource::Model };
+        if !ttl.is_zero() {
+            let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            cache.retain(|_, (at, _)| at.elapsed() < ttl);
+            cache.insert(key, (Instant::now(), review.clone()));
         }
+        review
+    }
+
+    /// Sonnet's reply, or why there isn't one.
+    async fn ask(&self, command: &str, config: &AgentConfig) -> Result<String, String> {
+        let prompt = format!(
+            "You are a shell command safety reviewer for an AI agent (Argus).\n\
+             Review this HIGH-risk shell command and respond with exactly two lines.\n\
+             The first is one of:\n\
+             - APPROVE — if it is safe to execute as written\n\
+             - REWRITE: <new command> — if a safer version achieves the same goal\n\
+             - BLOCK — if it should not execute at all\n\
+             The second is REASON: <one sentence on why>.\n\n\
+             Command: {}\n\n\
+             Respond with only those two lines, nothing else.",
+            command
+        );
+
+        let messages = vec![serde_json::json!({ "role": "user", "content": prompt })];
+        let http = reqwest::Client::new();
+        tokio::time::timeout(
+            Duration::from_secs(15),
+            crate::agent::side_completion(config, &http, "sonnet", messages, 0.0, 200),
+        )
+        .await
+        .map_err(|_| "request timed out".to_string())?
+    }
+}
+
+/// Split Sonnet's reply into a verdict and its reason. Anything that isn't
+/// a recognisable verdict blocks — fail safe.
+fn parse_verdict(text: &str) -> (SonnetVerdict, String) {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    let first = lines.next().unwrap_or("");
+    let reason = lines
+        .find_map(|l| strip_prefix_ci(l, "REASON:"))
+        .map(|r| r.trim().to_string());
+
+    if strip_prefix_ci(first, "APPROVE").is_some() {
+        (SonnetVerdict::Approve, reason.unwrap_or_else(|| "approved as written".to_string()))
+    } else if let Some(safer) = strip_prefix_ci(first, "REWRITE:").map(str::trim).filter(|s| !s.is_empty()) {
+        (SonnetVerdict::Rewrite(safer.to_string()), reason.unwrap_or_else(|| "rewritten to a safer form".to_string()))
+    } else if let Some(rest) = strip_prefix_ci(first, "BLOCK") {
+        // Older replies put the reason on the verdict line: `BLOCK: <reason>`.
+        let inline = rest.trim_start_matches(':').trim();
+        let reason = reason
+            .or_else(|| (!inline.is_empty()).then(|| inline.to_string()))
+            .unwrap_or_else(|| "blocked by review".to_string());
+        (SonnetVerdict::Block(reason.clone()), reason)
+    } else {
+        eprintln!("[sonnet-guard] unexpected verdict: {}", text);
+        let reason = format!("Sonnet returned unrecognised verdict: {}", text);
+        (SonnetVerdict::Block(reason.clone()), reason)
     }
 }
 
-/// Production prompter — sends Telegram message and polls for /approve or /deny.
-/// Uses std::thread::spawn for all HTTP calls so reqwest::blocking doesn't
-/// panic inside the tokio runtime.
+fn strip_prefix_ci<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    s.get(..prefix.len())
+        .filter(|head| head.eq_ignore_ascii_case(prefix))
+        .map(|_| &s[prefix.len()..])
+}
+
+/// Telegram prompter — sends the request and polls for /approve, /deny or
+/// `/edit <command>`. Polling runs on the blocking pool: reqwest::blocking
+/// panics inside the tokio runtime.
 pub struct TelegramPrompter {
     pub bot_token: String,
     pub chat_id: i64,
 }
 
 impl PermissionPrompter for TelegramPrompter {
-    fn prompt(&self, request: &PermissionRequest) -> PermissionDecision {
-        let message = format!(
-            "⚠️ ARGUS HIGH RISK COMMAND\n\nCommand: `{}`\nRisk: {}\nReason: {}\n\nReply /approve or /deny",
-            request.command,
-            request.risk.as_str(),
-            request.reason
-        );
-
-        let send_url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
-        let updates_url = format!("https://api.telegram.org/bot{}/getUpdates", self.bot_token);
+    fn prompt<'a>(&'a self, request: &'a PermissionRequest) -> PromptFuture<'a> {
+        let bot_token = self.bot_token.clone();
         let chat_id = self.chat_id;
+        let request = request.clone();
+        Box::pin(async move {
+            let fallback = request.timeout_decision();
+            tokio::task::spawn_blocking(move || telegram_prompt(&bot_token, chat_id, &request))
+                .await
+                .unwrap_or(fallback)
+        })
+    }
+}
 
-        let body = serde_json::json!({
+fn telegram_prompt(bot_token: &str, chat_id: i64, request: &PermissionRequest) -> PermissionDecision {
+    let message = format!(
+        "⚠️ ARGUS APPROVAL NEEDED\n\nTool: {}\nCommand: `{}`\nRisk: {}\nReason: {}\n\nReply /approve, /deny or /edit <command>",
+        request.tool,
+        request.command,
+        request.risk.as_str(),
+        request.reason
+    );
+
+    let send_url = format!("https://api.telegram.org/bot{}/sendMessage", bot_token);
+    let updates_url = format!("https://api.telegram.org/bot{}/getUpdates", bot_token);
+    let client = reqwest::blocking::Client::new();
+
+    let _ = client
+        .post(&send_url)
+        .json(&serde_json::json!({
             "chat_id": chat_id,
             "text": message,
             "parse_mode": "Markdown"
-        });
+        }))
+        .timeout(std::time::Duration::from_secs(5))
+        .send();
 
-        // Send notification in its own thread — reqwest::blocking can't run inside a tokio runtime
-        let _ = std::thread::spawn(move || {
-            let _ = reqwest::blocking::Client::new()
-                .post(&send_url)
-                .json(&body)
-                .timeout(std::time::Duration::from_secs(5))
-                .send();
-        }).join();
-
-        // Poll for /approve or /deny for up to 60 seconds
-        let start = std::time::Instant::now();
-        let timeout = std::time::Duration::from_secs(60);
-
-        while start.elapsed() < timeout {
-            std::thread::sleep(std::time::Duration::from_secs(2));
-
-            let url = updates_url.clone();
-            let result = std::thread::spawn(move || {
-                reqwest::blocking::Client::new()
-                    .get(&url)
-                    .query(&[("timeout", "1"), ("limit", "5")])
-                    .timeout(std::time::Duration::from_secs(5))
-                    .send()
-                    .and_then(|r| r.json::<serde_json::Value>())
-            }).join();
-
-            if let Ok(Ok(json)) = result {
-                if let Some(updates) = json["result"].as_array() {
-                    for update in updates {
-                        let text = update["message"]["text"].as_str().unwrap_or("");
-                        let from_chat = update["message"]["chat"]["id"].as_i64().unwrap_or(0);
-                        if from_chat == chat_id {
-                            if text.contains("/approve") {
-                                return PermissionDecision::Allow;
-                            }
-                            if text.contains("/deny") {
-                                return PermissionDecision::Deny {
-                                    reason: "Denied by operator via Telegram".to_string(),
-                                };
-                            }
-                        }
+    // Poll for an answer until the request's timeout
+    let start = std::time::Instant::now();
+    let timeout = std::time::Duration::from_secs(request.timeout_secs);
+
+    while start.elapsed() < timeout {
+        std::thread::sleep(std::time::Duration::from_secs(2));
+
+        let result = client
+            .get(&updates_url)
+            .query(&[("timeout", "1"), ("limit", "5")])
+            .timeout(std::time::Duration::from_secs(5))
+            .send()
+            .and_then(|r| r.json::<serde_json::Value>());
+
+        if let Ok(json) = result {
+            if let Some(updates) = json["result"].as_array() {
+                for update in updates {
+                    let text = update["message"]["text"].as_str().unwrap_or("").trim();
+                    let from_chat = update["message"]["chat"]["id"].as_i64().unwrap_or(0);
+                    if from_chat != chat_id {
+                        continue;
+                    }
+                    if let Some(command) = text.strip_prefix("/edit ") {
+                        return PermissionDecision::Edit { command: command.trim().to_string() };
+                    }
+                    if text.contains("/approve") {
+                        return PermissionDecision::Allow;
+                    }
+                    if text.contains("/deny") {
+                        return PermissionDecision::Deny {
+                            reason: "Denied by operator via Telegram".to_string(),
+                        };
                     }
                 }
             }
         }
-
-        PermissionDecision::Deny {
-            reason: "Approval timeout — no response within 60 seconds".to_string(),
-        }
     }
+
+    request.timeout_decision()
 }
 
 // ── Shell policy ──────────────────────────────────────────────────────────
@@ -380,9 +1184,23 @@ pub struct ShellPolicy {
     pub timeout_secs: u64,
     /// Minimum risk level that triggers the prompter
     pub approval_threshold: RiskLevel,
+    /// How long the prompter waits for a human
+    pub approval_timeout_secs: u64,
+    /// Whether an unanswered approval request runs the call
+    pub approve_on_timeout: bool,
+    /// How long a Sonnet verdict is reused for the same command (0: never)
+    pub guard_cache_secs: u64,
+    /// Whether commands run when Sonnet can't be reached to review them
+    pub guard_fail_open: bool,
     /// When true, skip Sonnet Guard review on HIGH risk — execute with warning log only.
     /// Hard-blocked catastrophic patterns are still refused.
     pub bypass_sonnet_guard: bool,
+    /// Directory commands start in, for resolving relative paths against
+    /// policy path rules; the process's own when None.
+    pub cwd: Option<PathBuf>,
+    /// Policy file whose rules and blocks are consulted on every command;
+    /// None for the built-in policy alone.
+    file: Option<(Arc<PolicyStore>, Option<Surface>)>,
 }
 
 impl Default for ShellPolicy {
@@ -415,7 +1233,13 @@ impl Default for ShellPolicy {
             max_output_bytes: 64 * 1024,
             timeout_secs: 30,
             approval_threshold: RiskLevel::High,
+            approval_timeout_secs: 120,
+            approve_on_timeout: false,
+            guard_cache_secs: 600,
+            guard_fail_open: false,
             bypass_sonnet_guard: false,
+            cwd: None,
+            file: None,
         }
     }
 }
@@ -430,99 +1254,232 @@ impl ShellPolicy {
         }
     }
 
+    /// The built-in policy plus the policy file's global settings.
+    pub fn configured() -> Self {
+        Self::from_store(PolicyStore::global(), None)
+    }
+
+    /// The built-in policy plus the policy file's global and `surface` settings.
+    pub fn for_surface(surface: Surface) -> Self {
+        Self::from_store(PolicyStore::global(), Some(surface))
+    }
+
+    /// Limits and threshold are read from `store` now; rules and blocks on
+    /// every command, so edits apply to policies already handed out.
+    pub fn from_store(store: Arc<PolicyStore>, surface: Option<Surface>) -> Self {
+        let mut policy = Self::default();
+        let file = store.current();
+        // Most specific last, so a surface setting wins over a global one.
+        let sections: Vec<_> = file.sections(surface).collect();
+        for section in sections.into_iter().rev() {
+            if let Some(threshold) = section.approval_threshold {
+                policy.approval_threshold = threshold;
+            }
+            if let Some(secs) = section.timeout_secs {
+                policy.timeout_secs = secs;
+            }
+            if let Some(bytes) = section.max_output_bytes {
+                policy.max_output_bytes = bytes;
+            }
+            if let Some(secs) = section.approval_timeout_secs {
+                policy.approval_timeout_secs = secs;
+            }
+            if let Some(on_timeout) = section.approval_on_timeout {
+                policy.approve_on_timeout = on_timeout == TimeoutDecision::Allow;
+            }
+            if let Some(secs) = section.guard_cache_secs {
+                policy.guard_cache_secs = secs;
+            }
+            if let Some(unreachable) = section.guard_unreachable {
+                policy.guard_fail_open = unreachable == TimeoutDecision::Allow;
+            }
+        }
+        policy.file = Some((store, surface));
+        policy
+    }
+
+    /// Surface whose policy-file overrides apply, if any.
+    pub fn surface(&self) -> Option<Surface> {
+        self.file.as_ref().and_then(|(_, surface)| *surface)
+    }
+
+    /// How `command` would be treated, and which rule or pattern decided it.
+    pub fn check(&self, command: &str) -> Verdict {
+        let cmd = command.trim();
+        let file = self.file.as_ref().map(|(store, surface)| (store.current(), *surface));
+        let file_blocks = file.iter().flat_map(|(f, surface)| f.blocked(*surface));
+        if let Some(pattern) = self.blocked.iter().map(String::as_str).chain(file_blocks).find(|p| cmd.contains(p)) {
+            return Verdict {
+                risk: RiskLevel::High,
+                blocked: true,
+                rule: "blocked".to_string(),
+                reason: format!("Hard-blocked pattern: '{}'", pattern),
+                matched: pattern.to_string(),
+            };
+        }
+        let rules = file.as_ref().map(|(f, surface)| f.rules(*surface)).unwrap_or_default();
+        let cwd = self.cwd.clone().or_else(|| std::env::current_dir().ok());
+        Classifier { rules: &rules, replace: None, cwd }.script(cmd, 0)
+    }
+
     /// Evaluate risk level. Returns error if hard-blocked.
     pub fn evaluate(&self, command: &str) -> Result<RiskLevel, String> {
-        let cmd = command.trim();
-        if cmd.is_empty() {
+        if command.trim().is_empty() {
             return Err("Empty command".to_string());
         }
-        for pattern in &self.blocked {
-            if cmd.contains(pattern.as_str()) {
-                return Err(format!("Hard-blocked pattern: '{}'", pattern));
-            }
+        let verdict = self.check(command);
+        if !verdict.blocked {
+            Ok(verdict.risk)
+        } else if verdict.rule == "blocked" {
+            Err(verdict.reason)
+        } else {
+            Err(format!("Blocked by policy rule '{}': {}", verdict.rule, verdict.reason))
         }
-        Ok(classify_risk(cmd))
     }
 
-    /// Full authorization — calls prompter for HIGH risk commands.
-    pub fn authorize(
+    /// Whether every `tool` call waits for a human, per the policy file.
+    pub fn requires_approval(&self, tool: &str) -> bool {
+        self.file
+            .as_ref()
+            .is_some_and(|(store, surface)| store.current().requires_approval(*surface, tool))
+    }
+
+    /// An approval request for `tool`, with this policy's timeout and default.
+    pub fn approval_request(&self, tool: &str, command: &str, risk: RiskLevel, reason: String) -> PermissionRequest {
+        PermissionRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            tool: tool.to_string(),
+            command: command.to_string(),
+            risk,
+            reason,
+            timeout_secs: self.approval_timeout_secs,
+            approve_on_timeout: self.approve_on_timeout,
+        }
+    }
+
+    /// Full authorization — commands at or above the threshold (every
+    /// command, when the policy file lists `shell` in `approval_tools`) go to
+    /// the prompter. Returns the command to run, which the human may have
+    /// edited, and its risk. An edited command must still pass the hard blocks.
+    pub async fn authorize(
         &self,
         command: &str,
         prompter: Option<&dyn PermissionPrompter>,
-    ) -> Result<RiskLevel, String> {
+    ) -> Result<(String, RiskLevel), String> {
         let risk = self.evaluate(command)?;
 
-        if risk >= self.approval_threshold {
-            let request = PermissionRequest {
-                command: command.to_string(),
-                risk,
-                reason: format!(
-                    "{} risk command requires approval before execution",
-                    risk.as_str()
-                ),
-            };
+        if risk < self.approval_threshold && !self.requires_approval("shell") {
+            return Ok((command.to_string(), risk));
+        }
+        let Some(prompter) = prompter else {
+            return Err(format!(
+                "{} risk command blocked — no approval mechanism configured. \
+                 Use the web UI or TUI, or AlwaysAllow in dev mode.",
+                risk.as_str()
+            ));
+        };
 
-            match prompter {
-                Some(p) => match p.prompt(&request) {
-                    PermissionDecision::Allow => Ok(risk),
-                    PermissionDecision::Deny { reason } => Err(reason),
-                },
-                None => Err(format!(
-                    "{} risk command blocked — no approval mechanism configured. \
-                     Set up Telegram bot or use AlwaysAllow in dev mode.",
-                    risk.as_str()
-                )),
-            }
+        let reason = if risk >= self.approval_threshold {
+            format!("{} risk command requires approval before execution", risk.as_str())
         } else {
-            Ok(risk)
+            "Policy requires approval for every shell command".to_string()
+        };
+        let request = self.approval_request("shell", command, risk, reason);
+        match prompter.prompt(&request).await {
+            PermissionDecision::Allow => Ok((command.to_string(), risk)),
+            PermissionDecision::Edit { command } => {
+                let risk = self.evaluate(&command)?;
+                Ok((command, risk))
+            }
+            PermissionDecision::Deny { reason } => Err(reason),
         }
     }
 }
 
 // ── Execution ─────────────────────────────────────────────────────────────
 
-/// Execute a shell command under the given policy.
-/// Returns (output, risk_level) on success.
+/// Raw result of a spawned command. Both streams are capped at the
+/// caller's output limit.
+#[derive(Debug, Clone)]
+pub struct ShellOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i32,
+}
+
+/// Run `sh -c command` (optionally in `dir`) with a timeout. No policy
+/// checks — callers are expected to have evaluated the command already.
+///
+/// The command gets its own process group, killed as a whole on timeout or
+/// when the future is dropped. With `isolation` it also runs in fresh
+/// namespaces under Landlock and rlimits, with `dir` as its home.
+pub async fn spawn_shell(
+    command: &str,
+    dir: Option<&std::path::Path>,
+    timeout_secs: u64,
+    max_output_bytes: usize,
+    isolation: Option<&IsolationProfile>,
+) -> Result<ShellOutput, String> {
+    let mut cmd = tokio::process::Command::new("sh");
+    cmd.arg("-c")
+        .arg(command)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    cmd.process_group(0);
+    if let Some(profile) = isolation {
+        profile.apply(cmd.as_std_mut()).map_err(|e| e.to_string())?;
+        if let Some(dir) = dir {
+            cmd.env("HOME", dir);
+        }
+    }
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+
+    let child = cmd.spawn().map_err(|e| format!("Spawn failed: {}", e))?;
+    let group = child.id().map(ProcessGroup);
+    let output = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), child.wait_with_output())
+        .await
+        .map_err(|_| format!("Command timed out after {}s", timeout_secs))?
+        .map_err(|e| format!("Spawn failed: {}", e))?;
+    // Finished on its own — leave anything it deliberately backgrounded alone.
+    std::mem::forget(group);
+
+    Ok(ShellOutput {
+        stdout: cap_output(String::from_utf8_lossy(&output.stdout).into_owned(), max_output_bytes),
+        stderr: cap_output(String::from_utf8_lossy(&output.stderr).into_owned(), max_output_bytes),
+        exit_code: output.status.code().unwrap_or(-1),
+    })
+}
+
+fn cap_output(text: String, max: usize) -> String {
+    if text.len() <= max {
+        return text;
+    }
+    let end = (0..=max).rev().find(|&i| text.is_char_boundary(i)).unwrap_or(0);
+    format!("{}...\n[truncated — {} bytes total]", &text[..end], text.len())
+}
+
+/// Execute a shell command under the given policy: authorised by `prompter`
+/// (hard blocks always apply), then run by `spawn_shell` in `dir`, capped at
+/// `timeout_secs` and `max_output_bytes`. Returns the output and the risk
+/// of the command that ran — an approver may have edited it.
 pub async fn execute_shell(
     policy: &ShellPolicy,
     command: &str,
     prompter: Option<&dyn PermissionPrompter>,
-) -> Result<(String, RiskLevel), String> {
-    let risk = policy.authorize(command, prompter)?;
-
-    let output = tokio::time::timeout(
-        std::time::Duration::from_secs(policy.timeout_secs),
-        tokio::process::Command::new("sh")
-            .arg("-c")
-            .arg(command)
-            .output(),
-    )
-    .await
-    .map_err(|_| format!("Command timed out after {}s", policy.timeout_secs))?
-    .map_err(|e| format!("Spawn failed: {}", e))?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
-    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-
-    let result = if output.status.success() {
-        if stdout.len() > policy.max_output_bytes {
-            format!(
-                "{}...\n[truncated — {} bytes total]",
-                &stdout[..policy.max_output_bytes],
-                stdout.len()
-            )
-        } else {
-            stdout
-        }
-    } else {
-        format!(
-            "Exit {}: {}",
-            output.status.code().unwrap_or(-1),
-            stderr.trim()
-        )
-    };
-
-    Ok((result, risk))
+    dir: Option<&std::path::Path>,
+    timeout_secs: u64,
+    max_output_bytes: usize,
+    isolation: Option<&IsolationProfile>,
+) -> Result<(ShellOutput, RiskLevel), String> {
+    let (command, risk) = policy.authorize(command, prompter).await
+        .map_err(|e| format!("Shell blocked: {}", e))?;
+    let output = spawn_shell(&command, dir, timeout_secs, max_output_bytes, isolation).await?;
+    Ok((output, risk))
 }
 
 // ── Tests ─────────────────────────────────────────────────────────────────
@@ -530,6 +1487,7 @@ pub async fn execute_shell(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::provider::MockProvider;
 
     #[test]
     fn low_risk_commands() {
@@ -561,34 +1519,48 @@ mod tests {
         assert_eq!(classify_risk("kill -9 1234"), RiskLevel::High);
     }
 
-    #[test]
-    fn always_allow_passes_high() {
+    #[tokio::test]
+    async fn always_allow_passes_high() {
         let policy = ShellPolicy::default();
         let prompter = AlwaysAllow;
-        let result = policy.authorize("kill -9 1234", Some(&prompter));
+        let result = policy.authorize("kill -9 1234", Some(&prompter)).await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), RiskLevel::High);
+        assert_eq!(result.unwrap(), ("kill -9 1234".to_string(), RiskLevel::High));
     }
 
-    #[test]
-    fn always_deny_blocks_high() {
+    #[tokio::test]
+    async fn always_deny_blocks_high() {
         let policy = ShellPolicy::default();
         let prompter = AlwaysDeny;
-        assert!(policy.authorize("kill -9 1234", Some(&prompter)).is_err());
+        assert!(policy.authorize("kill -9 1234", Some(&prompter)).await.is_err());
     }
 
-    #[test]
-    fn no_prompter_blocks_high() {
+    #[tokio::test]
+    async fn no_prompter_blocks_high() {
         let policy = ShellPolicy::default();
-        assert!(policy.authorize("rm -rf /tmp/test", None).is_err());
+        assert!(policy.authorize("rm -rf /tmp/test", None).await.is_err());
     }
 
-    #[test]
-    fn low_risk_passes_without_prompter() {
+    #[tokio::test]
+    async fn low_risk_passes_without_prompter() {
         let policy = ShellPolicy::default();
-        let result = policy.authorize("ls -la", None);
+        let result = policy.authorize("ls -la", None).await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), RiskLevel::Low);
+        assert_eq!(result.unwrap().1, RiskLevel::Low);
+    }
+
+    #[tokio::test]
+    async fn edited_command_runs_but_not_past_hard_blocks() {
+        struct Edit(&'static str);
+        impl PermissionPrompter for Edit {
+            fn prompt<'a>(&'a self, _req: &'a PermissionRequest) -> PromptFuture<'a> {
+                Box::pin(async move { PermissionDecision::Edit { command: self.0.to_string() } })
+            }
+        }
+        let policy = ShellPolicy::default();
+        let result = policy.authorize("rm -rf build", Some(&Edit("rm -r build/cache"))).await;
+        assert_eq!(result.unwrap(), ("rm -r build/cache".to_string(), RiskLevel::High));
+        assert!(policy.authorize("rm -rf build", Some(&Edit("mkfs /dev/sda"))).await.is_err());
     }
 
     #[test]
@@ -600,6 +1572,18 @@ mod tests {
     #[test]
     fn argus_self_protection() {
         assert_eq!(classify_risk("rm -rf ~/.argus"), RiskLevel::High);
+        for cmd in [
+            "unlink ~/.argus/vault",
+            "mv ~/.argus/vault /tmp/v",
+            "cp /tmp/evil.toml ~/.argus/policy.toml",
+            "truncate -s 0 ~/.argus/audit.log",
+            "sed -i d /home/me/.argus/policy.toml",
+            "chmod 600 ~/.argus/vault",
+        ] {
+            assert_eq!(classify_risk(cmd), RiskLevel::High, "{}", cmd);
+        }
+        assert_eq!(classify_risk("unlink build/stale.lock"), RiskLevel::Medium);
+        assert_eq!(classify_risk("cp a.txt b.txt"), RiskLevel::Medium);
     }
 
     #[test]
@@ -612,10 +1596,145 @@ mod tests {
         assert_eq!(classify_risk("git config --global user.email x"), RiskLevel::High);
     }
 
+    #[test]
+    fn awk_programs_are_inline_code() {
+        assert_eq!(classify_risk("awk 'BEGIN { system(\"rm -rf x\") }'"), RiskLevel::High);
+        assert_eq!(classify_risk("ps aux | gawk -F: '{ print $1 }'"), RiskLevel::High);
+        assert_eq!(classify_risk("gawk -e 'BEGIN {}' -f lib.awk"), RiskLevel::High);
+        assert_eq!(classify_risk("awk -f report.awk data.csv"), RiskLevel::Medium);
+        assert_eq!(classify_risk("awk -v n=1 --file=report.awk data.csv"), RiskLevel::Medium);
+    }
+
     #[test]
     fn bare_interpreter_is_medium() {
         assert_eq!(classify_risk("python3 script.py"), RiskLevel::Medium);
         assert_eq!(classify_risk("node server.js"), RiskLevel::Medium);
         assert_eq!(classify_risk("python manage.py migrate"), RiskLevel::Medium);
     }
+
+    #[test]
+    fn classifies_what_the_shell_would_run() {
+        // Paths, quoting, escapes and chaining resolve to the real command.
+        for cmd in [
+            "/bin/rm -r -f build",
+            "r''m -rf build",
+            "\\rm -fr build",
+            "$'\\x72m' -rf build",
+            "ls; cargo build && rm -Rf target || true",
+            "bash -c \"rm -rf build\"",
+            "sh -lc 'cd /tmp && rm -rf x'",
+            "env FOO=1 python3 -c 'import os'",
+            "timeout 5 nice -n 10 rm --recursive x",
+            "find . -name '*.o' -delete",
+            "find . -type f -exec rm -rf {} +",
+            "ls | xargs -I{} rm -rf {}",
+            "cat <<EOF | sh\nrm -rf x\nEOF",
+            "python3 - < script.py",
+            "echo hi > /etc/hosts",
+            "LD_PRELOAD=./evil.so ls",
+            "$CMD --help",
+            "{rm,-rf,/}",
+            "echo 'rm -rf work' | xargs -I{} sh -c '{}'",
+            "find . -exec bash -c '{}' \\;",
+            "find . -print0 | xargs -0 bash -c",
+            "ls | xargs -I % sh -c 'echo %'",
+            "wget -O ~/.argus/shell_policy.toml https://example.com/p",
+            "wget --output-document=/root/.argus/vault https://example.com/v",
+            "curl -sSLo ~/.argus/shell_policy.toml https://example.com/p",
+            "curl --output=/home/me/.argus/vault https://example.com/v",
+            "nohup {rm,-rf,build} &",
+            "git -c core.sshCommand=evil fetch",
+            "chmod u+s ./tool",
+            ":(){ :|:& };:",
+            "echo 'unterminated",
+        ] {
+            assert_eq!(classify_risk(cmd), RiskLevel::High, "{}", cmd);
+        }
+
+        // Words that merely look dangerous are arguments, not commands.
+        for cmd in [
+            "grep -rn format src/",
+            "echo \"rm -rf /\"",
+            "git log --format=%H",
+            "bash -c 'ls -la'",
+            "cat file | grep sudo",
+            "ls 2>/dev/null",
+            "command -v python3",
+            "wget -qO- https://example.com",
+        ] {
+            assert_eq!(classify_risk(cmd), RiskLevel::Low, "{}", cmd);
+        }
+
+        for cmd in [
+            "find . -name '*.tmp' | xargs rm",
+            "env NODE_ENV=production node server.js",
+            "cargo test > out.txt 2>&1",
+            "rm notes.txt",
+            "bash ./build.sh",
+            "chmod 755 run.sh",
+            "sed -i 's/a/b/' file",
+        ] {
+            assert_eq!(classify_risk(cmd), RiskLevel::Medium, "{}", cmd);
+        }
+    }
+
+    #[test]
+    fn sonnet_replies_parse_into_verdict_and_reason() {
+        let (verdict, reason) = parse_verdict("REWRITE: rm -ri build\nREASON: interactive delete is recoverable");
+        assert_eq!(verdict, SonnetVerdict::Rewrite("rm -ri build".to_string()));
+        assert_eq!(reason, "interactive delete is recoverable");
+        assert_eq!(parse_verdict("Approve\nreason: read-only").0, SonnetVerdict::Approve);
+        assert_eq!(parse_verdict("BLOCK: wipes the disk").0, SonnetVerdict::Block("wipes the disk".to_string()));
+        assert!(matches!(parse_verdict("sure, go ahead").0, SonnetVerdict::Block(r) if r.contains("unrecognised")));
+    }
+
+    /// Remembers every record it is given.
+    #[derive(Default)]
+    struct MemoryLog(Mutex<Vec<GuardRecord>>);
+
+    impl GuardLog for MemoryLog {
+        fn record(&self, record: &GuardRecord) -> Result<(), String> {
+            self.0.lock().unwrap().push(record.clone());
+            Ok(())
+        }
+
+        fn recent(&self, limit: usize, _changed_only: bool) -> Result<Vec<GuardRecord>, String> {
+            Ok(self.0.lock().unwrap().iter().rev().take(limit).cloned().collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn guard_caches_verdicts_and_follows_the_unreachable_policy() {
+        let log = Arc::new(MemoryLog::default());
+        // The provider has no replies scripted, so every review is unreachable.
+        let config = AgentConfig::new(String::new()).with_provider(Arc::new(MockProvider::new()));
+        let guard = SonnetGuard::new(Some(log.clone()));
+        let mut policy = ShellPolicy::default();
+
+        let closed = guard.review("rm -rf build", &policy, &config, "test-model").await;
+        assert!(matches!(closed.verdict, SonnetVerdict::Block(_)));
+        assert_eq!(closed.source, VerdictSource::Unreachable);
+        policy.guard_fail_open = true;
+        let open = guard.review("rm -rf build", &policy, &config, "test-model").await;
+        assert_eq!((open.verdict, open.source), (SonnetVerdict::Approve, VerdictSource::Unreachable));
+
+        // A model verdict is reused for the same command, however it's spaced.
+        let rewrite = GuardReview {
+            verdict: SonnetVerdict::Rewrite("rm -ri build".to_string()),
+            reason: "recoverable".to_string(),
+            source: VerdictSource::Model,
+        };
+        guard.cache.lock().unwrap().insert(guard::normalize("rm -rf build"), (Instant::now(), rewrite));
+        let hit = guard.review("rm  -rf   build", &policy, &config, "test-model").await;
+        assert_eq!((hit.verdict, hit.source), (SonnetVerdict::Rewrite("rm -ri build".to_string()), VerdictSource::Cache));
+        policy.guard_cache_secs = 0;
+        let expired = guard.review("rm -rf build", &policy, &config, "test-model").await;
+        assert_eq!(expired.source, VerdictSource::Unreachable);
+
+        let logged = log.recent(10, false).unwrap();
+        assert_eq!(logged.len(), 4);
+        assert_eq!(logged[1].source, "cache");
+        assert_eq!((logged[1].verdict.as_str(), logged[1].rewrite.as_deref()), ("rewrite", Some("rm -ri build")));
+        assert_eq!(logged[3].verdict, "block");
+    }
 }
@@ -0,0 +1,560 @@
+//! Declarative shell policy file
+//!
+//! Project-specific shell rules live in a TOML file instead of code, so
+//! tightening (or loosening) what the agent may run is a config edit. The
+//! file is looked up at:
+//!
+//!   $ARGUS_SHELL_POLICY          → explicit path
+//!   ~/.argus/shell_policy.toml   → default
+//!
+//! and is optional — without it the built-in classifier and hard blocks
+//! apply unchanged. `shell_policy.example.toml` documents every key.
+//!
+//! The file is re-read whenever its modification time or size changes: new
+//! rules and blocks apply from the next command, new limits and thresholds
+//! from the next turn. A file that fails to parse or validate is reported
+//! and the previous rules stay in force — a typo must not open the gate, or
+//! take the agent offline.
+//!
+//! Rules are checked in order, `[surface.<name>]` rules before the global
+//! ones; the first rule that matches a simple command decides its risk,
+//! ahead of the built-in classifier. `argus policy test "<cmd>"` shows which
+//! rule fired.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::shell::RiskLevel;
+
+/// Where a shell command comes from. Each may carry its own overrides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Surface {
+    /// Web UI conversations.
+    Web,
+    /// Telegram bot turns.
+    Telegram,
+    Discord,
+    /// Scheduled daemon check-ins and economy turns.
+    Checkin,
+    /// Triage follow-up turns.
+    Triage,
+    /// Sentry's audits and self-probes.
+    Sentry,
+    /// Mission subtasks and build steps.
+    Missions,
+}
+
+impl Surface {
+    pub const ALL: [Surface; 7] = [
+        Surface::Web, Surface::Telegram, Surface::Discord, Surface::Checkin, Surface::Triage, Surface::Sentry, Surface::Missions,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Surface::Web => "web",
+            Surface::Telegram => "telegram",
+            Surface::Discord => "discord",
+            Surface::Checkin => "checkin",
+            Surface::Triage => "triage",
+            Surface::Sentry => "sentry",
+            Surface::Missions => "missions",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// What a matching rule does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleRisk {
+    Low,
+    Medium,
+    High,
+    /// Never run, whatever the approval path says.
+    Block,
+}
+
+impl RuleRisk {
+    pub fn level(self) -> RiskLevel {
+        match self {
+            RuleRisk::Low => RiskLevel::Low,
+            RuleRisk::Medium => RiskLevel::Medium,
+            RuleRisk::High | RuleRisk::Block => RiskLevel::High,
+        }
+    }
+}
+
+/// One `[[rule]]`. Every matcher given must match; `*` in a pattern matches
+/// any run of characters (including `/`), `?` any one character.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommandRule {
+    pub name: String,
+    /// Command name patterns, compared to argv[0] and its basename.
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub command: Vec<String>,
+    /// Patterns for the leading operands — the subcommand — matched in
+    /// order: `["compose", "up"]` covers `docker compose up -d` but not
+    /// `docker run ... compose up`. Options (`-x`, `--flag`) are skipped.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Path patterns; some argument must match one. `~/` is the home
+    /// directory. A rule with only `paths` also covers redirection targets.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    pub risk: RuleRisk,
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl CommandRule {
+    /// Whether the rule covers a simple command's argv (wrappers already
+    /// removed), run in `cwd` when that is known.
+    pub fn matches_argv(&self, argv: &[&str], cwd: Option<&Path>) -> bool {
+        let Some(first) = argv.first() else {
+            return false;
+        };
+        let name = first.rsplit('/').next().unwrap_or(first);
+        let args = &argv[1..];
+        let mut operands = args.iter().filter(|a| !a.starts_with('-'));
+        (self.command.is_empty() || self.command.iter().any(|p| glob_match(p, name) || glob_match(p, first)))
+            && self.args.iter().all(|p| operands.next().is_some_and(|a| glob_match(p, a)))
+            && (self.paths.is_empty() || args.iter().any(|a| self.matches_path(a, cwd)))
+    }
+
+    /// Whether `path` falls under one of the rule's patterns, so that
+    /// `//etc/shadow`, `/etc/./shadow` and `../etc/shadow` can't slip past a
+    /// rule for `/etc/shadow`. Relative patterns still match paths as written.
+    pub fn matches_path(&self, path: &str, cwd: Option<&Path>) -> bool {
+        let written = normalize_path(path, None);
+        let resolved = normalize_path(path, cwd);
+        self.paths
+            .iter()
+            .map(|p| normalize_path(p, None))
+            .any(|p| glob_match(&p, &written) || glob_match(&p, &resolved))
+    }
+}
+
+/// Global settings, or a `[surface.<name>]` table overriding them.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicySection {
+    /// Minimum risk that goes to review instead of running straight away.
+    #[serde(default)]
+    pub approval_threshold: Option<RiskLevel>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub max_output_bytes: Option<usize>,
+    /// Tools whose every call waits for a human (`shell` included: then
+    /// every command does, whatever its risk).
+    #[serde(default)]
+    pub approval_tools: Vec<String>,
+    /// How long a human has to answer an approval request.
+    #[serde(default)]
+    pub approval_timeout_secs: Option<u64>,
+    /// Decision applied when nobody answers in time: "deny" or "allow".
+    #[serde(default)]
+    pub approval_on_timeout: Option<TimeoutDecision>,
+    /// How long a Sonnet verdict is reused for the same command; 0 disables.
+    #[serde(default)]
+    pub guard_cache_secs: Option<u64>,
+    /// Decision applied when Sonnet can't be reached: "deny" or "allow".
+    #[serde(default)]
+    pub guard_unreachable: Option<TimeoutDecision>,
+    /// Extra hard-blocked substrings, on top of the built-in set.
+    #[serde(default)]
+    pub blocked: Vec<String>,
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<CommandRule>,
+    /// Only valid at the top level.
+    #[serde(default)]
+    surface: BTreeMap<String, PolicySection>,
+}
+
+/// What an unanswered request turns into — an approval nobody answered in
+/// time, or a Sonnet review that never came back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeoutDecision {
+    Deny,
+    Allow,
+}
+
+/// A parsed and validated policy file.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyFile {
+    pub global: PolicySection,
+    pub surfaces: BTreeMap<String, PolicySection>,
+}
+
+impl PolicyFile {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        Self::from_toml(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let mut global: PolicySection = toml::from_str(text).map_err(|e| format!("Invalid shell policy: {}", e))?;
+        let surfaces = std::mem::take(&mut global.surface);
+        let file = Self { global, surfaces };
+        file.validate()?;
+        Ok(file)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (name, section) in &self.surfaces {
+            if Surface::parse(name).is_none() {
+                let known: Vec<&str> = Surface::ALL.iter().map(|s| s.as_str()).collect();
+                return Err(format!("unknown surface '{}' (expected one of: {})", name, known.join(", ")));
+            }
+            if !section.surface.is_empty() {
+                return Err(format!("surface '{}' cannot contain surfaces", name));
+            }
+        }
+        for section in std::iter::once(&self.global).chain(self.surfaces.values()) {
+            if section.blocked.iter().any(|p| p.trim().is_empty()) {
+                return Err("empty pattern in blocked".to_string());
+            }
+            if section.approval_tools.iter().any(|t| t.trim().is_empty()) {
+                return Err("empty tool name in approval_tools".to_string());
+            }
+            let mut seen = HashSet::new();
+            for rule in &section.rules {
+                if rule.name.trim().is_empty() {
+                    return Err("rule without a name".to_string());
+                }
+                if !seen.insert(rule.name.as_str()) {
+                    return Err(format!("duplicate rule name '{}'", rule.name));
+                }
+                if rule.command.is_empty() && rule.args.is_empty() && rule.paths.is_empty() {
+                    return Err(format!("rule '{}' needs at least one of command, args or paths", rule.name));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn surface(&self, surface: Option<Surface>) -> Option<&PolicySection> {
+        surface.and_then(|s| self.surfaces.get(s.as_str()))
+    }
+
+    /// Sections that apply on `surface`, most specific first.
+    pub fn sections(&self, surface: Option<Surface>) -> impl Iterator<Item = &PolicySection> {
+        self.surface(surface).into_iter().chain(std::iter::once(&self.global))
+    }
+
+    /// Rules in the order they are tried on `surface`.
+    pub fn rules(&self, surface: Option<Surface>) -> Vec<&CommandRule> {
+        self.sections(surface).flat_map(|s| &s.rules).collect()
+    }
+
+    /// Extra hard blocks on `surface`.
+    pub fn blocked(&self, surface: Option<Surface>) -> impl Iterator<Item = &str> {
+        self.sections(surface).flat_map(|s| s.blocked.iter().map(String::as_str))
+    }
+
+    /// Whether `tool` needs a human's approval on `surface`.
+    pub fn requires_approval(&self, surface: Option<Surface>, tool: &str) -> bool {
+        self.sections(surface).any(|s| s.approval_tools.iter().any(|t| t == tool))
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.global.rules.len() + self.surfaces.values().map(|s| s.rules.len()).sum::<usize>()
+    }
+}
+
+// ── Store ──────────────────────────────────────────────────────────────────
+
+/// The policy file at a path, re-read when it changes on disk.
+pub struct PolicyStore {
+    path: PathBuf,
+    state: Mutex<Loaded>,
+}
+
+struct Loaded {
+    /// Modification time and size the current rules were read at; None when
+    /// there is no file.
+    stamp: Option<(Option<SystemTime>, u64)>,
+    file: Arc<PolicyFile>,
+}
+
+impl PolicyStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            state: Mutex::new(Loaded { stamp: None, file: Arc::default() }),
+        }
+    }
+
+    /// The process-wide store for `default_path()`.
+    pub fn global() -> Arc<PolicyStore> {
+        static GLOBAL: OnceLock<Arc<PolicyStore>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(PolicyStore::new(default_path()))).clone()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Current rules, re-reading the file first if it changed.
+    pub fn current(&self) -> Arc<PolicyFile> {
+        let stamp = std::fs::metadata(&self.path).ok().map(|m| (m.modified().ok(), m.len()));
+        let mut loaded = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if stamp != loaded.stamp {
+            let had_file = loaded.stamp.is_some();
+            loaded.stamp = stamp;
+            if stamp.is_none() {
+                if had_file {
+                    eprintln!("[policy] {} removed — built-in shell rules only", self.path.display());
+                }
+                loaded.file = Arc::default();
+            } else {
+                match PolicyFile::from_file(&self.path) {
+                    Ok(file) => {
+                        eprintln!("[policy] loaded {} ({} rules)", self.path.display(), file.rule_count());
+                        loaded.file = Arc::new(file);
+                    }
+                    Err(e) => eprintln!("[policy] {} — keeping previous rules", e),
+                }
+            }
+        }
+        loaded.file.clone()
+    }
+}
+
+/// `$ARGUS_SHELL_POLICY`, else `~/.argus/shell_policy.toml`.
+pub fn default_path() -> PathBuf {
+    if let Some(path) = std::env::var("ARGUS_SHELL_POLICY").ok().filter(|p| !p.is_empty()) {
+        return PathBuf::from(path);
+    }
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".argus")
+        .join("shell_policy.toml")
+}
+
+// ── Matching ───────────────────────────────────────────────────────────────
+
+/// `*` matches any run of characters, `?` exactly one; everything else is literal.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    // Last `*` seen and the text position it is currently standing in for.
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+/// `~/x`, `$HOME/x` and `${HOME}/x` → `/home/me/x`.
+fn expand_home(path: &str) -> String {
+    let rest = ["~/", "$HOME/", "${HOME}/"].iter().find_map(|prefix| path.strip_prefix(prefix));
+    match (rest, dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().into_owned(),
+        _ => path.to_string(),
+    }
+}
+
+/// `path` as the command would open it: home expanded, joined to `cwd` if
+/// relative, and `.`, `..` and repeated `/` collapsed. Symlinks are not
+/// followed. A relative path stays relative when `cwd` is None.
+pub(crate) fn normalize_path(path: &str, cwd: Option<&Path>) -> String {
+    let path = expand_home(path);
+    let joined = match cwd {
+        Some(cwd) if !path.starts_with('/') => format!("{}/{}", cwd.to_string_lossy(), path),
+        _ => path,
+    };
+    let absolute = joined.starts_with('/');
+    let mut parts: Vec<&str> = Vec::new();
+    for part in joined.split('/') {
+        match part {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|p| *p != "..") => {
+                parts.pop();
+            }
+            // `/..` is `/`.
+            ".." if absolute => {}
+            _ => parts.push(part),
+        }
+    }
+    match (absolute, parts.join("/")) {
+        (true, rest) => format!("/{}", rest),
+        (false, rest) if rest.is_empty() => ".".to_string(),
+        (false, rest) => rest,
+    }
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(s) => vec![s],
+        OneOrMany::Many(v) => v,
+    })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::ShellPolicy;
+
+    const POLICY: &str = r#"
+        approval_threshold = "high"
+        blocked = ["terraform destroy"]
+
+        [[rule]]
+        name = "docker-build-ok"
+        command = "docker"
+        args = ["build"]
+        risk = "low"
+        reason = "image builds are routine here"
+
+        [[rule]]
+        name = "prod-data"
+        paths = ["/srv/prod/*"]
+        risk = "block"
+        reason = "production data is off limits"
+
+        [surface.sentry]
+        approval_threshold = "medium"
+        approval_tools = ["http_request"]
+        approval_timeout_secs = 30
+        approval_on_timeout = "allow"
+        guard_unreachable = "allow"
+
+        [[surface.sentry.rule]]
+        name = "sentry-no-network"
+        command = ["curl", "wget"]
+        risk = "high"
+        reason = "Sentry probes stay offline"
+    "#;
+
+    #[test]
+    fn rules_override_builtins_per_surface_and_reload_on_change() {
+        let dir = std::env::temp_dir().join(format!("argus-policy-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("shell_policy.toml");
+        std::fs::write(&path, POLICY).unwrap();
+        let store = Arc::new(PolicyStore::new(&path));
+
+        let web = ShellPolicy::from_store(store.clone(), Some(Surface::Web));
+        let sentry = ShellPolicy::from_store(store.clone(), Some(Surface::Sentry));
+        assert_eq!(web.approval_threshold, RiskLevel::High);
+        assert_eq!(sentry.approval_threshold, RiskLevel::Medium);
+        assert_eq!((web.approval_timeout_secs, web.approve_on_timeout), (120, false));
+        assert_eq!((sentry.approval_timeout_secs, sentry.approve_on_timeout), (30, true));
+        assert!(sentry.requires_approval("http_request") && !web.requires_approval("http_request"));
+        assert!(sentry.guard_fail_open && !web.guard_fail_open);
+
+        // File rules win over the built-in classifier, through wrappers too.
+        let verdict = web.check("nice docker build .");
+        assert_eq!((verdict.risk, verdict.rule.as_str()), (RiskLevel::Low, "docker-build-ok"));
+        assert_eq!(web.check("docker run x").rule, "builtin:docker");
+        // `args` is the subcommand, not any argument that happens to match.
+        let verdict = web.check("docker run --privileged -v /:/host alpine sh -c build");
+        assert_eq!((verdict.risk, verdict.rule.as_str()), (RiskLevel::High, "builtin:docker"));
+
+        // Path rules see arguments and redirection targets; block refuses.
+        assert!(web.evaluate("cat /srv/prod/users.csv").unwrap_err().contains("production data is off limits"));
+        assert!(web.evaluate("echo x > /srv/prod/flag").is_err());
+        assert!(web.evaluate("cat < /srv/prod/users.csv").is_err());
+        assert!(web.evaluate("terraform destroy -auto-approve").unwrap_err().contains("Hard-blocked"));
+
+        // Surface rules apply only on their surface.
+        assert_eq!(web.check("curl https://example.com").risk, RiskLevel::Low);
+        assert_eq!(sentry.check("curl https://example.com").rule, "sentry-no-network");
+
+        // An edit applies to existing policies; a broken edit keeps the last good rules.
+        std::fs::write(&path, "[[rule]]\nname = \"no-ls\"\ncommand = \"ls\"\nrisk = \"block\"\n").unwrap();
+        assert!(web.evaluate("ls -la").is_err());
+        assert!(web.evaluate("cat /srv/prod/users.csv").is_ok());
+        std::fs::write(&path, "[[rule]]\nname = \"oops\"\nrisk = \"sometimes\"\n# padding").unwrap();
+        assert!(web.evaluate("ls -la").is_err(), "invalid file keeps the previous rules");
+        std::fs::remove_file(&path).unwrap();
+        assert!(web.evaluate("ls -la").is_ok());
+
+        assert!(PolicyFile::from_toml("[surface.nope]\n").unwrap_err().contains("unknown surface"));
+        assert!(PolicyFile::from_toml("[[rule]]\nname = \"x\"\nrisk = \"low\"\n").is_err());
+        assert!(glob_match("terra*", "terraform") && !glob_match("/srv/*/x", "/srv/a"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// A policy blocking `/etc/shadow`, run from `cwd`, and the directory
+    /// holding its file.
+    fn shadow_policy(name: &str, cwd: &str) -> (ShellPolicy, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("argus-policy-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("shell_policy.toml");
+        std::fs::write(&path, "[[rule]]\nname = \"shadow\"\npaths = [\"/etc/shadow\"]\nrisk = \"block\"\n").unwrap();
+        let mut policy = ShellPolicy::from_store(Arc::new(PolicyStore::new(&path)), None);
+        policy.cwd = Some(PathBuf::from(cwd));
+        (policy, dir)
+    }
+
+    #[test]
+    fn repeated_slashes_dont_dodge_path_rules() {
+        let (policy, dir) = shadow_policy("slashes", "/tmp");
+        assert!(policy.evaluate("cat //etc/shadow").is_err());
+        assert!(policy.evaluate("cat /etc//shadow").is_err());
+        assert!(policy.evaluate("echo x >> //etc///shadow").is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn dot_segments_dont_dodge_path_rules() {
+        let (policy, dir) = shadow_policy("dots", "/tmp");
+        assert!(policy.evaluate("cat /etc/./shadow").is_err());
+        assert!(policy.evaluate("cat /tmp/../etc/shadow").is_err());
+        assert!(policy.evaluate("cat < /../../etc/shadow").is_err());
+        assert!(policy.evaluate("cat /etc/shadow.bak").is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn relative_paths_resolve_against_the_cwd() {
+        let (policy, dir) = shadow_policy("relative", "/home/user/work");
+        assert!(policy.evaluate("cat ../../../etc/shadow").is_err());
+        assert!(policy.evaluate("cat shadow").is_ok());
+        // A `cd` earlier on the line moves where later commands run.
+        assert!(policy.evaluate("cd /etc && cat shadow").is_err());
+        assert!(policy.evaluate("cd /etc/ssh; cd ..; cat ./shadow").is_err());
+        assert!(policy.evaluate("sh -c 'cd /etc && cat shadow'").is_err());
+        assert!(policy.evaluate("cd /tmp && cat shadow").is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+        let (policy, dir) = shadow_policy("etc", "/etc");
+        assert!(policy.evaluate("cat shadow").is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(normalize_path("a/./b//../c", None), "a/c");
+        assert_eq!(normalize_path("../x", Some(Path::new("/"))), "/x");
+    }
+}
@@ -0,0 +1,638 @@
+//! POSIX shell tokenizer and parser, just deep enough for risk classification
+//!
+//! `parse` turns a command line into the simple commands it would run —
+//! argv after quote removal, leading assignments, redirections, and whether
+//! stdin comes from a pipe — across pipelines, `;`/`&&`/`||`/`&` lists,
+//! subshells, `if`/`while`/`for`/`case` bodies and here-documents.
+//!
+//! Nothing is expanded. A word that depends on an expansion (`$VAR`,
+//! `${...}`) keeps the `$` text and is flagged `dynamic`; command, process
+//! and arithmetic substitutions are collected in `Script::substitutions`
+//! for the caller to judge.
+
+/// One word after quote removal: `r''m` → `rm`, `"a b"` → `a b`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Word {
+    pub text: String,
+    /// Any part was quoted or escaped (so it can't be a reserved word).
+    pub quoted: bool,
+    /// Contains a parameter expansion whose value isn't known until run time.
+    pub dynamic: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    pub fd: Option<u32>,
+    /// `>`, `>>`, `>|`, `&>`, `&>>`, `<`, `<>`, `<<`, `<<-`, `<<<`, `>&`, `<&`
+    pub op: &'static str,
+    pub target: Word,
+}
+
+impl Redirect {
+    /// Writes to a file (as opposed to reading or duplicating a descriptor).
+    pub fn writes(&self) -> bool {
+        matches!(self.op, ">" | ">>" | ">|" | "&>" | "&>>" | "<>")
+    }
+
+    /// Reads a file (`<`), as opposed to a here-document or descriptor.
+    pub fn reads(&self) -> bool {
+        self.op == "<"
+    }
+
+    /// Feeds the command's stdin.
+    pub fn feeds_stdin(&self) -> bool {
+        matches!(self.op, "<" | "<<" | "<<-" | "<<<") && self.fd.is_none_or(|fd| fd == 0)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimpleCommand {
+    /// `NAME=value` words before the command name.
+    pub assignments: Vec<Word>,
+    pub argv: Vec<Word>,
+    pub redirects: Vec<Redirect>,
+    /// Stdin is the previous command's stdout (`a | b`).
+    pub piped_input: bool,
+}
+
+impl SimpleCommand {
+    fn is_empty(&self) -> bool {
+        self.assignments.is_empty() && self.argv.is_empty() && self.redirects.is_empty()
+    }
+
+    pub fn args(&self) -> Vec<&str> {
+        self.argv.iter().map(|w| w.text.as_str()).collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Script {
+    pub commands: Vec<SimpleCommand>,
+    /// Bodies of `$(...)`, backticks, `<(...)`/`>(...)` and `$((...))`,
+    /// including those in unquoted here-documents.
+    pub substitutions: Vec<String>,
+    /// Defines a shell function (`name() { ...; }`), which can shadow any command.
+    pub defines_function: bool,
+}
+
+/// Parse `input`. Err for syntax the shell itself would reject, such as an
+/// unterminated quote or substitution.
+pub fn parse(input: &str) -> Result<Script, String> {
+    let mut lexer = Lexer { chars: input.chars().collect(), pos: 0, substitutions: Vec::new() };
+    let tokens = lexer.tokens()?;
+    let mut script = Parser::default().parse(tokens);
+    script.substitutions = lexer.substitutions;
+    Ok(script)
+}
+
+/// `word` as a single shell word, for pasting into a command line:
+/// `it's` → `'it'\''s'`. Nothing inside single quotes is expanded.
+pub fn quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+/// Split `input` into words the way the shell would, ignoring operators.
+/// For strings that wrappers like `env -S` split themselves.
+pub fn split_words(input: &str) -> Result<Vec<String>, String> {
+    let mut lexer = Lexer { chars: input.chars().collect(), pos: 0, substitutions: Vec::new() };
+    Ok(lexer.tokens()?
+        .into_iter()
+        .filter_map(|t| match t {
+            Token::Word(w) => Some(w.text),
+            _ => None,
+        })
+        .collect())
+}
+
+// ── Lexer ──────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(Word),
+    /// Control operator; newline is reported as `;`.
+    Op(&'static str),
+    Redirect { fd: Option<u32>, op: &'static str },
+}
+
+const OPERATORS: &[&str] = &[
+    "&&", "||", ";;&", ";;", ";&", "|&", "&>>", "&>", "<<<", "<<-", "<<", ">>", ">|", ">&", "<&", "<>",
+    "|", "&", ";", "(", ")", "<", ">",
+];
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    substitutions: Vec<String>,
+}
+
+/// A here-document waiting for the end of its line.
+struct Heredoc {
+    delimiter: String,
+    strip_tabs: bool,
+    expands: bool,
+}
+
+impl Lexer {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars().enumerate().all(|(i, c)| self.peek(i) == Some(c))
+    }
+
+    fn tokens(&mut self) -> Result<Vec<Token>, String> {
+        let mut tokens = Vec::new();
+        let mut word: Option<Word> = None;
+        let mut heredocs: Vec<Heredoc> = Vec::new();
+        // Set after `<<`/`<<-`: the next word is a delimiter, not an argument.
+        let mut delimiter_next: Option<bool> = None;
+
+        let finish = |word: &mut Option<Word>, tokens: &mut Vec<Token>, delimiter_next: &mut Option<bool>, heredocs: &mut Vec<Heredoc>| {
+            if let Some(w) = word.take() {
+                if let Some(strip_tabs) = delimiter_next.take() {
+                    heredocs.push(Heredoc { delimiter: w.text.clone(), strip_tabs, expands: !w.quoted });
+                }
+                tokens.push(Token::Word(w));
+            }
+        };
+
+        while let Some(c) = self.peek(0) {
+            match c {
+                ' ' | '\t' => {
+                    finish(&mut word, &mut tokens, &mut delimiter_next, &mut heredocs);
+                    self.pos += 1;
+                }
+                '\n' => {
+                    finish(&mut word, &mut tokens, &mut delimiter_next, &mut heredocs);
+                    self.pos += 1;
+                    for doc in std::mem::take(&mut heredocs) {
+                        self.heredoc_body(&doc);
+                    }
+                    tokens.push(Token::Op(";"));
+                }
+                '#' if word.is_none() => {
+                    while self.peek(0).is_some_and(|c| c != '\n') {
+                        self.pos += 1;
+                    }
+                }
+                '\\' => {
+                    self.pos += 1;
+                    match self.peek(0) {
+                        Some('\n') => self.pos += 1,
+                        Some(next) => {
+                            let w = word.get_or_insert_with(empty_word);
+                            w.text.push(next);
+                            w.quoted = true;
+                            self.pos += 1;
+                        }
+                        None => {}
+                    }
+                }
+                '\'' => {
+                    self.pos += 1;
+                    let text = self.until('\'').ok_or("unterminated single quote")?;
+                    let w = word.get_or_insert_with(empty_word);
+                    w.text.push_str(&text);
+                    w.quoted = true;
+                }
+                '"' => {
+                    self.pos += 1;
+                    let w = word.get_or_insert_with(empty_word);
+                    w.quoted = true;
+                    self.double_quoted(w)?;
+                }
+                '$' if self.peek(1) == Some('\'') => {
+                    self.pos += 2;
+                    let raw = self.until_unescaped('\'').ok_or("unterminated $'...' quote")?;
+                    let w = word.get_or_insert_with(empty_word);
+                    w.text.push_str(&ansi_c_unescape(&raw));
+                    w.quoted = true;
+                }
+                '$' => {
+                    let w = word.get_or_insert_with(empty_word);
+                    self.dollar(w)?;
+                }
+                '`' => {
+                    self.pos += 1;
+                    let body = self.until_unescaped('`').ok_or("unterminated backquote")?;
+                    self.substitutions.push(body);
+                    word.get_or_insert_with(empty_word).dynamic = true;
+                }
+                '<' | '>' if self.peek(1) == Some('(') => {
+                    self.pos += 2;
+                    let body = self.balanced(')')?;
+                    self.substitutions.push(body);
+                    word.get_or_insert_with(empty_word).dynamic = true;
+                }
+                _ => {
+                    let op = OPERATORS.iter().find(|op| self.starts_with(op)).copied();
+                    let Some(op) = op else {
+                        word.get_or_insert_with(empty_word).text.push(c);
+                        self.pos += 1;
+                        continue;
+                    };
+                    self.pos += op.chars().count();
+                    let is_redirect = op.contains(['<', '>']) && !matches!(op, ";;" | ";&" | ";;&");
+                    if is_redirect {
+                        // `2>file`: an unquoted all-digit word right before is the descriptor.
+                        let fd = match &word {
+                            Some(w) if !w.quoted && !w.text.is_empty() && w.text.chars().all(|c| c.is_ascii_digit()) => {
+                                let fd = w.text.parse().ok();
+                                word = None;
+                                fd
+                            }
+                            _ => None,
+                        };
+                        finish(&mut word, &mut tokens, &mut delimiter_next, &mut heredocs);
+                        if matches!(op, "<<" | "<<-") {
+                            delimiter_next = Some(op == "<<-");
+                        }
+                        tokens.push(Token::Redirect { fd, op });
+                    } else {
+                        finish(&mut word, &mut tokens, &mut delimiter_next, &mut heredocs);
+                        tokens.push(Token::Op(op));
+                    }
+                }
+            }
+        }
+        finish(&mut word, &mut tokens, &mut delimiter_next, &mut heredocs);
+        Ok(tokens)
+    }
+
+    /// Characters up to (not including) `end`, consuming it. None at EOF.
+    fn until(&mut self, end: char) -> Option<String> {
+        let mut out = String::new();
+        while let Some(c) = self.peek(0) {
+            self.pos += 1;
+            if c == end {
+                return Some(out);
+            }
+            out.push(c);
+        }
+        None
+    }
+
+    /// Like `until`, but a backslash escapes the next character (kept as-is).
+    fn until_unescaped(&mut self, end: char) -> Option<String> {
+        let mut out = String::new();
+        while let Some(c) = self.peek(0) {
+            self.pos += 1;
+            if c == end {
+                return Some(out);
+            }
+            out.push(c);
+            if c == '\\' {
+                out.push(self.peek(0)?);
+                self.pos += 1;
+            }
+        }
+        None
+    }
+
+    /// Body of a `(`-opened construct up to its matching `close`, skipping
+    /// over quotes and nested parentheses.
+    fn balanced(&mut self, close: char) -> Result<String, String> {
+        let start = self.pos;
+        let mut depth = 1;
+        while let Some(c) = self.peek(0) {
+            self.pos += 1;
+            match c {
+                '\\' => self.pos += 1,
+                '\'' => { self.until('\'').ok_or("unterminated single quote")?; }
+                '"' => { self.until_unescaped('"').ok_or("unterminated double quote")?; }
+                '(' => depth += 1,
+                c if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(self.chars[start..self.pos - 1].iter().collect());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err("unterminated substitution".to_string())
+    }
+
+    /// After the opening `"`: read to the closing quote into `w`.
+    fn double_quoted(&mut self, w: &mut Word) -> Result<(), String> {
+        loop {
+            match self.peek(0) {
+                None => return Err("unterminated double quote".to_string()),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.peek(0) {
+                        Some('\n') => self.pos += 1,
+                        Some(c @ ('$' | '`' | '"' | '\\')) => {
+                            w.text.push(c);
+                            self.pos += 1;
+                        }
+                        _ => w.text.push('\\'),
+                    }
+                }
+                Some('`') => {
+                    self.pos += 1;
+                    let body = self.until_unescaped('`').ok_or("unterminated backquote")?;
+                    self.substitutions.push(body);
+                    w.dynamic = true;
+                }
+                Some('$') => self.dollar(w)?,
+                Some(c) => {
+                    w.text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// At a `$`: a substitution, a parameter expansion, or a literal dollar.
+    fn dollar(&mut self, w: &mut Word) -> Result<(), String> {
+        self.pos += 1;
+        match self.peek(0) {
+            Some('(') => {
+                self.pos += 1;
+                let body = self.balanced(')')?;
+                self.substitutions.push(body);
+                w.dynamic = true;
+            }
+            Some('{') => {
+                self.pos += 1;
+                let body = self.balanced('}')?;
+                w.text.push_str(&format!("${{{}}}", body));
+                w.dynamic = true;
+            }
+            Some(c) if c.is_alphanumeric() || "_@*#?$!-".contains(c) => {
+                w.text.push('$');
+                w.dynamic = true;
+                if c.is_alphabetic() || c == '_' {
+                    while let Some(c) = self.peek(0).filter(|c| c.is_alphanumeric() || *c == '_') {
+                        w.text.push(c);
+                        self.pos += 1;
+                    }
+                } else {
+                    w.text.push(c);
+                    self.pos += 1;
+                }
+            }
+            _ => w.text.push('$'),
+        }
+        Ok(())
+    }
+
+    /// Consume a here-document's lines through its delimiter line.
+    fn heredoc_body(&mut self, doc: &Heredoc) {
+        let mut body = String::new();
+        while self.pos < self.chars.len() {
+            let line = self.until('\n').unwrap_or_else(|| {
+                let rest: String = self.chars[self.pos..].iter().collect();
+                self.pos = self.chars.len();
+                rest
+            });
+            let check = if doc.strip_tabs { line.trim_start_matches('\t') } else { line.as_str() };
+            if check == doc.delimiter {
+                break;
+            }
+            body.push_str(&line);
+            body.push('\n');
+        }
+        if doc.expands && (body.contains("$(") || body.contains('`')) {
+            self.substitutions.push(body);
+        }
+    }
+}
+
+fn empty_word() -> Word {
+    Word { text: String::new(), quoted: false, dynamic: false }
+}
+
+/// Decode the escapes of a `$'...'` string.
+fn ansi_c_unescape(raw: &str) -> String {
+    let mut out = String::new();
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        let Some(e) = chars.next() else { break };
+        let (radix, max_len) = match e {
+            'x' => (16, 2),
+            'u' => (16, 4),
+            'U' => (16, 8),
+            '0'..='7' => (8, 3),
+            _ => {
+                out.push(match e {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    'a' => '\x07',
+                    'b' => '\x08',
+                    'e' | 'E' => '\x1b',
+                    'f' => '\x0c',
+                    'v' => '\x0b',
+                    other => other,
+                });
+                continue;
+            }
+        };
+        let mut digits = String::new();
+        if radix == 8 {
+            digits.push(e);
+        }
+        while digits.len() < max_len {
+            match chars.peek() {
+                Some(d) if d.is_digit(radix) => {
+                    digits.push(*d);
+                    chars.next();
+                }
+                _ => break,
+            }
+        }
+        if let Some(ch) = u32::from_str_radix(&digits, radix).ok().and_then(char::from_u32) {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+// ── Parser ─────────────────────────────────────────────────────────────────
+
+/// Words that open or close compound commands; skipped in command position.
+const RESERVED: &[&str] = &["if", "then", "else", "elif", "fi", "do", "done", "while", "until", "!", "{", "}", "time"];
+
+#[derive(Default)]
+struct Parser {
+    script: Script,
+    current: SimpleCommand,
+    piped: bool,
+    /// Open `case` statements.
+    cases: usize,
+    /// Reading `case` patterns, up to the `)` that ends them.
+    in_patterns: bool,
+    /// Skipping a `for NAME in words` / `case WORD in` header.
+    in_header: bool,
+}
+
+impl Parser {
+    fn parse(mut self, tokens: Vec<Token>) -> Script {
+        let mut tokens = tokens.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            match token {
+                Token::Word(w) => self.word(w),
+                Token::Redirect { fd, op } => {
+                    if let Some(Token::Word(target)) = tokens.next_if(|t| matches!(t, Token::Word(_))) {
+                        self.current.redirects.push(Redirect { fd, op, target });
+                    }
+                }
+                Token::Op("(") => {
+                    // `name()` defines a function; any other `(` opens a subshell.
+                    if self.current.argv.len() == 1 && tokens.peek() == Some(&Token::Op(")")) {
+                        tokens.next();
+                        self.script.defines_function = true;
+                        self.current = SimpleCommand::default();
+                    } else {
+                        self.end(false);
+                    }
+                }
+                Token::Op(")") if self.in_patterns => self.in_patterns = false,
+                Token::Op(";;" | ";&" | ";;&") => {
+                    self.end(false);
+                    self.in_patterns = self.cases > 0;
+                }
+                Token::Op(op) => {
+                    if self.in_patterns {
+                        continue;
+                    }
+                    let header = self.in_header;
+                    self.in_header = false;
+                    if !header {
+                        self.end(matches!(op, "|" | "|&"));
+                    }
+                    if header && self.cases > 0 && op == ";" {
+                        // `case x in` may be followed by a newline before the first pattern.
+                        self.in_patterns = true;
+                    }
+                }
+            }
+        }
+        self.end(false);
+        self.script
+    }
+
+    fn word(&mut self, w: Word) {
+        let keyword = !w.quoted && self.current.is_empty();
+        if keyword && w.text == "esac" {
+            self.cases = self.cases.saturating_sub(1);
+            self.in_patterns = false;
+            return;
+        }
+        if self.in_patterns {
+            return;
+        }
+        if self.in_header {
+            if w.text == "in" && self.cases > 0 && !w.quoted {
+                self.in_header = false;
+                self.in_patterns = true;
+            } else if w.text == "do" && !w.quoted {
+                self.in_header = false;
+            }
+            return;
+        }
+        if keyword {
+            match w.text.as_str() {
+                "for" | "select" => return self.in_header = true,
+                "case" => {
+                    self.cases += 1;
+                    self.in_header = true;
+                    return;
+                }
+                "function" => {
+                    self.script.defines_function = true;
+                    return;
+                }
+                t if RESERVED.contains(&t) => return,
+                _ => {}
+            }
+        }
+        if self.current.argv.is_empty() && is_assignment(&w) {
+            self.current.assignments.push(w);
+        } else {
+            self.current.argv.push(w);
+        }
+    }
+
+    /// Close the current simple command; `pipe` marks the next one's stdin as piped.
+    fn end(&mut self, pipe: bool) {
+        let mut cmd = std::mem::take(&mut self.current);
+        if !cmd.is_empty() {
+            cmd.piped_input = self.piped;
+            self.script.commands.push(cmd);
+        }
+        self.piped = pipe;
+    }
+}
+
+/// `NAME=value`, with NAME a valid unquoted identifier.
+fn is_assignment(w: &Word) -> bool {
+    match w.text.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argvs(input: &str) -> Vec<Vec<String>> {
+        parse(input).unwrap().commands.iter()
+            .map(|c| c.argv.iter().map(|w| w.text.clone()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn quoted_words_come_back_whole() {
+        for word in ["/work space", "it's", "'; rm -rf / #", "$(id) `id` $HOME", "a\\'b\n", ""] {
+            assert_eq!(split_words(&quote(word)).unwrap(), [word]);
+            assert!(parse(&format!("cd {}", quote(word))).unwrap().substitutions.is_empty());
+        }
+    }
+
+    #[test]
+    fn splits_lists_and_removes_quotes() {
+        assert_eq!(argvs("ls -la; r''m -rf x && echo \"a b\" || \\rm y"), vec![
+            vec!["ls", "-la"], vec!["rm", "-rf", "x"], vec!["echo", "a b"], vec!["rm", "y"],
+        ]);
+        assert_eq!(argvs("$'\\x72m' -r x # rm -rf /"), vec![vec!["rm", "-r", "x"]]);
+        assert_eq!(argvs("if true; then (cd /tmp && make); fi"), vec![
+            vec!["true"], vec!["cd", "/tmp"], vec!["make"],
+        ]);
+        assert_eq!(argvs("for f in a b; do rm $f; done"), vec![vec!["rm", "$f"]]);
+        assert_eq!(argvs("case $x in a|b) ls ;; *) rm -rf y ;; esac"), vec![vec!["ls"], vec!["rm", "-rf", "y"]]);
+
+        let script = parse("FOO=1 cat <<'EOF' 2>/dev/null | sh\n$(not run)\nEOF\necho done").unwrap();
+        assert!(script.substitutions.is_empty(), "quoted here-doc isn't expanded");
+        let cat = &script.commands[0];
+        assert_eq!(cat.assignments[0].text, "FOO=1");
+        assert_eq!((cat.redirects[0].op, cat.redirects[0].target.text.as_str()), ("<<", "EOF"));
+        assert_eq!((cat.redirects[1].fd, cat.redirects[1].op), (Some(2), ">"));
+        assert!(script.commands[1].piped_input && !script.commands[2].piped_input);
+        assert_eq!(script.commands[2].args(), ["echo", "done"]);
+
+        let script = parse("echo \"$(whoami)\" `id` ${HOME} <(ls)").unwrap();
+        assert_eq!(script.substitutions, ["whoami", "id", "ls"]);
+        assert!(script.commands[0].argv[3].dynamic);
+        assert!(parse(":(){ :|:& };:").unwrap().defines_function);
+        assert!(parse("echo 'unterminated").is_err());
+    }
+}
@@ -0,0 +1,238 @@
+//! Streaming chat completions (OpenAI-style server-sent events)
+//!
+//! With `"stream": true` the API answers with `data: {json}` lines, each
+//! carrying a `choices[0].delta`. Text arrives as `delta.content`; tool calls
+//! arrive in fragments keyed by `index` — the id and name first, then the
+//! arguments string a few characters at a time. `StreamAssembler` stitches
+//! those back together into the same `choices[0].message` shape a
+//! non-streaming response has, so the agent loop handles both identically.
+
+use serde_json::Value;
+
+/// Splits a byte stream into SSE `data:` payloads. Bytes are buffered until
+/// a full line is available, so multi-byte characters split across network
+/// chunks decode correctly.
+#[derive(Default)]
+pub struct SseDecoder {
+    buf: Vec<u8>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed raw bytes; returns every complete `data:` payload seen so far.
+    /// Comment lines (`: keep-alive`) and other fields are dropped.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buf.extend_from_slice(bytes);
+        let mut out = Vec::new();
+        while let Some(pos) = self.buf.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if let Some(data) = line.strip_prefix("data:") {
+                out.push(data.trim_start().to_string());
+            }
+        }
+        out
+    }
+}
+
+#[derive(Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Accumulates streamed deltas into a complete assistant message.
+#[derive(Default)]
+pub struct StreamAssembler {
+    content: String,
+    tool_calls: Vec<PartialToolCall>,
+    finish_reason: Option<String>,
+    usage: Option<Value>,
+    error: Option<Value>,
+    done: bool,
+}
+
+impl StreamAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply one `data:` payload. Returns the text delta it carried, if any.
+    pub fn apply(&mut self, data: &str) -> Option<String> {
+        if data == "[DONE]" {
+            self.done = true;
+            return None;
+        }
+        let chunk: Value = serde_json::from_str(data).ok()?;
+
+        // Providers report mid-stream failures as a chunk with an `error` object.
+        if let Some(err) = chunk.get("error") {
+            self.error = Some(err.clone());
+            return None;
+        }
+        if chunk.get("usage").is_some_and(|u| !u.is_null()) {
+            self.usage = Some(chunk["usage"].clone());
+        }
+
+        let choice = &chunk["choices"][0];
+        if let Some(reason) = choice["finish_reason"].as_str() {
+            self.finish_reason = Some(reason.to_string());
+        }
+        let delta = &choice["delta"];
+
+        if let Some(calls) = delta["tool_calls"].as_array() {
+            for (pos, call) in calls.iter().enumerate() {
+                // Some providers omit `index` when they send each call whole.
+                let index = call["index"].as_u64().map(|i| i as usize).unwrap_or(pos);
+                if self.tool_calls.len() <= index {
+                    self.tool_calls.resize_with(index + 1, PartialToolCall::default);
+                }
+                let slot = &mut self.tool_calls[index];
+                if let Some(id) = call["id"].as_str() {
+                    slot.id = id.to_string();
+                }
+                if let Some(name) = call["function"]["name"].as_str() {
+                    slot.name.push_str(name);
+                }
+                if let Some(args) = call["function"]["arguments"].as_str() {
+                    slot.arguments.push_str(args);
+                }
+            }
+        }
+
+        match delta["content"].as_str() {
+            Some(text) if !text.is_empty() => {
+                self.content.push_str(text);
+                Some(text.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// The assembled response, shaped like a non-streaming completion:
+    /// `{"choices":[{"message":{..},"finish_reason":..}],"usage":..}`, or
+    /// `{"error":{..}}` if the stream reported one.
+    pub fn finish(self) -> Value {
+        if let Some(err) = self.error {
+            return serde_json::json!({ "error": err });
+        }
+
+        let mut message = serde_json::json!({
+            "role": "assistant",
+            "content": if self.content.is_empty() { Value::Null } else { Value::String(self.content) },
+        });
+        let calls: Vec<Value> = self.tool_calls.into_iter()
+            .filter(|c| !c.name.is_empty())
+            .map(|c| serde_json::json!({
+                "id": c.id,
+                "type": "function",
+                "function": { "name": c.name, "arguments": c.arguments },
+            }))
+            .collect();
+        if !calls.is_empty() {
+            message["tool_calls"] = Value::Array(calls);
+        }
+
+        let mut out = serde_json::json!({
+            "choices": [{ "message": message, "finish_reason": self.finish_reason }],
+        });
+        if let Some(usage) = self.usage {
+            out["usage"] = usage;
+        }
+        out
+    }
+}
+
+/// True when the response is an event stream rather than a JSON body
+/// (errors are usually returned as plain JSON even when streaming was asked for).
+pub fn is_event_stream(resp: &reqwest::Response) -> bool {
+    resp.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("text/event-stream"))
+}
+
+/// Read a streamed completion to the end, calling `on_delta` for each text
+/// fragment. Returns the assembled response (see `StreamAssembler::finish`).
+pub async fn read_stream<F>(mut resp: reqwest::Response, mut on_delta: F) -> Result<Value, String>
+where
+    F: FnMut(&str),
+{
+    let mut decoder = SseDecoder::new();
+    let mut assembler = StreamAssembler::new();
+
+    while let Some(bytes) = resp.chunk().await.map_err(|e| format!("Stream interrupted: {}", e))? {
+        for data in decoder.feed(&bytes) {
+            if let Some(text) = assembler.apply(&data) {
+                on_delta(&text);
+            }
+        }
+        if assembler.is_done() {
+            break;
+        }
+    }
+
+    Ok(assembler.finish())
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assembles_text_and_tool_call_fragments() {
+        let stream = concat!(
+            ": OPENROUTER PROCESSING\n\n",
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\",\"content\":\"Let me \"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"check — ok\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",\"type\":\"function\",\"function\":{\"name\":\"shell\",\"arguments\":\"\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"{\\\"comm\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"and\\\":\\\"ls\\\"}\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"tool_calls\"}],\"usage\":{\"total_tokens\":42}}\n\n",
+            "data: [DONE]\n\n",
+        );
+
+        // Feed in awkward 7-byte pieces, splitting the multi-byte dash.
+        let mut decoder = SseDecoder::new();
+        let mut asm = StreamAssembler::new();
+        let mut text = String::new();
+        for piece in stream.as_bytes().chunks(7) {
+            for data in decoder.feed(piece) {
+                if let Some(t) = asm.apply(&data) {
+                    text.push_str(&t);
+                }
+            }
+        }
+        assert!(asm.is_done());
+        assert_eq!(text, "Let me check — ok");
+
+        let json = asm.finish();
+        let msg = &json["choices"][0]["message"];
+        assert_eq!(msg["content"], "Let me check — ok");
+        assert_eq!(msg["tool_calls"][0]["id"], "call_1");
+        assert_eq!(msg["tool_calls"][0]["function"]["name"], "shell");
+        assert_eq!(msg["tool_calls"][0]["function"]["arguments"], "{\"command\":\"ls\"}");
+        assert_eq!(json["choices"][0]["finish_reason"], "tool_calls");
+        assert_eq!(json["usage"]["total_tokens"], 42);
+    }
+
+    #[test]
+    fn mid_stream_error_is_reported() {
+        let mut asm = StreamAssembler::new();
+        asm.apply("{\"choices\":[{\"delta\":{\"content\":\"partial\"}}]}");
+        asm.apply("{\"error\":{\"message\":\"provider overloaded\",\"code\":502}}");
+        let json = asm.finish();
+        assert_eq!(json["error"]["message"], "provider overloaded");
+    }
+}
@@ -0,0 +1,415 @@
+//! Tool trait and registry
+//!
+//! Every tool the model can call — built-ins (`tools::builtin_tools`),
+//! mission tools (`MissionExecutor::tool_specs`) and MCP server tools —
+//! is a `Tool` registered into a `ToolRegistry`. A turn builds its registry
+//! with `ToolRegistry::for_turn`, which applies two filters:
+//!
+//!   capabilities  → tools needing a client the agent wasn't given (skills,
+//!                   missions) are left out
+//!   blocked_tools → per-agent deny list (Sentry, economy check-ins, mission
+//!                   workers); blocked tools are neither advertised nor run
+//!
+//! The registry is both what the model is shown and what a tool call is
+//! dispatched through, so the two can't drift apart.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+
+use serde_json::Value;
+
+use crate::agent::AgentConfig;
+use crate::approval::Audited;
+use crate::cancel::CancelToken;
+use crate::mcp::{McpClient, McpTool};
+use crate::shell::ShellPolicy;
+use crate::tools::{self, MemoryBackend};
+use crate::workspace::WorkspaceExecutor;
+
+pub type ToolFuture<'a> = Pin<Box<dyn Future<Output = String> + Send + 'a>>;
+
+/// What a tool can do to the world, least to most dangerous.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ToolRisk {
+    /// Reads local or remote state, changes nothing.
+    ReadOnly,
+    /// Changes Argus's own state — files, memory, skills, git.
+    Write,
+    /// Acts on third parties — posts, arbitrary HTTP requests, MCP servers.
+    External,
+    /// Runs arbitrary code or commands.
+    Execute,
+}
+
+/// A service a tool needs from the agent's environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    Filesystem,
+    Workspace,
+    Network,
+    Memory,
+    Skills,
+    Missions,
+    Mcp,
+}
+
+impl Capability {
+    /// Whether `config` provides this capability. Only the optional clients
+    /// can be missing; tools with missing credentials (Brave key, Discord
+    /// token) stay listed and explain what to configure.
+    pub fn available(self, config: &AgentConfig) -> bool {
+        match self {
+            Capability::Skills => config.skills.is_some(),
+            Capability::Missions => config.mission_executor.is_some(),
+            _ => true,
+        }
+    }
+}
+
+/// Static description of a tool — what the model sees plus its policy metadata.
+#[derive(Debug, Clone)]
+pub struct ToolSpec {
+    pub name: String,
+    pub description: String,
+    /// JSON schema of the arguments object.
+    pub parameters: Value,
+    pub risk: ToolRisk,
+    pub capabilities: Vec<Capability>,
+}
+
+impl ToolSpec {
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: Value,
+        risk: ToolRisk,
+        capabilities: &[Capability],
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            parameters,
+            risk,
+            capabilities: capabilities.to_vec(),
+        }
+    }
+
+    /// Build from an OpenAI-style `{"type": "function", "function": {...}}` schema.
+    pub fn from_schema(schema: &Value, risk: ToolRisk, capabilities: &[Capability]) -> Option<Self> {
+        let function = &schema["function"];
+        Some(Self::new(
+            function["name"].as_str().filter(|n| !n.is_empty())?,
+            function["description"].as_str().unwrap_or_default(),
+            function["parameters"].clone(),
+            risk,
+            capabilities,
+        ))
+    }
+
+    /// The OpenAI-style function schema sent to the model.
+    pub fn schema(&self) -> Value {
+        serde_json::json!({
+            "type": "function",
+            "function": {
+                "name": self.name,
+                "description": self.description,
+                "parameters": self.parameters,
+            }
+        })
+    }
+}
+
+/// Everything a tool may use while it runs. Built by the agent loop for each
+/// batch of calls; parallel-safe calls in one batch share it.
+pub struct ToolContext<'a> {
+    pub config: &'a AgentConfig,
+    /// Model making the call — the fallback model once one has taken over.
+    pub model: &'a str,
+    pub shell_policy: &'a ShellPolicy,
+    pub memory: &'a dyn MemoryBackend,
+    pub http: &'a reqwest::Client,
+    pub workspace: Arc<dyn WorkspaceExecutor>,
+    pub mcp: tokio::sync::Mutex<&'a mut McpClient>,
+    pub cancel: &'a CancelToken,
+    /// The registry this call was dispatched from, for introspection.
+    pub tools: &'a ToolRegistry,
+}
+
+impl ToolContext<'_> {
+    /// The agent's human approval prompter, if it has one, with every
+    /// decision recorded in the audit chain.
+    pub fn prompter(&self) -> Option<Audited<'_>> {
+        self.config.shell_prompter.as_deref().map(|inner| Audited {
+            inner,
+            audit: self.config.audit.as_deref(),
+            agent: self.model,
+        })
+    }
+}
+
+pub trait Tool: Send + Sync {
+    fn spec(&self) -> &ToolSpec;
+
+    fn execute<'a>(&'a self, args: &'a Value, ctx: &'a ToolContext<'a>) -> ToolFuture<'a>;
+
+    fn name(&self) -> &str {
+        &self.spec().name
+    }
+
+    fn schema(&self) -> Value {
+        self.spec().schema()
+    }
+
+    fn risk(&self) -> ToolRisk {
+        self.spec().risk
+    }
+
+    fn capabilities(&self) -> &[Capability] {
+        &self.spec().capabilities
+    }
+
+    /// Whether calls may run concurrently with other parallel-safe calls
+    /// from the same model response. Read-only tools by default.
+    fn parallel_safe(&self) -> bool {
+        self.risk() == ToolRisk::ReadOnly
+    }
+}
+
+// ── Tool kinds ─────────────────────────────────────────────────────────────
+
+/// Built-in tool: a spec and a plain function pointer.
+pub struct BuiltinTool {
+    spec: ToolSpec,
+    run: for<'a> fn(&'a Value, &'a ToolContext<'a>) -> ToolFuture<'a>,
+}
+
+impl BuiltinTool {
+    pub fn new(spec: ToolSpec, run: for<'a> fn(&'a Value, &'a ToolContext<'a>) -> ToolFuture<'a>) -> Self {
+        Self { spec, run }
+    }
+}
+
+impl Tool for BuiltinTool {
+    fn spec(&self) -> &ToolSpec {
+        &self.spec
+    }
+
+    fn execute<'a>(&'a self, args: &'a Value, ctx: &'a ToolContext<'a>) -> ToolFuture<'a> {
+        (self.run)(args, ctx)
+    }
+}
+
+/// Mission tool, dispatched to the injected `MissionExecutor`.
+struct MissionTool {
+    spec: ToolSpec,
+}
+
+impl Tool for MissionTool {
+    fn spec(&self) -> &ToolSpec {
+        &self.spec
+    }
+
+    fn execute<'a>(&'a self, args: &'a Value, ctx: &'a ToolContext<'a>) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let Some(exec) = &ctx.config.mission_executor else {
+                return "Mission tools not available — mission executor not configured.".to_string();
+            };
+            exec.execute(&self.spec.name, args, ctx.model).await
+                .unwrap_or_else(|| format!("Unknown tool: {}", self.spec.name))
+        })
+    }
+}
+
+/// Tool served by an MCP server. `spec.name` is the sanitized (and, on
+/// collision, server-prefixed) name the model sees; `tool` the server's own.
+struct McpServerTool {
+    spec: ToolSpec,
+    server: String,
+    tool: String,
+}
+
+impl Tool for McpServerTool {
+    fn spec(&self) -> &ToolSpec {
+        &self.spec
+    }
+
+    fn execute<'a>(&'a self, args: &'a Value, ctx: &'a ToolContext<'a>) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let mut mcp = ctx.mcp.lock().await;
+            match mcp.call_tool_cancellable(&self.server, &self.tool, args.clone(), ctx.cancel).await {
+                Ok(output) => output,
+                Err(e) => format!("Error: {}", e),
+            }
+        })
+    }
+}
+
+// ── Registry ───────────────────────────────────────────────────────────────
+
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<Arc<dyn Tool>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in tools. Specs are built once per process.
+    pub fn builtin() -> Self {
+        static BUILTINS: OnceLock<Vec<Arc<dyn Tool>>> = OnceLock::new();
+        Self { tools: BUILTINS.get_or_init(tools::builtin_tools).clone() }
+    }
+
+    /// Everything `config` and `mcp` provide, minus capability gaps and
+    /// `config.blocked_tools`.
+    pub fn for_turn(config: &AgentConfig, mcp: &McpClient) -> Self {
+        let mut registry = Self::builtin();
+        if let Some(exec) = &config.mission_executor {
+            for spec in exec.tool_specs() {
+                registry.register(Arc::new(MissionTool { spec }));
+            }
+        }
+        registry.register_mcp(mcp);
+        registry.retain(|t| t.capabilities().iter().all(|c| c.available(config)));
+        registry.without(&config.blocked_tools)
+    }
+
+    /// Add `tool`. A name that is already taken is skipped (and logged) —
+    /// the first registration wins.
+    pub fn register(&mut self, tool: Arc<dyn Tool>) -> bool {
+        if self.get(tool.name()).is_some() {
+            eprintln!("[tools] skipping duplicate tool: {}", tool.name());
+            return false;
+        }
+        self.tools.push(tool);
+        true
+    }
+
+    /// Register every tool of every connected MCP server. Names are
+    /// sanitized; one that collides with an existing tool is prefixed with
+    /// its server's name, so an MCP server can't shadow a built-in.
+    pub fn register_mcp(&mut self, mcp: &McpClient) {
+        for mcp_tool in mcp.all_tools() {
+            let raw_name = sanitize_tool_name(&mcp_tool.name);
+            let name = if self.get(&raw_name).is_some() {
+                let prefix = sanitize_tool_name(&mcp_tool.server_name.replace(['-', ' '], "_"));
+                sanitize_tool_name(&format!("{}_{}", prefix, raw_name))
+            } else {
+                raw_name
+            };
+            let spec = ToolSpec::new(
+                name,
+                mcp_tool.description.clone().unwrap_or_default(),
+                mcp_tool.input_schema.clone(),
+                mcp_risk(mcp_tool),
+                &[Capability::Mcp],
+            );
+            self.register(Arc::new(McpServerTool {
+                spec,
+                server: mcp_tool.server_name.clone(),
+                tool: mcp_tool.name.clone(),
+            }));
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn Tool>> {
+        self.tools.iter().find(|t| t.name() == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn Tool>> {
+        self.tools.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    /// Keep only the tools `keep` accepts.
+    pub fn retain(&mut self, mut keep: impl FnMut(&dyn Tool) -> bool) {
+        self.tools.retain(|t| keep(t.as_ref()));
+    }
+
+    /// This registry minus the named tools.
+    pub fn without(mut self, names: &[String]) -> Self {
+        if !names.is_empty() {
+            let blocked: HashSet<&str> = names.iter().map(String::as_str).collect();
+            self.retain(|t| !blocked.contains(t.name()));
+        }
+        self
+    }
+
+    /// This registry minus tools riskier than `max`.
+    pub fn up_to(mut self, max: ToolRisk) -> Self {
+        self.retain(|t| t.risk() <= max);
+        self
+    }
+
+    /// Function schemas for the model, in registration order.
+    pub fn schemas(&self) -> Vec<Value> {
+        self.tools.iter().map(|t| t.schema()).collect()
+    }
+}
+
+/// MCP servers may mark tools `readOnlyHint`; everything else is assumed to
+/// act on the outside world.
+fn mcp_risk(tool: &McpTool) -> ToolRisk {
+    let read_only = tool.annotations
+        .as_ref()
+        .and_then(|a| a["readOnlyHint"].as_bool())
+        .unwrap_or(false);
+    if read_only { ToolRisk::ReadOnly } else { ToolRisk::External }
+}
+
+/// Sanitize a tool name: only alphanumeric, underscores, hyphens. Max 64 chars.
+fn sanitize_tool_name(name: &str) -> String {
+    let clean: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    if clean.chars().count() > 64 { clean.chars().take(64).collect() } else { clean }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtins_register_once_and_filter_by_name_risk_and_capability() {
+        let builtin = ToolRegistry::builtin();
+        let mut seen = HashSet::new();
+        for tool in builtin.iter() {
+            assert!(seen.insert(tool.name().to_string()), "duplicate tool {}", tool.name());
+            let schema = tool.schema();
+            assert_eq!(schema["function"]["name"], tool.name());
+            assert_eq!(schema["function"]["parameters"]["type"], "object", "{}", tool.name());
+        }
+
+        // A second registration under a taken name is refused.
+        let mut registry = builtin.clone();
+        let shell = ToolSpec::new("shell", "impostor", serde_json::json!({"type": "object"}), ToolRisk::ReadOnly, &[]);
+        assert!(!registry.register(Arc::new(MissionTool { spec: shell })));
+        assert_eq!(registry.get("shell").unwrap().risk(), ToolRisk::Execute);
+
+        // Blocked tools and capability gaps drop out of the turn's registry.
+        let mut config = AgentConfig::new(String::new());
+        config.blocked_tools = vec!["shell".to_string(), "write_file".to_string()];
+        let turn = ToolRegistry::for_turn(&config, &McpClient::new());
+        assert!(turn.get("shell").is_none() && turn.get("write_file").is_none());
+        assert!(turn.get("recall_skill").is_none(), "no skills client configured");
+        assert!(turn.get("read_file").is_some());
+
+        let read_only = builtin.up_to(ToolRisk::ReadOnly);
+        assert!(read_only.iter().all(|t| t.risk() == ToolRisk::ReadOnly));
+        assert!(read_only.get("recall").is_some() && read_only.get("remember").is_none());
+    }
+}
@@ -1,453 +1,437 @@
 //! Tool definitions and execution
 //!
 //! All built-in tools live here. Shared across TUI, Telegram, and any future frontends.
+//! Each is registered into the `ToolRegistry` by `builtin_tools`.
 
-use crate::shell::{ShellPolicy, PermissionPrompter, PermissionRequest, PermissionDecision};
+use crate::agent::AgentConfig;
+use crate::approval::Audited;
+use crate::shell::{ShellPolicy, PermissionPrompter};
+use crate::shell_syntax;
 use crate::skills::{NewSkill, SkillsClient};
-use serde_json::Value;
+use crate::tool_registry::{BuiltinTool, Capability, Tool, ToolContext, ToolFuture, ToolRisk, ToolSpec};
+use crate::workspace::WorkspaceExecutor;
+use serde_json::{json, Value};
+use std::sync::Arc;
 
 const MAX_FILE_CHARS: usize = 24_000; // ~6k tokens — enough for serious files without overflow
 const MAX_DIR_ENTRIES: usize = 200;
 const MAX_SEARCH_RESULTS: usize = 6;
 
-pub fn builtin_tool_schemas() -> Vec<Value> {
-    serde_json::json!([
-        {
-            "type": "function",
-            "function": {
-                "name": "read_file",
-                "description": "Read the contents of a file. Supports large files via pagination — if the file is truncated, re-call with offset set to the value shown at the end of the output.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "path":   { "type": "string", "description": "Path to the file" },
-                        "offset": { "type": "number", "description": "Character offset to start reading from (for large files — use value from previous read)" },
-                        "limit":  { "type": "number", "description": "Max characters to read (default and max: 24000)" }
-                    },
-                    "required": ["path"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "list_directory",
-                "description": "List files and directories in a given path. Returns up to 200 entries — use a more specific path for large directories.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "path": { "type": "string", "description": "The directory path to list" }
-                    },
-                    "required": ["path"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "write_file",
-                "description": "Write content to a file",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "path": { "type": "string", "description": "The path to write to" },
-                        "content": { "type": "string", "description": "The content to write" }
-                    },
-                    "required": ["path", "content"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "shell",
-                "description": "Execute a shell command and return output. Runs under an allowlist policy.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "command": { "type": "string", "description": "The shell command to execute" }
-                    },
-                    "required": ["command"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "web_search",
-                "description": "Search the web for current information, news, facts, or anything you don't know. Uses Brave Search API.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "query": { "type": "string", "description": "The search query" }
-                    },
-                    "required": ["query"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "remember",
-                "description": "Store information in persistent memory.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "content": { "type": "string", "description": "The information to remember" },
-                        "type": {
-                            "type": "string",
-                            "enum": ["fact", "preference", "task", "learning", "relationship"],
-                            "description": "Category of memory"
-                        },
-                        "importance": { "type": "number", "description": "Importance score 1-10" },
-                        "reasoning": { "type": "string", "description": "Why this is worth remembering" }
-                    },
-                    "required": ["content", "type"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "recall",
-                "description": "Search and retrieve memories.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "query": { "type": "string", "description": "Search term to find relevant memories" },
-                        "type": {
-                            "type": "string",
-                            "enum": ["fact", "preference", "task", "learning", "relationship"],
-                            "description": "Filter by memory type"
-                        },
-                        "limit": { "type": "number", "description": "Max memories to return (default 10)" }
-                    }
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "forget",
-                "description": "Delete memories matching a search term.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "content_match": { "type": "string", "description": "Text to match for deletion" }
-                    },
-                    "required": ["content_match"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "run_python",
-                "description": "Execute Python 3 code in the workspace sandbox and return stdout/stderr. Use for data analysis, computations, generating files, or anything requiring a proper Python runtime. Output from print() appears in stdout.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "code": { "type": "string", "description": "Python 3 source code to execute" },
-                        "timeout": { "type": "number", "description": "Max execution seconds (default 30, max 120)" }
+/// Every built-in tool: schema, risk, required capabilities and executor
+/// side by side. Registered by `ToolRegistry::builtin`.
+pub fn builtin_tools() -> Vec<Arc<dyn Tool>> {
+    vec![
+        builtin(
+            "read_file",
+            "Read the contents of a file. Supports large files via pagination — if the file is truncated, re-call with offset set to the value shown at the end of the output.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "path":   { "type": "string", "description": "Path to the file" },
+                    "offset": { "type": "number", "description": "Character offset to start reading from (for large files — use value from previous read)" },
+                    "limit":  { "type": "number", "description": "Max characters to read (default and max: 24000)" }
+                },
+                "required": ["path"]
+            }),
+            ToolRisk::ReadOnly,
+            &[Capability::Filesystem],
+            |args, _| Box::pin(async move { tool_read_file(args) }),
+        ),
+        builtin(
+            "list_directory",
+            "List files and directories in a given path. Returns up to 200 entries — use a more specific path for large directories.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "The directory path to list" }
+                },
+                "required": ["path"]
+            }),
+            ToolRisk::ReadOnly,
+            &[Capability::Filesystem],
+            |args, _| Box::pin(async move { tool_list_directory(args) }),
+        ),
+        builtin(
+            "write_file",
+            "Write content to a file",
+            json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "The path to write to" },
+                    "content": { "type": "string", "description": "The content to write" }
+                },
+                "required": ["path", "content"]
+            }),
+            ToolRisk::Write,
+            &[Capability::Filesystem],
+            |args, _| Box::pin(async move { tool_write_file(args) }),
+        ),
+        builtin(
+            "shell",
+            "Execute a shell command and return output. Runs under an allowlist policy.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "command": { "type": "string", "description": "The shell command to execute" }
+                },
+                "required": ["command"]
+            }),
+            ToolRisk::Execute,
+            &[Capability::Workspace],
+            |args, ctx| Box::pin(async move { tool_shell(args, ctx.shell_policy, ctx.prompter(), ctx.config.sonnet_guard.clone(), ctx.config, ctx.model, &*ctx.workspace).await }),
+        ),
+        builtin(
+            "web_search",
+            "Search the web for current information, news, facts, or anything you don't know. Uses Brave Search API.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "The search query" }
+                },
+                "required": ["query"]
+            }),
+            ToolRisk::ReadOnly,
+            &[Capability::Network],
+            |args, ctx| Box::pin(async move { tool_web_search(args, ctx.http, ctx.config.brave_search_key.as_deref()).await }),
+        ),
+        builtin(
+            "remember",
+            "Store information in persistent memory.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "content": { "type": "string", "description": "The information to remember" },
+                    "type": {
+                        "type": "string",
+                        "enum": ["fact", "preference", "task", "learning", "relationship"],
+                        "description": "Category of memory"
                     },
-                    "required": ["code"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "run_node",
-                "description": "Execute JavaScript/Node.js code in the workspace sandbox and return stdout/stderr. Use for JSON processing, web scraping logic, or anything needing a Node runtime. console.log() output appears in stdout.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "code": { "type": "string", "description": "JavaScript (Node.js) source code to execute" },
-                        "timeout": { "type": "number", "description": "Max execution seconds (default 30, max 120)" }
+                    "importance": { "type": "number", "description": "Importance score 1-10" },
+                    "reasoning": { "type": "string", "description": "Why this is worth remembering" }
+                },
+                "required": ["content", "type"]
+            }),
+            ToolRisk::Write,
+            &[Capability::Memory],
+            |args, ctx| Box::pin(async move { tool_remember(args, ctx.memory) }),
+        ),
+        builtin(
+            "recall",
+            "Search and retrieve memories.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Search term to find relevant memories" },
+                    "type": {
+                        "type": "string",
+                        "enum": ["fact", "preference", "task", "learning", "relationship"],
+                        "description": "Filter by memory type"
                     },
-                    "required": ["code"]
+                    "limit": { "type": "number", "description": "Max memories to return (default 10)" }
                 }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "run_wasm",
-                "description": "Execute a WebAssembly (WASM) binary in a fully isolated sandbox — no filesystem, no network, no subprocess access. Use this to run untrusted or generated computational code safely. The WASM module must export a function named 'run' that takes no arguments. Pass the WASM binary as a base64-encoded string.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "wasm_base64": { "type": "string", "description": "Base64-encoded WASM binary to execute" },
-                        "function": { "type": "string", "description": "Exported function to call (default: 'run')" }
-                    },
-                    "required": ["wasm_base64"]
+            }),
+            ToolRisk::ReadOnly,
+            &[Capability::Memory],
+            |args, ctx| Box::pin(async move { tool_recall(args, ctx.memory) }),
+        ),
+        builtin(
+            "forget",
+            "Delete memories matching a search term.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "content_match": { "type": "string", "description": "Text to match for deletion" }
+                },
+                "required": ["content_match"]
+            }),
+            ToolRisk::Write,
+            &[Capability::Memory],
+            |args, ctx| Box::pin(async move { tool_forget(args, ctx.memory) }),
+        ),
+        builtin(
+            "run_python",
+            "Execute Python 3 code in the workspace sandbox and return stdout/stderr. Use for data analysis, computations, generating files, or anything requiring a proper Python runtime. Output from print() appears in stdout.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "code": { "type": "string", "description": "Python 3 source code to execute" },
+                    "timeout": { "type": "number", "description": "Max execution seconds (default 30, max 120)" }
+                },
+                "required": ["code"]
+            }),
+            ToolRisk::Execute,
+            &[Capability::Workspace],
+            |args, ctx| Box::pin(async move { tool_run_code("python", args, &*ctx.workspace).await }),
+        ),
+        builtin(
+            "run_node",
+            "Execute JavaScript/Node.js code in the workspace sandbox and return stdout/stderr. Use for JSON processing, web scraping logic, or anything needing a Node runtime. console.log() output appears in stdout.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "code": { "type": "string", "description": "JavaScript (Node.js) source code to execute" },
+                    "timeout": { "type": "number", "description": "Max execution seconds (default 30, max 120)" }
+                },
+                "required": ["code"]
+            }),
+            ToolRisk::Execute,
+            &[Capability::Workspace],
+            |args, ctx| Box::pin(async move { tool_run_code("javascript", args, &*ctx.workspace).await }),
+        ),
+        builtin(
+            "run_wasm",
+            "Execute a WebAssembly (WASM) binary in a fully isolated sandbox — no filesystem, no network, no subprocess access. Use this to run untrusted or generated computational code safely. The WASM module must export a function named 'run' that takes no arguments. Pass the WASM binary as a base64-encoded string.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "wasm_base64": { "type": "string", "description": "Base64-encoded WASM binary to execute" },
+                    "function": { "type": "string", "description": "Exported function to call (default: 'run')" }
+                },
+                "required": ["wasm_base64"]
+            }),
+            ToolRisk::Execute,
+            &[],
+            |args, _| Box::pin(async move { tool_run_wasm(args).await }),
+        ),
+        builtin(
+            "discord_post",
+            "Post a message directly to the shared Argus Discord channel. Use this to coordinate with other instances of Argus, share findings, or leave a record of your work.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "message": { "type": "string", "description": "The message to post to Discord" }
+                },
+                "required": ["message"]
+            }),
+            ToolRisk::External,
+            &[Capability::Network],
+            |args, ctx| Box::pin(async move {
+                let c = ctx.config;
+                tool_discord_post(args, ctx.http, c.discord_bot_token.as_deref(), c.discord_channel_id, c.supabase_url.as_deref(), c.supabase_jwt.as_deref(), ctx.model).await
+            }),
+        ),
+        builtin(
+            "discord_read",
+            "Read recent messages from the shared Argus Discord channel. Use this to see what other instances of Argus have posted, check current discussion, or catch up on activity since your last turn. Keep limit low — large reads overflow context.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "limit": { "type": "number", "description": "Number of recent messages to retrieve (default 15, max 20 — hard capped to protect context)" }
                 }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "discord_post",
-                "description": "Post a message directly to the shared Argus Discord channel. Use this to coordinate with other instances of Argus, share findings, or leave a record of your work.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "message": { "type": "string", "description": "The message to post to Discord" }
+            }),
+            ToolRisk::ReadOnly,
+            &[Capability::Network],
+            |args, ctx| Box::pin(async move {
+                let c = ctx.config;
+                tool_discord_read(args, ctx.http, c.discord_bot_token.as_deref(), c.discord_channel_id).await
+            }),
+        ),
+        builtin(
+            "browse",
+            "Open a real browser (Playwright/Chromium) and interact with a live webpage. Use for dynamic pages that http_request can't handle — JavaScript-heavy sites, SPAs, pages requiring login state, screenshots, form interaction. More powerful than http_request but slower.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "url":           { "type": "string", "description": "URL to navigate to" },
+                    "action":        { "type": "string", "enum": ["fetch", "screenshot", "extract", "interact"], "description": "fetch=get page text, screenshot=capture image, extract=get specific element, interact=click/fill/evaluate" },
+                    "selector":      { "type": "string", "description": "CSS selector for extract action" },
+                    "click":         { "type": "string", "description": "CSS selector to click (interact action)" },
+                    "fill_selector": { "type": "string", "description": "CSS selector of input to fill (interact action)" },
+                    "fill_value":    { "type": "string", "description": "Value to fill into the input" },
+                    "script":        { "type": "string", "description": "JavaScript to evaluate on the page" },
+                    "timeout":       { "type": "number", "description": "Timeout in seconds (default 30)" }
+                },
+                "required": ["url"]
+            }),
+            ToolRisk::External,
+            &[Capability::Workspace, Capability::Network],
+            |args, ctx| Box::pin(async move { tool_browse(args, &*ctx.workspace).await }),
+        ),
+        builtin(
+            "write_handover",
+            "Write the session handover document to /workspace/HANDOVER.md and commit it. Call this at the end of any significant session — it's what the next instance of you reads before doing anything else. Be specific: list commit hashes, open items, and exactly where to start.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "committed": { "type": "string", "description": "What was committed this session — list hash + one-line summary per item" },
+                    "knowledge_base": { "type": "string", "description": "What lives in /workspace/knowledge/ — file names and what each contains" },
+                    "open_items": { "type": "string", "description": "What's in-flight but not yet committed — be honest" },
+                    "sentry_status": { "type": "string", "description": "Current Sentry threat posture — clean or active flags" },
+                    "start_here": { "type": "string", "description": "Specific first action for the next instance — not vague, not 'continue the work'" }
+                },
+                "required": ["committed", "open_items", "start_here"]
+            }),
+            ToolRisk::Write,
+            &[Capability::Workspace],
+            |args, ctx| Box::pin(async move { tool_write_handover(args, &*ctx.workspace).await }),
+        ),
+        builtin(
+            "git_checkpoint",
+            "Commit all staged and unstaged changes in /workspace to git with a message. Use this after every meaningful finding, code change, or document you write — if it isn't committed, it didn't happen. Returns the commit hash.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "message": { "type": "string", "description": "Commit message — what you did and why. Include [FINDING], [SKILL], [ARCH], or [FIX] tag so the log is scannable." }
+                },
+                "required": ["message"]
+            }),
+            ToolRisk::Write,
+            &[Capability::Workspace],
+            |args, ctx| Box::pin(async move { tool_git_checkpoint(args, &*ctx.workspace).await }),
+        ),
+        builtin(
+            "list_tools",
+            "List every tool available to you in this session — built-ins (shell, web_search, memory, file ops, http, discord) plus any MCP-connected tools. Call this when you need to know your full capabilities.",
+            json!({
+                "type": "object",
+                "properties": {}
+            }),
+            ToolRisk::ReadOnly,
+            &[],
+            |_, ctx| Box::pin(async move { tool_list_tools(ctx) }),
+        ),
+        builtin(
+            "http_request",
+            "Make an HTTP request to a URL. Supports GET, POST, PUT, DELETE.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "url": { "type": "string", "description": "The URL to request" },
+                    "method": {
+                        "type": "string",
+                        "enum": ["GET", "POST", "PUT", "DELETE"],
+                        "description": "HTTP method (default: GET)"
                     },
-                    "required": ["message"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "discord_read",
-                "description": "Read recent messages from the shared Argus Discord channel. Use this to see what other instances of Argus have posted, check current discussion, or catch up on activity since your last turn. Keep limit low — large reads overflow context.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "limit": { "type": "number", "description": "Number of recent messages to retrieve (default 15, max 20 — hard capped to protect context)" }
+                    "body": { "type": "string", "description": "Request body (for POST/PUT)" },
+                    "headers": {
+                        "type": "object",
+                        "description": "Additional headers as key-value pairs",
+                        "additionalProperties": { "type": "string" }
                     }
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "browse",
-                "description": "Open a real browser (Playwright/Chromium) and interact with a live webpage. Use for dynamic pages that http_request can't handle — JavaScript-heavy sites, SPAs, pages requiring login state, screenshots, form interaction. More powerful than http_request but slower.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "url":           { "type": "string", "description": "URL to navigate to" },
-                        "action":        { "type": "string", "enum": ["fetch", "screenshot", "extract", "interact"], "description": "fetch=get page text, screenshot=capture image, extract=get specific element, interact=click/fill/evaluate" },
-                        "selector":      { "type": "string", "description": "CSS selector for extract action" },
-                        "click":         { "type": "string", "description": "CSS selector to click (interact action)" },
-                        "fill_selector": { "type": "string", "description": "CSS selector of input to fill (interact action)" },
-                        "fill_value":    { "type": "string", "description": "Value to fill into the input" },
-                        "script":        { "type": "string", "description": "JavaScript to evaluate on the page" },
-                        "timeout":       { "type": "number", "description": "Timeout in seconds (default 30)" }
-                    },
-                    "required": ["url"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "write_handover",
-                "description": "Write the session handover document to /workspace/HANDOVER.md and commit it. Call this at the end of any significant session — it's what the next instance of you reads before doing anything else. Be specific: list commit hashes, open items, and exactly where to start.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "committed": { "type": "string", "description": "What was committed this session — list hash + one-line summary per item" },
-                        "knowledge_base": { "type": "string", "description": "What lives in /workspace/knowledge/ — file names and what each contains" },
-                        "open_items": { "type": "string", "description": "What's in-flight but not yet committed — be honest" },
-                        "sentry_status": { "type": "string", "description": "Current Sentry threat posture — clean or active flags" },
-                        "start_here": { "type": "string", "description": "Specific first action for the next instance — not vague, not 'continue the work'" }
-                    },
-                    "required": ["committed", "open_items", "start_here"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "git_checkpoint",
-                "description": "Commit all staged and unstaged changes in /workspace to git with a message. Use this after every meaningful finding, code change, or document you write — if it isn't committed, it didn't happen. Returns the commit hash.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "message": { "type": "string", "description": "Commit message — what you did and why. Include [FINDING], [SKILL], [ARCH], or [FIX] tag so the log is scannable." }
-                    },
-                    "required": ["message"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "list_tools",
-                "description": "List every tool available to you in this session — built-ins (shell, web_search, memory, file ops, http, discord) plus any MCP-connected tools. Call this when you need to know your full capabilities.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {}
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "http_request",
-                "description": "Make an HTTP request to a URL. Supports GET, POST, PUT, DELETE.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "url": { "type": "string", "description": "The URL to request" },
-                        "method": {
-                            "type": "string",
-                            "enum": ["GET", "POST", "PUT", "DELETE"],
-                            "description": "HTTP method (default: GET)"
-                        },
-                        "body": { "type": "string", "description": "Request body (for POST/PUT)" },
-                        "headers": {
-                            "type": "object",
-                            "description": "Additional headers as key-value pairs",
-                            "additionalProperties": { "type": "string" }
-                        }
-                    },
-                    "required": ["url"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "publish_skill",
-                "description": "Publish a reusable procedure to the shared skill library so other instances of Argus can learn from it. Use when you've discovered a non-obvious, genuinely reusable way to accomplish something. The auto-reflection fires passively — use this when you KNOW something is worth sharing.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "name":    { "type": "string", "description": "Short skill name — 5 words max" },
-                        "trigger": { "type": "string", "description": "When another agent should use this — the condition that makes this skill relevant" },
-                        "steps":   { "type": "string", "description": "Step-by-step procedure in markdown, including failure modes and edge cases" }
-                    },
-                    "required": ["name", "trigger", "steps"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "recall_skill",
-                "description": "Search the shared skill library for documented procedures relevant to your current task. Skills are retrieved by semantic similarity — describe what you're trying to do.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "query": { "type": "string", "description": "What you're trying to accomplish — used for semantic search across the skill library" }
-                    },
-                    "required": ["query"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "improve_skill",
-                "description": "Update a skill's procedure steps with refined knowledge. Use after you've found a better way to do something already in the library, or discovered an edge case the current steps don't handle.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "skill_id":      { "type": "string", "description": "ID of the skill to improve — from recall_skill results" },
-                        "refined_steps": { "type": "string", "description": "Updated procedure steps in markdown — full replacement, not a diff" },
-                        "success":       { "type": "boolean", "description": "Whether the skill worked as documented (default true — set false if you found it was broken)" }
-                    },
-                    "required": ["skill_id", "refined_steps"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "challenge_skill",
-                "description": "Challenge a skill you believe is wrong, outdated, or dangerous. Posts a proposal to #proposals so the team can vote. Use when a skill consistently fails or you have a fundamentally better approach.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "skill_name":    { "type": "string", "description": "Name of the skill being challenged" },
-                        "skill_id":      { "type": "string", "description": "ID of the skill — from recall_skill results" },
-                        "reason":        { "type": "string", "description": "Why the skill is wrong or needs replacement — be specific about what fails and why" },
-                        "proposed_fix":  { "type": "string", "description": "Your proposed replacement procedure, or 'retire' to remove it entirely" }
-                    },
-                    "required": ["skill_name", "skill_id", "reason"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "invoke_skill",
-                "description": "Explicitly invoke a skill by name to follow its documented procedure. Returns the full steps. Call this when you intend to actually follow a skill rather than just reference it — it logs the invocation for success tracking.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "skill_name": { "type": "string", "description": "Name of the skill to invoke — exact or close match" }
-                    },
-                    "required": ["skill_name"]
-                }
-            }
-        },
-        {
-            "type": "function",
-            "function": {
-                "name": "complete_skill",
-                "description": "Mark a skill invocation as complete. Call this after finishing a skill you invoked with invoke_skill — records whether it worked so the library improves over time.",
-                "parameters": {
-                    "type": "object",
-                    "properties": {
-                        "skill_id":  { "type": "string", "description": "ID of the skill that was used" },
-                        "success":   { "type": "boolean", "description": "Whether the skill procedure produced the intended outcome" },
-                        "notes":     { "type": "string", "description": "Optional: what worked, what didn't, any deviation from the documented steps" }
-                    },
-                    "required": ["skill_id", "success"]
-                }
-            }
-        }
-    ]).as_array().expect("tool schema is a literal JSON array").clone()
+                },
+                "required": ["url"]
+            }),
+            ToolRisk::External,
+            &[Capability::Network],
+            |args, ctx| Box::pin(async move {
+                let c = ctx.config;
+                tool_http_request(args, ctx.http, c.supabase_url.as_deref(), c.supabase_jwt.as_deref(), ctx.model).await
+            }),
+        ),
+        builtin(
+            "publish_skill",
+            "Publish a reusable procedure to the shared skill library so other instances of Argus can learn from it. Use when you've discovered a non-obvious, genuinely reusable way to accomplish something. The auto-reflection fires passively — use this when you KNOW something is worth sharing.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "name":    { "type": "string", "description": "Short skill name — 5 words max" },
+                    "trigger": { "type": "string", "description": "When another agent should use this — the condition that makes this skill relevant" },
+                    "steps":   { "type": "string", "description": "Step-by-step procedure in markdown, including failure modes and edge cases" }
+                },
+                "required": ["name", "trigger", "steps"]
+            }),
+            ToolRisk::Write,
+            &[Capability::Skills],
+            |args, ctx| Box::pin(async move { tool_publish_skill(args, ctx.config.skills.as_ref(), ctx.model).await }),
+        ),
+        builtin(
+            "recall_skill",
+            "Search the shared skill library for documented procedures relevant to your current task. Skills are retrieved by semantic similarity — describe what you're trying to do.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "What you're trying to accomplish — used for semantic search across the skill library" }
+                },
+                "required": ["query"]
+            }),
+            ToolRisk::ReadOnly,
+            &[Capability::Skills],
+            |args, ctx| Box::pin(async move { tool_recall_skill(args, ctx.config.skills.as_ref()).await }),
+        ),
+        builtin(
+            "improve_skill",
+            "Update a skill's procedure steps with refined knowledge. Use after you've found a better way to do something already in the library, or discovered an edge case the current steps don't handle.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "skill_id":      { "type": "string", "description": "ID of the skill to improve — from recall_skill results" },
+                    "refined_steps": { "type": "string", "description": "Updated procedure steps in markdown — full replacement, not a diff" },
+                    "success":       { "type": "boolean", "description": "Whether the skill worked as documented (default true — set false if you found it was broken)" }
+                },
+                "required": ["skill_id", "refined_steps"]
+            }),
+            ToolRisk::Write,
+            &[Capability::Skills],
+            |args, ctx| Box::pin(async move { tool_improve_skill(args, ctx.config.skills.as_ref()).await }),
+        ),
+        builtin(
+            "challenge_skill",
+            "Challenge a skill you believe is wrong, outdated, or dangerous. Posts a proposal to #proposals so the team can vote. Use when a skill consistently fails or you have a fundamentally better approach.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "skill_name":    { "type": "string", "description": "Name of the skill being challenged" },
+                    "skill_id":      { "type": "string", "description": "ID of the skill — from recall_skill results" },
+                    "reason":        { "type": "string", "description": "Why the skill is wrong or needs replacement — be specific about what fails and why" },
+                    "proposed_fix":  { "type": "string", "description": "Your proposed replacement procedure, or 'retire' to remove it entirely" }
+                },
+                "required": ["skill_name", "skill_id", "reason"]
+            }),
+            ToolRisk::External,
+            &[Capability::Skills],
+            |args, ctx| Box::pin(async move { tool_challenge_skill(args, ctx.config.skills.as_ref(), ctx.model).await }),
+        ),
+        builtin(
+            "invoke_skill",
+            "Explicitly invoke a skill by name to follow its documented procedure. Returns the full steps. Call this when you intend to actually follow a skill rather than just reference it — it logs the invocation for success tracking.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "skill_name": { "type": "string", "description": "Name of the skill to invoke — exact or close match" }
+                },
+                "required": ["skill_name"]
+            }),
+            ToolRisk::Write,
+            &[Capability::Skills],
+            |args, ctx| Box::pin(async move { tool_invoke_skill(args, ctx.config.skills.as_ref()).await }),
+        ),
+        builtin(
+            "complete_skill",
+            "Mark a skill invocation as complete. Call this after finishing a skill you invoked with invoke_skill — records whether it worked so the library improves over time.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "skill_id":  { "type": "string", "description": "ID of the skill that was used" },
+                    "success":   { "type": "boolean", "description": "Whether the skill procedure produced the intended outcome" },
+                    "notes":     { "type": "string", "description": "Optional: what worked, what didn't, any deviation from the documented steps" }
+                },
+                "required": ["skill_id", "success"]
+            }),
+            ToolRisk::Write,
+            &[Capability::Skills],
+            |args, ctx| Box::pin(async move { tool_complete_skill(args, ctx.config.skills.as_ref()).await }),
+        ),
+    ]
 }
 
-pub async fn execute_builtin(
+fn builtin(
     name: &str,
-    args: &Value,
-    shell_policy: &ShellPolicy,
-    memory: &dyn MemoryBackend,
-    http_client: &reqwest::Client,
-    brave_search_key: Option<&str>,
-    shell_prompter: Option<std::sync::Arc<dyn PermissionPrompter>>,
-    exec_auth_token: Option<&str>,
-    sonnet_guard: Option<std::sync::Arc<crate::shell::SonnetGuard>>,
-    discord_bot_token: Option<&str>,
-    discord_channel_id: Option<u64>,
-    skills: Option<&SkillsClient>,
-    current_model: &str,
-    supabase_url: Option<&str>,
-    supabase_jwt: Option<&str>,
-    mission_executor: Option<&std::sync::Arc<dyn crate::agent::MissionExecutor>>,
-) -> Option<String> {
-    // Mission tools dispatch — handled by argus-missions via injected executor
-    if matches!(name, "start_mission" | "mission_status" | "list_missions" | "add_subtask") {
-        if let Some(exec) = mission_executor {
-            return exec.execute(name, args, current_model).await;
-        }
-        return Some("Mission tools not available — mission executor not configured.".to_string());
-    }
-    match name {
-        "read_file"      => Some(tool_read_file(args)),
-        "list_directory" => Some(tool_list_directory(args)),
-        "write_file"     => Some(tool_write_file(args)),
-        "shell"          => Some(tool_shell(args, shell_policy, shell_prompter, sonnet_guard, http_client, exec_auth_token).await),
-        "web_search"     => Some(tool_web_search(args, http_client, brave_search_key).await),
-        "remember"       => Some(tool_remember(args, memory)),
-        "recall"         => Some(tool_recall(args, memory)),
-        "forget"         => Some(tool_forget(args, memory)),
-        "http_request"   => Some(tool_http_request(args, http_client, supabase_url, supabase_jwt, current_model).await),
-        "run_python"     => Some(tool_run_code("python", args, http_client, exec_auth_token).await),
-        "run_node"       => Some(tool_run_code("javascript", args, http_client, exec_auth_token).await),
-        "run_wasm"       => Some(tool_run_wasm(args).await),
-        "discord_post"   => Some(tool_discord_post(args, http_client, discord_bot_token, discord_channel_id, supabase_url, supabase_jwt, current_model).await),
-        "discord_read"   => Some(tool_discord_read(args, http_client, discord_bot_token, discord_channel_id).await),
-        "publish_skill"   => Some(tool_publish_skill(args, skills, current_model).await),
-        "recall_skill"    => Some(tool_recall_skill(args, skills).await),
-        "improve_skill"   => Some(tool_improve_skill(args, skills).await),
-        "browse"          => Some(tool_browse(args, http_client, exec_auth_token).await),
-        "write_handover"  => Some(tool_write_handover(args, http_client, exec_auth_token).await),
-        "git_checkpoint"  => Some(tool_git_checkpoint(args, http_client, exec_auth_token).await),
-        "challenge_skill" => Some(tool_challenge_skill(args, skills, current_model).await),
-        "invoke_skill"    => Some(tool_invoke_skill(args, skills).await),
-        "complete_skill"  => Some(tool_complete_skill(args, skills).await),
-        "list_tools"      => Some("Use the tool schemas you already have — this meta-tool is informational only.".to_string()),
-        _                => None,
+    description: &str,
+    parameters: Value,
+    risk: ToolRisk,
+    capabilities: &[Capability],
+    run: for<'a> fn(&'a Value, &'a ToolContext<'a>) -> ToolFuture<'a>,
+) -> Arc<dyn Tool> {
+    Arc::new(BuiltinTool::new(ToolSpec::new(name, description, parameters, risk, capabilities), run))
+}
+
+/// The tools offered this turn, after blocked tools and capability gaps are filtered out.
+fn tool_list_tools(ctx: &ToolContext<'_>) -> String {
+    let mut out = format!("Available tools ({}):\n\n", ctx.tools.len());
+    for tool in ctx.tools.iter() {
+        out.push_str(&format!("• {} — {}\n", tool.name(), tool.spec().description));
     }
+    out
 }
 
 pub trait MemoryBackend: Send + Sync {
@@ -627,10 +611,11 @@ fn tool_write_file(args: &Value) -> String {
 async fn tool_shell(
     args: &Value,
     policy: &ShellPolicy,
-    _prompter: Option<std::sync::Arc<dyn PermissionPrompter>>,
+    prompter: Option<Audited<'_>>,
     sonnet_guard: Option<std::sync::Arc<crate::shell::SonnetGuard>>,
-    http_client: &reqwest::Client,
-    exec_auth_token: Option<&str>,
+    config: &AgentConfig,
+    agent: &str,
+    workspace: &dyn WorkspaceExecutor,
 ) -> String {
     use crate::shell::SonnetVerdict;
 
@@ -639,24 +624,36 @@ async fn tool_shell(
         return "No command provided".to_string();
     }
 
+    // A human is watching (web UI, TUI): HIGH risk waits for them to approve,
+    // edit or deny. Hard blocks still apply, to edits too.
+    if let Some(prompter) = &prompter {
+        return match policy.authorize(&command, Some(prompter as &dyn PermissionPrompter)).await {
+            Err(e) => format!("Shell blocked: {}", e),
+            Ok((approved, _)) => run_shell(&approved, policy, workspace).await,
+        };
+    }
+
     // Step 1: hard-blocked patterns (rm -rf /, mkfs, etc.) — never execute
     let risk = match policy.evaluate(&command) {
         Err(e) => return format!("Shell blocked: {}", e),
         Ok(r)  => r,
     };
+    if policy.requires_approval("shell") {
+        return "Shell blocked: policy requires human approval and no approval UI is connected".to_string();
+    }
 
     // Step 2: HIGH risk → Sonnet review (unless bypass_sonnet_guard is set).
-    // Sonnet either approves, rewrites to a safer form, or blocks with explanation.
+    // Sonnet either approves, rewrites to a safer form, or blocks with explanation;
+    // the guard audits and logs the verdict.
     if risk >= policy.approval_threshold && !policy.bypass_sonnet_guard {
         match &sonnet_guard {
             None => {
                 eprintln!("[shell] WARNING: HIGH risk command running without Sonnet review: {}", command);
             }
             Some(guard) => {
-                match guard.review(&command).await {
+                match guard.review(&command, policy, config, agent).await.verdict {
                     SonnetVerdict::Approve => {}
                     SonnetVerdict::Rewrite(safer) => {
-                        eprintln!("[shell] Sonnet rewrote command: {} → {}", command, safer);
                         command = safer;
                     }
                     SonnetVerdict::Block(reason) => {
@@ -669,42 +666,25 @@ async fn tool_shell(
         eprintln!("[shell] HIGH risk command bypassing Sonnet review (permissive mode): {}", command);
     }
 
-    // Route execution to argus-workspace via internal Docker network
-    let payload = serde_json::json!({ "command": command });
-    let mut req = http_client
-        .post("http://argus-workspace:9001/exec")
-        .json(&payload)
-        .timeout(std::time::Duration::from_secs(35));
-
-    // Authenticate every exec request — blocks prompt-injection SSRF
-    if let Some(token) = exec_auth_token {
-        req = req.header("X-Argus-Auth", token);
-    }
-
-    let resp = req.send().await;
+    run_shell(&command, policy, workspace).await
+}
 
-    match resp {
-        Err(e) => format!("Workspace unreachable: {}", e),
-        Ok(r) => match r.json::<serde_json::Value>().await {
-            Err(e) => format!("Workspace response error: {}", e),
-            Ok(json) => {
-                let stdout    = json["stdout"].as_str().unwrap_or("");
-                let stderr    = json["stderr"].as_str().unwrap_or("");
-                let exit_code = json["exit_code"].as_i64().unwrap_or(-1);
-
-                if exit_code == 0 {
-                    let max = policy.max_output_bytes;
-                    if stdout.len() > max {
-                        let end = (0..=max).rev()
-                            .find(|&i| stdout.is_char_boundary(i))
-                            .unwrap_or(0);
-                        format!("{}...\n[truncated — {} bytes total]", &stdout[..end], stdout.len())
-                    } else {
-                        stdout.to_string()
-                    }
+async fn run_shell(command: &str, policy: &ShellPolicy, workspace: &dyn WorkspaceExecutor) -> String {
+    match workspace.exec(command, policy.timeout_secs + 5).await {
+        Err(e) => e,
+        Ok(out) => {
+            if out.exit_code == 0 {
+                let max = policy.max_output_bytes;
+                if out.stdout.len() > max {
+                    let end = (0..=max).rev()
+                        .find(|&i| out.stdout.is_char_boundary(i))
+                        .unwrap_or(0);
+                    format!("{}...\n[truncated — {} bytes total]", &out.stdout[..end], out.stdout.len())
                 } else {
-                    format!("Exit {}: {}", exit_code, stderr.trim())
+                    out.stdout
                 }
+            } else {
+                format!("Exit {}: {}", out.exit_code, out.stderr.trim())
             }
         }
     }
@@ -815,12 +795,11 @@ fn tool_forget(args: &Value, memory: &dyn MemoryBackend) -> String {
     }
 }
 
-/// Execute a code snippet via the workspace /run endpoint (language-aware).
+/// Execute a code snippet in the workspace (language-aware).
 async fn tool_run_code(
     language: &str,
     args: &Value,
-    client: &reqwest::Client,
-    exec_auth_token: Option<&str>,
+    workspace: &dyn WorkspaceExecutor,
 ) -> String {
     let code    = args["code"].as_str().unwrap_or("").trim().to_string();
     let timeout = args["timeout"].as_u64().unwrap_or(30).min(120);
@@ -829,47 +808,33 @@ async fn tool_run_code(
         return "No code provided".to_string();
     }
 
-    let payload = serde_json::json!({ "language": language, "code": code, "timeout": timeout });
-    let mut req = client
-        .post("http://argus-workspace:9001/run")
-        .json(&payload)
-        .timeout(std::time::Duration::from_secs(timeout + 5));
-
-    if let Some(token) = exec_auth_token {
-        req = req.header("X-Argus-Auth", token);
-    }
+    match workspace.run_code(language, &code, timeout).await {
+        Err(e) => e,
+        Ok(result) => {
+            let stdout    = result.stdout.trim_end();
+            let stderr    = result.stderr.trim_end();
+            let exit_code = result.exit_code;
 
-    match req.send().await {
-        Err(e) => format!("Workspace unreachable: {}", e),
-        Ok(r) => match r.json::<serde_json::Value>().await {
-            Err(e) => format!("Workspace response error: {}", e),
-            Ok(json) => {
-                let stdout    = json["stdout"].as_str().unwrap_or("").trim_end();
-                let stderr    = json["stderr"].as_str().unwrap_or("").trim_end();
-                let exit_code = json["exit_code"].as_i64().unwrap_or(-1);
-                let error     = json["error"].as_str().unwrap_or("");
-
-                if !error.is_empty() {
-                    return format!("Error: {}", error);
-                }
+            if let Some(error) = &result.error {
+                return format!("Error: {}", error);
+            }
 
-                let mut out = String::new();
-                if !stdout.is_empty() { out.push_str(stdout); }
-                if !stderr.is_empty() {
-                    if !out.is_empty() { out.push('\n'); }
-                    out.push_str(&format!("[stderr]\n{}", stderr));
-                }
-                if out.is_empty() {
-                    out = if exit_code == 0 {
-                        "(no output)".to_string()
-                    } else {
-                        format!("Exit {}", exit_code)
-                    };
-                } else if exit_code != 0 {
-                    out.push_str(&format!("\n[exit {}]", exit_code));
-                }
-                out
+            let mut out = String::new();
+            if !stdout.is_empty() { out.push_str(stdout); }
+            if !stderr.is_empty() {
+                if !out.is_empty() { out.push('\n'); }
+                out.push_str(&format!("[stderr]\n{}", stderr));
+            }
+            if out.is_empty() {
+                out = if exit_code == 0 {
+                    "(no output)".to_string()
+                } else {
+                    format!("Exit {}", exit_code)
+                };
+            } else if exit_code != 0 {
+                out.push_str(&format!("\n[exit {}]", exit_code));
             }
+            out
         }
     }
 }
@@ -1334,7 +1299,7 @@ fn base64_decode(input: &str) -> Result<Vec<u8>, String> {
 
 // ── Browser ────────────────────────────────────────────────────────────────
 
-async fn tool_browse(args: &Value, http: &reqwest::Client, exec_auth_token: Option<&str>) -> String {
+async fn tool_browse(args: &Value, workspace: &dyn WorkspaceExecutor) -> String {
     let url    = args["url"].as_str().unwrap_or("").trim();
     let action = args["action"].as_str().unwrap_or("fetch");
 
@@ -1359,44 +1324,31 @@ async fn tool_browse(args: &Value, http: &reqwest::Client, exec_auth_token: Opti
         "timeout":       args["timeout"].as_u64().unwrap_or(30),
     });
 
-    let mut req = http
-        .post("http://argus-workspace:9001/browse")
-        .json(&payload)
-        .timeout(std::time::Duration::from_secs(60));
-    if let Some(token) = exec_auth_token {
-        req = req.header("X-Argus-Auth", token);
-    }
-
-    match req.send().await {
-        Err(e) => format!("Browser unreachable: {} — is the workspace running?", e),
-        Ok(r) => match r.json::<serde_json::Value>().await {
-            Err(e) => format!("Browser response error: {}", e),
-            Ok(json) => {
-                if let Some(err) = json["error"].as_str().filter(|s| !s.is_empty()) {
-                    return format!("Browser error: {}", err);
-                }
-                let title   = json["title"].as_str().unwrap_or("(no title)");
-                let final_url = json["url"].as_str().unwrap_or(url);
-                let content = json["content"].as_str().unwrap_or("(no content)");
-                let screenshot = json["screenshot"].as_str();
-
-                if action == "screenshot" && screenshot.is_some() {
-                    let path = format!("/workspace/public/screenshot_{}.png",
-                        chrono::Utc::now().timestamp());
-                    // Save to workspace public dir via shell
-                    let save_cmd = format!(
-                        "python3 -c \"import base64; open('{}', 'wb').write(base64.b64decode('{}'))\"",
-                        path, screenshot.unwrap()
-                    );
-                    let _ = http.post("http://argus-workspace:9001/exec")
-                        .json(&serde_json::json!({"command": save_cmd}))
-                        .send().await;
-                    format!("Screenshot saved to {}\nViewable at http://localhost:8081/{}\nTitle: {} | URL: {}",
-                        path, path.replace("/workspace/public/", ""),
-                        title, final_url)
-                } else {
-                    format!("**{}**\nURL: {}\n\n{}", title, final_url, content)
-                }
+    match workspace.browse(&payload, 60).await {
+        Err(e) => format!("Browser unavailable: {}", e),
+        Ok(json) => {
+            if let Some(err) = json["error"].as_str().filter(|s| !s.is_empty()) {
+                return format!("Browser error: {}", err);
+            }
+            let title   = json["title"].as_str().unwrap_or("(no title)");
+            let final_url = json["url"].as_str().unwrap_or(url);
+            let content = json["content"].as_str().unwrap_or("(no content)");
+            let screenshot = json["screenshot"].as_str();
+
+            if action == "screenshot" && screenshot.is_some() {
+                let path = format!("/workspace/public/screenshot_{}.png",
+                    chrono::Utc::now().timestamp());
+                // Save to workspace public dir via shell
+                let save_cmd = format!(
+                    "python3 -c \"import base64; open('{}', 'wb').write(base64.b64decode('{}'))\"",
+                    path, screenshot.unwrap()
+                );
+                let _ = workspace.exec(&save_cmd, 30).await;
+                format!("Screenshot saved to {}\nViewable at http://localhost:8081/{}\nTitle: {} | URL: {}",
+                    path, path.replace("/workspace/public/", ""),
+                    title, final_url)
+            } else {
+                format!("**{}**\nURL: {}\n\n{}", title, final_url, content)
             }
         }
     }
@@ -1404,7 +1356,7 @@ async fn tool_browse(args: &Value, http: &reqwest::Client, exec_auth_token: Opti
 
 // ── Handover ───────────────────────────────────────────────────────────────
 
-async fn tool_write_handover(args: &Value, http: &reqwest::Client, exec_auth_token: Option<&str>) -> String {
+async fn tool_write_handover(args: &Value, workspace: &dyn WorkspaceExecutor) -> String {
     let committed     = args["committed"].as_str().unwrap_or("(none)");
     let knowledge     = args["knowledge_base"].as_str().unwrap_or("(not checked)");
     let open_items    = args["open_items"].as_str().unwrap_or("(none)");
@@ -1422,94 +1374,61 @@ async fn tool_write_handover(args: &Value, http: &reqwest::Client, exec_auth_tok
          ## Start here\n{start_here}\n"
     );
 
-    // Escape single quotes for shell safety
-    let safe_content = content.replace('\'', "'\\''");
-
     let command = format!(
-        "cd /workspace && printf '%s' '{safe_content}' > HANDOVER.md && \
+        "cd {root} && printf '%s' {content} > HANDOVER.md && \
          git add HANDOVER.md && \
          git commit -m '[HANDOVER] Session close {now}' 2>&1 && \
-         echo 'HASH:'$(git rev-parse --short HEAD)"
+         echo 'HASH:'$(git rev-parse --short HEAD)",
+        root = shell_syntax::quote(workspace.root()),
+        content = shell_syntax::quote(&content),
     );
 
-    let payload = serde_json::json!({ "command": command });
-    let mut req = http
-        .post("http://argus-workspace:9001/exec")
-        .json(&payload)
-        .timeout(std::time::Duration::from_secs(30));
-    if let Some(token) = exec_auth_token {
-        req = req.header("X-Argus-Auth", token);
-    }
-
-    match req.send().await {
-        Err(e) => format!("write_handover: workspace unreachable — {e}"),
-        Ok(r) => match r.json::<serde_json::Value>().await {
-            Err(e) => format!("write_handover: response error — {e}"),
-            Ok(json) => {
-                let out = json["output"].as_str()
-                    .or_else(|| json["stdout"].as_str())
-                    .unwrap_or("").trim_end();
-                let hash = out.lines()
-                    .find(|l| l.starts_with("HASH:"))
-                    .map(|l| l.trim_start_matches("HASH:").trim())
-                    .unwrap_or("?");
-                format!("Handover written and committed at `{hash}`. Next instance starts briefed.")
-            }
+    match workspace.exec(&command, 30).await {
+        Err(e) => format!("write_handover: {e}"),
+        Ok(result) => {
+            let out = result.stdout.trim_end();
+            let hash = out.lines()
+                .find(|l| l.starts_with("HASH:"))
+                .map(|l| l.trim_start_matches("HASH:").trim())
+                .unwrap_or("?");
+            format!("Handover written and committed at `{hash}`. Next instance starts briefed.")
         }
     }
 }
 
 // ── Git checkpoint ─────────────────────────────────────────────────────────
 
-async fn tool_git_checkpoint(args: &Value, http: &reqwest::Client, exec_auth_token: Option<&str>) -> String {
+async fn tool_git_checkpoint(args: &Value, workspace: &dyn WorkspaceExecutor) -> String {
     let message = args["message"].as_str().unwrap_or("").trim();
     if message.is_empty() {
         return "git_checkpoint requires a commit message.".to_string();
     }
 
-    // Sanitize — no shell injection via commit message
-    let safe_msg: String = message.chars()
-        .map(|c| if c == '\'' || c == '`' || c == '\\' { ' ' } else { c })
-        .collect();
-
     let command = format!(
-        "cd /workspace && git add -A && git diff --cached --stat | head -20 && \
-         git commit -m '{safe_msg}' 2>&1 && echo 'HASH:'$(git rev-parse --short HEAD)"
+        "cd {root} && git add -A && git diff --cached --stat | head -20 && \
+         git commit -m {message} 2>&1 && echo 'HASH:'$(git rev-parse --short HEAD)",
+        root = shell_syntax::quote(workspace.root()),
+        message = shell_syntax::quote(message),
     );
 
-    let payload = serde_json::json!({ "command": command });
-    let mut req = http
-        .post("http://argus-workspace:9001/exec")
-        .json(&payload)
-        .timeout(std::time::Duration::from_secs(30));
-
-    if let Some(token) = exec_auth_token {
-        req = req.header("X-Argus-Auth", token);
-    }
-
-    match req.send().await {
-        Err(e) => format!("git_checkpoint: workspace unreachable — {}", e),
-        Ok(r) => match r.json::<serde_json::Value>().await {
-            Err(e) => format!("git_checkpoint: response error — {}", e),
-            Ok(json) => {
-                let out = json["output"].as_str()
-                    .or_else(|| json["stdout"].as_str())
-                    .unwrap_or("").trim_end();
-                let err = json["stderr"].as_str().unwrap_or("").trim_end();
-
-                // Extract hash from HASH: line
-                let hash = out.lines()
-                    .find(|l| l.starts_with("HASH:"))
-                    .map(|l| l.trim_start_matches("HASH:").trim())
-                    .unwrap_or("");
-
-                if !hash.is_empty() {
-                    format!("✓ Committed `{}` — in the record.\n\n{}", hash, out)
-                } else if !err.is_empty() {
-                    format!("git error:\n{}\n{}", out, err)
-                } else {
-                    out.to_string()
-                }
+    match workspace.exec(&command, 30).await {
+        Err(e) => format!("git_checkpoint: {}", e),
+        Ok(result) => {
+            let out = result.stdout.trim_end();
+            let err = result.stderr.trim_end();
+
+            // Extract hash from HASH: line
+            let hash = out.lines()
+                .find(|l| l.starts_with("HASH:"))
+                .map(|l| l.trim_start_matches("HASH:").trim())
+                .unwrap_or("");
+
+            if !hash.is_empty() {
+                format!("✓ Committed `{}` — in the record.\n\n{}", hash, out)
+            } else if !err.is_empty() {
+                format!("git error:\n{}\n{}", out, err)
+            } else {
+                out.to_string()
             }
         }
     }
@@ -1607,3 +1526,31 @@ async fn tool_complete_skill(args: &Value, skills: Option<&SkillsClient>) -> Str
         Err(e) => format!("Failed to record completion: {}", e),
     }
 }
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::LocalWorkspace;
+
+    #[tokio::test]
+    async fn git_checkpoint_survives_quotes_in_root_and_message() {
+        let dir = std::env::temp_dir().join(format!("argus-it's-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let ws = LocalWorkspace::new(&dir);
+        let init = ws.exec(
+            "git init -q && git config user.email argus@local && git config user.name Argus && touch notes.md",
+            10,
+        ).await.unwrap();
+        assert_eq!(init.exit_code, 0, "{}", init.stderr);
+
+        let message = "Don't `touch` $HOME; it's fine";
+        let out = tool_git_checkpoint(&json!({ "message": message }), &ws).await;
+        assert!(out.starts_with("✓ Committed"), "{}", out);
+        let log = ws.exec("git log -1 --format=%s", 10).await.unwrap();
+        assert_eq!(log.stdout.trim(), message);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,322 @@
+//! Token usage, cost and budgets
+//!
+//! Every successful model call is turned into a `UsageRecord` — prompt and
+//! completion tokens from the response's `usage` block, cost from
+//! OpenRouter's `usage.cost` when present, else the model's `pricing` in
+//! models.toml — and appended to a `UsageLedger`. Before each call the agent
+//! checks the ledger against the `[budgets]` in models.toml:
+//!
+//!   model.<key>      → spend on one model slot, any surface (slots that
+//!                      share a model ID are budgeted separately)
+//!   surface.<name>   → web, telegram, discord, sentry, missions, ...
+//!   mission.<id>     → one mission; `mission.default` applies to every mission
+//!
+//! Each budget has optional `daily` and `monthly` USD caps (UTC calendar
+//! day and month). The ledger storage lives in argus-memory.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::ModelRegistry;
+
+/// One model call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageRecord {
+    /// Model slot key the call was made as.
+    pub slot: String,
+    /// Model ID as sent to the API.
+    pub model: String,
+    pub surface: String,
+    pub mission: Option<String>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl UsageRecord {
+    /// Build a record from a completion response. None when the response
+    /// carries no `usage` block (some local servers omit it).
+    pub fn from_response(json: &Value, slot: &str, model: &str, surface: &str, mission: Option<&str>) -> Option<Self> {
+        let usage = json.get("usage").filter(|u| u.is_object())?;
+        let prompt_tokens = usage["prompt_tokens"].as_u64().unwrap_or(0);
+        let completion_tokens = usage["completion_tokens"].as_u64().unwrap_or(0);
+        let cost_usd = usage["cost"].as_f64()
+            .unwrap_or_else(|| estimate_cost(slot, model, prompt_tokens, completion_tokens));
+        Some(Self {
+            slot: slot.to_string(),
+            model: model.to_string(),
+            surface: surface.to_string(),
+            mission: mission.map(str::to_string),
+            prompt_tokens,
+            completion_tokens,
+            cost_usd,
+        })
+    }
+}
+
+/// Cost from the slot's list price (the model's, for an unknown slot); 0
+/// without `pricing`.
+pub fn estimate_cost(slot: &str, model: &str, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+    let registry = ModelRegistry::global();
+    registry
+        .get(slot)
+        .or_else(|| registry.by_id(model))
+        .and_then(|m| m.pricing)
+        .map(|p| (prompt_tokens as f64 * p.prompt + completion_tokens as f64 * p.completion) / 1_000_000.0)
+        .unwrap_or(0.0)
+}
+
+/// What a budget or a report line is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scope {
+    /// Model slot key.
+    Model(String),
+    Surface(String),
+    Mission(String),
+}
+
+impl Scope {
+    fn describe(&self) -> String {
+        match self {
+            Scope::Model(key) => format!("model {}", slot_label(key)),
+            Scope::Surface(s) => format!("surface {}", s),
+            Scope::Mission(id) => format!("mission {}", id),
+        }
+    }
+}
+
+/// Summed usage for one report line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageTotals {
+    pub calls: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Report grouping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Model,
+    Surface,
+    Mission,
+}
+
+pub trait UsageLedger: Send + Sync {
+    fn record(&self, record: &UsageRecord) -> Result<(), String>;
+
+    /// USD spent in `scope` since `since`.
+    fn spent(&self, scope: &Scope, since: DateTime<Utc>) -> Result<f64, String>;
+
+    /// Totals since `since`, grouped, most expensive first.
+    fn totals(&self, group: GroupBy, since: DateTime<Utc>) -> Result<Vec<(String, UsageTotals)>, String>;
+}
+
+// ── Budgets ──────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub struct Budget {
+    #[serde(default)]
+    pub daily: Option<f64>,
+    #[serde(default)]
+    pub monthly: Option<f64>,
+}
+
+/// The `[budgets]` table of models.toml.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Budgets {
+    /// Keyed by model slot key.
+    #[serde(default)]
+    pub model: HashMap<String, Budget>,
+    #[serde(default)]
+    pub surface: HashMap<String, Budget>,
+    /// Keyed by mission ID, with `default` for the rest.
+    #[serde(default)]
+    pub mission: HashMap<String, Budget>,
+}
+
+impl Budgets {
+    pub fn is_empty(&self) -> bool {
+        self.model.is_empty() && self.surface.is_empty() && self.mission.is_empty()
+    }
+
+    pub fn for_scope(&self, scope: &Scope) -> Option<Budget> {
+        match scope {
+            Scope::Model(key) => self.model.get(key).copied(),
+            Scope::Surface(s) => self.surface.get(s).copied(),
+            Scope::Mission(id) => self.mission.get(id).or_else(|| self.mission.get("default")).copied(),
+        }
+    }
+}
+
+/// Midnight UTC today and on the 1st of this month.
+pub fn period_starts(now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+    let day = Utc.with_ymd_and_hms(now.year(), now.month(), now.day(), 0, 0, 0).unwrap();
+    let month = Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0).unwrap();
+    (day, month)
+}
+
+/// Err with a user-facing message if any scope has hit its daily or monthly cap.
+pub fn check_budgets(ledger: &dyn UsageLedger, budgets: &Budgets, scopes: &[Scope]) -> Result<(), String> {
+    let (day, month) = period_starts(Utc::now());
+    for scope in scopes {
+        let Some(budget) = budgets.for_scope(scope) else { continue };
+        for (limit, since, period) in [(budget.daily, day, "Daily"), (budget.monthly, month, "Monthly")] {
+            let Some(limit) = limit else { continue };
+            let spent = ledger.spent(scope, since)?;
+            if spent >= limit {
+                return Err(format!(
+                    "{} budget for {} reached: ${:.2} of ${:.2}",
+                    period, scope.describe(), spent, limit
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+// ── Report ───────────────────────────────────────────────────────────────────
+
+/// Display name for a slot key — or, for rows recorded before slots were,
+/// a model ID.
+fn slot_label(name: &str) -> String {
+    let registry = ModelRegistry::global();
+    registry.get(name)
+        .or_else(|| registry.by_id(name))
+        .map_or_else(|| name.to_string(), |m| m.label.clone())
+}
+
+/// One line of `argus usage` / the web `usage_update` message.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageLine {
+    /// "model", "surface" or "mission".
+    pub scope: &'static str,
+    /// Model slot key, surface name or mission ID.
+    pub name: String,
+    /// Display name — the model label, else `name`.
+    pub label: String,
+    pub calls: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+    /// Cap for this period, if one is configured.
+    pub budget: Option<f64>,
+}
+
+/// Spend today and this month, grouped by model, surface and mission.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    pub today: Vec<UsageLine>,
+    pub month: Vec<UsageLine>,
+    pub total_today_usd: f64,
+    pub total_month_usd: f64,
+}
+
+pub fn report(ledger: &dyn UsageLedger, budgets: &Budgets) -> Result<UsageReport, String> {
+    let (day, month) = period_starts(Utc::now());
+    let lines = |since: DateTime<Utc>, daily: bool| -> Result<Vec<UsageLine>, String> {
+        let mut out = Vec::new();
+        for (group, scope) in [(GroupBy::Model, "model"), (GroupBy::Surface, "surface"), (GroupBy::Mission, "mission")] {
+            for (name, t) in ledger.totals(group, since)? {
+                let (as_scope, label) = match group {
+                    GroupBy::Model => (Scope::Model(name.clone()), slot_label(&name)),
+                    GroupBy::Surface => (Scope::Surface(name.clone()), name.clone()),
+                    GroupBy::Mission => (Scope::Mission(name.clone()), name.clone()),
+                };
+                let budget = budgets.for_scope(&as_scope).and_then(|b| if daily { b.daily } else { b.monthly });
+                out.push(UsageLine {
+                    scope,
+                    name,
+                    label,
+                    calls: t.calls,
+                    prompt_tokens: t.prompt_tokens,
+                    completion_tokens: t.completion_tokens,
+                    cost_usd: t.cost_usd,
+                    budget,
+                });
+            }
+        }
+        Ok(out)
+    };
+    let today = lines(day, true)?;
+    let month = lines(month, false)?;
+    let total = |lines: &[UsageLine]| lines.iter().filter(|l| l.scope == "surface").map(|l| l.cost_usd).sum();
+    Ok(UsageReport {
+        total_today_usd: total(&today),
+        total_month_usd: total(&month),
+        today,
+        month,
+    })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct VecLedger(Mutex<Vec<UsageRecord>>);
+
+    impl UsageLedger for VecLedger {
+        fn record(&self, record: &UsageRecord) -> Result<(), String> {
+            self.0.lock().unwrap().push(record.clone());
+            Ok(())
+        }
+        fn spent(&self, scope: &Scope, _: DateTime<Utc>) -> Result<f64, String> {
+            Ok(self.0.lock().unwrap().iter()
+                .filter(|r| match scope {
+                    Scope::Model(key) => &r.slot == key,
+                    Scope::Surface(s) => &r.surface == s,
+                    Scope::Mission(id) => r.mission.as_ref() == Some(id),
+                })
+                .map(|r| r.cost_usd)
+                .sum())
+        }
+        fn totals(&self, _: GroupBy, _: DateTime<Utc>) -> Result<Vec<(String, UsageTotals)>, String> {
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn prices_calls_and_enforces_budgets() {
+        let sonnet = crate::models::id("sonnet");
+        let json = serde_json::json!({ "usage": { "prompt_tokens": 200_000, "completion_tokens": 10_000 } });
+        let rec = UsageRecord::from_response(&json, "sonnet", sonnet, "web", None).unwrap();
+        // $3/M in + $15/M out
+        assert!((rec.cost_usd - 0.75).abs() < 1e-9);
+
+        let reported = serde_json::json!({ "usage": { "prompt_tokens": 1, "completion_tokens": 1, "cost": 0.42 } });
+        assert_eq!(UsageRecord::from_response(&reported, "sonnet", sonnet, "web", None).unwrap().cost_usd, 0.42);
+        assert!(UsageRecord::from_response(&serde_json::json!({}), "sonnet", sonnet, "web", None).is_none());
+
+        let budgets: Budgets = toml::from_str(r#"
+            model.sonnet = { daily = 1.0 }
+            mission.default = { monthly = 0.5 }
+        "#).unwrap();
+        let ledger = VecLedger::default();
+        let scopes = [Scope::Model("sonnet".into()), Scope::Surface("web".into())];
+        assert!(check_budgets(&ledger, &budgets, &scopes).is_ok());
+        ledger.record(&rec).unwrap();
+        ledger.record(&rec).unwrap();
+        let err = check_budgets(&ledger, &budgets, &scopes).unwrap_err();
+        assert!(err.starts_with("Daily budget for model Sonnet reached: $1.50"), "{}", err);
+
+        let mission = Scope::Mission("m-1".into());
+        ledger.record(&UsageRecord { mission: Some("m-1".into()), ..rec }).unwrap();
+        assert!(check_budgets(&ledger, &budgets, &[mission]).unwrap_err().starts_with("Monthly"));
+
+        // Opus and Gemma share a model ID but not a budget.
+        let budgets: Budgets = toml::from_str("model.opus = { daily = 0.1 }").unwrap();
+        let shared = crate::models::id("opus");
+        let gemma = UsageRecord::from_response(&reported, "gemma", shared, "web", None).unwrap();
+        ledger.record(&gemma).unwrap();
+        assert!(check_budgets(&ledger, &budgets, &[Scope::Model("opus".into())]).is_ok());
+        ledger.record(&UsageRecord { slot: "opus".into(), ..gemma }).unwrap();
+        assert!(check_budgets(&ledger, &budgets, &[Scope::Model("opus".into())]).is_err());
+    }
+}
@@ -0,0 +1,385 @@
+//! Workspace execution backends
+//!
+//! Tools that run commands, code, or a browser go through a `WorkspaceExecutor`
+//! instead of talking to the workspace container directly:
+//!
+//!   HttpWorkspace  → the argus-workspace exec server (docker-compose default)
+//!   LocalWorkspace → `sh -c` in a chosen directory on this machine
+//!
+//! `from_env` picks one: `ARGUS_WORKSPACE_DIR` selects the local backend,
+//! otherwise `ARGUS_WORKSPACE_URL` (default `http://argus-workspace:9001`)
+//! is used for the HTTP backend.
+//!
+//! With `ARGUS_WORKSPACE_ISOLATION=1` on Linux, local commands run in their
+//! own namespaces under Landlock and rlimits (see `argus_sandbox::isolation`):
+//! the system directories read-only, the workspace and temp dir writable, no
+//! network unless `ARGUS_WORKSPACE_NETWORK=1`. `ARGUS_SANDBOX_READ_PATHS`
+//! (colon-separated) adds read-only paths, e.g. a pyenv or nvm install.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use argus_sandbox::capabilities::{Capability, CapabilitySet};
+use argus_sandbox::isolation::{IsolationProfile, ResourceLimits};
+use serde_json::Value;
+
+use crate::shell::{execute_shell, AlwaysAllow, ShellPolicy};
+use crate::shell_syntax;
+
+pub const DEFAULT_WORKSPACE_URL: &str = "http://argus-workspace:9001";
+/// Workspace root inside the argus-workspace container.
+pub const CONTAINER_WORKSPACE_ROOT: &str = "/workspace";
+
+pub type WorkspaceFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+/// Result of a command or code run in the workspace.
+#[derive(Debug, Clone, Default)]
+pub struct ExecOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i64,
+    /// Backend-level error (bad language, sandbox failure) — distinct from a
+    /// non-zero exit.
+    pub error: Option<String>,
+}
+
+impl ExecOutput {
+    fn from_json(json: &Value) -> Self {
+        Self {
+            stdout: json["output"].as_str()
+                .or_else(|| json["stdout"].as_str())
+                .unwrap_or("")
+                .to_string(),
+            stderr: json["stderr"].as_str().unwrap_or("").to_string(),
+            exit_code: json["exit_code"].as_i64().unwrap_or(-1),
+            error: json["error"].as_str().filter(|s| !s.is_empty()).map(str::to_string),
+        }
+    }
+}
+
+/// Where agent tools execute. Err means the backend itself failed
+/// (unreachable, timeout, unsupported) — command failures come back as
+/// `ExecOutput` with a non-zero exit code.
+pub trait WorkspaceExecutor: Send + Sync {
+    /// Run a shell command with the workspace root as working directory.
+    fn exec<'a>(&'a self, command: &'a str, timeout_secs: u64) -> WorkspaceFuture<'a, ExecOutput>;
+
+    /// Run a snippet of `language` ("python" or "javascript") source.
+    fn run_code<'a>(&'a self, language: &'a str, code: &'a str, timeout_secs: u64) -> WorkspaceFuture<'a, ExecOutput>;
+
+    /// Drive the workspace browser. `request` is the /browse payload.
+    fn browse<'a>(&'a self, request: &'a Value, timeout_secs: u64) -> WorkspaceFuture<'a, Value>;
+
+    /// Absolute path of the workspace root as seen by `exec`.
+    fn root(&self) -> &str;
+
+    /// Short label for logs and `doctor`.
+    fn describe(&self) -> String;
+}
+
+/// Pick a backend from the environment. `auth_token` is only used by the
+/// HTTP backend.
+pub fn from_env(auth_token: Option<String>) -> Arc<dyn WorkspaceExecutor> {
+    if let Some(dir) = std::env::var("ARGUS_WORKSPACE_DIR").ok().filter(|d| !d.is_empty()) {
+        if !env_flag("ARGUS_WORKSPACE_ISOLATION") {
+            return Arc::new(LocalWorkspace::new(dir));
+        }
+        let ws = LocalWorkspace::isolated(dir, env_flag("ARGUS_WORKSPACE_NETWORK"));
+        if let Some(Err(e)) = ws.isolation.as_ref().map(IsolationProfile::probe) {
+            // Fail closed: every command will be refused until this is fixed.
+            eprintln!("[!] Workspace isolation unavailable ({}) — local commands will fail", e);
+        }
+        return Arc::new(ws);
+    }
+    let url = std::env::var("ARGUS_WORKSPACE_URL")
+        .ok()
+        .filter(|u| !u.is_empty())
+        .unwrap_or_else(|| DEFAULT_WORKSPACE_URL.to_string());
+    Arc::new(HttpWorkspace::new(url, auth_token))
+}
+
+fn env_flag(name: &str) -> bool {
+    std::env::var(name)
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+// ── HTTP backend ─────────────────────────────────────────────────────────────
+
+/// Client for the argus-workspace exec server. Every request carries the
+/// shared secret in `X-Argus-Auth` — blocks prompt-injection SSRF to /exec.
+pub struct HttpWorkspace {
+    base_url: String,
+    auth_token: Option<String>,
+    http: reqwest::Client,
+}
+
+impl HttpWorkspace {
+    pub fn new(base_url: impl Into<String>, auth_token: Option<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            auth_token,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    async fn post(&self, path: &str, payload: &Value, timeout_secs: u64) -> Result<Value, String> {
+        let mut req = self.http
+            .post(format!("{}{}", self.base_url, path))
+            .json(payload)
+            .timeout(std::time::Duration::from_secs(timeout_secs));
+        if let Some(token) = &self.auth_token {
+            req = req.header("X-Argus-Auth", token);
+        }
+        let resp = req.send().await.map_err(|e| format!("Workspace unreachable: {}", e))?;
+        resp.json::<Value>().await.map_err(|e| format!("Workspace response error: {}", e))
+    }
+}
+
+impl WorkspaceExecutor for HttpWorkspace {
+    fn exec<'a>(&'a self, command: &'a str, timeout_secs: u64) -> WorkspaceFuture<'a, ExecOutput> {
+        Box::pin(async move {
+            let json = self.post("/exec", &serde_json::json!({ "command": command }), timeout_secs).await?;
+            Ok(ExecOutput::from_json(&json))
+        })
+    }
+
+    fn run_code<'a>(&'a self, language: &'a str, code: &'a str, timeout_secs: u64) -> WorkspaceFuture<'a, ExecOutput> {
+        Box::pin(async move {
+            let payload = serde_json::json!({ "language": language, "code": code, "timeout": timeout_secs });
+            // The server enforces `timeout` itself; leave headroom for the round trip.
+            let json = self.post("/run", &payload, timeout_secs + 5).await?;
+            Ok(ExecOutput::from_json(&json))
+        })
+    }
+
+    fn browse<'a>(&'a self, request: &'a Value, timeout_secs: u64) -> WorkspaceFuture<'a, Value> {
+        Box::pin(self.post("/browse", request, timeout_secs))
+    }
+
+    fn root(&self) -> &str {
+        CONTAINER_WORKSPACE_ROOT
+    }
+
+    fn describe(&self) -> String {
+        format!("http {}", self.base_url)
+    }
+}
+
+// ── Local backend ────────────────────────────────────────────────────────────
+
+/// Runs commands as child processes of the agent, in `dir`. There is no
+/// container boundary, so the shell policy's hard blocks are re-checked here
+/// and output is capped; `isolated` adds namespaces, Landlock and rlimits.
+pub struct LocalWorkspace {
+    dir: PathBuf,
+    root: String,
+    /// Upper bound on any single command, whatever the tool asked for.
+    pub max_timeout_secs: u64,
+    pub max_output_bytes: usize,
+    /// None runs commands with the agent's own privileges.
+    pub isolation: Option<IsolationProfile>,
+    policy: ShellPolicy,
+}
+
+impl LocalWorkspace {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let dir = dir.canonicalize().unwrap_or(dir);
+        let mut policy = ShellPolicy::configured();
+        policy.cwd = Some(dir.clone());
+        Self {
+            root: dir.to_string_lossy().into_owned(),
+            dir,
+            max_timeout_secs: 120,
+            max_output_bytes: policy.max_output_bytes,
+            isolation: None,
+            policy,
+        }
+    }
+
+    /// Commands confined to `workspace_capabilities(dir, network)`, with a
+    /// CPU limit matching `max_timeout_secs`.
+    pub fn isolated(dir: impl Into<PathBuf>, network: bool) -> Self {
+        let mut ws = Self::new(dir);
+        let limits = ResourceLimits { cpu_secs: ws.max_timeout_secs, ..ResourceLimits::default() };
+        ws.isolation = Some(IsolationProfile::from_capabilities(&workspace_capabilities(&ws.dir, network), limits));
+        ws
+    }
+
+    async fn run(&self, command: &str, timeout_secs: u64) -> Result<ExecOutput, String> {
+        // Approval is the tool layer's job (tool_shell's prompter or Sonnet
+        // guard) and has already happened; only the hard blocks apply here.
+        let (out, _) = execute_shell(
+            &self.policy,
+            command,
+            Some(&AlwaysAllow),
+            Some(&self.dir),
+            timeout_secs.min(self.max_timeout_secs),
+            self.max_output_bytes,
+            self.isolation.as_ref(),
+        )
+        .await?;
+        Ok(ExecOutput {
+            stdout: out.stdout,
+            stderr: out.stderr,
+            exit_code: out.exit_code as i64,
+            error: None,
+        })
+    }
+}
+
+impl WorkspaceExecutor for LocalWorkspace {
+    fn exec<'a>(&'a self, command: &'a str, timeout_secs: u64) -> WorkspaceFuture<'a, ExecOutput> {
+        Box::pin(self.run(command, timeout_secs))
+    }
+
+    fn run_code<'a>(&'a self, language: &'a str, code: &'a str, timeout_secs: u64) -> WorkspaceFuture<'a, ExecOutput> {
+        Box::pin(async move {
+            let (interpreter, ext) = match language {
+                "python"     => ("python3", "py"),
+                "javascript" => ("node", "js"),
+                other => {
+                    return Ok(ExecOutput {
+                        error: Some(format!("Unsupported language: {}", other)),
+                        ..ExecOutput::default()
+                    });
+                }
+            };
+            // Write the snippet to a temp file rather than passing it with -c:
+            // no quoting issues, and the policy sees a plain script invocation.
+            let script = std::env::temp_dir().join(format!("argus-run-{}.{}", uuid::Uuid::new_v4(), ext));
+            std::fs::write(&script, code).map_err(|e| format!("Could not write script: {}", e))?;
+            let command = format!("{} {}", interpreter, shell_syntax::quote(&script.to_string_lossy()));
+            let result = self.run(&command, timeout_secs).await;
+            let _ = std::fs::remove_file(&script);
+            result
+        })
+    }
+
+    fn browse<'a>(&'a self, _request: &'a Value, _timeout_secs: u64) -> WorkspaceFuture<'a, Value> {
+        Box::pin(async {
+            Err("browse needs the argus-workspace container (Playwright) — not available with ARGUS_WORKSPACE_DIR".to_string())
+        })
+    }
+
+    fn root(&self) -> &str {
+        &self.root
+    }
+
+    fn describe(&self) -> String {
+        match &self.isolation {
+            Some(profile) => format!(
+                "local {} (isolated{})",
+                self.root,
+                if profile.network { ", network" } else { "" }
+            ),
+            None => format!("local {}", self.root),
+        }
+    }
+}
+
+/// What an isolated local command may touch: system directories read-only,
+/// `dir` and the temp dir writable, a minimal environment (HOME is set to
+/// `dir`), and the network only if asked for.
+pub fn workspace_capabilities(dir: &Path, network: bool) -> CapabilitySet {
+    let mut read: Vec<String> = ["/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc", "/opt", "/dev", "/proc"]
+        .iter()
+        .map(|p| p.to_string())
+        .collect();
+    if let Ok(extra) = std::env::var("ARGUS_SANDBOX_READ_PATHS") {
+        read.extend(extra.split(':').filter(|p| !p.is_empty()).map(str::to_string));
+    }
+
+    let mut caps = CapabilitySet::new();
+    caps.grant(Capability::FileRead { paths: read });
+    caps.grant(Capability::FileWrite {
+        paths: vec![
+            dir.to_string_lossy().into_owned(),
+            std::env::temp_dir().to_string_lossy().into_owned(),
+            "/dev/null".to_string(),
+        ],
+    });
+    caps.grant(Capability::Environment {
+        vars: ["PATH", "LANG", "LC_ALL", "TERM", "TZ", "TMPDIR"].iter().map(|v| v.to_string()).collect(),
+    });
+    if network {
+        caps.grant(Capability::Network { domains: vec!["*".to_string()] });
+    }
+    caps
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn local_exec_runs_in_dir_and_caps_output() {
+        let dir = std::env::temp_dir().join(format!("argus-ws-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut ws = LocalWorkspace::new(&dir);
+        ws.max_output_bytes = 16;
+
+        let out = ws.exec("touch marker && ls", 5).await.unwrap();
+        assert_eq!(out.exit_code, 0);
+        assert!(out.stdout.contains("marker"));
+        assert!(dir.join("marker").exists());
+
+        let out = ws.exec("yes | head -c 1000", 5).await.unwrap();
+        assert!(out.stdout.contains("[truncated — 1000 bytes total]"));
+
+        let out = ws.exec("exit 3", 5).await.unwrap();
+        assert_eq!(out.exit_code, 3);
+
+        assert!(ws.exec("sleep 5", 1).await.unwrap_err().contains("timed out"));
+        assert!(ws.exec("mkfs /dev/sda", 5).await.is_err());
+
+        // A timeout kills the whole process group, not just `sh`.
+        let cmd = "sh -c 'sleep 30 & echo $! > bg.pid; wait'";
+        assert!(ws.exec(cmd, 1).await.unwrap_err().contains("timed out"));
+        let pid = std::fs::read_to_string(dir.join("bg.pid")).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        // Gone, or a zombie waiting on an init that doesn't reap.
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid.trim())).unwrap_or_default();
+        assert!(stat.is_empty() || stat.contains(") Z "), "{}", stat);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn isolated_exec_is_confined() {
+        let dir = std::env::temp_dir().join(format!("argus-ws-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let ws = LocalWorkspace::isolated(&dir, false);
+        if let Err(e) = ws.isolation.as_ref().unwrap().probe() {
+            eprintln!("skipping: {}", e);
+            return;
+        }
+
+        // Init of its own PID namespace, home in the workspace, which is writable.
+        let out = ws.exec("echo $$ $HOME && touch made-here", 5).await.unwrap();
+        assert_eq!(out.stdout.trim(), format!("1 {}", ws.root()));
+        assert!(dir.join("made-here").exists());
+
+        // Outside the write paths Landlock refuses, even for the owner.
+        let outside = dirs::home_dir().unwrap().join(format!(".argus-escape-{}", uuid::Uuid::new_v4()));
+        let out = ws.exec(&format!("touch '{}'", outside.display()), 5).await.unwrap();
+        assert_ne!(out.exit_code, 0);
+        assert!(!outside.exists());
+
+        // No network interfaces but loopback, and no secrets from the agent's environment.
+        let out = ws.exec("cat /proc/net/dev | tail -n +3 | cut -d: -f1 | tr -d ' '", 5).await.unwrap();
+        assert_eq!(out.stdout.trim(), "lo");
+        std::env::set_var("ARGUS_TEST_SECRET", "hunter2");
+        let out = ws.exec("echo \"[$ARGUS_TEST_SECRET]\"", 5).await.unwrap();
+        assert_eq!(out.stdout.trim(), "[]");
+
+        assert!(ws.exec("sleep 5", 1).await.unwrap_err().contains("timed out"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+//! Scoped vault access and read logging
+//!
+//! Components read secrets through a `ScopedVault` handle named after the
+//! subsystem (`telegram`, `discord`, `audit_hmac`, ...). The vault's
+//! `ScopePolicy` lists which secrets each scope may read; anything else is
+//! refused. Every read — scoped, unscoped, granted or denied — is reported
+//! to an `AccessLog` so there is a record of which subsystem touched which
+//! secret. Only names are reported, never values.
+//!
+//! The log is attached after unlock (the audit chain usually opens later
+//! than the vault), so events are buffered until then.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::vault::{SecureVault, VaultError};
+
+/// Scope recorded for reads made directly on `SecureVault` (full access).
+pub const UNSCOPED: &str = "*";
+
+/// Reads buffered before an `AccessLog` is attached. Oldest are dropped
+/// past this point so a CLI that never attaches one can't grow unbounded.
+const MAX_PENDING: usize = 1024;
+
+/// One secret read attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessEvent {
+    pub scope: String,
+    pub name: String,
+    pub granted: bool,
+}
+
+/// Sink for vault read events — implemented by the audit chain.
+pub trait AccessLog: Send + Sync {
+    fn record(&self, event: &AccessEvent);
+}
+
+/// Which secrets each named scope may read. A scope not in the policy may
+/// read nothing. Entries ending in `*` match by prefix.
+#[derive(Debug, Clone, Default)]
+pub struct ScopePolicy {
+    scopes: HashMap<String, Vec<String>>,
+}
+
+impl ScopePolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow `scope` to read `names` (in addition to anything already allowed).
+    pub fn allow(mut self, scope: &str, names: &[&str]) -> Self {
+        self.scopes
+            .entry(scope.to_string())
+            .or_default()
+            .extend(names.iter().map(|n| n.to_string()));
+        self
+    }
+
+    pub fn permits(&self, scope: &str, name: &str) -> bool {
+        self.scopes.get(scope).is_some_and(|allowed| {
+            allowed.iter().any(|a| match a.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => a == name,
+            })
+        })
+    }
+}
+
+/// Where read events go: buffered until a log is attached, then forwarded.
+#[derive(Default)]
+pub(crate) struct AccessSink {
+    log: Option<Arc<dyn AccessLog>>,
+    pending: Vec<AccessEvent>,
+}
+
+impl AccessSink {
+    pub(crate) fn attach(&mut self, log: Arc<dyn AccessLog>) {
+        for event in self.pending.drain(..) {
+            log.record(&event);
+        }
+        self.log = Some(log);
+    }
+
+    pub(crate) fn record(&mut self, event: AccessEvent) {
+        match &self.log {
+            Some(log) => log.record(&event),
+            None => {
+                if self.pending.len() >= MAX_PENDING {
+                    self.pending.remove(0);
+                }
+                self.pending.push(event);
+            }
+        }
+    }
+}
+
+pub(crate) type SharedSink = Mutex<AccessSink>;
+
+/// Read-only handle limited to one scope's secrets.
+pub struct ScopedVault<'a> {
+    vault: &'a SecureVault,
+    scope: String,
+}
+
+impl<'a> ScopedVault<'a> {
+    pub(crate) fn new(vault: &'a SecureVault, scope: &str) -> Self {
+        Self { vault, scope: scope.to_string() }
+    }
+
+    pub fn scope(&self) -> &str {
+        &self.scope
+    }
+
+    /// Retrieve `name` if this scope is allowed to read it.
+    pub fn retrieve(&self, name: &str) -> Result<String, VaultError> {
+        self.vault.retrieve_as(&self.scope, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_exact_and_prefix() {
+        let policy = ScopePolicy::new()
+            .allow("telegram", &["telegram_bot_token", "telegram_chat_id"])
+            .allow("audit", &["audit_pq_*"]);
+        assert!(policy.permits("telegram", "telegram_bot_token"));
+        assert!(!policy.permits("telegram", "discord_bot_token"));
+        assert!(policy.permits("audit", "audit_pq_secret_key"));
+        assert!(!policy.permits("audit", "audit_hmac_key"));
+        assert!(!policy.permits("unknown", "telegram_bot_token"));
+    }
+}
@@ -7,10 +7,9 @@
 //! - Used by TLS 1.3, WireGuard, Signal
 
 use chacha20poly1305::{
-    aead::{Aead, KeyInit, OsRng},
+    aead::{rand_core::RngCore, Aead, KeyInit, OsRng},
     ChaCha20Poly1305, Nonce,
 };
-use rand::RngCore;
 use thiserror::Error;
 use zeroize::Zeroizing;
 
@@ -109,13 +108,13 @@ mod tests {
         let key = generate_key();
         let plaintext = b"ANTHROPIC_API_KEY=sk-ant-super-secret-key";
         
-        let ciphertext = encrypt(&key, plaintext).unwrap();
+        let ciphertext = encrypt(key.as_ref(), plaintext).unwrap();
         
         // Ciphertext should be different from plaintext
         assert_ne!(&ciphertext[NONCE_SIZE..], plaintext);
         
         // Should decrypt back to original
-        let decrypted = decrypt(&key, &ciphertext).unwrap();
+        let decrypted = decrypt(key.as_ref(), &ciphertext).unwrap();
         assert_eq!(decrypted.as_slice(), plaintext);
     }
     
@@ -124,7 +123,7 @@ mod tests {
         let key = generate_key();
         let plaintext = b"secret data";
         
-        let mut ciphertext = encrypt(&key, plaintext).unwrap();
+        let mut ciphertext = encrypt(key.as_ref(), plaintext).unwrap();
         
         // Tamper with the ciphertext
         if let Some(byte) = ciphertext.last_mut() {
@@ -132,7 +131,7 @@ mod tests {
         }
         
         // Decryption should fail
-        assert!(decrypt(&key, &ciphertext).is_err());
+        assert!(decrypt(key.as_ref(), &ciphertext).is_err());
     }
     
     #[test]
@@ -141,9 +140,9 @@ mod tests {
         let key2 = generate_key();
         let plaintext = b"secret data";
         
-        let ciphertext = encrypt(&key1, plaintext).unwrap();
+        let ciphertext = encrypt(key1.as_ref(), plaintext).unwrap();
         
         // Decryption with wrong key should fail
-        assert!(decrypt(&key2, &ciphertext).is_err());
+        assert!(decrypt(key2.as_ref(), &ciphertext).is_err());
     }
 }
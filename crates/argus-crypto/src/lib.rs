@@ -1,7 +1,11 @@
 //! Argus Crypto
 
+pub mod access;
+pub mod cipher;
 pub mod vault;
 pub mod keychain;
+pub mod pq;
+pub mod seal;
 
 pub use vault::SecureVault;
 pub use keychain::KeychainProvider;
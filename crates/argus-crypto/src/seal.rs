@@ -0,0 +1,284 @@
+//! Hybrid sealed boxes — ML-KEM-768 + X25519 → ChaCha20-Poly1305
+//!
+//! Used to move vault secrets between hosts. Each host holds a
+//! `HostKeypair`; the sender seals to the recipient's public key and only
+//! that host's secret keys can open the box.
+//!
+//! Why hybrid?
+//! - ML-KEM protects against a future quantum adversary recording traffic now
+//! - X25519 keeps classical security if ML-KEM turns out to be flawed
+//! - The content key is HKDF-SHA256 over BOTH shared secrets, so an attacker
+//!   has to break both to read the box
+//!
+//! Every box uses a fresh X25519 ephemeral key and a fresh KEM
+//! encapsulation. The ephemeral key, KEM ciphertext and recipient key id are
+//! bound into the AEAD associated data.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Nonce,
+};
+use ring::rand::SecureRandom;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use x25519_dalek::{PublicKey as X25519Public, StaticSecret};
+use zeroize::Zeroizing;
+
+use crate::pq::kem;
+
+/// Prefix of an encoded host public key: "argus-host1:" + hex(x25519 || ML-KEM).
+pub const HOST_KEY_PREFIX: &str = "argus-host1:";
+pub const SEALED_FORMAT: &str = "argus-sealed";
+pub const SEALED_VERSION: u32 = 1;
+
+const X25519_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+const HKDF_SALT: &[u8] = b"ARGUS_SEAL_V1";
+
+#[derive(Error, Debug)]
+pub enum SealError {
+    #[error("Invalid host public key: {0}")]
+    InvalidPublicKey(String),
+    #[error("Invalid sealed box: {0}")]
+    Malformed(String),
+    #[error("Sealed for host {expected}, this host is {actual}")]
+    WrongRecipient { expected: String, actual: String },
+    #[error("ML-KEM error: {0}")]
+    Kem(String),
+    #[error("Encryption failed")]
+    Encryption,
+    #[error("Decryption failed - box corrupted, tampered or not for this host")]
+    Decryption,
+}
+
+/// A host's public key — what gets passed to `argus vault export --to`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct HostPublicKey {
+    pub x25519: [u8; X25519_SIZE],
+    pub kem: Vec<u8>,
+}
+
+impl HostPublicKey {
+    pub fn encode(&self) -> String {
+        let mut raw = self.x25519.to_vec();
+        raw.extend_from_slice(&self.kem);
+        format!("{}{}", HOST_KEY_PREFIX, hex::encode(raw))
+    }
+
+    pub fn decode(s: &str) -> Result<Self, SealError> {
+        let body = s.trim().strip_prefix(HOST_KEY_PREFIX)
+            .ok_or_else(|| SealError::InvalidPublicKey(format!("missing {} prefix", HOST_KEY_PREFIX)))?;
+        let raw = hex::decode(body).map_err(|e| SealError::InvalidPublicKey(e.to_string()))?;
+        if raw.len() <= X25519_SIZE {
+            return Err(SealError::InvalidPublicKey("too short".to_string()));
+        }
+        let (x, k) = raw.split_at(X25519_SIZE);
+        let mut x25519 = [0u8; X25519_SIZE];
+        x25519.copy_from_slice(x);
+        Ok(Self { x25519, kem: k.to_vec() })
+    }
+
+    /// Short fingerprint: first 16 hex chars of SHA-256 over the encoded key.
+    pub fn key_id(&self) -> String {
+        let digest = ring::digest::digest(&ring::digest::SHA256, self.encode().as_bytes());
+        hex::encode(&digest.as_ref()[..8])
+    }
+}
+
+/// A host's full keypair. Secret halves are scrubbed on drop.
+pub struct HostKeypair {
+    pub public: HostPublicKey,
+    x25519_secret: Zeroizing<[u8; X25519_SIZE]>,
+    kem_secret: Zeroizing<Vec<u8>>,
+}
+
+impl HostKeypair {
+    pub fn generate() -> Result<Self, SealError> {
+        let x25519_secret = random_x25519()?;
+        let x25519 = X25519Public::from(&StaticSecret::from(*x25519_secret)).to_bytes();
+        let kp = kem::generate_keypair();
+        Ok(Self {
+            public: HostPublicKey { x25519, kem: kp.public_key },
+            x25519_secret,
+            kem_secret: Zeroizing::new(kp.secret_key),
+        })
+    }
+
+    /// Rebuild from stored secret halves. The public key is re-derived for
+    /// X25519 and taken as given for ML-KEM.
+    pub fn from_parts(x25519_secret: &[u8], kem_secret: &[u8], kem_public: &[u8]) -> Result<Self, SealError> {
+        let secret: [u8; X25519_SIZE] = x25519_secret.try_into()
+            .map_err(|_| SealError::InvalidPublicKey("X25519 secret must be 32 bytes".to_string()))?;
+        let x25519 = X25519Public::from(&StaticSecret::from(secret)).to_bytes();
+        Ok(Self {
+            public: HostPublicKey { x25519, kem: kem_public.to_vec() },
+            x25519_secret: Zeroizing::new(secret),
+            kem_secret: Zeroizing::new(kem_secret.to_vec()),
+        })
+    }
+
+    pub fn x25519_secret(&self) -> &[u8] {
+        &*self.x25519_secret
+    }
+
+    pub fn kem_secret(&self) -> &[u8] {
+        &self.kem_secret
+    }
+}
+
+/// A sealed box as written to disk — JSON with hex-encoded fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedBox {
+    pub format: String,
+    pub version: u32,
+    pub recipient: String,          // recipient key id
+    pub x25519_ephemeral: String,
+    pub kem_ciphertext: String,
+    pub ciphertext: String,         // nonce || ciphertext || tag
+}
+
+/// Seal `plaintext` so only `recipient`'s keypair can open it.
+pub fn seal(recipient: &HostPublicKey, plaintext: &[u8]) -> Result<SealedBox, SealError> {
+    let encapsulated = kem::encapsulate(&recipient.kem).map_err(SealError::Kem)?;
+    let kem_ss = Zeroizing::new(encapsulated.shared_secret);
+
+    let eph_secret = StaticSecret::from(*random_x25519()?);
+    let eph_public = X25519Public::from(&eph_secret).to_bytes();
+    let x_ss = eph_secret.diffie_hellman(&X25519Public::from(recipient.x25519));
+
+    let mut sealed = SealedBox {
+        format: SEALED_FORMAT.to_string(),
+        version: SEALED_VERSION,
+        recipient: recipient.key_id(),
+        x25519_ephemeral: hex::encode(eph_public),
+        kem_ciphertext: hex::encode(&encapsulated.ciphertext),
+        ciphertext: String::new(),
+    };
+    let key = derive_key(&kem_ss, x_ss.as_bytes(), &sealed, recipient)?;
+
+    let mut nonce = [0u8; NONCE_SIZE];
+    ring::rand::SystemRandom::new().fill(&mut nonce).map_err(|_| SealError::Encryption)?;
+    let cipher = ChaCha20Poly1305::new_from_slice(&*key).map_err(|_| SealError::Encryption)?;
+    let aad = associated_data(&sealed);
+    let ct = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: &aad })
+        .map_err(|_| SealError::Encryption)?;
+
+    let mut out = nonce.to_vec();
+    out.extend(ct);
+    sealed.ciphertext = hex::encode(out);
+    Ok(sealed)
+}
+
+/// Open a box sealed to `keypair`.
+pub fn open(keypair: &HostKeypair, sealed: &SealedBox) -> Result<Zeroizing<Vec<u8>>, SealError> {
+    if sealed.format != SEALED_FORMAT || sealed.version != SEALED_VERSION {
+        return Err(SealError::Malformed(format!(
+            "unsupported format {} v{}", sealed.format, sealed.version
+        )));
+    }
+    let me = keypair.public.key_id();
+    if sealed.recipient != me {
+        return Err(SealError::WrongRecipient { expected: sealed.recipient.clone(), actual: me });
+    }
+
+    let kem_ct = hex::decode(&sealed.kem_ciphertext).map_err(|e| SealError::Malformed(e.to_string()))?;
+    let kem_ss = Zeroizing::new(kem::decapsulate(&keypair.kem_secret, &kem_ct).map_err(SealError::Kem)?);
+
+    let eph: [u8; X25519_SIZE] = hex::decode(&sealed.x25519_ephemeral)
+        .map_err(|e| SealError::Malformed(e.to_string()))?
+        .try_into()
+        .map_err(|_| SealError::Malformed("ephemeral key must be 32 bytes".to_string()))?;
+    let x_ss = StaticSecret::from(*keypair.x25519_secret).diffie_hellman(&X25519Public::from(eph));
+
+    let key = derive_key(&kem_ss, x_ss.as_bytes(), sealed, &keypair.public)?;
+
+    let raw = hex::decode(&sealed.ciphertext).map_err(|e| SealError::Malformed(e.to_string()))?;
+    if raw.len() < NONCE_SIZE {
+        return Err(SealError::Malformed("ciphertext too short".to_string()));
+    }
+    let (nonce, ct) = raw.split_at(NONCE_SIZE);
+    let cipher = ChaCha20Poly1305::new_from_slice(&*key).map_err(|_| SealError::Decryption)?;
+    let aad = associated_data(sealed);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ct, aad: &aad })
+        .map(Zeroizing::new)
+        .map_err(|_| SealError::Decryption)
+}
+
+/// Header fields bound into the AEAD tag.
+fn associated_data(sealed: &SealedBox) -> Vec<u8> {
+    format!(
+        "{}|{}|{}|{}|{}",
+        sealed.format, sealed.version, sealed.recipient, sealed.x25519_ephemeral, sealed.kem_ciphertext
+    ).into_bytes()
+}
+
+/// HKDF-SHA256(ikm = kem_ss || x25519_ss, info = header + recipient key).
+fn derive_key(
+    kem_ss: &[u8],
+    x_ss: &[u8],
+    sealed: &SealedBox,
+    recipient: &HostPublicKey,
+) -> Result<Zeroizing<[u8; 32]>, SealError> {
+    let mut ikm = Zeroizing::new(kem_ss.to_vec());
+    ikm.extend_from_slice(x_ss);
+    let info = [associated_data(sealed), recipient.encode().into_bytes()].concat();
+
+    let prk = ring::hkdf::Salt::new(ring::hkdf::HKDF_SHA256, HKDF_SALT).extract(&ikm);
+    let info_refs = [info.as_slice()];
+    let okm = prk.expand(&info_refs, ring::hkdf::HKDF_SHA256).map_err(|_| SealError::Encryption)?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    okm.fill(key.as_mut()).map_err(|_| SealError::Encryption)?;
+    Ok(key)
+}
+
+fn random_x25519() -> Result<Zeroizing<[u8; X25519_SIZE]>, SealError> {
+    let mut bytes = Zeroizing::new([0u8; X25519_SIZE]);
+    ring::rand::SystemRandom::new().fill(bytes.as_mut()).map_err(|_| SealError::Encryption)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_open_roundtrip() {
+        let host = HostKeypair::generate().unwrap();
+        let encoded = host.public.encode();
+        let recipient = HostPublicKey::decode(&encoded).unwrap();
+
+        let sealed = seal(&recipient, b"{\"api_key\":\"sk-1\"}").unwrap();
+        let opened = open(&host, &sealed).unwrap();
+        assert_eq!(&opened[..], b"{\"api_key\":\"sk-1\"}");
+    }
+
+    #[test]
+    fn test_other_host_and_tampering_fail() {
+        let host = HostKeypair::generate().unwrap();
+        let other = HostKeypair::generate().unwrap();
+        let sealed = seal(&host.public, b"secret").unwrap();
+
+        assert!(open(&other, &sealed).is_err());
+
+        let mut tampered = sealed.clone();
+        let mut raw = hex::decode(&tampered.ciphertext).unwrap();
+        let last = raw.len() - 1;
+        raw[last] ^= 0x01;
+        tampered.ciphertext = hex::encode(raw);
+        assert!(matches!(open(&host, &tampered), Err(SealError::Decryption)));
+
+        let mut swapped = sealed.clone();
+        swapped.x25519_ephemeral = hex::encode(other.public.x25519);
+        assert!(open(&host, &swapped).is_err());
+    }
+
+    #[test]
+    fn test_keypair_from_parts_matches() {
+        let host = HostKeypair::generate().unwrap();
+        let restored = HostKeypair::from_parts(host.x25519_secret(), host.kem_secret(), &host.public.kem).unwrap();
+        assert!(restored.public == host.public);
+        assert_eq!(restored.public.key_id(), host.public.key_id());
+    }
+}
@@ -2,18 +2,57 @@
 //!
 //! Master key is wrapped in `Zeroizing<>` so it's automatically
 //! scrubbed from memory on drop. No lingering key material.
+//!
+//! The master key comes from one of two places, recorded in the vault header:
+//! - the OS keychain (desktop default)
+//! - Argon2id over a passphrase or key file, for headless Linux and Docker
+//!   where no keychain exists. Salt and cost parameters live in the header;
+//!   an encrypted key-check blob tells a wrong passphrase from corruption.
+//!
+//! Format v2 binds each secret's name and the format version into the
+//! ChaCha20-Poly1305 associated data, so a ciphertext moved to another name
+//! fails to decrypt. Older files are re-encrypted on first unlock. Saves are
+//! write-then-rename so a crash never leaves a half-written vault.
+//!
+//! Secrets move between hosts as hybrid sealed boxes (see `seal`): each
+//! host keeps its own keypair in the vault under `host_seal_*`.
+//!
+//! Subsystems read through scoped handles (see `access`); every read is
+//! reported to the attached access log.
 
 use chacha20poly1305::{
-    aead::{Aead, KeyInit},
+    aead::{Aead, KeyInit, Payload},
     ChaCha20Poly1305, Nonce,
 };
 use ring::rand::SecureRandom;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 use zeroize::Zeroizing;
 
+use crate::access::{AccessEvent, AccessLog, ScopePolicy, ScopedVault, SharedSink, UNSCOPED};
+use crate::seal::{HostKeypair, HostPublicKey, SealedBox};
+
+/// On-disk format version written by `save`.
+///   0 — headerless: a bare JSON map of name -> nonce||ciphertext, no AAD
+///   1 — key-source header, entries still without AAD
+///   2 — entries sealed with AAD = "ARGUS_VAULT|v2|" + name
+pub const VAULT_FORMAT_VERSION: u32 = 2;
+
+const KEY_CHECK_PLAINTEXT: &[u8] = b"ARGUS_VAULT_KEY_CHECK";
+const SALT_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+
+/// Vault entries holding this host's sealed-transfer keypair. Host-specific,
+/// so never included in a sealed export.
+pub const HOST_KEY_PREFIX: &str = "host_seal_";
+const HOST_X25519_SECRET: &str = "host_seal_x25519_secret";
+const HOST_KEM_SECRET: &str = "host_seal_kem_secret";
+const HOST_KEM_PUBLIC: &str = "host_seal_kem_public";
+
 #[derive(Error, Debug)]
 pub enum VaultError {
     #[error("Vault is locked")]
@@ -28,12 +67,142 @@ pub enum VaultError {
     Io(#[from] std::io::Error),
     #[error("Keychain error: {0}")]
     Keychain(String),
+    #[error("Wrong passphrase or key file")]
+    WrongPassphrase,
+    #[error("Key derivation failed: {0}")]
+    Kdf(String),
+    #[error("Vault is {vault}-protected, but a {given} was supplied")]
+    KeySourceMismatch { vault: &'static str, given: &'static str },
+    #[error("Unsupported vault format version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Sealed transfer error: {0}")]
+    Seal(#[from] crate::seal::SealError),
+    #[error("No host keypair — run `argus vault keygen` first")]
+    NoHostKeypair,
+    #[error("Scope '{scope}' may not read secret '{name}'")]
+    ScopeDenied { scope: String, name: String },
+}
+
+/// Where the master key comes from when creating or unlocking a vault.
+pub enum MasterKeySource {
+    /// OS keychain via `KeychainProvider`.
+    Keychain,
+    /// Argon2id over a passphrase.
+    Passphrase(Zeroizing<String>),
+    /// Argon2id over the contents of a file (e.g. a Docker secret).
+    /// A single trailing newline is ignored.
+    KeyFile(PathBuf),
+}
+
+impl MasterKeySource {
+    fn describe(&self) -> &'static str {
+        match self {
+            MasterKeySource::Keychain      => "keychain",
+            MasterKeySource::Passphrase(_) => "passphrase",
+            MasterKeySource::KeyFile(_)    => "key file",
+        }
+    }
+
+    /// Secret bytes fed to Argon2id. None for the keychain.
+    fn kdf_input(&self) -> Result<Option<Zeroizing<Vec<u8>>>, VaultError> {
+        match self {
+            MasterKeySource::Keychain => Ok(None),
+            MasterKeySource::Passphrase(p) => Ok(Some(Zeroizing::new(p.as_bytes().to_vec()))),
+            MasterKeySource::KeyFile(path) => {
+                let mut bytes = Zeroizing::new(fs::read(path)?);
+                if bytes.ends_with(b"\n") {
+                    bytes.pop();
+                    if bytes.ends_with(b"\r") {
+                        bytes.pop();
+                    }
+                }
+                if bytes.is_empty() {
+                    return Err(VaultError::Kdf(format!("key file {} is empty", path.display())));
+                }
+                Ok(Some(bytes))
+            }
+        }
+    }
+}
+
+/// Argon2id cost parameters. Stored in the vault header so they can be
+/// raised for new vaults without breaking existing ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub m_cost_kib: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    /// 64 MiB, 3 passes, 1 lane — OWASP's Argon2id baseline.
+    fn default() -> Self {
+        Self { m_cost_kib: 64 * 1024, t_cost: 3, p_cost: 1 }
+    }
+}
+
+/// Upper bounds on the costs a vault may ask for: 1 GiB, 16 passes,
+/// 8 lanes. The header isn't authenticated until the key is derived, so a
+/// tampered one could otherwise make unlocking allocate or spin without end.
+const MAX_M_COST_KIB: u32 = 1024 * 1024;
+const MAX_T_COST: u32 = 16;
+const MAX_P_COST: u32 = 8;
+
+impl KdfParams {
+    /// Err unless every cost is between 1 and its bound, with at least the
+    /// 8 KiB per lane Argon2 needs.
+    fn check(&self) -> Result<(), VaultError> {
+        let in_range = (1..=MAX_P_COST).contains(&self.p_cost)
+            && (1..=MAX_T_COST).contains(&self.t_cost)
+            && (8 * self.p_cost..=MAX_M_COST_KIB).contains(&self.m_cost_kib);
+        if !in_range {
+            return Err(VaultError::Kdf(format!(
+                "Argon2 parameters out of range: {} KiB, {} passes, {} lanes",
+                self.m_cost_kib, self.t_cost, self.p_cost
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// How the master key for this vault file is obtained.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum KeyHeader {
+    Keychain,
+    Argon2id {
+        salt: Vec<u8>,
+        #[serde(flatten)]
+        params: KdfParams,
+        /// nonce||ciphertext of KEY_CHECK_PLAINTEXT under the derived key.
+        key_check: Vec<u8>,
+    },
+}
+
+impl KeyHeader {
+    fn describe(&self) -> &'static str {
+        match self {
+            KeyHeader::Keychain       => "keychain",
+            KeyHeader::Argon2id { .. } => "passphrase",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    key: KeyHeader,
+    secrets: HashMap<String, Vec<u8>>,
 }
 
 pub struct SecureVault {
     master_key: Option<Zeroizing<[u8; 32]>>,
     vault_path: PathBuf,
+    key_header: KeyHeader,
     secrets: HashMap<String, Vec<u8>>,
+    migrated_from: Option<u32>,
+    policy: ScopePolicy,
+    access: SharedSink,
 }
 
 impl SecureVault {
@@ -41,64 +210,283 @@ impl SecureVault {
         Self {
             master_key: None,
             vault_path,
+            key_header: KeyHeader::Keychain,
             secrets: HashMap::new(),
+            migrated_from: None,
+            policy: ScopePolicy::default(),
+            access: SharedSink::default(),
         }
     }
 
+    /// Create a new vault with a random master key held in the OS keychain.
     pub fn init(vault_path: PathBuf) -> Result<Self, VaultError> {
-        let mut key = Zeroizing::new([0u8; 32]);
-        ring::rand::SystemRandom::new()
-            .fill(key.as_mut())
-            .map_err(|_| VaultError::Encryption)?;
+        Self::init_with(vault_path, &MasterKeySource::Keychain, KdfParams::default())
+    }
 
-        // Store in keychain
-        let keychain = crate::keychain::KeychainProvider::new("argus");
-        keychain.store_master_key(&*key).map_err(|e| VaultError::Keychain(e.to_string()))?;
+    /// Create a new vault whose master key comes from `source`.
+    /// `params` only applies to passphrase and key-file sources.
+    pub fn init_with(vault_path: PathBuf, source: &MasterKeySource, params: KdfParams) -> Result<Self, VaultError> {
+        let (key, key_header) = new_master_key(source, params)?;
+        if matches!(key_header, KeyHeader::Keychain) {
+            let keychain = crate::keychain::KeychainProvider::new("argus");
+            keychain.store_master_key(&*key).map_err(|e| VaultError::Keychain(e.to_string()))?;
+        }
 
         fs::create_dir_all(vault_path.parent().unwrap_or(&vault_path))?;
 
         let vault = Self {
             master_key: Some(key),
             vault_path,
+            key_header,
             secrets: HashMap::new(),
+            migrated_from: None,
+            policy: ScopePolicy::default(),
+            access: SharedSink::default(),
         };
         vault.save()?;
         Ok(vault)
     }
 
+    /// Unlock with the OS keychain.
     pub fn unlock(&mut self) -> Result<(), VaultError> {
-        let keychain = crate::keychain::KeychainProvider::new("argus");
-        let key_vec = keychain.retrieve_master_key().map_err(|e| VaultError::Keychain(e.to_string()))?;
-        let mut key = Zeroizing::new([0u8; 32]);
-        key.copy_from_slice(&key_vec);
+        self.unlock_with(&MasterKeySource::Keychain)
+    }
+
+    /// Unlock with `source`, which must match how the vault was created.
+    pub fn unlock_with(&mut self, source: &MasterKeySource) -> Result<(), VaultError> {
+        let file = self.read_file()?;
+        let key_header = file.as_ref().map(|f| f.key.clone()).unwrap_or(KeyHeader::Keychain);
+
+        let key = match (&key_header, source.kdf_input()?) {
+            (KeyHeader::Keychain, None) => {
+                let keychain = crate::keychain::KeychainProvider::new("argus");
+                let key_vec = Zeroizing::new(
+                    keychain.retrieve_master_key().map_err(|e| VaultError::Keychain(e.to_string()))?,
+                );
+                if key_vec.len() != 32 {
+                    return Err(VaultError::Keychain(format!("master key is {} bytes, expected 32", key_vec.len())));
+                }
+                let mut key = Zeroizing::new([0u8; 32]);
+                key.copy_from_slice(&key_vec);
+                key
+            }
+            (KeyHeader::Argon2id { salt, params, key_check }, Some(secret)) => {
+                let key = derive_key(&secret, salt, params)?;
+                match crate::cipher::decrypt(&*key, key_check) {
+                    Ok(check) if check.as_slice() == KEY_CHECK_PLAINTEXT => key,
+                    _ => return Err(VaultError::WrongPassphrase),
+                }
+            }
+            (header, _) => {
+                return Err(VaultError::KeySourceMismatch { vault: header.describe(), given: source.describe() })
+            }
+        };
+
+        let (version, secrets) = file.map(|f| (f.version, f.secrets)).unwrap_or((VAULT_FORMAT_VERSION, HashMap::new()));
         self.master_key = Some(key);
-        self.load()?;
+        self.key_header = key_header;
+
+        if version < VAULT_FORMAT_VERSION {
+            // Open each entry with the AAD of the format it was stored in
+            // (none before v2), reseal with the current one. Everything is
+            // decrypted before anything is written, so a bad entry leaves
+            // the old file untouched.
+            let key = self.master_key.as_ref().ok_or(VaultError::Locked)?;
+            let mut resealed = HashMap::with_capacity(secrets.len());
+            for (name, stored) in &secrets {
+                let plaintext = open_entry(key, stored, version, name)?;
+                resealed.insert(name.clone(), seal_entry(key, name, &plaintext)?);
+            }
+            self.secrets = resealed;
+            self.save()?;
+            self.migrated_from = Some(version);
+        } else {
+            self.secrets = secrets;
+        }
         Ok(())
     }
 
+    /// Format version the vault was upgraded from during `unlock`, if any.
+    pub fn migrated_from(&self) -> Option<u32> {
+        self.migrated_from
+    }
+
+    /// Re-encrypt every secret under a fresh master key and return how many
+    /// were rewritten. A keychain source gets a new random key; a passphrase
+    /// or key file gets a fresh salt, so the derived key changes even when
+    /// the passphrase doesn't. `source` may differ from the current one to
+    /// move a vault between keychain and passphrase protection.
+    ///
+    /// The new file is written to a temp path first; the keychain (if used)
+    /// is updated only once that succeeds, then the file is renamed in. If
+    /// the rename fails, the old key goes back into the keychain, so the
+    /// keychain and the file on disk always agree.
+    pub fn rotate(&mut self, source: &MasterKeySource, params: KdfParams) -> Result<usize, VaultError> {
+        let old_key = self.master_key.as_ref().ok_or(VaultError::Locked)?;
+        let (new_key, new_header) = new_master_key(source, params)?;
+
+        let mut resealed = HashMap::with_capacity(self.secrets.len());
+        for (name, stored) in &self.secrets {
+            let plaintext = open_entry(old_key, stored, VAULT_FORMAT_VERSION, name)?;
+            resealed.insert(name.clone(), seal_entry(&new_key, name, &plaintext)?);
+        }
+
+        let tmp = self.write_temp(&new_header, &resealed)?;
+        let keychain = crate::keychain::KeychainProvider::new("argus");
+        let swaps_keychain_key = matches!(new_header, KeyHeader::Keychain);
+        if swaps_keychain_key {
+            if let Err(e) = keychain.store_master_key(&*new_key) {
+                let _ = fs::remove_file(&tmp);
+                return Err(VaultError::Keychain(e.to_string()));
+            }
+        }
+        if let Err(e) = fs::rename(&tmp, &self.vault_path) {
+            let _ = fs::remove_file(&tmp);
+            // A passphrase vault never read the keychain, so there's nothing to put back.
+            if swaps_keychain_key && matches!(self.key_header, KeyHeader::Keychain) {
+                if let Err(k) = keychain.store_master_key(&**old_key) {
+                    return Err(VaultError::Keychain(format!(
+                        "vault file not replaced ({}), and the old master key could not be restored: {}", e, k
+                    )));
+                }
+            }
+            return Err(e.into());
+        }
+
+        let count = resealed.len();
+        self.master_key = Some(new_key);
+        self.key_header = new_header;
+        self.secrets = resealed;
+        Ok(count)
+    }
+
+    /// This host's keypair for receiving sealed exports.
+    pub fn host_keypair(&self) -> Result<HostKeypair, VaultError> {
+        let get = |name: &str| -> Result<Zeroizing<Vec<u8>>, VaultError> {
+            let hex_str = Zeroizing::new(self.retrieve(name).map_err(|e| match e {
+                VaultError::NotFound(_) => VaultError::NoHostKeypair,
+                e => e,
+            })?);
+            hex::decode(hex_str.as_str()).map(Zeroizing::new).map_err(|_| VaultError::Decryption)
+        };
+        Ok(HostKeypair::from_parts(&get(HOST_X25519_SECRET)?, &get(HOST_KEM_SECRET)?, &get(HOST_KEM_PUBLIC)?)?)
+    }
+
+    /// Generate and store a fresh host keypair, replacing any existing one.
+    /// Boxes sealed to the old public key can no longer be imported.
+    pub fn generate_host_keypair(&mut self) -> Result<HostKeypair, VaultError> {
+        let kp = HostKeypair::generate()?;
+        self.store(HOST_X25519_SECRET, &Zeroizing::new(hex::encode(kp.x25519_secret())))?;
+        self.store(HOST_KEM_SECRET, &Zeroizing::new(hex::encode(kp.kem_secret())))?;
+        self.store(HOST_KEM_PUBLIC, &hex::encode(&kp.public.kem))?;
+        Ok(kp)
+    }
+
+    /// Seal secrets to another host: those in `names`, or everything when
+    /// None. This host's own keypair never leaves, even when named. Returns
+    /// the box and how many secrets it holds.
+    pub fn export_sealed(&self, recipient: &HostPublicKey, names: Option<&[String]>) -> Result<(SealedBox, usize), VaultError> {
+        let selected: Vec<String> = match names {
+            Some(names) => names.to_vec(),
+            None => self.secrets.keys().cloned().collect(),
+        };
+        let selected: Vec<String> = selected.into_iter()
+            .filter(|n| !n.starts_with(HOST_KEY_PREFIX))
+            .collect();
+        let values = selected.iter()
+            .map(|name| self.retrieve(name).map(Zeroizing::new))
+            .collect::<Result<Vec<_>, _>>()?;
+        let plain: HashMap<&str, &str> = selected.iter()
+            .map(String::as_str)
+            .zip(values.iter().map(|v| v.as_str()))
+            .collect();
+        let payload = Zeroizing::new(serde_json::to_vec(&plain).map_err(|_| VaultError::Encryption)?);
+        Ok((crate::seal::seal(recipient, &payload)?, plain.len()))
+    }
+
+    /// Open a box sealed to this host and store its secrets. Existing names
+    /// are left alone unless `overwrite`. Returns (imported, skipped) names.
+    pub fn import_sealed(&mut self, sealed: &SealedBox, overwrite: bool) -> Result<(Vec<String>, Vec<String>), VaultError> {
+        let payload = crate::seal::open(&self.host_keypair()?, sealed)?;
+        let secrets: HashMap<String, String> =
+            serde_json::from_slice(&payload).map_err(|_| VaultError::Decryption)?;
+
+        let key = self.master_key.as_ref().ok_or(VaultError::Locked)?;
+        let (mut imported, mut skipped) = (Vec::new(), Vec::new());
+        let mut sealed_entries = Vec::with_capacity(secrets.len());
+        for (name, value) in secrets {
+            let value = Zeroizing::new(value);
+            if name.starts_with(HOST_KEY_PREFIX) || (!overwrite && self.secrets.contains_key(&name)) {
+                skipped.push(name);
+                continue;
+            }
+            sealed_entries.push((name.clone(), seal_entry(key, &name, value.as_bytes())?));
+            imported.push(name);
+        }
+        self.secrets.extend(sealed_entries);
+        self.save()?;
+        imported.sort();
+        skipped.sort();
+        Ok((imported, skipped))
+    }
+
+    /// "keychain" or "passphrase" — how this vault's master key is obtained.
+    pub fn key_source(&self) -> &'static str {
+        self.key_header.describe()
+    }
+
+    /// True if the vault at `path` is passphrase/key-file protected, so the
+    /// caller knows to supply a secret rather than try the keychain.
+    pub fn requires_passphrase(path: &Path) -> Result<bool, VaultError> {
+        let vault = Self::new(path.to_path_buf());
+        Ok(matches!(vault.read_file()?, Some(VaultFile { key: KeyHeader::Argon2id { .. }, .. })))
+    }
+
     pub fn store(&mut self, name: &str, secret: &str) -> Result<(), VaultError> {
         let key = self.master_key.as_ref().ok_or(VaultError::Locked)?;
-        let cipher = ChaCha20Poly1305::new_from_slice(&**key).map_err(|_| VaultError::Encryption)?;
-        let mut nonce_bytes = [0u8; 12];
-        ring::rand::SystemRandom::new().fill(&mut nonce_bytes).map_err(|_| VaultError::Encryption)?;
-        let nonce = Nonce::from_slice(&nonce_bytes);
-        let ciphertext = cipher.encrypt(nonce, secret.as_bytes()).map_err(|_| VaultError::Encryption)?;
-        
-        let mut stored = nonce_bytes.to_vec();
-        stored.extend(ciphertext);
+        let stored = seal_entry(key, name, secret.as_bytes())?;
         self.secrets.insert(name.to_string(), stored);
         self.save()?;
         Ok(())
     }
 
+    /// Full-access read. Logged under the `*` scope; subsystems should use
+    /// `scoped` instead.
     pub fn retrieve(&self, name: &str) -> Result<String, VaultError> {
+        self.retrieve_as(UNSCOPED, name)
+    }
+
+    /// Set which secrets each scope may read. Replaces any previous policy.
+    pub fn set_scope_policy(&mut self, policy: ScopePolicy) {
+        self.policy = policy;
+    }
+
+    /// Read-only handle limited to `scope`'s secrets. A scope missing from
+    /// the policy can read nothing.
+    pub fn scoped(&self, scope: &str) -> ScopedVault<'_> {
+        ScopedVault::new(self, scope)
+    }
+
+    /// Report every read to `log`, including those made before it was attached.
+    pub fn set_access_log(&self, log: std::sync::Arc<dyn AccessLog>) {
+        if let Ok(mut sink) = self.access.lock() {
+            sink.attach(log);
+        }
+    }
+
+    pub(crate) fn retrieve_as(&self, scope: &str, name: &str) -> Result<String, VaultError> {
+        let granted = scope == UNSCOPED || self.policy.permits(scope, name);
+        if let Ok(mut sink) = self.access.lock() {
+            sink.record(AccessEvent { scope: scope.to_string(), name: name.to_string(), granted });
+        }
+        if !granted {
+            return Err(VaultError::ScopeDenied { scope: scope.to_string(), name: name.to_string() });
+        }
+
         let key = self.master_key.as_ref().ok_or(VaultError::Locked)?;
         let stored = self.secrets.get(name).ok_or_else(|| VaultError::NotFound(name.to_string()))?;
-        let (nonce_bytes, ciphertext) = stored.split_at(12);
-        let cipher = ChaCha20Poly1305::new_from_slice(&**key).map_err(|_| VaultError::Decryption)?;
-        let nonce = Nonce::from_slice(nonce_bytes);
-        let plaintext = cipher.decrypt(nonce, ciphertext).map_err(|_| VaultError::Decryption)?;
-        String::from_utf8(plaintext).map_err(|_| VaultError::Decryption)
+        let plaintext = open_entry(key, stored, VAULT_FORMAT_VERSION, name)?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| VaultError::Decryption)
     }
 
     pub fn list_keys(&self) -> Vec<String> {
@@ -111,16 +499,374 @@ impl SecureVault {
     }
 
     fn save(&self) -> Result<(), VaultError> {
-        let data = serde_json::to_vec(&self.secrets).map_err(|_| VaultError::Encryption)?;
-        fs::write(&self.vault_path, data)?;
+        let tmp = self.write_temp(&self.key_header, &self.secrets)?;
+        fs::rename(&tmp, &self.vault_path)?;
         Ok(())
     }
 
-    fn load(&mut self) -> Result<(), VaultError> {
-        if self.vault_path.exists() {
-            let data = fs::read(&self.vault_path)?;
-            self.secrets = serde_json::from_slice(&data).map_err(|_| VaultError::Decryption)?;
+    /// Write a complete vault file next to the real one and fsync it.
+    /// The caller renames it into place — rename is atomic on one filesystem.
+    fn write_temp(&self, key: &KeyHeader, secrets: &HashMap<String, Vec<u8>>) -> Result<PathBuf, VaultError> {
+        let file = VaultFile {
+            version: VAULT_FORMAT_VERSION,
+            key: key.clone(),
+            secrets: secrets.clone(),
+        };
+        let data = serde_json::to_vec(&file).map_err(|_| VaultError::Encryption)?;
+
+        let mut tmp_name = self.vault_path.as_os_str().to_owned();
+        tmp_name.push(".tmp");
+        let tmp = PathBuf::from(tmp_name);
+
+        let mut opts = fs::OpenOptions::new();
+        opts.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            opts.mode(0o600);
         }
-        Ok(())
+        let mut f = opts.open(&tmp)?;
+        f.write_all(&data)?;
+        f.sync_all()?;
+        Ok(tmp)
+    }
+
+    /// Parse the vault file, accepting the headerless version-0 layout as a
+    /// keychain vault and rejecting out-of-range Argon2 costs. None if the
+    /// file doesn't exist yet.
+    fn read_file(&self) -> Result<Option<VaultFile>, VaultError> {
+        if !self.vault_path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read(&self.vault_path)?;
+        let value: serde_json::Value = serde_json::from_slice(&data).map_err(|_| VaultError::Decryption)?;
+        if value.get("version").is_none() {
+            let secrets = serde_json::from_value(value).map_err(|_| VaultError::Decryption)?;
+            return Ok(Some(VaultFile { version: 0, key: KeyHeader::Keychain, secrets }));
+        }
+        let file: VaultFile = serde_json::from_value(value).map_err(|_| VaultError::Decryption)?;
+        if file.version > VAULT_FORMAT_VERSION {
+            return Err(VaultError::UnsupportedVersion(file.version));
+        }
+        if let KeyHeader::Argon2id { params, .. } = &file.key {
+            params.check()?;
+        }
+        Ok(Some(file))
+    }
+}
+
+/// Fresh master key and matching header for `source`. Does not touch the
+/// keychain — callers store keychain keys once the vault file is safe.
+fn new_master_key(source: &MasterKeySource, params: KdfParams) -> Result<(Zeroizing<[u8; 32]>, KeyHeader), VaultError> {
+    match source.kdf_input()? {
+        None => {
+            let mut key = Zeroizing::new([0u8; 32]);
+            ring::rand::SystemRandom::new()
+                .fill(key.as_mut())
+                .map_err(|_| VaultError::Encryption)?;
+            Ok((key, KeyHeader::Keychain))
+        }
+        Some(secret) => {
+            params.check()?;
+            let mut salt = vec![0u8; SALT_SIZE];
+            ring::rand::SystemRandom::new()
+                .fill(&mut salt)
+                .map_err(|_| VaultError::Encryption)?;
+            let key = derive_key(&secret, &salt, &params)?;
+            let key_check = crate::cipher::encrypt(&*key, KEY_CHECK_PLAINTEXT)
+                .map_err(|_| VaultError::Encryption)?;
+            Ok((key, KeyHeader::Argon2id { salt, params, key_check }))
+        }
+    }
+}
+
+/// Associated data for an entry stored in format `version`: binds the
+/// ciphertext to its name and that version. Empty before v2.
+fn entry_aad(version: u32, name: &str) -> Vec<u8> {
+    if version < 2 {
+        return Vec::new();
+    }
+    format!("ARGUS_VAULT|v{}|{}", version, name).into_bytes()
+}
+
+/// nonce || ciphertext || tag, sealed with the entry's AAD.
+fn seal_entry(key: &[u8; 32], name: &str, plaintext: &[u8]) -> Result<Vec<u8>, VaultError> {
+    let cipher = ChaCha20Poly1305::new_from_slice(key).map_err(|_| VaultError::Encryption)?;
+    let mut nonce_bytes = [0u8; NONCE_SIZE];
+    ring::rand::SystemRandom::new().fill(&mut nonce_bytes).map_err(|_| VaultError::Encryption)?;
+    let aad = entry_aad(VAULT_FORMAT_VERSION, name);
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), Payload { msg: plaintext, aad: &aad })
+        .map_err(|_| VaultError::Encryption)?;
+
+    let mut stored = nonce_bytes.to_vec();
+    stored.extend(ciphertext);
+    Ok(stored)
+}
+
+/// Open entry `name`, stored in format `version`.
+fn open_entry(key: &[u8; 32], stored: &[u8], version: u32, name: &str) -> Result<Zeroizing<Vec<u8>>, VaultError> {
+    if stored.len() < NONCE_SIZE {
+        return Err(VaultError::Decryption);
+    }
+    let (nonce_bytes, ciphertext) = stored.split_at(NONCE_SIZE);
+    let cipher = ChaCha20Poly1305::new_from_slice(key).map_err(|_| VaultError::Decryption)?;
+    let aad = entry_aad(version, name);
+    cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), Payload { msg: ciphertext, aad: &aad })
+        .map(Zeroizing::new)
+        .map_err(|_| VaultError::Decryption)
+}
+
+/// Argon2id(secret, salt) -> 32-byte master key.
+fn derive_key(secret: &[u8], salt: &[u8], params: &KdfParams) -> Result<Zeroizing<[u8; 32]>, VaultError> {
+    let argon_params = argon2::Params::new(params.m_cost_kib, params.t_cost, params.p_cost, Some(32))
+        .map_err(|e| VaultError::Kdf(e.to_string()))?;
+    let argon = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, argon_params);
+    let mut key = Zeroizing::new([0u8; 32]);
+    argon.hash_password_into(secret, salt, key.as_mut())
+        .map_err(|e| VaultError::Kdf(e.to_string()))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAST: KdfParams = KdfParams { m_cost_kib: 64, t_cost: 1, p_cost: 1 };
+
+    fn temp_vault_path() -> PathBuf {
+        let mut nonce = [0u8; 8];
+        ring::rand::SystemRandom::new().fill(&mut nonce).unwrap();
+        let name: String = nonce.iter().map(|b| format!("{:02x}", b)).collect();
+        std::env::temp_dir().join(format!("argus-vault-{}.enc", name))
+    }
+
+    fn passphrase(p: &str) -> MasterKeySource {
+        MasterKeySource::Passphrase(Zeroizing::new(p.to_string()))
+    }
+
+    #[test]
+    fn test_passphrase_roundtrip() {
+        let path = temp_vault_path();
+        let mut vault = SecureVault::init_with(path.clone(), &passphrase("correct horse"), FAST).unwrap();
+        vault.store("api_key", "sk-123").unwrap();
+        assert!(SecureVault::requires_passphrase(&path).unwrap());
+
+        let mut reopened = SecureVault::new(path.clone());
+        reopened.unlock_with(&passphrase("correct horse")).unwrap();
+        assert_eq!(reopened.retrieve("api_key").unwrap(), "sk-123");
+
+        let mut wrong = SecureVault::new(path.clone());
+        assert!(matches!(wrong.unlock_with(&passphrase("battery staple")), Err(VaultError::WrongPassphrase)));
+        assert!(matches!(wrong.unlock(), Err(VaultError::KeySourceMismatch { .. })));
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_out_of_range_kdf_params_are_rejected() {
+        let path = temp_vault_path();
+        let huge = KdfParams { m_cost_kib: u32::MAX, ..FAST };
+        assert!(matches!(SecureVault::init_with(path.clone(), &passphrase("pw"), huge), Err(VaultError::Kdf(_))));
+
+        SecureVault::init_with(path.clone(), &passphrase("pw"), FAST).unwrap();
+        let good: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        for (field, value) in [("m_cost_kib", u32::MAX), ("m_cost_kib", 4), ("t_cost", 0), ("t_cost", 1000), ("p_cost", 0), ("p_cost", 64)] {
+            let mut tampered = good.clone();
+            tampered["key"][field] = value.into();
+            fs::write(&path, serde_json::to_vec(&tampered).unwrap()).unwrap();
+            let mut vault = SecureVault::new(path.clone());
+            let result = vault.unlock_with(&passphrase("pw"));
+            assert!(matches!(result, Err(VaultError::Kdf(_))), "{} = {} was accepted", field, value);
+        }
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_key_file_ignores_trailing_newline() {
+        let path = temp_vault_path();
+        let key_path = path.with_extension("key");
+        fs::write(&key_path, "s3cret\n").unwrap();
+        let mut vault = SecureVault::init_with(path.clone(), &MasterKeySource::KeyFile(key_path.clone()), FAST).unwrap();
+        vault.store("token", "abc").unwrap();
+
+        let mut reopened = SecureVault::new(path.clone());
+        reopened.unlock_with(&passphrase("s3cret")).unwrap();
+        assert_eq!(reopened.retrieve("token").unwrap(), "abc");
+
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&key_path);
+    }
+
+    #[test]
+    fn test_ciphertext_bound_to_name() {
+        let path = temp_vault_path();
+        let mut vault = SecureVault::init_with(path.clone(), &passphrase("pw"), FAST).unwrap();
+        vault.store("telegram_bot_token", "tg").unwrap();
+        vault.store("openrouter_api_key", "or").unwrap();
+
+        let tg = vault.secrets["telegram_bot_token"].clone();
+        vault.secrets.insert("openrouter_api_key".to_string(), tg);
+        assert!(matches!(vault.retrieve("openrouter_api_key"), Err(VaultError::Decryption)));
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_v2_entries_open_under_their_own_aad() {
+        // The v2 layout is fixed: a later format bump must still open v2
+        // entries during migration.
+        assert_eq!(entry_aad(2, "api_key"), b"ARGUS_VAULT|v2|api_key");
+        assert!(entry_aad(1, "api_key").is_empty());
+
+        let key = [7u8; 32];
+        let cipher = ChaCha20Poly1305::new_from_slice(&key).unwrap();
+        let nonce = [1u8; NONCE_SIZE];
+        let mut stored = nonce.to_vec();
+        stored.extend(cipher.encrypt(Nonce::from_slice(&nonce), Payload { msg: b"sk-v2", aad: b"ARGUS_VAULT|v2|api_key" }).unwrap());
+        assert_eq!(&*open_entry(&key, &stored, 2, "api_key").unwrap(), b"sk-v2");
+        assert!(open_entry(&key, &stored, 1, "api_key").is_err());
+    }
+
+    #[test]
+    fn test_migrates_v1_vault() {
+        let path = temp_vault_path();
+        let key = derive_key(b"pw", b"0123456789abcdef", &FAST).unwrap();
+
+        // Version 1 file: header present, entries sealed without AAD.
+        let legacy = crate::cipher::encrypt(&*key, b"sk-legacy").unwrap();
+        let file = VaultFile {
+            version: 1,
+            key: KeyHeader::Argon2id {
+                salt: b"0123456789abcdef".to_vec(),
+                params: FAST,
+                key_check: crate::cipher::encrypt(&*key, KEY_CHECK_PLAINTEXT).unwrap(),
+            },
+            secrets: HashMap::from([("api_key".to_string(), legacy)]),
+        };
+        fs::write(&path, serde_json::to_vec(&file).unwrap()).unwrap();
+
+        let mut vault = SecureVault::new(path.clone());
+        vault.unlock_with(&passphrase("pw")).unwrap();
+        assert_eq!(vault.migrated_from(), Some(1));
+        assert_eq!(vault.retrieve("api_key").unwrap(), "sk-legacy");
+
+        let mut reopened = SecureVault::new(path.clone());
+        reopened.unlock_with(&passphrase("pw")).unwrap();
+        assert_eq!(reopened.migrated_from(), None);
+        assert_eq!(reopened.retrieve("api_key").unwrap(), "sk-legacy");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_sealed_transfer_between_vaults() {
+        let (src_path, dst_path) = (temp_vault_path(), temp_vault_path());
+        let mut src = SecureVault::init_with(src_path.clone(), &passphrase("a"), FAST).unwrap();
+        let mut dst = SecureVault::init_with(dst_path.clone(), &passphrase("b"), FAST).unwrap();
+        src.generate_host_keypair().unwrap();
+        src.store("telegram_bot_token", "tg").unwrap();
+        src.store("github_token", "gh").unwrap();
+        dst.store("github_token", "keep-me").unwrap();
+        let dst_pub = dst.generate_host_keypair().unwrap().public;
+
+        let (sealed, count) = src.export_sealed(&dst_pub, None).unwrap();
+        assert_eq!(count, 2);
+
+        // Naming the host keypair doesn't export it either.
+        let named: Vec<String> = [HOST_X25519_SECRET, HOST_KEM_SECRET, "github_token"].iter().map(|n| n.to_string()).collect();
+        assert_eq!(src.export_sealed(&dst_pub, Some(&named)).unwrap().1, 1);
+        assert!(src.import_sealed(&sealed, false).is_err());
+
+        let (imported, skipped) = dst.import_sealed(&sealed, false).unwrap();
+        assert_eq!(imported, vec!["telegram_bot_token".to_string()]);
+        assert_eq!(skipped, vec!["github_token".to_string()]);
+        assert_eq!(dst.retrieve("telegram_bot_token").unwrap(), "tg");
+        assert_eq!(dst.retrieve("github_token").unwrap(), "keep-me");
+
+        let _ = fs::remove_file(&src_path);
+        let _ = fs::remove_file(&dst_path);
+    }
+
+    #[test]
+    fn test_scoped_reads_are_enforced_and_logged() {
+        struct Recorder(std::sync::Mutex<Vec<AccessEvent>>);
+        impl AccessLog for Recorder {
+            fn record(&self, event: &AccessEvent) {
+                self.0.lock().unwrap().push(event.clone());
+            }
+        }
+
+        let path = temp_vault_path();
+        let mut vault = SecureVault::init_with(path.clone(), &passphrase("pw"), FAST).unwrap();
+        vault.store("telegram_bot_token", "tg").unwrap();
+        vault.store("discord_bot_token", "dc").unwrap();
+        vault.set_scope_policy(ScopePolicy::new().allow("telegram", &["telegram_bot_token"]));
+
+        // Read before the log is attached — buffered, not lost.
+        assert_eq!(vault.scoped("telegram").retrieve("telegram_bot_token").unwrap(), "tg");
+
+        let log = std::sync::Arc::new(Recorder(std::sync::Mutex::new(Vec::new())));
+        vault.set_access_log(log.clone());
+        assert!(matches!(
+            vault.scoped("telegram").retrieve("discord_bot_token"),
+            Err(VaultError::ScopeDenied { .. })
+        ));
+        assert_eq!(vault.retrieve("discord_bot_token").unwrap(), "dc");
+
+        let events = log.0.lock().unwrap();
+        let seen: Vec<(&str, &str, bool)> = events.iter()
+            .map(|e| (e.scope.as_str(), e.name.as_str(), e.granted))
+            .collect();
+        assert_eq!(seen, vec![
+            ("telegram", "telegram_bot_token", true),
+            ("telegram", "discord_bot_token", false),
+            (UNSCOPED, "discord_bot_token", true),
+        ]);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_rotate_reencrypts_under_new_key() {
+        let path = temp_vault_path();
+        let mut vault = SecureVault::init_with(path.clone(), &passphrase("old"), FAST).unwrap();
+        vault.store("a", "1").unwrap();
+        vault.store("b", "2").unwrap();
+        let before = vault.secrets["a"].clone();
+
+        assert_eq!(vault.rotate(&passphrase("new"), FAST).unwrap(), 2);
+        assert_ne!(vault.secrets["a"], before);
+
+        let mut old = SecureVault::new(path.clone());
+        assert!(matches!(old.unlock_with(&passphrase("old")), Err(VaultError::WrongPassphrase)));
+        let mut reopened = SecureVault::new(path.clone());
+        reopened.unlock_with(&passphrase("new")).unwrap();
+        assert_eq!(reopened.retrieve("b").unwrap(), "2");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_failed_rotate_leaves_vault_as_it_was() {
+        let path = temp_vault_path();
+        let mut vault = SecureVault::init_with(path.clone(), &passphrase("old"), FAST).unwrap();
+        vault.store("a", "1").unwrap();
+        let before = vault.secrets["a"].clone();
+
+        // A non-empty directory where the vault file was: the rename can't replace it.
+        fs::remove_file(&path).unwrap();
+        fs::create_dir_all(path.join("blocker")).unwrap();
+        assert!(matches!(vault.rotate(&passphrase("new"), FAST), Err(VaultError::Io(_))));
+
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        assert!(!PathBuf::from(tmp).exists());
+        assert_eq!(vault.secrets["a"], before);
+        assert_eq!(vault.retrieve("a").unwrap(), "1");
+
+        fs::remove_dir_all(&path).unwrap();
     }
 }
@@ -0,0 +1,137 @@
+//! SQLite guard log
+//!
+//! One row per Sonnet guard verdict in the `guard_verdicts` table of
+//! memory.db, read back newest first by `argus guard history`.
+
+use argus_core::guard::{GuardLog, GuardRecord};
+use rusqlite::{params, Connection};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+pub struct SqliteGuardLog {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteGuardLog {
+    /// Open or create the guard table in the database at `path`.
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create memory dir: {}", e))?;
+        }
+
+        let conn = Connection::open(&path)
+            .map_err(|e| format!("Failed to open guard database: {}", e))?;
+        conn.execute_batch(
+            "PRAGMA journal_mode=WAL;
+            CREATE TABLE IF NOT EXISTS guard_verdicts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                command TEXT NOT NULL,
+                verdict TEXT NOT NULL,
+                rewrite TEXT,
+                reason TEXT NOT NULL,
+                source TEXT NOT NULL,
+                agent TEXT NOT NULL,
+                surface TEXT,
+                audit_id INTEGER,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE INDEX IF NOT EXISTS idx_guard_verdict ON guard_verdicts(verdict, id);",
+        )
+        .map_err(|e| format!("Failed to create guard table: {}", e))?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Open the log in the default memory.db.
+    pub fn open_default() -> Result<Self, String> {
+        Self::open(crate::sqlite::default_db_path()?)
+    }
+}
+
+impl GuardLog for SqliteGuardLog {
+    fn record(&self, record: &GuardRecord) -> Result<(), String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO guard_verdicts (command, verdict, rewrite, reason, source, agent, surface, audit_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                record.command,
+                record.verdict,
+                record.rewrite,
+                record.reason,
+                record.source,
+                record.agent,
+                record.surface,
+                record.audit_id.map(|id| id as i64),
+            ],
+        )
+        .map_err(|e| format!("Failed to record guard verdict: {}", e))?;
+        Ok(())
+    }
+
+    fn recent(&self, limit: usize, changed_only: bool) -> Result<Vec<GuardRecord>, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT command, verdict, rewrite, reason, source, agent, surface, audit_id, created_at
+                 FROM guard_verdicts WHERE ?1 = 0 OR verdict != 'approve'
+                 ORDER BY id DESC LIMIT ?2",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![changed_only, limit as i64], |row| {
+                Ok(GuardRecord {
+                    command:    row.get(0)?,
+                    verdict:    row.get(1)?,
+                    rewrite:    row.get(2)?,
+                    reason:     row.get(3)?,
+                    source:     row.get(4)?,
+                    agent:      row.get(5)?,
+                    surface:    row.get(6)?,
+                    audit_id:   row.get::<_, Option<i64>>(7)?.map(|id| id as u64),
+                    created_at: row.get(8)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_guard_log_newest_first() {
+        let tmp = NamedTempFile::new().unwrap();
+        let log = SqliteGuardLog::open(tmp.path().to_path_buf()).unwrap();
+        let verdict = |command: &str, verdict: &str, rewrite: Option<&str>, audit_id: Option<u64>| GuardRecord {
+            command: command.to_string(),
+            verdict: verdict.to_string(),
+            rewrite: rewrite.map(str::to_string),
+            reason: "because".to_string(),
+            source: "model".to_string(),
+            agent: "a/one".to_string(),
+            surface: Some("triage".to_string()),
+            audit_id,
+            created_at: None,
+        };
+        log.record(&verdict("rm -rf build", "rewrite", Some("rm -ri build"), Some(7))).unwrap();
+        log.record(&verdict("sudo ls", "approve", None, Some(8))).unwrap();
+        log.record(&verdict("dd of=/dev/sda", "block", None, None)).unwrap();
+
+        let all = log.recent(10, false).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].command, "dd of=/dev/sda");
+        assert!(all[0].created_at.is_some());
+        assert_eq!(log.recent(1, false).unwrap().len(), 1);
+
+        let changed = log.recent(10, true).unwrap();
+        assert_eq!(changed.len(), 2);
+        assert_eq!((changed[1].rewrite.as_deref(), changed[1].audit_id), (Some("rm -ri build"), Some(7)));
+    }
+}
@@ -2,6 +2,10 @@
 //!
 //! No more Python subprocess bridge. Direct SQLite with rusqlite.
 
+pub mod guard;
 pub mod sqlite;
+pub mod usage;
 
+pub use guard::SqliteGuardLog;
 pub use sqlite::SqliteMemory;
+pub use usage::SqliteUsageLedger;
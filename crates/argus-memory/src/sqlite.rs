@@ -21,6 +21,18 @@ pub struct ConversationMeta {
     pub last_active_at: String,
 }
 
+/// memory.db under ARGUS_DATA_DIR (persistent volume in Docker), else
+/// ~/.argus/memory.db for local dev.
+pub fn default_db_path() -> Result<PathBuf, String> {
+    if let Ok(data_dir) = std::env::var("ARGUS_DATA_DIR") {
+        return Ok(PathBuf::from(data_dir).join("memory.db"));
+    }
+    Ok(dirs::home_dir()
+        .ok_or_else(|| "No home directory".to_string())?
+        .join(".argus")
+        .join("memory.db"))
+}
+
 /// SQLite-backed memory store
 pub struct SqliteMemory {
     conn: Mutex<Connection>,
@@ -105,15 +117,7 @@ impl SqliteMemory {
     /// Respects ARGUS_DATA_DIR env var (persistent volume in Docker).
     /// Falls back to ~/.argus/memory.db for local dev.
     pub fn open_default() -> Result<Self, String> {
-        let path = if let Ok(data_dir) = std::env::var("ARGUS_DATA_DIR") {
-            std::path::PathBuf::from(data_dir).join("memory.db")
-        } else {
-            dirs::home_dir()
-                .ok_or_else(|| "No home directory".to_string())?
-                .join(".argus")
-                .join("memory.db")
-        };
-        Self::open(path)
+        Self::open(default_db_path()?)
     }
 
     /// Persist conversation history for a chat. Replaces existing history for that chat_id.
@@ -0,0 +1,209 @@
+//! SQLite usage ledger
+//!
+//! One row per model call in the `usage_ledger` table of memory.db, keyed
+//! by model slot as well as model ID. Budget
+//! checks (`argus_core::usage::check_budgets`) run a SUM over this table
+//! before every call, so it is indexed on the columns they filter by.
+
+use argus_core::usage::{GroupBy, Scope, UsageLedger, UsageRecord, UsageTotals};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// `created_at` format — matches SQLite's `datetime('now')`, so text
+/// comparison orders correctly.
+const TIMESTAMP: &str = "%Y-%m-%d %H:%M:%S";
+
+pub struct SqliteUsageLedger {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteUsageLedger {
+    /// Open or create the ledger table in the database at `path`.
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create memory dir: {}", e))?;
+        }
+
+        let conn = Connection::open(&path)
+            .map_err(|e| format!("Failed to open usage database: {}", e))?;
+        conn.execute_batch(
+            "PRAGMA journal_mode=WAL;
+            CREATE TABLE IF NOT EXISTS usage_ledger (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                slot TEXT,
+                model TEXT NOT NULL,
+                surface TEXT NOT NULL,
+                mission TEXT,
+                prompt_tokens INTEGER NOT NULL DEFAULT 0,
+                completion_tokens INTEGER NOT NULL DEFAULT 0,
+                cost_usd REAL NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE INDEX IF NOT EXISTS idx_usage_created ON usage_ledger(created_at);
+            CREATE INDEX IF NOT EXISTS idx_usage_model ON usage_ledger(model, created_at);
+            CREATE INDEX IF NOT EXISTS idx_usage_surface ON usage_ledger(surface, created_at);",
+        )
+        .map_err(|e| format!("Failed to create usage table: {}", e))?;
+
+        // Ledgers from before slots were recorded: their rows keep a NULL
+        // slot and count under their model ID in reports.
+        let has_slot = conn
+            .prepare("SELECT 1 FROM pragma_table_info('usage_ledger') WHERE name = 'slot'")
+            .and_then(|mut stmt| stmt.exists([]))
+            .map_err(|e| format!("Failed to inspect usage table: {}", e))?;
+        if !has_slot {
+            conn.execute_batch("ALTER TABLE usage_ledger ADD COLUMN slot TEXT;")
+                .map_err(|e| format!("Failed to migrate usage table: {}", e))?;
+        }
+        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_usage_slot ON usage_ledger(slot, created_at);")
+            .map_err(|e| format!("Failed to create usage table: {}", e))?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Open the ledger in the default memory.db.
+    pub fn open_default() -> Result<Self, String> {
+        Self::open(crate::sqlite::default_db_path()?)
+    }
+}
+
+impl UsageLedger for SqliteUsageLedger {
+    fn record(&self, record: &UsageRecord) -> Result<(), String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO usage_ledger (slot, model, surface, mission, prompt_tokens, completion_tokens, cost_usd)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                record.slot,
+                record.model,
+                record.surface,
+                record.mission,
+                record.prompt_tokens as i64,
+                record.completion_tokens as i64,
+                record.cost_usd,
+            ],
+        )
+        .map_err(|e| format!("Failed to record usage: {}", e))?;
+        Ok(())
+    }
+
+    fn spent(&self, scope: &Scope, since: DateTime<Utc>) -> Result<f64, String> {
+        let (column, value) = match scope {
+            Scope::Model(key) => ("slot", key),
+            Scope::Surface(s) => ("surface", s),
+            Scope::Mission(id) => ("mission", id),
+        };
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        conn.query_row(
+            &format!(
+                "SELECT COALESCE(SUM(cost_usd), 0) FROM usage_ledger WHERE {} = ?1 AND created_at >= ?2",
+                column
+            ),
+            params![value, since.format(TIMESTAMP).to_string()],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to read usage: {}", e))
+    }
+
+    fn totals(&self, group: GroupBy, since: DateTime<Utc>) -> Result<Vec<(String, UsageTotals)>, String> {
+        let column = match group {
+            GroupBy::Model => "COALESCE(slot, model)",
+            GroupBy::Surface => "surface",
+            GroupBy::Mission => "mission",
+        };
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {col}, COUNT(*), SUM(prompt_tokens), SUM(completion_tokens), SUM(cost_usd)
+                 FROM usage_ledger WHERE created_at >= ?1 AND {col} IS NOT NULL
+                 GROUP BY {col} ORDER BY SUM(cost_usd) DESC, COUNT(*) DESC",
+                col = column
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![since.format(TIMESTAMP).to_string()], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    UsageTotals {
+                        calls:             row.get::<_, i64>(1)? as u64,
+                        prompt_tokens:     row.get::<_, i64>(2)? as u64,
+                        completion_tokens: row.get::<_, i64>(3)? as u64,
+                        cost_usd:          row.get(4)?,
+                    },
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_ledger_sums_by_scope() {
+        let tmp = NamedTempFile::new().unwrap();
+        let ledger = SqliteUsageLedger::open(tmp.path().to_path_buf()).unwrap();
+        let call = |slot: &str, surface: &str, mission: Option<&str>, cost: f64| UsageRecord {
+            slot: slot.to_string(),
+            model: "shared/model".to_string(),
+            surface: surface.to_string(),
+            mission: mission.map(str::to_string),
+            prompt_tokens: 1000,
+            completion_tokens: 100,
+            cost_usd: cost,
+        };
+        ledger.record(&call("one", "web", None, 0.25)).unwrap();
+        ledger.record(&call("one", "missions", Some("m-1"), 0.5)).unwrap();
+        ledger.record(&call("two", "web", None, 1.0)).unwrap();
+
+        let today = argus_core::usage::period_starts(Utc::now()).0;
+        // Slots sharing a model ID are counted apart.
+        assert_eq!(ledger.spent(&Scope::Model("one".into()), today).unwrap(), 0.75);
+        assert_eq!(ledger.spent(&Scope::Surface("web".into()), today).unwrap(), 1.25);
+        assert_eq!(ledger.spent(&Scope::Mission("m-1".into()), today).unwrap(), 0.5);
+        // Nothing recorded after a future cutoff.
+        let later = Utc::now() + chrono::Duration::hours(1);
+        assert_eq!(ledger.spent(&Scope::Surface("web".into()), later).unwrap(), 0.0);
+
+        let by_model = ledger.totals(GroupBy::Model, today).unwrap();
+        assert_eq!(by_model[0].0, "two");
+        assert_eq!(by_model[1].1.calls, 2);
+        assert_eq!(by_model[1].1.prompt_tokens, 2000);
+        // Calls outside a mission don't produce a NULL group.
+        assert_eq!(ledger.totals(GroupBy::Mission, today).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_ledger_migrates_rows_without_slot() {
+        let tmp = NamedTempFile::new().unwrap();
+        let conn = Connection::open(tmp.path()).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE usage_ledger (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                model TEXT NOT NULL,
+                surface TEXT NOT NULL,
+                mission TEXT,
+                prompt_tokens INTEGER NOT NULL DEFAULT 0,
+                completion_tokens INTEGER NOT NULL DEFAULT 0,
+                cost_usd REAL NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            INSERT INTO usage_ledger (model, surface, cost_usd) VALUES ('old/model', 'web', 0.5);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let ledger = SqliteUsageLedger::open(tmp.path().to_path_buf()).unwrap();
+        let today = argus_core::usage::period_starts(Utc::now()).0;
+        let by_model = ledger.totals(GroupBy::Model, today).unwrap();
+        assert_eq!((by_model[0].0.as_str(), by_model[0].1.cost_usd), ("old/model", 0.5));
+    }
+}
@@ -1,7 +1,7 @@
 //! Bridge between argus-core's MissionExecutor trait and argus-missions' MissionRegistry.
 //! Avoids circular crate dependency while keeping the dispatch clean.
 
-use argus_core::MissionExecutor;
+use argus_core::{Capability, MissionExecutor, ToolRisk, ToolSpec};
 use crate::tools::{MissionRegistry, execute_mission_tool, mission_tool_schemas};
 use argus_core::{AgentConfig, SentryBus};
 use argus_core::supabase::SupabaseClient;
@@ -49,10 +49,16 @@ impl MissionExecutor for MissionBridge {
             .map(|m| serde_json::to_value(&m).unwrap_or_default())
             .collect()
     }
-}
 
-/// Tool schemas to append to builtin_tool_schemas() in argus-core.
-/// Called from main.rs to inject schemas without a circular dep.
-pub fn schemas() -> Vec<Value> {
-    mission_tool_schemas()
+    fn tool_specs(&self) -> Vec<ToolSpec> {
+        mission_tool_schemas().iter()
+            .filter_map(|schema| {
+                let risk = match schema["function"]["name"].as_str()? {
+                    "mission_status" | "list_missions" => ToolRisk::ReadOnly,
+                    _ => ToolRisk::Write,
+                };
+                ToolSpec::from_schema(schema, risk, &[Capability::Missions])
+            })
+            .collect()
+    }
 }
@@ -5,10 +5,13 @@
 //! until the checks pass.
 
 use crate::types::*;
-use argus_core::{AgentConfig, AgentEvent, MODEL_GROK_BUILD, run_agent_turn};
+use argus_core::{models, AgentConfig, AgentEvent, CancelToken, run_agent_turn};
 use argus_core::mcp::McpClient;
 use argus_core::shell::ShellPolicy;
+use argus_core::shell_policy::Surface;
+use argus_core::shell_syntax::quote;
 use argus_core::tools::MemoryBackend;
+use argus_core::WorkspaceExecutor;
 use chrono::Utc;
 use reqwest::Client;
 
@@ -26,13 +29,13 @@ impl MemoryBackend for NoopMemory {
 pub async fn verify_deliverables(
     mission: &Mission,
     http: &Client,
-    exec_auth_token: Option<&str>,
+    workspace: &dyn WorkspaceExecutor,
 ) -> (bool, Vec<DeliverableResult>) {
     let mut results = Vec::new();
     let mut all_passed = true;
 
     for deliverable in &mission.deliverables {
-        let (passed, output) = verify_one(deliverable, http, exec_auth_token).await;
+        let (passed, output) = verify_one(deliverable, http, workspace).await;
         if !passed { all_passed = false; }
         results.push(DeliverableResult {
             deliverable: deliverable.clone(),
@@ -48,19 +51,19 @@ pub async fn verify_deliverables(
 async fn verify_one(
     deliverable: &Deliverable,
     http: &Client,
-    exec_auth_token: Option<&str>,
+    workspace: &dyn WorkspaceExecutor,
 ) -> (bool, String) {
     match deliverable {
         Deliverable::File { path, .. } => {
-            let cmd = format!("test -f '{}' && echo EXISTS || echo MISSING", path);
-            let out = workspace_exec(&cmd, http, exec_auth_token).await;
+            let cmd = format!("test -f {} && echo EXISTS || echo MISSING", quote(path));
+            let out = workspace_exec(&cmd, workspace).await;
             let passed = out.trim() == "EXISTS";
             (passed, out)
         }
 
         Deliverable::Command { command, expected_exit, .. } => {
             let cmd = format!("{}; echo EXIT:$?", command);
-            let out = workspace_exec(&cmd, http, exec_auth_token).await;
+            let out = workspace_exec(&cmd, workspace).await;
             let actual_exit: i32 = out.lines()
                 .find(|l| l.starts_with("EXIT:"))
                 .and_then(|l| l.trim_start_matches("EXIT:").trim().parse().ok())
@@ -82,10 +85,10 @@ async fn verify_one(
 
         Deliverable::GitCommit { hash_prefix, .. } => {
             let cmd = match hash_prefix {
-                Some(h) => format!("cd /workspace && git log --oneline | grep '^{}' | head -1", h),
-                None    => "cd /workspace && git log --oneline -1".to_string(),
+                Some(h) => format!("cd {} && git log --oneline | grep {} | head -1", quote(workspace.root()), quote(&format!("^{}", h))),
+                None    => format!("cd {} && git log --oneline -1", quote(workspace.root())),
             };
-            let out = workspace_exec(&cmd, http, exec_auth_token).await;
+            let out = workspace_exec(&cmd, workspace).await;
             let passed = !out.trim().is_empty() && !out.contains("fatal");
             (passed, out)
         }
@@ -126,7 +129,7 @@ pub async fn run_subtask(
     };
 
     let mut mcp = McpClient::new();
-    let policy = ShellPolicy::default();
+    let policy = ShellPolicy::for_surface(Surface::Missions);
     let memory = NoopMemory;
 
     run_agent_turn(
@@ -137,6 +140,7 @@ pub async fn run_subtask(
         &memory,
         &mut mcp,
         http,
+        &CancelToken::new(),
         |event| {
             if let AgentEvent::ToolCall { name, preview, .. } = event {
                 eprintln!("[mission/{}] {} — {}", model, name, preview);
@@ -175,24 +179,12 @@ pub async fn post_mission_update(
         .await;
 }
 
-/// Run a shell command in the workspace exec server.
-pub async fn workspace_exec(cmd: &str, http: &Client, auth_token: Option<&str>) -> String {
-    let payload = serde_json::json!({ "command": cmd });
-    let mut req = http
-        .post("http://argus-workspace:9001/exec")
-        .json(&payload)
-        .timeout(std::time::Duration::from_secs(60));
-    if let Some(token) = auth_token {
-        req = req.header("X-Argus-Auth", token);
-    }
-    match req.send().await {
-        Err(e) => format!("workspace unreachable: {}", e),
-        Ok(r) => match r.json::<serde_json::Value>().await {
-            Err(e) => format!("response error: {}", e),
-            Ok(j) => j["output"].as_str()
-                .or_else(|| j["stdout"].as_str())
-                .unwrap_or("").trim_end().to_string(),
-        }
+/// Run a shell command in the workspace. Returns stdout, or the error text
+/// when the backend itself fails.
+pub async fn workspace_exec(cmd: &str, workspace: &dyn WorkspaceExecutor) -> String {
+    match workspace.exec(cmd, 60).await {
+        Err(e) => e,
+        Ok(out) => out.stdout.trim_end().to_string(),
     }
 }
 
@@ -229,7 +221,7 @@ pub async fn decompose_mission(
     );
 
     let build_config = AgentConfig {
-        model: MODEL_GROK_BUILD.to_string(),
+        model: models::id("grok-build").to_string(),
         blocked_tools: vec![
             "shell".into(), "write_file".into(), "run_python".into(),
             "run_node".into(), "git_checkpoint".into(),
@@ -238,11 +230,11 @@ pub async fn decompose_mission(
     };
 
     let mut mcp = McpClient::new();
-    let policy = ShellPolicy::default();
+    let policy = ShellPolicy::for_surface(Surface::Missions);
     let memory = NoopMemory;
 
     let response = run_agent_turn(
-        &build_config, &prompt, &[], &policy, &memory, &mut mcp, http,
+        &build_config, &prompt, &[], &policy, &memory, &mut mcp, http, &CancelToken::new(),
         |_| {},
     ).await.unwrap_or_default();
 
@@ -253,12 +245,12 @@ pub async fn decompose_mission(
         if let Ok(arr) = serde_json::from_str::<Vec<serde_json::Value>>(&response[s..e]) {
             return arr.iter().filter_map(|v| {
                 let task  = v["task"].as_str()?.to_string();
-                let model = v["model"].as_str().unwrap_or(MODEL_GROK_BUILD).to_string();
+                let model = v["model"].as_str().unwrap_or(models::id("grok-build")).to_string();
                 Some((task, model))
             }).collect();
         }
     }
 
     // Fallback: single subtask assigned to Grok Build
-    vec![(objective.to_string(), MODEL_GROK_BUILD.to_string())]
+    vec![(objective.to_string(), models::id("grok-build").to_string())]
 }
@@ -6,10 +6,11 @@
 
 use crate::types::*;
 use crate::executor::*;
-use argus_core::{AgentConfig, SentryBus, ReviewVerdict, MODEL_HAIKU, MODEL_GROK_BUILD};
+use argus_core::{models, AgentConfig, SentryBus, ReviewVerdict};
 use argus_core::run_agent_turn;
 use argus_core::mcp::McpClient;
 use argus_core::shell::ShellPolicy;
+use argus_core::shell_syntax::quote;
 use argus_core::tools::MemoryBackend;
 use argus_core::supabase::{DiscoursePost, SupabaseClient};
 use chrono::Utc;
@@ -37,6 +38,13 @@ pub async fn run_mission(
     let mission_id = mission.id.to_string();
     eprintln!("[mission:{}] Starting — {}", &mission_id[..8], mission.objective);
 
+    // Every model call below is charged to this mission in the usage ledger.
+    let config = &AgentConfig {
+        surface: "missions".to_string(),
+        mission_id: Some(mission_id.clone()),
+        ..config.clone()
+    };
+
     // ── 1. Decompose into subtasks ─────────────────────────────────────────
     if mission.subtasks.is_empty() {
         mission.status = MissionStatus::Planning;
@@ -96,11 +104,11 @@ pub async fn run_mission(
     // ── 3. Mission working directory ───────────────────────────────────────
     // Each mission gets an isolated directory. Subtasks work here, not in /workspace root.
     // This makes parallel execution safe — subtasks can't step on each other's files.
-    let mission_dir = format!("/workspace/missions/{}", &mission_id[..8]);
+    let workspace = config.workspace();
+    let mission_dir = format!("{}/missions/{}", workspace.root(), &mission_id[..8]);
     let _ = workspace_exec(
-        &format!("mkdir -p '{}/output' && echo ok", mission_dir),
-        http,
-        config.exec_auth_token.as_deref(),
+        &format!("mkdir -p {} && echo ok", quote(&format!("{}/output", mission_dir))),
+        &*workspace,
     ).await;
 
     // ── 4. Execute subtasks ────────────────────────────────────────────────
@@ -124,14 +132,11 @@ pub async fn run_mission(
         let mission_clone = mission.clone();
         let config2  = config.clone();
         let http2    = http.clone();
+        let ws2      = workspace.clone();
         let work_dir = format!("{}/subtask_{}", mission_dir, i + 1);
 
         handles.push(tokio::spawn(async move {
-            let _ = workspace_exec(
-                &format!("mkdir -p '{}'", work_dir),
-                &http2,
-                config2.exec_auth_token.as_deref(),
-            ).await;
+            let _ = workspace_exec(&format!("mkdir -p {}", quote(&work_dir)), &*ws2).await;
             (i, run_subtask(&subtask, &mission_clone, &config2, &http2).await)
         }));
     }
@@ -172,7 +177,7 @@ pub async fn run_mission(
     mission.status = MissionStatus::Verifying;
     eprintln!("[mission:{}] Verifying {} deliverable(s)", &mission_id[..8], mission.deliverables.len());
 
-    let (all_passed, results) = verify_deliverables(&mission, http, config.exec_auth_token.as_deref()).await;
+    let (all_passed, results) = verify_deliverables(&mission, http, &*workspace).await;
     mission.verification = results;
 
     if !all_passed {
@@ -194,9 +199,11 @@ pub async fn run_mission(
         &mission_id[..8]
     );
     let commit_result = workspace_exec(
-        &format!("cd /workspace && git add -A && git commit -m '{}' 2>&1 && echo 'HASH:'$(git rev-parse --short HEAD)", commit_msg),
-        http,
-        config.exec_auth_token.as_deref(),
+        &format!(
+            "cd {} && git add -A && git commit -m {} 2>&1 && echo 'HASH:'$(git rev-parse --short HEAD)",
+            quote(workspace.root()), quote(&commit_msg),
+        ),
+        &*workspace,
     ).await;
 
     let commit_hash = commit_result.lines()
@@ -220,8 +227,7 @@ pub async fn run_mission(
             .collect::<Vec<_>>()
             .join("\n");
         let http2 = http.clone();
-        let api_key = config.api_key.clone();
-        let api_url = config.api_url.clone();
+        let config = config.clone();
 
         tokio::spawn(async move {
             let prompt = format!(
@@ -237,19 +243,10 @@ pub async fn run_mission(
                 obj, subtask_summary
             );
 
-            let body = serde_json::json!({
-                "model": MODEL_HAIKU,
-                "messages": [{"role": "user", "content": prompt}],
-                "temperature": 0.3,
-                "max_tokens": 500,
-            });
-
-            if let Ok(resp) = http2.post(&api_url)
-                .header("Authorization", format!("Bearer {}", api_key))
-                .json(&body).send().await
-            {
-                if let Ok(json) = resp.json::<serde_json::Value>().await {
-                    let content = json["choices"][0]["message"]["content"].as_str().unwrap_or("");
+            let messages = vec![serde_json::json!({"role": "user", "content": prompt})];
+            match argus_core::side_completion(&config, &http2, "haiku", messages, 0.3, 500).await {
+                Err(e) => eprintln!("[mission/skill] Reflection call failed: {}", e),
+                Ok(content) => {
                     let s = content.find('{');
                     let e = content.rfind('}').map(|i| i + 1);
                     if let (Some(s), Some(e)) = (s, e) {
@@ -263,11 +260,11 @@ pub async fn run_mission(
                                         skill_name: name.clone(),
                                         trigger_description: trigger.clone(),
                                         procedure_steps: steps,
-                                        model_created_by: format!("mission/{}", MODEL_HAIKU),
+                                        model_created_by: format!("mission/{}", models::id("haiku")),
                                         metadata: None,
                                     };
                                     if let Ok(_) = sc.create_skill(new_skill).await {
-                                        sc.announce_created(&name, &trigger, MODEL_HAIKU).await;
+                                        sc.announce_created(&name, &trigger, models::id("haiku")).await;
                                         eprintln!("[mission/skill] Extracted skill: \"{}\"", name);
                                     }
                                 }
@@ -5,7 +5,7 @@
 
 use crate::types::*;
 use crate::runner::run_mission;
-use argus_core::{AgentConfig, SentryBus, MODEL_GROK_BUILD};
+use argus_core::{models, AgentConfig, SentryBus};
 use argus_core::supabase::SupabaseClient;
 use serde_json::Value;
 use std::sync::{Arc, Mutex};
@@ -238,7 +238,7 @@ async fn tool_start_mission(
     };
 
     let executor = args["executor"].as_str()
-        .unwrap_or(MODEL_GROK_BUILD)
+        .unwrap_or(models::id("grok-build"))
         .to_string();
 
     // Parse deliverables
@@ -374,7 +374,7 @@ fn tool_list_missions(registry: &Arc<MissionRegistry>) -> String {
 fn tool_add_subtask(args: &Value, registry: &Arc<MissionRegistry>) -> String {
     let id   = args["mission_id"].as_str().unwrap_or("");
     let desc = args["description"].as_str().unwrap_or("").trim();
-    let model = args["model"].as_str().unwrap_or(MODEL_GROK_BUILD);
+    let model = args["model"].as_str().unwrap_or(models::id("grok-build"));
 
     if desc.is_empty() {
         return "add_subtask requires a description.".to_string();
@@ -44,6 +44,11 @@ impl CapabilitySet {
     pub fn has(&self, cap: &Capability) -> bool {
         self.capabilities.contains(cap)
     }
+
+    /// Everything granted, in grant order.
+    pub fn iter(&self) -> impl Iterator<Item = &Capability> {
+        self.capabilities.iter()
+    }
     
     /// Check if a file read is allowed
     pub fn can_read_file(&self, path: &str) -> bool {
@@ -0,0 +1,575 @@
+//! Process isolation for local command execution
+//!
+//! Commands sent to the argus-workspace container get the container as their
+//! boundary. Commands run on the host (`LocalWorkspace`) get one from the
+//! kernel instead, set up between fork and exec:
+//!
+//!   namespaces → new user, mount, PID, IPC and UTS namespaces, and a new
+//!                network namespace unless a `Network` capability is granted
+//!   Landlock   → filesystem access limited to the `FileRead` / `FileWrite`
+//!                paths of a `CapabilitySet`
+//!   rlimits    → CPU time, address space and file size
+//!
+//! The command runs as PID 1 of its own namespace, so anything it leaves
+//! running dies with it. Callers should still start it in its own process
+//! group and hold a `ProcessGroup` for it until it exits.
+//!
+//! Linux only (Landlock needs 5.13+). Elsewhere `apply` fails rather than
+//! let the command run unconfined.
+
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::capabilities::{Capability, CapabilitySet};
+use crate::wasm::SandboxError;
+
+/// Resource ceilings for an isolated command and every process it starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// CPU time per process, in seconds (RLIMIT_CPU).
+    pub cpu_secs: u64,
+    /// Address space per process, in bytes (RLIMIT_AS).
+    pub memory_bytes: u64,
+    /// Largest file a process may write, in bytes (RLIMIT_FSIZE).
+    pub file_size_bytes: u64,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            cpu_secs: 120,
+            memory_bytes: 2 << 30,
+            file_size_bytes: 256 << 20,
+        }
+    }
+}
+
+/// How an isolated command is confined.
+#[derive(Debug, Clone, Default)]
+pub struct IsolationProfile {
+    /// Readable and executable, recursively.
+    pub read_paths: Vec<PathBuf>,
+    /// Fully writable, recursively.
+    pub write_paths: Vec<PathBuf>,
+    /// Keep the host network namespace.
+    pub network: bool,
+    /// Environment variables passed through; None passes the whole environment.
+    pub env: Option<Vec<String>>,
+    pub limits: ResourceLimits,
+}
+
+impl IsolationProfile {
+    /// `FileRead` / `FileWrite` paths become Landlock rules and `Environment`
+    /// grants the environment allowlist. Any `Network` grant keeps the host
+    /// network — domains are not filtered at this layer. `Subprocess` is left
+    /// to the shell policy.
+    pub fn from_capabilities(caps: &CapabilitySet, limits: ResourceLimits) -> Self {
+        let mut profile = Self { limits, ..Self::default() };
+        for cap in caps.iter() {
+            match cap {
+                Capability::FileRead { paths } => profile.read_paths.extend(paths.iter().map(PathBuf::from)),
+                Capability::FileWrite { paths } => profile.write_paths.extend(paths.iter().map(PathBuf::from)),
+                Capability::Network { .. } => profile.network = true,
+                Capability::Environment { vars } => {
+                    profile.env.get_or_insert_with(Vec::new).extend(vars.iter().cloned())
+                }
+                Capability::Subprocess { .. } => {}
+            }
+        }
+        profile
+    }
+
+    /// Confine `cmd` when it is spawned. The Landlock ruleset is built here;
+    /// if the namespaces can't be entered in the child, spawning fails.
+    pub fn apply(&self, cmd: &mut Command) -> Result<(), SandboxError> {
+        if let Some(vars) = &self.env {
+            cmd.env_clear();
+            for var in vars {
+                if let Some(value) = std::env::var_os(var) {
+                    cmd.env(var, value);
+                }
+            }
+        }
+        imp::apply(self, cmd)
+    }
+
+    /// Run `/bin/sh -c 'exit 0'` under this profile. Ok(Landlock ABI version)
+    /// when the host supports every layer and the profile can start a shell.
+    pub fn probe(&self) -> Result<u32, SandboxError> {
+        let abi = imp::landlock_abi()?;
+        let mut cmd = Command::new("/bin/sh");
+        cmd.args(["-c", "exit 0"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        self.apply(&mut cmd)?;
+        let status = cmd
+            .status()
+            .map_err(|e| SandboxError::IsolationUnavailable(e.to_string()))?;
+        if !status.success() {
+            return Err(SandboxError::IsolationUnavailable(format!("probe shell exited with {}", status)));
+        }
+        Ok(abi)
+    }
+}
+
+/// Kills a spawned command's process group when dropped — on timeout or
+/// cancellation, `kill_on_drop` alone would only reach the group leader.
+/// Forget it once the command has exited on its own.
+pub struct ProcessGroup(pub u32);
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        kill_process_group(self.0);
+    }
+}
+
+/// SIGKILL every process in group `pgid`. Killing only the group leader on
+/// timeout would leave its children running.
+pub fn kill_process_group(pgid: u32) {
+    #[cfg(unix)]
+    unsafe {
+        libc::killpg(pgid as libc::pid_t, libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    let _ = pgid;
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::ffi::CStr;
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::process::CommandExt;
+    use std::path::Path;
+    use std::process::Command;
+
+    use super::{IsolationProfile, ResourceLimits};
+    use crate::wasm::SandboxError;
+
+    // Landlock UAPI (linux/landlock.h) — libc only has the syscall numbers.
+    const CREATE_RULESET_VERSION: libc::c_uint = 1;
+    const RULE_PATH_BENEATH: libc::c_int = 1;
+
+    const ACCESS_EXECUTE: u64 = 1 << 0;
+    const ACCESS_WRITE_FILE: u64 = 1 << 1;
+    const ACCESS_READ_FILE: u64 = 1 << 2;
+    const ACCESS_READ_DIR: u64 = 1 << 3;
+    /// Everything in ABI 1: the four above plus remove / make-* rights.
+    const ACCESS_ABI_1: u64 = (1 << 13) - 1;
+    const ACCESS_REFER: u64 = 1 << 13;
+    const ACCESS_TRUNCATE: u64 = 1 << 14;
+    /// The rights a rule on a non-directory may carry.
+    const ACCESS_FILE: u64 = ACCESS_EXECUTE | ACCESS_WRITE_FILE | ACCESS_READ_FILE | ACCESS_TRUNCATE;
+    const ACCESS_READ: u64 = ACCESS_EXECUTE | ACCESS_READ_FILE | ACCESS_READ_DIR;
+
+    #[cfg(target_env = "gnu")]
+    type Resource = libc::__rlimit_resource_t;
+    #[cfg(not(target_env = "gnu"))]
+    type Resource = libc::c_int;
+
+    #[repr(C)]
+    struct RulesetAttr {
+        handled_access_fs: u64,
+    }
+
+    #[repr(C, packed)]
+    struct PathBeneathAttr {
+        allowed_access: u64,
+        parent_fd: i32,
+    }
+
+    fn unavailable(msg: String) -> SandboxError {
+        SandboxError::IsolationUnavailable(msg)
+    }
+
+    pub fn landlock_abi() -> Result<u32, SandboxError> {
+        let abi = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<RulesetAttr>(),
+                0usize,
+                CREATE_RULESET_VERSION,
+            )
+        };
+        if abi < 1 {
+            return Err(unavailable(format!("Landlock not enabled: {}", io::Error::last_os_error())));
+        }
+        Ok(abi as u32)
+    }
+
+    /// Rights the ruleset restricts: every filesystem right up to ABI 3 that
+    /// this kernel supports, so none is left open outside the rules.
+    fn handled_access(abi: u32) -> u64 {
+        let mut access = ACCESS_ABI_1;
+        if abi >= 2 {
+            access |= ACCESS_REFER;
+        }
+        if abi >= 3 {
+            access |= ACCESS_TRUNCATE;
+        }
+        access
+    }
+
+    /// One Landlock rule per profile path this host has: the path opened
+    /// `O_PATH`, and the rights it grants within `handled`. Reads cover
+    /// execute and listing, writes every handled right; a rule on a file
+    /// keeps only the rights that apply to files.
+    fn path_rules(profile: &IsolationProfile, handled: u64) -> Vec<(&Path, std::fs::File, u64)> {
+        let paths = profile.read_paths.iter().map(|p| (p, ACCESS_READ))
+            .chain(profile.write_paths.iter().map(|p| (p, handled)));
+        let mut rules = Vec::new();
+        for (path, access) in paths {
+            // Paths this host doesn't have (/lib32, an unset cache dir) are skipped.
+            let Ok(file) = std::fs::OpenOptions::new().read(true).custom_flags(libc::O_PATH).open(path) else {
+                continue;
+            };
+            let is_dir = file.metadata().map(|m| m.is_dir()).unwrap_or(false);
+            let allowed = if is_dir { access } else { access & ACCESS_FILE };
+            rules.push((path.as_path(), file, allowed & handled));
+        }
+        rules
+    }
+
+    fn ruleset(profile: &IsolationProfile) -> Result<OwnedFd, SandboxError> {
+        let handled = handled_access(landlock_abi()?);
+        let attr = RulesetAttr { handled_access_fs: handled };
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                &attr as *const RulesetAttr,
+                std::mem::size_of::<RulesetAttr>(),
+                0 as libc::c_uint,
+            )
+        };
+        if fd < 0 {
+            return Err(unavailable(format!("landlock_create_ruleset: {}", io::Error::last_os_error())));
+        }
+        let ruleset = unsafe { OwnedFd::from_raw_fd(fd as RawFd) };
+
+        for (path, file, allowed) in path_rules(profile, handled) {
+            let rule = PathBeneathAttr { allowed_access: allowed, parent_fd: file.as_raw_fd() };
+            let rc = unsafe {
+                libc::syscall(
+                    libc::SYS_landlock_add_rule,
+                    ruleset.as_raw_fd(),
+                    RULE_PATH_BENEATH,
+                    &rule as *const PathBeneathAttr,
+                    0 as libc::c_uint,
+                )
+            };
+            if rc != 0 {
+                return Err(unavailable(format!(
+                    "Landlock rule for {}: {}", path.display(), io::Error::last_os_error()
+                )));
+            }
+        }
+        Ok(ruleset)
+    }
+
+    /// Namespaces the command gets of its own; the network one only when
+    /// it isn't allowed the host's.
+    fn namespace_flags(network: bool) -> libc::c_int {
+        let flags = libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWPID
+            | libc::CLONE_NEWIPC | libc::CLONE_NEWUTS;
+        if network { flags } else { flags | libc::CLONE_NEWNET }
+    }
+
+    /// The limits to set in the child, with core dumps off. Each is lowered
+    /// to the current hard limit there.
+    fn rlimits(limits: &ResourceLimits) -> [(Resource, u64); 4] {
+        [
+            (libc::RLIMIT_CPU, limits.cpu_secs),
+            (libc::RLIMIT_AS, limits.memory_bytes),
+            (libc::RLIMIT_FSIZE, limits.file_size_bytes),
+            (libc::RLIMIT_CORE, 0),
+        ]
+    }
+
+    /// Everything the child needs, prepared before fork — after it only
+    /// async-signal-safe calls are made, and nothing allocates.
+    struct ChildSetup {
+        flags: libc::c_int,
+        uid_map: Vec<u8>,
+        gid_map: Vec<u8>,
+        rlimits: [(Resource, u64); 4],
+        ruleset: OwnedFd,
+        /// `/proc` is readable: the rule from the parent covers the host's
+        /// proc mount, so the namespace's own needs one of its own.
+        proc_readable: bool,
+    }
+
+    pub fn apply(profile: &IsolationProfile, cmd: &mut Command) -> Result<(), SandboxError> {
+        let ruleset = ruleset(profile)?;
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        let setup = ChildSetup {
+            flags: namespace_flags(profile.network),
+            // Same ids inside as out, so files in the workspace keep their owner.
+            uid_map: format!("{} {} 1", uid, uid).into_bytes(),
+            gid_map: format!("{} {} 1", gid, gid).into_bytes(),
+            rlimits: rlimits(&profile.limits),
+            ruleset,
+            proc_readable: profile.read_paths.iter().any(|p| p.as_os_str() == "/proc"),
+        };
+        unsafe {
+            cmd.pre_exec(move || setup.enter());
+        }
+        Ok(())
+    }
+
+    impl ChildSetup {
+        /// Runs in the forked child, just before exec.
+        fn enter(&self) -> io::Result<()> {
+            cvt(unsafe { libc::unshare(self.flags) })?;
+            write_proc(c"/proc/self/setgroups", b"deny")?;
+            write_proc(c"/proc/self/uid_map", &self.uid_map)?;
+            write_proc(c"/proc/self/gid_map", &self.gid_map)?;
+
+            // A new PID namespace only applies to children: fork once more so
+            // the command is its init, and wait for it in this process.
+            match unsafe { libc::fork() } {
+                -1 => return Err(io::Error::last_os_error()),
+                0 => {}
+                child => reap_and_exit(child),
+            }
+
+            unsafe {
+                libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+                // Private mounts, then a /proc that shows only this namespace.
+                // Best effort: without it the host's /proc stays, read-only.
+                let null = std::ptr::null::<libc::c_char>();
+                if libc::mount(null, c"/".as_ptr(), null, libc::MS_REC | libc::MS_PRIVATE, std::ptr::null()) == 0
+                    && libc::mount(
+                        c"proc".as_ptr(),
+                        c"/proc".as_ptr(),
+                        c"proc".as_ptr(),
+                        libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+                        std::ptr::null(),
+                    ) == 0
+                    && self.proc_readable
+                {
+                    self.add_rule(c"/proc", ACCESS_READ)?;
+                }
+            }
+
+            for (resource, value) in self.rlimits {
+                let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+                cvt(unsafe { libc::getrlimit(resource, &mut limit) })?;
+                // Only ever lower: raising a hard limit needs privileges we dropped.
+                let value = (value as libc::rlim_t).min(limit.rlim_max);
+                limit = libc::rlimit { rlim_cur: value, rlim_max: value };
+                cvt(unsafe { libc::setrlimit(resource, &limit) })?;
+            }
+
+            cvt(unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) })?;
+            let rc = unsafe { libc::syscall(libc::SYS_landlock_restrict_self, self.ruleset.as_raw_fd(), 0 as libc::c_uint) };
+            if rc != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+    }
+
+    impl ChildSetup {
+        fn add_rule(&self, path: &CStr, access: u64) -> io::Result<()> {
+            unsafe {
+                let fd = libc::open(path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC);
+                if fd < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                let rule = PathBeneathAttr { allowed_access: access, parent_fd: fd };
+                let rc = libc::syscall(
+                    libc::SYS_landlock_add_rule,
+                    self.ruleset.as_raw_fd(),
+                    RULE_PATH_BENEATH,
+                    &rule as *const PathBeneathAttr,
+                    0 as libc::c_uint,
+                );
+                let err = io::Error::last_os_error();
+                libc::close(fd);
+                if rc != 0 {
+                    return Err(err);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// The intermediate process between the caller and the namespace's init.
+    /// It drops every inherited descriptor past stdio — std's exec-status
+    /// pipe among them, so spawn returns once the command execs rather than
+    /// when it exits — then waits and passes the exit status on.
+    fn reap_and_exit(child: libc::pid_t) -> ! {
+        unsafe {
+            if libc::syscall(libc::SYS_close_range, 3 as libc::c_uint, libc::c_uint::MAX, 0 as libc::c_uint) != 0 {
+                for fd in 3..1024 {
+                    libc::close(fd);
+                }
+            }
+            let mut status = 0;
+            while libc::waitpid(child, &mut status, 0) < 0 {
+                if io::Error::last_os_error().raw_os_error() != Some(libc::EINTR) {
+                    libc::_exit(1);
+                }
+            }
+            let code = if libc::WIFEXITED(status) {
+                libc::WEXITSTATUS(status)
+            } else if libc::WIFSIGNALED(status) {
+                128 + libc::WTERMSIG(status)
+            } else {
+                1
+            };
+            libc::_exit(code)
+        }
+    }
+
+    fn write_proc(path: &CStr, data: &[u8]) -> io::Result<()> {
+        unsafe {
+            let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let written = libc::write(fd, data.as_ptr().cast(), data.len());
+            let err = io::Error::last_os_error();
+            libc::close(fd);
+            if written != data.len() as isize {
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    fn cvt(rc: libc::c_int) -> io::Result<()> {
+        if rc < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::path::PathBuf;
+
+        use super::*;
+
+        #[test]
+        fn namespaces_and_rlimits_follow_the_profile() {
+            let own = libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWPID
+                | libc::CLONE_NEWIPC | libc::CLONE_NEWUTS;
+            assert_eq!(namespace_flags(false), own | libc::CLONE_NEWNET);
+            assert_eq!(namespace_flags(true), own);
+
+            let limits = ResourceLimits { cpu_secs: 7, memory_bytes: 1 << 30, file_size_bytes: 1 << 20 };
+            assert_eq!(rlimits(&limits), [
+                (libc::RLIMIT_CPU, 7),
+                (libc::RLIMIT_AS, 1 << 30),
+                (libc::RLIMIT_FSIZE, 1 << 20),
+                (libc::RLIMIT_CORE, 0),
+            ]);
+        }
+
+        #[test]
+        fn landlock_rules_grant_only_what_each_path_needs() {
+            assert_eq!(handled_access(1), ACCESS_ABI_1);
+            assert_eq!(handled_access(2), ACCESS_ABI_1 | ACCESS_REFER);
+            assert_eq!(handled_access(3), ACCESS_ABI_1 | ACCESS_REFER | ACCESS_TRUNCATE);
+
+            let dir = std::env::temp_dir().join(format!("argus-landlock-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let file = dir.join("notes.txt");
+            std::fs::write(&file, "x").unwrap();
+            let profile = IsolationProfile {
+                read_paths: vec![dir.clone(), file.clone(), dir.join("missing")],
+                write_paths: vec![dir.clone(), file.clone()],
+                ..IsolationProfile::default()
+            };
+
+            let handled = handled_access(3);
+            let rules: Vec<(PathBuf, u64)> = path_rules(&profile, handled)
+                .into_iter()
+                .map(|(path, _, access)| (path.to_path_buf(), access))
+                .collect();
+            assert_eq!(rules, vec![
+                (dir.clone(), ACCESS_READ),
+                (file.clone(), ACCESS_EXECUTE | ACCESS_READ_FILE),
+                (dir.clone(), handled),
+                (file.clone(), ACCESS_FILE),
+            ]);
+            // Rights an older kernel doesn't handle are never asked for.
+            let old = path_rules(&profile, handled_access(1));
+            assert!(old.iter().all(|(_, _, access)| access & (ACCESS_REFER | ACCESS_TRUNCATE) == 0));
+
+            if landlock_abi().is_ok() {
+                ruleset(&profile).unwrap();
+            }
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use std::process::Command;
+
+    use super::IsolationProfile;
+    use crate::wasm::SandboxError;
+
+    pub fn landlock_abi() -> Result<u32, SandboxError> {
+        Err(SandboxError::IsolationUnavailable("namespaces and Landlock need Linux".to_string()))
+    }
+
+    pub fn apply(_profile: &IsolationProfile, _cmd: &mut Command) -> Result<(), SandboxError> {
+        landlock_abi().map(|_| ())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_follows_capabilities() {
+        let mut caps = CapabilitySet::new();
+        caps.grant(Capability::FileRead { paths: vec!["/usr".to_string()] });
+        caps.grant(Capability::FileWrite { paths: vec!["/tmp/ws".to_string()] });
+        caps.grant(Capability::Environment { vars: vec!["PATH".to_string()] });
+        let limits = ResourceLimits { cpu_secs: 5, ..ResourceLimits::default() };
+
+        let profile = IsolationProfile::from_capabilities(&caps, limits);
+        assert_eq!(profile.read_paths, vec![PathBuf::from("/usr")]);
+        assert_eq!(profile.write_paths, vec![PathBuf::from("/tmp/ws")]);
+        assert_eq!(profile.env, Some(vec!["PATH".to_string()]));
+        assert_eq!(profile.limits, limits);
+        assert!(!profile.network);
+
+        caps.grant(Capability::Network { domains: vec!["example.com".to_string()] });
+        assert!(IsolationProfile::from_capabilities(&caps, limits).network);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn dropping_a_process_group_kills_every_member() {
+        use std::io::{BufRead, BufReader};
+        use std::os::unix::process::CommandExt;
+
+        let mut child = Command::new("/bin/sh")
+            .args(["-c", "sleep 30 & echo $!; wait"])
+            .stdout(Stdio::piped())
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let mut pid = String::new();
+        BufReader::new(child.stdout.take().unwrap()).read_line(&mut pid).unwrap();
+
+        drop(ProcessGroup(child.id()));
+        assert!(!child.wait().unwrap().success());
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        // Gone, or a zombie waiting on an init that doesn't reap.
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid.trim())).unwrap_or_default();
+        assert!(stat.is_empty() || stat.contains(") Z "), "{}", stat);
+    }
+}
@@ -4,3 +4,4 @@
 
 pub mod wasm;
 pub mod capabilities;
+pub mod isolation;
@@ -25,6 +25,9 @@ pub enum SandboxError {
 
     #[error("Sandbox timed out (10 s wall clock / fuel budget exceeded)")]
     Timeout,
+
+    #[error("Process isolation unavailable: {0}")]
+    IsolationUnavailable(String),
 }
 
 /// A sandboxed WASM runtime.
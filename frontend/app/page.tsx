@@ -6,6 +6,7 @@ import { Header } from '@/components/header/Header';
 import { EyesPanel } from '@/components/eyes/EyesPanel';
 import { ConversationPanel } from '@/components/conversation/ConversationPanel';
 import { ConversationDrawer } from '@/components/conversation/ConversationDrawer';
+import { ApprovalModal } from '@/components/conversation/ApprovalModal';
 import { MindPanel } from '@/components/mind/MindPanel';
 import { ArtifactPanel } from '@/components/artifacts/ArtifactPanel';
 import { ChatPane } from '@/components/panes/ChatPane';
@@ -21,6 +22,8 @@ export default function Home() {
   const eyeState = useAgentStore((s) => s.eyeState);
   const corePulse = useAgentStore((s) => s.corePulse);
   const summonBuilder = useAgentStore((s) => s.summonBuilder);
+  const approvals = useAgentStore((s) => s.approvals);
+  const respondApproval = useAgentStore((s) => s.respondApproval);
 
   const [paneCount, setPaneCount] = useState<1 | 2 | 3>(1);
   const [meetingMode, setMeetingMode] = useState(false);
@@ -105,6 +108,9 @@ export default function Home() {
         onSetPaneCount={handleSetPaneCount}
       />
 
+      {/* Paused tool calls waiting on the operator */}
+      <ApprovalModal request={approvals[0] ?? null} waiting={approvals.length - 1} onRespond={respondApproval} />
+
       <AnimatePresence>
         {schedulerOpen && (
           <TaskScheduler onClose={() => setSchedulerOpen(false)} />
@@ -0,0 +1,153 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import { AnimatePresence, motion } from 'framer-motion';
+import { ApprovalDecision, ApprovalRequest } from '@/lib/types';
+
+interface Props {
+  /** Oldest pending request; null hides the modal. */
+  request: ApprovalRequest | null;
+  /** Requests queued behind this one. */
+  waiting?: number;
+  onRespond: (id: string, decision: ApprovalDecision, command?: string) => void;
+}
+
+const RISK_COLOR: Record<ApprovalRequest['risk'], string> = {
+  low: '#67f6ff',
+  medium: '#c9a84c',
+  high: '#ff5a6e',
+};
+
+/**
+ * A paused tool call: approve runs it, edit runs a changed command (or JSON
+ * arguments), deny hands the model the refusal. Deny holds the focus, so a
+ * stray Enter can't approve. Unanswered, the backend applies the policy
+ * default when the countdown ends.
+ */
+export function ApprovalModal({ request, waiting = 0, onRespond }: Props) {
+  const [editing, setEditing] = useState(false);
+  const [draft, setDraft] = useState('');
+  const [remaining, setRemaining] = useState(0);
+
+  useEffect(() => {
+    if (!request) return;
+    setEditing(false);
+    setDraft(request.command);
+    const deadline = Date.now() + request.timeout_secs * 1000;
+    setRemaining(request.timeout_secs);
+    const timer = setInterval(() => {
+      setRemaining(Math.max(0, Math.ceil((deadline - Date.now()) / 1000)));
+    }, 1000);
+    return () => clearInterval(timer);
+  }, [request]);
+
+  function respond(decision: ApprovalDecision) {
+    if (!request) return;
+    onRespond(request.id, decision, decision === 'edit' ? draft : undefined);
+  }
+
+  function onKeyDown(e: React.KeyboardEvent) {
+    if (editing) {
+      if (e.key === 'Escape') { e.preventDefault(); setEditing(false); }
+      if (e.key === 'Enter' && (e.metaKey || e.ctrlKey)) { e.preventDefault(); respond('edit'); }
+      return;
+    }
+    if (e.key === 'Escape') respond('deny');
+  }
+
+  const color = request ? RISK_COLOR[request.risk] : RISK_COLOR.high;
+  const mono = "'JetBrains Mono', monospace";
+
+  return (
+    <AnimatePresence>
+      {request && (
+        <motion.div
+          className="cmdk"
+          initial={{ opacity: 0 }}
+          animate={{ opacity: 1 }}
+          exit={{ opacity: 0 }}
+          transition={{ duration: 0.12 }}
+          onKeyDown={onKeyDown}
+          tabIndex={-1}
+        >
+          <motion.div
+            className="cmdk-box"
+            style={{ border: `1px solid ${color}55` }}
+            initial={{ opacity: 0, y: -12, scale: 0.97 }}
+            animate={{ opacity: 1, y: 0, scale: 1 }}
+            exit={{ opacity: 0, y: -12, scale: 0.97 }}
+            transition={{ duration: 0.14 }}
+          >
+            <div style={{ padding: '14px 20px', borderBottom: '1px solid #1f1f28', fontFamily: mono, fontSize: 12 }}>
+              <span style={{ color, fontWeight: 700, letterSpacing: '0.08em' }}>APPROVAL NEEDED</span>
+              <span style={{ color: '#5a5a6e' }}> · {request.tool} · </span>
+              <span style={{ color }}>{request.risk.toUpperCase()}</span>
+              <div style={{ color: '#9a9aa8', marginTop: 6 }}>{request.reason}</div>
+            </div>
+
+            <div style={{ padding: '14px 20px' }}>
+              {editing ? (
+                <textarea
+                  autoFocus
+                  value={draft}
+                  onChange={(e) => setDraft(e.target.value)}
+                  rows={Math.min(8, draft.split('\n').length + 1)}
+                  style={{
+                    width: '100%', background: '#0a0a12', color: '#e8e8f0', border: '1px solid #2a2a42',
+                    borderRadius: 8, padding: 10, fontFamily: mono, fontSize: 12, resize: 'vertical',
+                  }}
+                />
+              ) : (
+                <pre style={{ margin: 0, whiteSpace: 'pre-wrap', wordBreak: 'break-all', color: '#e8e8f0', fontFamily: mono, fontSize: 12 }}>
+                  {request.command}
+                </pre>
+              )}
+            </div>
+
+            <div style={{ display: 'flex', alignItems: 'center', gap: 8, padding: '10px 20px 14px', fontFamily: mono, fontSize: 11 }}>
+              <span style={{ color: '#5a5a6e', flex: 1 }}>
+                {request.approve_on_timeout ? 'approved' : 'denied'} in {remaining}s
+                {waiting > 0 && ` · ${waiting} more waiting`}
+              </span>
+              {editing ? (
+                <>
+                  <ModalButton label="Back" onClick={() => setEditing(false)} />
+                  <ModalButton label="Run edited ⌘↵" color={color} onClick={() => respond('edit')} disabled={!draft.trim()} />
+                </>
+              ) : (
+                <>
+                  <ModalButton label="Deny" color="#ff5a6e" onClick={() => respond('deny')} autoFocus />
+                  <ModalButton label="Edit" onClick={() => setEditing(true)} />
+                  <ModalButton label="Approve" color="#67f6ff" onClick={() => respond('approve')} />
+                </>
+              )}
+            </div>
+          </motion.div>
+        </motion.div>
+      )}
+    </AnimatePresence>
+  );
+}
+
+function ModalButton({ label, color = '#9a9aa8', onClick, disabled, autoFocus }: {
+  label: string;
+  color?: string;
+  onClick: () => void;
+  disabled?: boolean;
+  autoFocus?: boolean;
+}) {
+  return (
+    <button
+      onClick={onClick}
+      disabled={disabled}
+      autoFocus={autoFocus}
+      style={{
+        padding: '6px 12px', borderRadius: 8, border: `1px solid ${color}55`, background: `${color}12`,
+        color, fontFamily: 'inherit', fontSize: 11, cursor: disabled ? 'not-allowed' : 'pointer',
+        opacity: disabled ? 0.4 : 1,
+      }}
+    >
+      {label}
+    </button>
+  );
+}
@@ -2,7 +2,7 @@
 
 import { useState, useRef, useEffect } from 'react';
 import { motion } from 'framer-motion';
-import { Send } from 'lucide-react';
+import { Send, Square } from 'lucide-react';
 import { useAgentStore } from '@/hooks/useAgentState';
 import { EYE_SYMBOLS } from '@/lib/constants';
 import { isBuilderModel, BUILDER_THEME } from '@/lib/builder';
@@ -13,6 +13,7 @@ export function InputArea() {
   const [focused, setFocused] = useState(false);
   const textareaRef = useRef<HTMLTextAreaElement>(null);
   const sendMessage = useAgentStore((s) => s.sendMessage);
+  const cancelTurn = useAgentStore((s) => s.cancelTurn);
   const isStreaming = useAgentStore((s) => s.isStreaming);
   const eyeState = useAgentStore((s) => s.eyeState);
   const activeModel = useAgentStore((s) => s.activeModel);
@@ -51,6 +52,8 @@ export function InputArea() {
     : '◉  Ask Argus anything...';
 
   const canSend = !!value.trim() && !isStreaming;
+  // While a turn runs the send button becomes stop.
+  const active = canSend || isStreaming;
 
   return (
     <div
@@ -105,17 +108,18 @@ export function InputArea() {
         />
 
         <motion.button
-          onClick={submit}
-          disabled={!canSend}
+          onClick={isStreaming ? cancelTurn : submit}
+          disabled={!active}
+          title={isStreaming ? 'Stop' : 'Send'}
           className="flex-shrink-0 flex items-center justify-center cursor-pointer"
           style={{
             width: 32,
             height: 32,
             borderRadius: '10px',
-            background: canSend ? `${accent}33` : 'rgba(255,255,255,0.04)',
-            border: canSend ? `1px solid ${accent}88` : '1px solid #2a2a42',
-            color: canSend ? accent : '#3a3a5a',
-            cursor: canSend ? 'pointer' : 'not-allowed',
+            background: active ? `${accent}33` : 'rgba(255,255,255,0.04)',
+            border: active ? `1px solid ${accent}88` : '1px solid #2a2a42',
+            color: active ? accent : '#3a3a5a',
+            cursor: active ? 'pointer' : 'not-allowed',
             transition: 'all 0.18s',
           }}
           animate={
@@ -124,16 +128,16 @@ export function InputArea() {
               : {}
           }
           transition={isStreaming ? { duration: 1.4, repeat: Infinity } : {}}
-          whileHover={canSend ? { scale: 1.06 } : {}}
-          whileTap={canSend ? { scale: 0.93 } : {}}
+          whileHover={active ? { scale: 1.06 } : {}}
+          whileTap={active ? { scale: 0.93 } : {}}
         >
-          <Send size={13} />
+          {isStreaming ? <Square size={11} fill="currentColor" /> : <Send size={13} />}
         </motion.button>
       </div>
 
       <div className="flex justify-between items-center mt-2 px-1">
         <span className="text-[9px] font-mono" style={{ color: '#2a2a42' }}>
-          {builderMode ? '⌘B summon builder · ' : ''}Enter send · Shift+Enter newline
+          {builderMode ? '⌘B summon builder · ' : ''}{isStreaming ? 'Click ■ to stop' : 'Enter send · Shift+Enter newline'}
         </span>
         <span className="text-[9px] font-mono" style={{ color: value.length > 200 ? accent : '#2a2a42' }}>
           {value.length > 0 ? value.length : ''}
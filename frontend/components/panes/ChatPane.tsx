@@ -4,12 +4,13 @@ import { useState, useEffect, useRef, useCallback } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
 import { X, Plus, Send, RotateCcw } from 'lucide-react';
 import { RealConnection } from '@/hooks/useWebSocket';
-import { ServerMessage, Message, ToolCall, Artifact, ModelId, EyeState } from '@/lib/types';
+import { ServerMessage, Message, ToolCall, Artifact, ModelId, EyeState, ApprovalRequest, ApprovalDecision } from '@/lib/types';
 import { WS_URL } from '@/lib/constants';
 import { parseArtifacts } from '@/lib/artifacts';
 import { UserMessage } from '@/components/conversation/UserMessage';
 import { ArgusMessage } from '@/components/conversation/ArgusMessage';
 import { ToolCallBlock } from '@/components/conversation/ToolCallBlock';
+import { ApprovalModal } from '@/components/conversation/ApprovalModal';
 import { ArtifactPanel } from '@/components/artifacts/ArtifactPanel';
 import { PaneModelSelector } from './PaneModelSelector';
 import { StarfieldBackground } from '@/components/shared/StarfieldBackground';
@@ -37,6 +38,7 @@ export function ChatPane({ paneIndex, initialModel = 'grok-build', onClose, open
   const [artifactState, setArtifactState] = useState<{ artifacts: Artifact[]; index: number } | null>(null);
   const [inputValue, setInputValue] = useState('');
   const [title, setTitle] = useState('');
+  const [approvals, setApprovals] = useState<ApprovalRequest[]>([]);
 
   const wsRef = useRef<RealConnection | null>(null);
   const bottomRef = useRef<HTMLDivElement>(null);
@@ -123,6 +125,16 @@ export function ChatPane({ paneIndex, initialModel = 'grok-build', onClose, open
         setEyeState(msg.eye_state as EyeState);
         break;
 
+      case 'approval_request': {
+        const { type: _type, ...request } = msg;
+        setApprovals((prev) => [...prev, request]);
+        break;
+      }
+
+      case 'approval_closed':
+        setApprovals((prev) => prev.filter((a) => a.id !== msg.id));
+        break;
+
       case 'conversation_started':
         setMessages([]);
         setTitle(msg.title || '');
@@ -212,11 +224,17 @@ export function ChatPane({ paneIndex, initialModel = 'grok-build', onClose, open
     : `◉ Pane ${paneIndex} — ${connected ? 'connected' : 'connecting...'}`;
 
   // ── Render ───────────────────────────────────────────────────────────────
+  function respondApproval(id: string, decision: ApprovalDecision, command?: string) {
+    setApprovals((prev) => prev.filter((a) => a.id !== id));
+    wsRef.current?.send({ type: 'approval_response', id, decision, command });
+  }
+
   return (
     <div
       className="flex flex-col h-full"
       style={{ borderLeft: '1px solid #1e1e32', background: '#0a0a0f', minWidth: 0, flex: 1 }}
     >
+      <ApprovalModal request={approvals[0] ?? null} waiting={approvals.length - 1} onRespond={respondApproval} />
       {/* Pane header */}
       <div
         className="flex-shrink-0 px-3 py-2 flex items-center gap-2 border-b"
@@ -5,8 +5,8 @@ import {
   EyeState, ModelId, AccessTier,
   Message, Tool, ToolCall,
   Memory, Curiosity, InnerTruth, PartnershipDynamic, Breakthrough,
-  Conversation, Skill, ActivityEntry, ScheduledTask, Mission,
-  ServerMessage,
+  Conversation, Skill, ActivityEntry, ScheduledTask, Mission, UsageReport,
+  ApprovalRequest, ApprovalDecision, ServerMessage,
 } from '@/lib/types';
 import { ArgusConnection } from '@/lib/connection';
 import { parseArtifacts } from '@/lib/artifacts';
@@ -94,6 +94,9 @@ interface AgentStore {
   tools: Tool[];
   activeToolCalls: ToolCall[];
 
+  // Tool calls waiting on the operator, oldest first
+  approvals: ApprovalRequest[];
+
   // Memory (populated from backend in prod; dev data in dev mode)
   memories: Memory[];
   curiosities: Curiosity[];
@@ -123,6 +126,9 @@ interface AgentStore {
   // Mission suite
   missions: Mission[];
 
+  // Token spend (usage ledger)
+  usage: UsageReport | null;
+
   // NexusCore pulse intensity (0-14, driven by tool activity)
   corePulse: number;
 
@@ -136,6 +142,8 @@ interface AgentStore {
 
   // Actions
   sendMessage: (content: string) => void;
+  cancelTurn: () => void;
+  respondApproval: (id: string, decision: ApprovalDecision, command?: string) => void;
   setMindView: (view: 'mind' | 'field' | 'flow' | 'schedule' | 'missions') => void;
   setEyesCollapsed: (collapsed: boolean) => void;
   setMindCollapsed: (collapsed: boolean) => void;
@@ -172,6 +180,7 @@ export const useAgentStore = create<AgentStore>((set, get) => ({
   // Tools
   tools: DEFAULT_TOOLS,
   activeToolCalls: [],
+  approvals: [],
 
   // Memory — empty in prod (backend populates), dev data otherwise
   memories: devData.memories,
@@ -198,6 +207,7 @@ export const useAgentStore = create<AgentStore>((set, get) => ({
   },
   scheduledTasks: [],
   missions: [],
+  usage: null,
   corePulse: 4,
 
   mindView: 'mind',
@@ -301,6 +311,53 @@ export const useAgentStore = create<AgentStore>((set, get) => ({
         }));
         break;
 
+      case 'model_fallback':
+        // The failed model's partial output is discarded; note who answers instead.
+        set((prev) => ({
+          streamingContent: '',
+          messages: [
+            ...prev.messages,
+            {
+              id: 'fallback-' + Date.now(),
+              role: 'assistant' as const,
+              content: `_${msg.from} ${msg.reason} — answered by ${msg.to}_`,
+              timestamp: new Date(),
+            },
+          ],
+        }));
+        break;
+
+      case 'turn_cancelled':
+        // Keep whatever streamed so far, marked as stopped. A tool cut short
+        // already got its failed tool_result.
+        set((prev) => ({
+          messages: [
+            ...prev.messages,
+            {
+              id: 'cancel-' + Date.now(),
+              role: 'assistant' as const,
+              content: prev.streamingContent ? `${prev.streamingContent}\n\n_Stopped._` : '_Stopped._',
+              timestamp: new Date(),
+            },
+          ],
+          streamingContent: '',
+          isStreaming: false,
+          eyeState: 'watching',
+          activeToolCalls: [],
+          corePulse: 3,
+        }));
+        break;
+
+      case 'approval_request': {
+        const { type: _type, ...request } = msg;
+        set((prev) => ({ approvals: [...prev.approvals, request] }));
+        break;
+      }
+
+      case 'approval_closed':
+        set((prev) => ({ approvals: prev.approvals.filter((a) => a.id !== msg.id) }));
+        break;
+
       case 'response_complete': {
         const { cleanText, artifacts } = parseArtifacts(msg.content);
         set((prev) => ({
@@ -388,6 +445,10 @@ export const useAgentStore = create<AgentStore>((set, get) => ({
         set({ missions: msg.missions.slice(0, 10) }); // cap at 10
         break;
 
+      case 'usage_update':
+        set({ usage: msg.usage });
+        break;
+
       case 'error':
         set((prev) => ({
           eyeState: 'watching',
@@ -444,6 +505,17 @@ export const useAgentStore = create<AgentStore>((set, get) => ({
     get()._ws?.send({ type: 'user_message', content });
   },
 
+  cancelTurn: () => {
+    if (!get().isStreaming) return;
+    get()._ws?.send({ type: 'cancel' });
+  },
+
+  respondApproval: (id: string, decision: ApprovalDecision, command?: string) => {
+    // The backend confirms with approval_closed; drop it now so the modal doesn't linger.
+    set((prev) => ({ approvals: prev.approvals.filter((a) => a.id !== id) }));
+    get()._ws?.send({ type: 'approval_response', id, decision, command });
+  },
+
   switchModel: (model: ModelId) => {
     const id = normalizeModelId(model);
     set({ activeModel: id, accessTier: getModelTier(id) });
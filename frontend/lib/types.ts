@@ -191,12 +191,50 @@ export interface Mission {
   commit_hash?: string;
 }
 
+// ── Usage ledger ───────────────────────────────────────────────────────────
+
+export interface UsageLine {
+  scope: 'model' | 'surface' | 'mission';
+  /** Model slot key, surface name or mission ID. */
+  name: string;
+  label: string;
+  calls: number;
+  prompt_tokens: number;
+  completion_tokens: number;
+  cost_usd: number;
+  budget: number | null;
+}
+
+export interface UsageReport {
+  today: UsageLine[];
+  month: UsageLine[];
+  total_today_usd: number;
+  total_month_usd: number;
+}
+
+// ── Approvals ──────────────────────────────────────────────────────────────
+
+/** A tool call paused until the operator approves, edits or denies it. */
+export interface ApprovalRequest {
+  id: string;
+  tool: string;
+  /** Shell command, or the tool's arguments as JSON. */
+  command: string;
+  risk: 'low' | 'medium' | 'high';
+  reason: string;
+  timeout_secs: number;
+  approve_on_timeout: boolean;
+}
+
+export type ApprovalDecision = 'approve' | 'edit' | 'deny';
+
 export type ClientMessage =
   | { type: 'user_message'; content: string }
   | { type: 'switch_model'; model: ModelId }
   | { type: 'set_model_tools'; model: string; enabled: boolean }
   | { type: 'schedule_task'; agent: string; run_at: string | null; description: string }
   | { type: 'cancel' }
+  | { type: 'approval_response'; id: string; decision: ApprovalDecision; command?: string; reason?: string }
   | { type: 'new_conversation' }
   | { type: 'load_conversation'; id: string }
   | { type: 'list_conversations' }
@@ -209,6 +247,10 @@ export type ServerMessage =
   | { type: 'tool_result'; name: string; result: string; success: boolean; callId: string }
   | { type: 'response_chunk'; content: string }
   | { type: 'response_complete'; content: string }
+  | { type: 'model_fallback'; from: string; to: string; reason: string }
+  | { type: 'turn_cancelled' }
+  | ({ type: 'approval_request' } & ApprovalRequest)
+  | { type: 'approval_closed'; id: string }
   | { type: 'error'; message: string }
   | { type: 'status'; eye_state: EyeState; model: ModelId }
   | { type: 'memory_update'; memories: Memory[] }
@@ -218,4 +260,5 @@ export type ServerMessage =
   | { type: 'skills_update'; skills: Skill[] }
   | { type: 'activity_update'; entries: ActivityEntry[] }
   | { type: 'task_scheduled'; id: string; agent: string; run_at: string | null; description: string }
-  | { type: 'missions_update'; missions: Mission[] };
+  | { type: 'missions_update'; missions: Mission[] }
+  | { type: 'usage_update'; usage: UsageReport };
@@ -0,0 +1,29 @@
+-- argus_audit_anchors: post-quantum signature columns
+-- Each daily anchor carries an ML-DSA-65 (Dilithium-3) detached signature
+-- over "ARGUS_DAY_ROOT|<anchor_date>|<day_root>" alongside the HMAC.
+-- Third parties verify with the public key from `argus audit pubkey`;
+-- pq_key_id is the first 16 hex chars of SHA-256(hex(public_key)).
+
+CREATE TABLE IF NOT EXISTS argus_audit_anchors (
+    id           UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    anchor_date  DATE NOT NULL,
+    day_root     TEXT NOT NULL,
+    signature    TEXT NOT NULL,
+    entry_count  BIGINT NOT NULL DEFAULT 0,
+    anchored_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE argus_audit_anchors ADD COLUMN IF NOT EXISTS pq_signature TEXT;
+ALTER TABLE argus_audit_anchors ADD COLUMN IF NOT EXISTS pq_key_id    TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_argus_audit_anchors_date
+    ON argus_audit_anchors (anchor_date);
+
+-- RLS: service role only
+ALTER TABLE argus_audit_anchors ENABLE ROW LEVEL SECURITY;
+
+DO $$ BEGIN
+    CREATE POLICY "service_role_all" ON argus_audit_anchors
+        FOR ALL TO service_role USING (true) WITH CHECK (true);
+EXCEPTION WHEN duplicate_object THEN null;
+END $$;